@@ -0,0 +1,170 @@
+use crate::error::{MarkError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Favorite markdown files, persisted to `~/.config/mark/bookmarks.toml`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    #[serde(default)]
+    files: Vec<PathBuf>,
+}
+
+impl Bookmarks {
+    /// Get the default bookmarks file path next to the config file
+    pub fn default_path() -> Result<PathBuf> {
+        let config_path = crate::config::get_default_config_path()?;
+        Ok(config_path.with_file_name("bookmarks.toml"))
+    }
+
+    /// Load bookmarks from `path`, starting empty if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|e| {
+            MarkError::config(format!(
+                "Failed to parse bookmarks file {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Save bookmarks to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string(self)
+            .map_err(|e| MarkError::config(format!("Failed to serialize bookmarks: {}", e)))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Bookmark a file, returning false if it was already bookmarked
+    pub fn add(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        if self.files.contains(&path) {
+            return false;
+        }
+
+        self.files.push(path);
+        true
+    }
+
+    /// Remove a bookmark, returning false if the file wasn't bookmarked
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let path = normalize(path);
+        let count = self.files.len();
+        self.files.retain(|file| *file != path);
+        self.files.len() != count
+    }
+
+    /// Toggle a bookmark, returning whether the file is now bookmarked
+    pub fn toggle(&mut self, path: &Path) -> bool {
+        if self.remove(path) {
+            false
+        } else {
+            self.add(path)
+        }
+    }
+
+    /// Check if a file is bookmarked
+    pub fn contains(&self, path: &Path) -> bool {
+        self.files.contains(&normalize(path))
+    }
+
+    /// Get the bookmarked file paths
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Check if there are no bookmarks
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Store absolute paths so bookmarks work from any directory
+fn normalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_and_remove() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.md");
+        File::create(&file).unwrap();
+
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.add(&file));
+        assert!(!bookmarks.add(&file));
+        assert!(bookmarks.contains(&file));
+        assert_eq!(bookmarks.files().len(), 1);
+
+        assert!(bookmarks.remove(&file));
+        assert!(!bookmarks.remove(&file));
+        assert!(!bookmarks.contains(&file));
+        assert!(bookmarks.is_empty());
+    }
+
+    #[test]
+    fn test_toggle() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.md");
+        File::create(&file).unwrap();
+
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(&file));
+        assert!(bookmarks.contains(&file));
+        assert!(!bookmarks.toggle(&file));
+        assert!(!bookmarks.contains(&file));
+    }
+
+    #[test]
+    fn test_round_trip_through_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first.md");
+        let second = temp_dir.path().join("second.md");
+        File::create(&first).unwrap();
+        File::create(&second).unwrap();
+        let path = temp_dir.path().join("mark").join("bookmarks.toml");
+
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add(&first);
+        bookmarks.add(&second);
+        bookmarks.save(&path).unwrap();
+
+        let loaded = Bookmarks::load(&path).unwrap();
+        assert_eq!(loaded, bookmarks);
+        assert!(loaded.contains(&first));
+        assert!(loaded.contains(&second));
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let loaded = Bookmarks::load(&temp_dir.path().join("missing.toml")).unwrap();
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("bookmarks.toml");
+        fs::write(&path, "files = 42").unwrap();
+
+        let result = Bookmarks::load(&path);
+        assert!(matches!(result, Err(MarkError::Config { .. })));
+    }
+}
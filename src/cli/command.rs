@@ -0,0 +1,274 @@
+use crate::cli::export::ExportFormat;
+use crate::cli::parser::Cli;
+use crate::config::Settings;
+use crate::error::{MarkError, Result};
+use crate::search::{self, SearchOptions};
+use std::path::{Path, PathBuf};
+
+/// What to do with the path arguments, resolved once from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Browse these directories together in the file browser
+    Browse(Vec<PathBuf>),
+    /// Open a markdown file in the viewer
+    View(PathBuf),
+    /// Read markdown from stdin and open it in the viewer
+    ViewStdin,
+    /// Read markdown from a named pipe or file descriptor, e.g. `/dev/fd/3`
+    ViewPipe(PathBuf),
+    /// Convert the files (or stdin) and print them
+    Export {
+        paths: Vec<PathBuf>,
+        format: ExportFormat,
+        crlf: bool,
+    },
+}
+
+/// Resolve the commands to run for `paths`, the path arguments after glob expansion
+///
+/// Files are viewed in the order given, followed by a single browser over every
+/// directory. Named pipes and terminals are read like stdin; any other special
+/// file, such as `/dev/zero`, is a `NotAFile` error since it may never end.
+/// Paths are kept as `PathBuf`s, so names that aren't valid UTF-8 work too.
+/// A path that doesn't exist is a `FileNotFound` error, before anything runs.
+pub fn resolve(cli: &Cli, paths: Vec<PathBuf>) -> Result<Vec<Command>> {
+    if let Some(format) = cli.export {
+        return Ok(vec![Command::Export {
+            paths,
+            format,
+            crlf: cli.crlf,
+        }]);
+    }
+
+    let mut commands = Vec::new();
+    let mut directories = Vec::new();
+    for path in paths {
+        if path.as_os_str() == "-" {
+            commands.push(Command::ViewStdin);
+        } else if path.is_file() {
+            commands.push(Command::View(path));
+        } else if path.is_dir() {
+            directories.push(path);
+        } else if is_pipe(&path) {
+            commands.push(Command::ViewPipe(path));
+        } else if path.exists() {
+            return Err(MarkError::NotAFile { path });
+        } else {
+            return Err(MarkError::FileNotFound { path });
+        }
+    }
+
+    if !directories.is_empty() {
+        commands.push(Command::Browse(directories));
+    }
+    Ok(commands)
+}
+
+/// Whether `path` is a named pipe or a terminal, which can be read like stdin
+#[cfg(unix)]
+fn is_pipe(path: &Path) -> bool {
+    use std::io::IsTerminal;
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    let file_type = metadata.file_type();
+    file_type.is_fifo()
+        || (file_type.is_char_device()
+            && std::fs::File::open(path).is_ok_and(|file| file.is_terminal()))
+}
+
+#[cfg(not(unix))]
+fn is_pipe(_path: &Path) -> bool {
+    false
+}
+
+/// The browser's search filters from `settings`, or none of them with `show_all`
+pub fn search_options(settings: &Settings, show_all: bool) -> SearchOptions {
+    SearchOptions {
+        ignored_dirs: if show_all {
+            Vec::new()
+        } else {
+            settings.ignored_dirs.clone()
+        },
+        ignored_files: if show_all {
+            Vec::new()
+        } else {
+            settings.ignored_files.clone()
+        },
+        skip_hidden: !show_all && !settings.hidden_files,
+        follow_symlinks: settings.follow_symlinks,
+        max_file_size: settings.max_file_size.filter(|_| !show_all),
+    }
+}
+
+/// View the markdown file directly when a browser would only list that one file
+///
+/// Does nothing unless `auto_open_single` is set. The directories are searched
+/// with the browser's filters, stopping as soon as a second file turns up.
+pub fn open_single_files(
+    commands: Vec<Command>,
+    settings: &Settings,
+    show_all: bool,
+) -> Result<Vec<Command>> {
+    if !settings.auto_open_single {
+        return Ok(commands);
+    }
+    let options = search_options(settings, show_all);
+
+    commands
+        .into_iter()
+        .map(|command| {
+            let Command::Browse(directories) = command else {
+                return Ok(command);
+            };
+            let mut files = Vec::new();
+            for directory in &directories {
+                files.extend(search::iter_markdown_files(directory, &options)?.take(2));
+                if files.len() > 1 {
+                    return Ok(Command::Browse(directories));
+                }
+            }
+            Ok(match files.pop() {
+                Some(file) => Command::View(file.path),
+                None => Command::Browse(directories),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    fn cli(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("mark").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_views_files_then_browses_directories() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.md");
+        File::create(&file).unwrap();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+
+        let paths = vec![docs.clone(), file.clone(), PathBuf::from("-")];
+        assert_eq!(
+            resolve(&cli(&[]), paths).unwrap(),
+            vec![
+                Command::View(file),
+                Command::ViewStdin,
+                Command::Browse(vec![docs]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_missing_path_is_file_not_found() {
+        let dir = TempDir::new().unwrap();
+        File::create(dir.path().join("a.md")).unwrap();
+        let missing = dir.path().join("missing.md");
+
+        let paths = vec![dir.path().join("a.md"), missing.clone()];
+        let error = resolve(&cli(&[]), paths).unwrap_err();
+        assert!(matches!(error, MarkError::FileNotFound { ref path } if *path == missing));
+        assert_eq!(error.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_resolve_export() {
+        let cli = cli(&["--export", "text", "--crlf", "a.md"]);
+        let commands = resolve(&cli, cli.files.clone()).unwrap();
+        assert_eq!(
+            commands,
+            vec![Command::Export {
+                paths: vec![PathBuf::from("a.md")],
+                format: ExportFormat::Text,
+                crlf: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_single_file_directory_opens_the_viewer() {
+        let dir = TempDir::new().unwrap();
+        let readme = dir.path().join("README.md");
+        File::create(&readme).unwrap();
+        let browse = vec![Command::Browse(vec![dir.path().to_path_buf()])];
+
+        let mut settings = Settings {
+            auto_open_single: true,
+            ..Settings::default()
+        };
+        assert_eq!(
+            open_single_files(browse.clone(), &settings, false).unwrap(),
+            vec![Command::View(readme)]
+        );
+
+        settings.auto_open_single = false;
+        assert_eq!(
+            open_single_files(browse.clone(), &settings, false).unwrap(),
+            browse
+        );
+
+        settings.auto_open_single = true;
+        File::create(dir.path().join("NOTES.md")).unwrap();
+        assert_eq!(
+            open_single_files(browse.clone(), &settings, false).unwrap(),
+            browse
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_named_pipe_is_read_like_stdin() {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = TempDir::new().unwrap();
+        let fifo = dir.path().join("notes.pipe");
+        let c_path = CString::new(fifo.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+
+        assert_eq!(
+            resolve(&cli(&[]), vec![fifo.clone()]).unwrap(),
+            vec![Command::ViewPipe(fifo)]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_endless_devices() {
+        let zero = PathBuf::from("/dev/zero");
+        assert!(matches!(
+            resolve(&cli(&[]), vec![zero]),
+            Err(MarkError::NotAFile { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = TempDir::new().unwrap();
+        let name = OsStr::from_bytes(b"notes-\xff");
+        let path = dir.path().join(name);
+        if fs::create_dir(&path).is_err() {
+            // Some filesystems reject names that aren't valid UTF-8
+            return;
+        }
+
+        let cli = Cli::try_parse_from([OsStr::new("mark"), path.as_os_str()]).unwrap();
+        assert_eq!(
+            resolve(&cli, cli.files.clone()).unwrap(),
+            vec![Command::Browse(vec![path])]
+        );
+    }
+}
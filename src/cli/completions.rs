@@ -0,0 +1,26 @@
+use crate::cli::parser::Cli;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io::Write;
+
+/// Write a completion script for `shell` to `out`
+pub fn generate_completions(shell: Shell, out: &mut dyn Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bash_completions() {
+        let mut out = Vec::new();
+        generate_completions(Shell::Bash, &mut out);
+
+        let script = String::from_utf8(out).unwrap();
+        assert!(!script.is_empty());
+        assert!(script.contains("mark"));
+    }
+}
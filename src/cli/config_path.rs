@@ -0,0 +1,58 @@
+use crate::config::get_default_config_path;
+use crate::error::Result;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The config file to load: `--config` if given, otherwise the default location
+pub fn resolve_config_path(explicit: Option<&Path>) -> Result<PathBuf> {
+    match explicit {
+        Some(path) => Ok(path.to_path_buf()),
+        None => get_default_config_path(),
+    }
+}
+
+/// Write `path` and whether a file exists there to `out`, for `mark config path`
+pub fn write_config_path(path: &Path, out: &mut dyn Write) -> Result<()> {
+    let status = if path.is_file() {
+        "exists"
+    } else {
+        "does not exist"
+    };
+    writeln!(out, "{} ({})", path.display(), status)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn printed(path: &Path) -> String {
+        let mut out = Vec::new();
+        write_config_path(path, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_prints_default_path() {
+        if let Ok(default) = get_default_config_path() {
+            let path = resolve_config_path(None).unwrap();
+            assert_eq!(path, default);
+            assert!(printed(&path).starts_with(&default.display().to_string()));
+        }
+    }
+
+    #[test]
+    fn test_prints_whether_config_exists() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        assert_eq!(resolve_config_path(Some(&path)).unwrap(), path);
+        assert_eq!(
+            printed(&path),
+            format!("{} (does not exist)\n", path.display())
+        );
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(printed(&path), format!("{} (exists)\n", path.display()));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::cli::command::{self, Command};
+use crate::config::settings::Settings;
+use crate::error::Result;
+use crate::search;
+use std::io::Write;
+
+/// Count the markdown files `commands` would open or browse and write the total to `out`
+///
+/// Directories are searched with the same filters as the browser, so the number
+/// matches what `--list` prints.
+pub fn write_count(
+    commands: &[Command],
+    settings: &Settings,
+    show_all: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let options = command::search_options(settings, show_all);
+    let mut count = 0;
+    for command in commands {
+        match command {
+            Command::View(_) => count += 1,
+            Command::Browse(directories) => {
+                for directory in directories {
+                    count += search::iter_markdown_files(directory, &options)?.count();
+                }
+            }
+            Command::ViewStdin | Command::ViewPipe(_) | Command::Export { .. } => {}
+        }
+    }
+
+    writeln!(out, "{}", count)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_count_matches_browser_filters() {
+        let dir = TempDir::new().unwrap();
+        for name in ["a.md", "b.md", "notes.txt"] {
+            File::create(dir.path().join(name)).unwrap();
+        }
+        fs::create_dir_all(dir.path().join("docs")).unwrap();
+        File::create(dir.path().join("docs").join("c.md")).unwrap();
+        fs::create_dir_all(dir.path().join(".hidden")).unwrap();
+        File::create(dir.path().join(".hidden").join("d.md")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+        File::create(dir.path().join("node_modules").join("e.md")).unwrap();
+
+        let settings = Settings {
+            ignored_dirs: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let commands = vec![
+            Command::View(PathBuf::from("README.md")),
+            Command::Browse(vec![dir.path().to_path_buf()]),
+        ];
+
+        let mut out = Vec::new();
+        write_count(&commands, &settings, false, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "4\n");
+
+        let mut out = Vec::new();
+        write_count(&commands, &settings, true, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "6\n");
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::settings::Settings;
+use crate::error::Result;
+use crate::markdown_parser::{normalize_line_endings, BatchParser};
+use crate::search::{self, markdown::read_lossy};
+use clap::ValueEnum;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Formats `--export` can convert markdown to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Html,
+    Text,
+}
+
+impl ExportFormat {
+    /// File extension for documents exported in this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "html",
+            ExportFormat::Text => "txt",
+        }
+    }
+}
+
+/// Convert each file in `paths` (or stdin for `-`) to `format` and write it to `out`
+///
+/// A directory exports every markdown file the browser would show in it,
+/// using the filters in `settings`. Output always uses `\n` line endings, whatever the input used, unless
+/// `crlf` asks for `\r\n`.
+pub fn write_export(
+    paths: &[PathBuf],
+    format: ExportFormat,
+    crlf: bool,
+    settings: &Settings,
+    stdin: &mut dyn Read,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let stdin_only = [PathBuf::from("-")];
+    let paths = if paths.is_empty() {
+        &stdin_only[..]
+    } else {
+        paths
+    };
+
+    let mut parser = BatchParser::lenient();
+    for path in paths {
+        if path.is_dir() {
+            render_directory(path, format, crlf, settings, &mut parser, out)?;
+        } else {
+            let content = read_source(path, stdin)?;
+            write_document(&content, format, crlf, &mut parser, out)?;
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Export every markdown file in `directory`, in path order, reusing `parser` for each
+///
+/// Files are found with the browser's filters from `settings`.
+pub fn render_directory(
+    directory: &Path,
+    format: ExportFormat,
+    crlf: bool,
+    settings: &Settings,
+    parser: &mut BatchParser,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let paths = search::list_markdown_paths(
+        directory,
+        &settings.ignored_dirs,
+        &settings.ignored_files,
+        settings.hidden_files,
+        false,
+        settings.follow_symlinks,
+        settings.max_file_size,
+    )?;
+    for path in paths {
+        let content = read_lossy(&directory.join(path))?.0;
+        write_document(&content, format, crlf, parser, out)?;
+    }
+    Ok(())
+}
+
+/// The files written by [`export_files`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Exported {
+    /// Paths written, in the order of their sources
+    pub written: Vec<PathBuf>,
+    /// How many were numbered because a file of that name was already there
+    pub renamed: usize,
+}
+
+/// Export each markdown file in `paths` to a file of its own in `directory`
+///
+/// The directory is created if needed. Files are named after their source,
+/// numbered when two sources share a name. Existing files are never
+/// overwritten; the export is numbered instead and counted in `renamed`.
+pub fn export_files(paths: &[PathBuf], format: ExportFormat, directory: &Path) -> Result<Exported> {
+    std::fs::create_dir_all(directory)?;
+    let mut parser = BatchParser::lenient();
+    let mut exported = Exported::default();
+    let written = &mut exported.written;
+    for path in paths {
+        // Rendering before the target is opened leaves nothing behind when a source fails
+        let mut rendered = Vec::new();
+        let content = read_lossy(path)?.0;
+        write_document(&content, format, false, &mut parser, &mut rendered)?;
+
+        let stem = path
+            .file_stem()
+            .map_or_else(|| "document".into(), |stem| stem.to_string_lossy());
+        let mut target = directory.join(format!("{}.{}", stem, format.extension()));
+        let mut number = 2;
+        let mut renamed = false;
+        // Creating the file only if it's new leaves no gap for another process to fill
+        let mut file = loop {
+            if !written.contains(&target) {
+                match OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&target)
+                {
+                    Ok(file) => break file,
+                    Err(e) if e.kind() == ErrorKind::AlreadyExists => renamed = true,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            target = directory.join(format!("{}-{}.{}", stem, number, format.extension()));
+            number += 1;
+        };
+        exported.renamed += usize::from(renamed);
+
+        if let Err(e) = file.write_all(&rendered) {
+            drop(file);
+            let _ = std::fs::remove_file(&target);
+            return Err(e.into());
+        }
+        written.push(target);
+    }
+    Ok(exported)
+}
+
+fn write_document(
+    content: &str,
+    format: ExportFormat,
+    crlf: bool,
+    parser: &mut BatchParser,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let ast = parser.parse(content)?;
+    let exported = match format {
+        ExportFormat::Html => ast.to_html(),
+        ExportFormat::Text => ast.to_plain_text(),
+    };
+    let ending = if crlf { "\r\n" } else { "\n" };
+    write!(out, "{}{}", normalize_line_endings(&exported, crlf), ending)?;
+    Ok(())
+}
+
+fn read_source(path: &Path, stdin: &mut dyn Read) -> Result<String> {
+    if path.as_os_str() == "-" {
+        let mut content = String::new();
+        stdin.read_to_string(&mut content)?;
+        Ok(content)
+    } else {
+        Ok(read_lossy(path)?.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(input: &str, format: ExportFormat, crlf: bool) -> String {
+        let mut stdin = input.as_bytes();
+        let mut out = Vec::new();
+        let settings = Settings::default();
+        write_export(
+            &[PathBuf::from("-")],
+            format,
+            crlf,
+            &settings,
+            &mut stdin,
+            &mut out,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_line_endings() {
+        let unix = "# Title\n\nSome text\n\n- one\n- two\n";
+        let windows = unix.replace('\n', "\r\n");
+
+        for format in [ExportFormat::Html, ExportFormat::Text] {
+            let lf = export(&windows, format, false);
+            assert_eq!(lf, export(unix, format, false));
+            assert!(!lf.contains('\r'));
+
+            let crlf = export(unix, format, true);
+            assert_eq!(crlf, export(&windows, format, true));
+            assert_eq!(crlf, lf.replace('\n', "\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_export_directory_in_path_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("b.md"), "Second").unwrap();
+        std::fs::write(dir.path().join("a.md"), "First").unwrap();
+        std::fs::write(dir.path().join("docs").join("c.md"), "# Third").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "Skipped").unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        std::fs::write(dir.path().join("node_modules").join("d.md"), "Skipped").unwrap();
+
+        let export_dir = |settings: &Settings| {
+            let mut out = Vec::new();
+            let paths = [dir.path().to_path_buf()];
+            write_export(
+                &paths,
+                ExportFormat::Text,
+                false,
+                settings,
+                &mut &b""[..],
+                &mut out,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(export_dir(&Settings::default()), "First\nSecond\nThird\n");
+
+        // The config's filters apply, as in the browser
+        let settings = Settings {
+            ignored_dirs: vec!["docs".to_string(), "node_modules".to_string()],
+            ignored_files: vec!["b.md".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(export_dir(&settings), "First\n");
+    }
+
+    #[test]
+    fn test_export_text() {
+        assert_eq!(
+            export("Some **bold** text\r\n", ExportFormat::Text, false),
+            "Some bold text\n"
+        );
+    }
+
+    #[test]
+    fn test_export_files_to_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        let first = dir.path().join("README.md");
+        let second = dir.path().join("docs").join("README.md");
+        std::fs::write(&first, "# One").unwrap();
+        std::fs::write(&second, "Two").unwrap();
+
+        let out = dir.path().join("out");
+        let exported = export_files(&[first.clone(), second], ExportFormat::Html, &out).unwrap();
+        assert_eq!(
+            exported.written,
+            vec![out.join("README.html"), out.join("README-2.html")]
+        );
+        assert_eq!(exported.renamed, 0);
+        assert_eq!(
+            std::fs::read_to_string(&exported.written[1]).unwrap(),
+            "<p>Two</p>\n"
+        );
+
+        // A second export leaves the first one alone
+        let again = export_files(&[first], ExportFormat::Html, &out).unwrap();
+        assert_eq!(again.written, vec![out.join("README-3.html")]);
+        assert_eq!(again.renamed, 1);
+        assert_eq!(
+            std::fs::read_to_string(out.join("README.html")).unwrap(),
+            "<h1>One</h1>\n"
+        );
+    }
+
+    #[test]
+    fn test_export_files_failing_source_leaves_no_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let broken = dir.path().join("broken.md");
+        std::fs::write(&broken, "99999999999999999999 apples").unwrap();
+
+        let out = dir.path().join("out");
+        assert!(export_files(std::slice::from_ref(&broken), ExportFormat::Html, &out).is_err());
+        assert!(!out.join("broken.html").exists());
+
+        // A later export of a fixed source takes the plain name
+        std::fs::write(&broken, "Fixed").unwrap();
+        let exported = export_files(&[broken], ExportFormat::Html, &out).unwrap();
+        assert_eq!(exported.written, vec![out.join("broken.html")]);
+        assert_eq!(exported.renamed, 0);
+    }
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+/// A 1-indexed, inclusive range of lines from `--lines START:END`
+///
+/// Either end may be left out: `10:` runs to the end of the file and `:40`
+/// starts at its first line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: Option<usize>,
+}
+
+impl LineRange {
+    /// The lines of `text` in the range, with their line endings
+    ///
+    /// A range running past the end of `text` stops at its last line.
+    pub fn slice<'a>(&self, text: &'a str) -> &'a str {
+        // Both bounds are just after a `\n`, so they are on char boundaries
+        let (start, end) = self.byte_bounds(text.as_bytes());
+        &text[start..end]
+    }
+
+    /// The lines of `text` in the range, for input that may not be UTF-8
+    pub fn slice_bytes<'a>(&self, text: &'a [u8]) -> &'a [u8] {
+        let (start, end) = self.byte_bounds(text);
+        &text[start..end]
+    }
+
+    fn byte_bounds(&self, text: &[u8]) -> (usize, usize) {
+        let mut start = if self.start <= 1 { 0 } else { text.len() };
+        let mut end = text.len();
+        let mut line = 1;
+        for (index, byte) in text.iter().enumerate() {
+            if *byte != b'\n' {
+                continue;
+            }
+            line += 1;
+            if line == self.start {
+                start = index + 1;
+            }
+            if self.end.is_some_and(|last| line > last) {
+                end = index + 1;
+                break;
+            }
+        }
+        (start.min(end), end)
+    }
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value
+            .split_once(':')
+            .ok_or_else(|| "expected START:END, e.g. 10:40".to_string())?;
+        let number = |part: &str| -> Result<Option<usize>, String> {
+            let part = part.trim();
+            if part.is_empty() {
+                return Ok(None);
+            }
+            match part.parse() {
+                Ok(0) | Err(_) => Err(format!(
+                    "'{}' is not a line number (lines start at 1)",
+                    part
+                )),
+                Ok(line) => Ok(Some(line)),
+            }
+        };
+
+        let range = LineRange {
+            start: number(start)?.unwrap_or(1),
+            end: number(end)?,
+        };
+        if range.end.is_some_and(|end| end < range.start) {
+            return Err(format!("the range {} ends before it starts", value));
+        }
+        Ok(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "one\ntwo\nthree\nfour\n";
+
+    fn range(value: &str) -> LineRange {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_valid_range() {
+        assert_eq!(
+            range("2:3"),
+            LineRange {
+                start: 2,
+                end: Some(3)
+            }
+        );
+        assert_eq!(range("2:3").slice(TEXT), "two\nthree\n");
+        assert_eq!(range("1:1").slice(TEXT), "one\n");
+        assert_eq!(range("3:").slice(TEXT), "three\nfour\n");
+        assert_eq!(range(":2").slice(TEXT), "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_out_of_bounds_range_is_clamped() {
+        assert_eq!(range("3:100").slice(TEXT), "three\nfour\n");
+        assert_eq!(range("2:9").slice("one\ntwo"), "two");
+        assert_eq!(range("10:20").slice(TEXT), "");
+        assert_eq!(range("2:3").slice_bytes(b"a\n\xff\nc"), b"\xff\nc");
+    }
+
+    #[test]
+    fn test_invalid_ranges_are_rejected() {
+        assert!("40:10".parse::<LineRange>().is_err());
+        assert!("0:10".parse::<LineRange>().is_err());
+        assert!("10".parse::<LineRange>().is_err());
+        assert!("a:b".parse::<LineRange>().is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::{MarkError, ParseError, Result};
+use crate::markdown_parser::parse_markdown;
+use crate::markdown_parser::parser::split_frontmatter;
+use crate::search::markdown::read_lossy;
+use std::io::Write;
+use std::path::Path;
+
+/// Parse `source` strictly and collect every problem found, in document order
+///
+/// The parser stops at its first error, so each block between blank lines is
+/// parsed on its own to find problems further down too. Code fences are kept
+/// whole, even across blank lines. Headings with more than six `#`s, which
+/// the parser reads as text, are reported as well.
+pub fn lint(source: &str) -> Vec<MarkError> {
+    let (mut line, body) = match split_frontmatter(source) {
+        Some(front) => (front.lines, front.rest),
+        None => (0, source),
+    };
+
+    let mut problems = Vec::new();
+    let mut block = String::new();
+    let mut block_start = line;
+    let mut in_fence = false;
+    let mut headings = Vec::new();
+    let mut check = |block: &mut String, start: usize| {
+        if block.trim().is_empty() {
+            block.clear();
+            return;
+        }
+        // Blank lines in front keep the reported line numbers right
+        let padded = format!("{}{}", "\n".repeat(start), block);
+        if let Err(e) = parse_markdown(&padded) {
+            problems.push(e);
+        }
+        block.clear();
+    };
+
+    for text in body.split_inclusive('\n') {
+        let trimmed = text.trim();
+        if in_fence {
+            in_fence = !closes_fence(text);
+        } else if trimmed.starts_with("```") {
+            in_fence = true;
+        } else {
+            headings.extend(heading_level_problem(text.trim_end(), line + 1));
+        }
+        if trimmed.is_empty() && !in_fence {
+            check(&mut block, block_start);
+            block_start = line + 1;
+        } else {
+            block.push_str(text);
+        }
+        line += 1;
+    }
+    check(&mut block, block_start);
+    problems.extend(headings.into_iter().map(MarkError::from));
+    problems.sort_by_key(|problem| problem.position());
+    problems
+}
+
+/// Whether `text` has a run of three to five backticks, which closes a code fence in the parser
+fn closes_fence(text: &str) -> bool {
+    text.split(|c| c != '`')
+        .any(|run| (3..=5).contains(&run.len()))
+}
+
+/// A heading marker on `text` with too many `#`s to be a heading
+fn heading_level_problem(text: &str, line: usize) -> Option<ParseError> {
+    let indent = text.len() - text.trim_start_matches(' ').len();
+    let rest = &text[indent..];
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    let after = rest[hashes..].chars().next();
+    if indent > 3 || hashes <= 6 || !matches!(after, None | Some(' ' | '\t')) {
+        return None;
+    }
+    let level = hashes.min(u8::MAX as usize) as u8;
+    Some(ParseError::invalid_heading_level(level, line, indent + 1))
+}
+
+/// Write the problems in the markdown file at `path` to `out`
+///
+/// Each problem is followed by the line it is on, with a caret under its column.
+/// Fails with `MarkError::Lint` if there were any, so the exit code shows it.
+pub fn write_lint(path: &Path, out: &mut dyn Write) -> Result<()> {
+    if !path.exists() {
+        return Err(MarkError::FileNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+    if !path.is_file() {
+        return Err(MarkError::NotAFile {
+            path: path.to_path_buf(),
+        });
+    }
+    let (content, _) = read_lossy(path)?;
+    let problems = lint(&content);
+    for problem in &problems {
+        writeln!(out, "{}: {}", path.display(), problem)?;
+        if let Some(snippet) = problem.snippet(&content) {
+            writeln!(out, "{}", snippet)?;
+        }
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(MarkError::Lint {
+        problems: problems.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lint_reports_every_problem() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(
+            &path,
+            "# Notes\n\nSee [docs](https://example.com\n\n```\n####### code\n\n```\n\n####### Deep\n",
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        let error = write_lint(&path, &mut out).unwrap_err();
+        assert!(matches!(error, MarkError::Lint { problems: 2 }));
+        assert_ne!(error.exit_code(), 0);
+
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output
+            .lines()
+            .filter(|line| line.starts_with(&*path.to_string_lossy()))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[0].contains("Malformed link at line 3"),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[1].contains("Invalid heading level 7 at line 10"),
+            "{}",
+            lines[1]
+        );
+        assert!(
+            output.contains("3 | See [docs](https://example.com\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("10 | ####### Deep\n   | ^"), "{}", output);
+    }
+
+    #[test]
+    fn test_fences_follow_the_parser() {
+        // `~~~` isn't a fence, so the heading between is checked
+        let problems = lint("~~~\n\n####### Deep\n\n~~~\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].to_string().contains("line 3"));
+
+        // Any run of three to five backticks closes a fence
+        let problems = lint("````\ncode\n```\n\n####### Deep\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].to_string().contains("line 5"));
+    }
+
+    #[test]
+    fn test_directory_is_not_a_file() {
+        let dir = TempDir::new().unwrap();
+        let error = write_lint(dir.path(), &mut Vec::new()).unwrap_err();
+        assert!(matches!(error, MarkError::NotAFile { .. }));
+
+        let missing = dir.path().join("missing.md");
+        let error = write_lint(&missing, &mut Vec::new()).unwrap_err();
+        assert!(matches!(error, MarkError::FileNotFound { .. }));
+    }
+
+    #[test]
+    fn test_clean_file_passes() {
+        assert!(lint("---\ntitle: x\n\n---\n# Title\n\n*fine*\n").is_empty());
+    }
+}
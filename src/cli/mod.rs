@@ -1,7 +1,31 @@
+pub mod command;
+pub mod completions;
+pub mod config_path;
+pub mod count;
+pub mod export;
+pub mod lines;
+pub mod lint;
 pub mod parser;
+pub mod paths;
+pub mod plain;
+pub mod raw;
+pub mod stats;
+pub mod themes;
+pub mod tokens;
+pub mod version;
 
-use crate::config::{get_default_config_path, ConfigLoader, MarkConfig};
-use crate::error::Result;
+use crate::cli::command::Command;
+use crate::cli::lines::LineRange;
+use crate::cli::plain::OutputMode;
+use crate::config::settings::{theme_for_background, Settings};
+use crate::config::{ConfigLoader, DownloadPolicy, MarkConfig};
+use crate::error::{MarkError, Result};
+use crate::history::{self, History};
+use crate::markdown_parser;
+use crate::search;
+use crate::ui::renderer::{CodeBlockBorder, HeadingPrefix, InlineCodeStyle, Renderer, Theme};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 
 use crate::ui::{self, App};
 use clap::Parser;
@@ -10,77 +34,473 @@ use clap::Parser;
 pub fn run() -> Result<()> {
     let cli = parser::Cli::parse();
 
-    let config_path = if let Some(path) = &cli.config {
-        path.clone()
+    if cli.version {
+        println!("{}", version::version_info(cli.verbose));
+        return Ok(());
+    }
+
+    match &cli.command {
+        Some(parser::Commands::Completions { shell }) => {
+            completions::generate_completions(*shell, &mut std::io::stdout());
+            return Ok(());
+        }
+        Some(parser::Commands::Stats { path, json }) => {
+            let mut loader = load_config(&cli)?;
+            if let Some(project_dir) = paths::project_dir(std::slice::from_ref(path)) {
+                loader.set_project_dir(project_dir)?;
+            }
+            loader.add_ignored_dirs(&cli.ignore_dirs);
+            return stats::write_stats(
+                path,
+                *json,
+                &loader.config().settings,
+                cli.all,
+                &mut std::io::stdout(),
+            );
+        }
+        Some(parser::Commands::Config {
+            action: parser::ConfigAction::Path,
+        }) => {
+            let path = config_path::resolve_config_path(cli.config.as_deref())?;
+            return config_path::write_config_path(&path, &mut std::io::stdout());
+        }
+        Some(parser::Commands::Lint { file }) => {
+            return lint::write_lint(file, &mut std::io::stdout());
+        }
+        Some(parser::Commands::Tokens { file, stats }) => {
+            let content = std::fs::read_to_string(file)?;
+            if *stats {
+                return tokens::dump_token_stats(&content, &mut std::io::stdout());
+            }
+            return tokens::dump_tokens(&content, &mut std::io::stdout());
+        }
+        None => {}
+    }
+
+    if cli.recent {
+        return print_recent();
+    }
+
+    if cli.export.is_some() {
+        let mut loader = load_config(&cli)?;
+        if let Some(project_dir) = paths::project_dir(&cli.files) {
+            loader.set_project_dir(project_dir)?;
+        }
+        loader.add_ignored_dirs(&cli.ignore_dirs);
+        return run_exports(
+            command::resolve(&cli, cli.files.clone())?,
+            &loader.config().settings,
+        );
+    }
+
+    if cli.raw {
+        return raw::write_raw(
+            &cli.files,
+            cli.lines,
+            &mut std::io::stdin(),
+            &mut std::io::stdout().lock(),
+        );
+    }
+
+    if cli.json {
+        return print_json(&cli.files, cli.lenient);
+    }
+
+    ui::style::set_no_color(ui::style::no_color_requested(
+        cli.no_color,
+        std::env::var_os("NO_COLOR"),
+    ));
+
+    let mut loader = load_config(&cli)?;
+
+    let paths = paths::resolve_paths(
+        &cli.files,
+        loader.config().settings.default_directory.as_deref(),
+    )?;
+    if let Some(project_dir) = paths::project_dir(&paths) {
+        loader.set_project_dir(project_dir)?;
+    }
+    if let Some(theme) = &cli.theme {
+        loader.set_theme_override(theme)?;
+    }
+    loader.add_ignored_dirs(&cli.ignore_dirs);
+    let config = loader.config();
+    for warning in config.color_warnings() {
+        eprintln!(
+            "Warning: {} (in a color scheme the theme doesn't use)",
+            warning
+        );
+    }
+
+    if cli.theme_list {
+        detect_auto_theme(&mut loader);
+        return themes::write_theme_list(loader.config(), &mut std::io::stdout().lock());
+    }
+
+    let display_width = if cli.width > 0 {
+        cli.width
     } else {
-        get_default_config_path()?
+        config.settings.width
     };
 
-    let loader = ConfigLoader::with_path(config_path)?;
-    let config = loader.config();
+    let show_urls = cli.show_urls || config.settings.show_urls;
+    let mode = plain::output_mode(cli.tui, cli.no_tui, std::io::stdout().is_terminal());
+    let renderer_theme = Theme::from_config(config);
+    let renderer = Renderer::new(&renderer_theme, display_width)
+        .with_show_urls(show_urls)
+        .with_indent_by_heading(config.settings.indent_by_heading)
+        .with_text_modifiers(config.settings.use_text_modifiers)
+        .with_wrap_code(config.settings.wrap_code)
+        .with_show_frontmatter(config.settings.show_frontmatter)
+        .with_heading_prefix(HeadingPrefix::from_setting(&config.settings.heading_prefix))
+        .with_conceal(config.settings.conceal)
+        .with_inline_code_style(InlineCodeStyle::from_setting(
+            &config.settings.inline_code_style,
+        ))
+        .with_code_block_border(CodeBlockBorder::from_setting(
+            &config.settings.code_block_border,
+        ))
+        .with_collapse_blank_lines(config.settings.collapse_blank_lines)
+        .with_show_image_urls(config.settings.show_image_urls);
+
+    if let Some(language) = &cli.as_code {
+        if mode == OutputMode::Plain {
+            return print_as_code(&cli.files, language, cli.lines, &renderer);
+        }
+        detect_auto_theme(&mut loader);
+        return view_as_code(&cli.files, language, cli.lines, display_width, &loader);
+    }
+
+    if paths.is_empty() && !cli.files.is_empty() {
+        eprintln!("No markdown files matched the given patterns");
+    }
+
+    let commands = command::resolve(&cli, paths)?;
 
-    if cli.all {
-        let directory = cli.file.as_ref().and_then(|p| p.to_str()).unwrap_or(".");
-        launch_file_browser(directory, &config, true)?;
+    if cli.list {
+        for command in &commands {
+            match command {
+                Command::View(file) => println!("{}", file.display()),
+                Command::Browse(directories) => list_files(directories, config, cli.all)?,
+                Command::ViewStdin | Command::ViewPipe(_) | Command::Export { .. } => {}
+            }
+        }
+    }
+
+    if cli.count {
+        return count::write_count(
+            &commands,
+            &config.settings,
+            cli.all,
+            &mut std::io::stdout().lock(),
+        );
+    }
+
+    if cli.list {
         return Ok(());
     }
 
-    // Handle the file argument
-    match cli.file {
-        Some(path) => {
-            if path.is_file() {
-                println!("Opening file: {}", path.display());
-                // TODO: Launch markdown viewer with the specific file
-                Ok(())
-            } else if path.is_dir() {
-                launch_file_browser(path.to_str().unwrap(), &config, false)?;
-                Ok(())
-            } else {
-                eprintln!("Error: Path does not exist: {}", path.display());
-                Ok(())
+    let commands = command::open_single_files(commands, &config.settings, cli.all)?;
+
+    // Piped output gets the rendered text instead of a viewer it can't show
+    let force_markdown = if cli.force_markdown {
+        Some(true)
+    } else if cli.force_plain {
+        Some(false)
+    } else {
+        None
+    };
+    if mode == OutputMode::Plain {
+        return print_plain(
+            commands,
+            config,
+            &renderer,
+            cli.lines,
+            cli.all,
+            force_markdown,
+        );
+    }
+
+    detect_auto_theme(&mut loader);
+    let config = loader.config();
+
+    let strict_emphasis = config.settings.strict_emphasis;
+    for command in commands {
+        let app = match command {
+            Command::View(file) => {
+                record_history(&file);
+                match cli.lines {
+                    Some(_) => {
+                        let content = read_source(&file, cli.lines)?;
+                        let ast = markdown_parser::parse_markdown_or_default_with(
+                            &content,
+                            strict_emphasis,
+                        );
+                        App::with_document(&file, ast, config)
+                    }
+                    None => App::with_file(&file, config)?,
+                }
             }
+            Command::ViewStdin => {
+                let content = read_source(Path::new("-"), cli.lines)?;
+                let ast = stream_document(&content, force_markdown, strict_emphasis);
+                App::with_document(Path::new("-"), ast, config)
+            }
+            Command::ViewPipe(path) => {
+                let content = read_source(&path, cli.lines)?;
+                let ast = stream_document(&content, force_markdown, strict_emphasis);
+                App::with_document(&path, ast, config)
+            }
+            Command::Browse(directories) => App::new(directories, config, cli.all)?,
+            Command::Export { .. } => continue,
+        };
+        run_app(
+            app.with_width(display_width)
+                .with_show_urls(show_urls)
+                .with_config_loader(loader.clone())
+                .with_watch(cli.watch)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print each command's documents rendered as text, listing the files of directories
+fn print_plain(
+    commands: Vec<Command>,
+    config: &MarkConfig,
+    renderer: &Renderer,
+    lines: Option<LineRange>,
+    show_all: bool,
+    force_markdown: Option<bool>,
+) -> Result<()> {
+    let strict_emphasis = config.settings.strict_emphasis;
+    let mut out = std::io::stdout().lock();
+    for (index, command) in commands.into_iter().enumerate() {
+        let ast = match command {
+            Command::View(file) => markdown_parser::parse_markdown_or_default_with(
+                &read_source(&file, lines)?,
+                strict_emphasis,
+            ),
+            Command::ViewStdin => stream_document(
+                &read_source(Path::new("-"), lines)?,
+                force_markdown,
+                strict_emphasis,
+            ),
+            Command::ViewPipe(path) => {
+                stream_document(&read_source(&path, lines)?, force_markdown, strict_emphasis)
+            }
+            Command::Browse(directories) => {
+                list_files(&directories, config, show_all)?;
+                continue;
+            }
+            Command::Export { .. } => continue,
+        };
+        if index > 0 {
+            writeln!(out)?;
         }
-        None => {
-            // Browse current directory
-            launch_file_browser(".", &config, false)?;
-            Ok(())
+        plain::write_plain(&ast, renderer, &mut out)?;
+    }
+    Ok(())
+}
+
+/// Parse text read from stdin or a pipe, showing it as plain text if it doesn't look like markdown
+///
+/// `force_markdown` skips the check, either way.
+fn stream_document(
+    content: &str,
+    force_markdown: Option<bool>,
+    strict_emphasis: bool,
+) -> markdown_parser::AstNode {
+    if force_markdown.unwrap_or_else(|| markdown_parser::detect::detect_markdown(content)) {
+        markdown_parser::parse_markdown_or_default_with(content, strict_emphasis)
+    } else {
+        markdown_parser::text_document(content)
+    }
+}
+
+/// Print stdin, or each of `files`, rendered as a single code block in `language`
+fn print_as_code(
+    files: &[PathBuf],
+    language: &str,
+    lines: Option<LineRange>,
+    renderer: &Renderer,
+) -> Result<()> {
+    let stdin = [PathBuf::from("-")];
+    let sources = if files.is_empty() { &stdin[..] } else { files };
+
+    let mut out = std::io::stdout().lock();
+    for source in sources {
+        let code = read_source(source, lines)?;
+        plain::write_plain(
+            &markdown_parser::code_document(&code, language),
+            renderer,
+            &mut out,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write each `--export` command's output to stdout
+fn run_exports(commands: Vec<Command>, settings: &Settings) -> Result<()> {
+    for command in commands {
+        if let Command::Export {
+            paths,
+            format,
+            crlf,
+        } = command
+        {
+            export::write_export(
+                &paths,
+                format,
+                crlf,
+                settings,
+                &mut std::io::stdin(),
+                &mut std::io::stdout().lock(),
+            )?;
         }
     }
+    Ok(())
 }
 
-fn launch_file_browser(directory: &str, config: &MarkConfig, show_all: bool) -> Result<()> {
-    // Initialize terminal
-    let mut terminal = ui::init()?;
+/// Load the config from `--config` or the default path, as `--no-config`, `--yes` and `--no-download` ask
+fn load_config(cli: &parser::Cli) -> Result<ConfigLoader> {
+    let config_path = config_path::resolve_config_path(cli.config.as_deref())?;
+    let download_policy = if cli.yes {
+        DownloadPolicy::Accept
+    } else if cli.no_download {
+        DownloadPolicy::Decline
+    } else {
+        DownloadPolicy::Prompt
+    };
+    if cli.no_config {
+        ConfigLoader::with_defaults(config_path)
+    } else {
+        ConfigLoader::with_download_policy(config_path, download_policy)
+    }
+}
+
+/// Print markdown file paths found in `directories`, one per line, as they are found
+fn list_files(directories: &[PathBuf], config: &MarkConfig, show_all: bool) -> Result<()> {
+    let options = command::search_options(&config.settings, show_all);
+    for directory in directories {
+        let root = search::expand_tilde(directory)?;
+        for file in search::iter_markdown_files(directory, &options)? {
+            let path = file.path.strip_prefix(&root).unwrap_or(&file.path);
+            if directory == Path::new(".") {
+                println!("{}", path.display());
+            } else {
+                println!("{}", directory.join(path).display());
+            }
+        }
+    }
 
-    // Create and run the app
-    let result = run_app(directory, &mut terminal, config, show_all);
+    Ok(())
+}
 
-    // Always restore terminal, even if there was an error
-    ui::restore()?;
+/// Pick dark or light colors from the terminal background when the theme is `auto`
+fn detect_auto_theme(loader: &mut ConfigLoader) {
+    if loader.config().settings.is_auto_theme() {
+        let background = ui::background::query_background_color(ui::background::QUERY_TIMEOUT);
+        loader.set_detected_theme(theme_for_background(
+            background.map(ui::background::luminance),
+        ));
+    }
+}
 
-    match result {
-        Ok(Some(file)) => {
-            println!("Selected file: {}", file.path.display());
-            // TODO: Launch markdown viewer with the selected file
-            Ok(())
+/// View stdin, or each of `files`, as a single code block in `language`
+fn view_as_code(
+    files: &[PathBuf],
+    language: &str,
+    lines: Option<LineRange>,
+    width: usize,
+    loader: &ConfigLoader,
+) -> Result<()> {
+    let stdin = [PathBuf::from("-")];
+    let sources = if files.is_empty() { &stdin[..] } else { files };
+
+    for source in sources {
+        let code = read_source(source, lines)?;
+
+        let ast = markdown_parser::code_document(&code, language);
+        run_app(
+            App::with_document(source, ast, loader.config())
+                .with_width(width)
+                .with_config_loader(loader.clone()),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Read `source`, or stdin for `-`, keeping only `lines` if given
+fn read_source(source: &Path, lines: Option<LineRange>) -> Result<String> {
+    let content = if source.as_os_str() == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        search::markdown::read_lossy(source)?.0
+    };
+    Ok(match lines {
+        Some(range) => range.slice(&content).to_string(),
+        None => content,
+    })
+}
+
+/// Print the AST of each markdown file as JSON, reporting parse errors as JSON too
+fn print_json(paths: &[PathBuf], lenient: bool) -> Result<()> {
+    for path in paths::expand_globs(paths)? {
+        if !path.exists() {
+            return Err(MarkError::FileNotFound { path });
         }
-        Ok(None) => {
-            // User quit without selecting a file
-            Ok(())
+        if !path.is_file() {
+            return Err(MarkError::NotAFile { path });
         }
-        Err(e) => {
-            ui::restore()?;
-            Err(e)
+
+        let content = std::fs::read_to_string(&path)?;
+        match markdown_parser::parse_to_json(&content, lenient) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                println!("{}", markdown_parser::error_to_json(&e));
+                return Err(e);
+            }
         }
     }
+
+    Ok(())
 }
 
-fn run_app(
-    directory: &str,
-    terminal: &mut crate::ui::Tui,
-    config: &MarkConfig,
-    show_all: bool,
-) -> Result<Option<crate::search::MarkdownFile>> {
-    let mut app = App::new(directory, config, show_all)?;
-    app.run(terminal)
+/// Print the recently opened files, most recent first
+fn print_recent() -> Result<()> {
+    let history = History::load(&History::default_path()?)?;
+
+    if history.is_empty() {
+        eprintln!("No recently opened files");
+    }
+
+    for entry in history.entries() {
+        println!("{}  {}", entry.display_time(), entry.path.display());
+    }
+
+    Ok(())
+}
+
+/// Add a file to the history, warning instead of failing if it can't be saved
+fn record_history(path: &Path) {
+    if let Err(e) = history::record_opened(path) {
+        eprintln!("Warning: Failed to update history: {}", e);
+    }
+}
+
+/// Run the app in the terminal, restoring the terminal afterwards
+fn run_app(mut app: App) -> Result<()> {
+    let mut terminal = ui::init()?;
+    // Restores the terminal if the app panics
+    let guard = ui::RestoreGuard::new(ui::restore);
+    let result = app.run(&mut terminal);
+
+    // Always restore terminal, even if there was an error
+    guard.restore()?;
+    result
 }
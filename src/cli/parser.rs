@@ -1,9 +1,13 @@
-use clap::Parser;
+use crate::cli::export::ExportFormat;
+use crate::cli::lines::LineRange;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "mark")]
 #[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(disable_version_flag = true)]
 #[command(about = "A terminal-based markdown viewer")]
 #[command(long_about = "Mark is a terminal-based markdown viewer built in Rust.
 
@@ -12,10 +16,15 @@ USAGE MODES:
   • Without file: mark                     - Opens file browser for current directory
   • Browse all:   mark -a                  - Shows ALL markdown files (including hidden AND ignored) in current directory
   • Browse all:   mark -a /path/to/dir     - Shows ALL markdown files (including hidden AND ignored) in specified directory
-  • Browse dir:   mark /path/to/directory  - Browse files in specified directory (respects hidden_files setting and ignored_dirs)")]
+  • Browse dir:   mark /path/to/directory  - Browse files in specified directory (respects hidden_files setting and ignored_dirs)
+  • Multiple:     mark a.md b.md docs/     - Opens each file and browses all listed directories together")]
 pub struct Cli {
-    /// Path to markdown file or directory to browse (optional)
-    pub file: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Markdown files to open and directories to browse (optional)
+    #[arg(value_name = "PATH")]
+    pub files: Vec<PathBuf>,
 
     /// Configuration file path
     #[arg(short = 'c', long = "config", value_name = "FILE")]
@@ -25,9 +34,164 @@ pub struct Cli {
     #[arg(short = 'w', long = "width", value_name = "WIDTH", default_value = "0")]
     pub width: usize,
 
+    /// Color theme to use for this run: dark, light or auto (overrides the config)
+    #[arg(short = 't', long = "theme", value_name = "THEME")]
+    pub theme: Option<String>,
+
+    /// Show stdin (or the given files) as a code block in LANG instead of parsing markdown
+    #[arg(long = "as-code", value_name = "LANG")]
+    pub as_code: Option<String>,
+
+    /// Download the default config without asking if the config file is missing
+    #[arg(short = 'y', long = "yes", conflicts_with = "no_download")]
+    pub yes: bool,
+
+    /// Fail instead of offering to download the default config if the config file is missing
+    #[arg(long = "no-download")]
+    pub no_download: bool,
+
+    /// Use the built-in default config without reading or downloading a config file
+    #[arg(long = "no-config", conflicts_with_all = ["config", "yes", "no_download"])]
+    pub no_config: bool,
+
+    /// Show each link's URL after its text (overrides the config)
+    #[arg(long = "show-urls")]
+    pub show_urls: bool,
+
+    /// Also ignore directories named NAME while searching (repeatable, adds to ignored_dirs)
+    #[arg(long = "ignore-dir", value_name = "NAME")]
+    pub ignore_dirs: Vec<String>,
+
     /// Browse ALL markdown files recursively (including hidden ones AND ignored directories - shows everything)
     #[arg(short = 'a', long = "all")]
     pub all: bool,
+
+    /// Disable all colored output (also enabled by the NO_COLOR environment variable)
+    #[arg(long = "no-color")]
+    pub no_color: bool,
+
+    /// Print discovered markdown file paths and exit
+    #[arg(short = 'l', long = "list")]
+    pub list: bool,
+
+    /// Print the number of markdown files found and exit (after the paths, with --list)
+    #[arg(long = "count", conflicts_with_all = ["json", "raw", "export"])]
+    pub count: bool,
+
+    /// Print the color schemes defined in the config, marking the active one, and exit
+    #[arg(long = "theme-list", conflicts_with_all = ["json", "list", "count", "raw", "export"])]
+    pub theme_list: bool,
+
+    /// Print the parsed AST of each file as JSON and exit
+    #[arg(long = "json")]
+    pub json: bool,
+
+    /// Accept unclosed code fences and emphasis when parsing (with --json) instead of reporting an error
+    #[arg(long = "lenient")]
+    pub lenient: bool,
+
+    /// Print the files (or stdin, with `-`) unchanged and exit, without rendering
+    #[arg(long = "raw", conflicts_with_all = ["json", "list"])]
+    pub raw: bool,
+
+    /// Print the files (or stdin, with `-`) converted to FORMAT and exit
+    #[arg(
+        long = "export",
+        value_name = "FORMAT",
+        value_enum,
+        conflicts_with_all = ["json", "list", "raw"]
+    )]
+    pub export: Option<ExportFormat>,
+
+    /// Use Windows (CRLF) line endings in --export output
+    #[arg(long = "crlf", requires = "export")]
+    pub crlf: bool,
+
+    /// Re-render the open file whenever it changes on disk
+    #[arg(
+        long = "watch",
+        visible_alias = "follow",
+        conflicts_with_all = ["json", "list", "count", "raw", "export"]
+    )]
+    pub watch: bool,
+
+    /// Always open the interactive viewer, even when stdout isn't a terminal
+    #[arg(long = "tui", conflicts_with = "no_tui")]
+    pub tui: bool,
+
+    /// Print the rendered document to stdout instead of opening the viewer
+    #[arg(long = "no-tui", visible_alias = "plain", conflicts_with = "watch")]
+    pub no_tui: bool,
+
+    /// Render stdin and pipes as markdown without checking that they look like it
+    #[arg(long = "force-markdown", conflicts_with = "force_plain")]
+    pub force_markdown: bool,
+
+    /// Show stdin and pipes as plain text instead of rendering them as markdown
+    #[arg(long = "force-plain")]
+    pub force_plain: bool,
+
+    /// Only show lines START to END of each file, e.g. 10:40 (either end may be left out)
+    #[arg(
+        long = "lines",
+        value_name = "START:END",
+        conflicts_with_all = ["json", "export", "watch"]
+    )]
+    pub lines: Option<LineRange>,
+
+    /// Print recently opened files, most recent first, and exit
+    #[arg(long = "recent")]
+    pub recent: bool,
+
+    /// Print version information
+    #[arg(short = 'V', long = "version")]
+    pub version: bool,
+
+    /// Show build target and profile (use with --version)
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print word, character, heading, link and code block counts
+    Stats {
+        /// Markdown file, or directory to count every markdown file in
+        path: PathBuf,
+        /// Print the counts as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+    /// Print the lexer tokens of a markdown file, one per line, for debugging
+    Tokens {
+        /// Markdown file to tokenize
+        file: PathBuf,
+        /// Print how many tokens of each kind there are instead
+        #[arg(long = "stats")]
+        stats: bool,
+    },
+    /// Report problems that keep a markdown file from parsing strictly
+    Lint {
+        /// Markdown file to check
+        file: PathBuf,
+    },
+    /// Inspect the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Print the path of the config file and whether it exists
+    Path,
 }
 
 #[cfg(test)]
@@ -37,28 +201,248 @@ mod tests {
     #[test]
     fn test_cli_parsing() {
         // Test basic file argument
-        let cli = Cli::try_parse_from(&["mark", "test.md"]).unwrap();
-        assert_eq!(cli.file, Some(PathBuf::from("test.md")));
+        let cli = Cli::try_parse_from(["mark", "test.md"]).unwrap();
+        assert_eq!(cli.files, vec![PathBuf::from("test.md")]);
         assert_eq!(cli.width, 0);
     }
 
     #[test]
     fn test_cli_with_width() {
-        let cli = Cli::try_parse_from(&["mark", "--width", "120", "test.md"]).unwrap();
+        let cli = Cli::try_parse_from(["mark", "--width", "120", "test.md"]).unwrap();
         assert_eq!(cli.width, 120);
-        assert_eq!(cli.file, Some(PathBuf::from("test.md")));
+        assert_eq!(cli.files, vec![PathBuf::from("test.md")]);
     }
 
     #[test]
     fn test_cli_with_config() {
-        let cli = Cli::try_parse_from(&["mark", "-c", "config.toml", "test.md"]).unwrap();
+        let cli = Cli::try_parse_from(["mark", "-c", "config.toml", "test.md"]).unwrap();
         assert_eq!(cli.config, Some(PathBuf::from("config.toml")));
     }
 
+    #[test]
+    fn test_multiple_paths() {
+        let cli = Cli::try_parse_from(["mark", "a.md", "b.md", "docs/"]).unwrap();
+        assert_eq!(
+            cli.files,
+            vec![
+                PathBuf::from("a.md"),
+                PathBuf::from("b.md"),
+                PathBuf::from("docs/")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completions_subcommand() {
+        let cli = Cli::try_parse_from(["mark", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Completions { shell: Shell::Zsh })
+        ));
+        assert!(cli.files.is_empty());
+    }
+
+    #[test]
+    fn test_tokens_subcommand() {
+        let cli = Cli::try_parse_from(["mark", "tokens", "test.md"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Tokens { file, stats: false }) if file == std::path::Path::new("test.md")
+        ));
+
+        let cli = Cli::try_parse_from(["mark", "tokens", "--stats", "test.md"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Tokens { stats: true, .. })
+        ));
+    }
+
+    #[test]
+    fn test_config_path_subcommand() {
+        let cli = Cli::try_parse_from(["mark", "-c", "my.toml", "config", "path"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                action: ConfigAction::Path
+            })
+        ));
+        assert_eq!(cli.config, Some(PathBuf::from("my.toml")));
+    }
+
+    #[test]
+    fn test_no_color_flag() {
+        let cli = Cli::try_parse_from(["mark", "--no-color"]).unwrap();
+        assert!(cli.no_color);
+    }
+
+    #[test]
+    fn test_recent_flag() {
+        let cli = Cli::try_parse_from(["mark", "--recent"]).unwrap();
+        assert!(cli.recent);
+        assert!(cli.files.is_empty());
+    }
+
+    #[test]
+    fn test_list_flag() {
+        let cli = Cli::try_parse_from(["mark", "--list", "docs"]).unwrap();
+        assert!(cli.list);
+        assert_eq!(cli.files, vec![PathBuf::from("docs")]);
+    }
+
+    #[test]
+    fn test_count_flag() {
+        let cli = Cli::try_parse_from(["mark", "--count", "docs"]).unwrap();
+        assert!(cli.count);
+        assert!(!cli.list);
+
+        assert!(Cli::try_parse_from(["mark", "--count", "--list", "docs"]).is_ok());
+        assert!(Cli::try_parse_from(["mark", "--count", "--json", "a.md"]).is_err());
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let cli = Cli::try_parse_from(["mark", "--watch", "notes.md"]).unwrap();
+        assert!(cli.watch);
+
+        let cli = Cli::try_parse_from(["mark", "--follow", "notes.md"]).unwrap();
+        assert!(cli.watch);
+
+        assert!(Cli::try_parse_from(["mark", "--watch", "--export", "text", "a.md"]).is_err());
+    }
+
+    #[test]
+    fn test_tui_flags() {
+        let cli = Cli::try_parse_from(["mark", "--plain", "notes.md"]).unwrap();
+        assert!(cli.no_tui);
+        assert!(!cli.tui);
+
+        assert!(
+            Cli::try_parse_from(["mark", "--tui", "notes.md"])
+                .unwrap()
+                .tui
+        );
+        assert!(Cli::try_parse_from(["mark", "--tui", "--no-tui", "notes.md"]).is_err());
+    }
+
+    #[test]
+    fn test_lines_flag() {
+        let cli = Cli::try_parse_from(["mark", "--lines", "10:40", "notes.md"]).unwrap();
+        assert_eq!(
+            cli.lines,
+            Some(LineRange {
+                start: 10,
+                end: Some(40)
+            })
+        );
+        assert!(Cli::try_parse_from(["mark", "--lines", "40:10", "notes.md"]).is_err());
+    }
+
+    #[test]
+    fn test_theme_list_flag() {
+        let cli = Cli::try_parse_from(["mark", "--theme-list"]).unwrap();
+        assert!(cli.theme_list);
+        assert!(Cli::try_parse_from(["mark", "--theme-list", "--json", "a.md"]).is_err());
+    }
+
+    #[test]
+    fn test_version_flag() {
+        let cli = Cli::try_parse_from(["mark", "--version"]).unwrap();
+        assert!(cli.version);
+        assert!(!cli.verbose);
+
+        let cli = Cli::try_parse_from(["mark", "-V", "--verbose"]).unwrap();
+        assert!(cli.version);
+        assert!(cli.verbose);
+
+        let command = <Cli as clap::CommandFactory>::command();
+        assert_eq!(command.get_version(), Some(env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn test_theme_flag() {
+        let cli = Cli::try_parse_from(["mark", "--theme", "light", "test.md"]).unwrap();
+        assert_eq!(cli.theme.as_deref(), Some("light"));
+
+        let cli = Cli::try_parse_from(["mark", "test.md"]).unwrap();
+        assert_eq!(cli.theme, None);
+    }
+
+    #[test]
+    fn test_json_flag() {
+        let cli = Cli::try_parse_from(["mark", "--json", "test.md"]).unwrap();
+        assert!(cli.json);
+        assert!(!cli.lenient);
+
+        let cli = Cli::try_parse_from(["mark", "--json", "--lenient", "test.md"]).unwrap();
+        assert!(cli.lenient);
+        assert_eq!(cli.files, vec![PathBuf::from("test.md")]);
+    }
+
+    #[test]
+    fn test_raw_flag() {
+        let cli = Cli::try_parse_from(["mark", "--raw", "-"]).unwrap();
+        assert!(cli.raw);
+        assert_eq!(cli.files, vec![PathBuf::from("-")]);
+
+        assert!(Cli::try_parse_from(["mark", "--raw", "--json", "test.md"]).is_err());
+    }
+
+    #[test]
+    fn test_export_flags() {
+        let cli = Cli::try_parse_from(["mark", "--export", "html", "test.md"]).unwrap();
+        assert_eq!(cli.export, Some(ExportFormat::Html));
+        assert!(!cli.crlf);
+
+        let cli = Cli::try_parse_from(["mark", "--export", "text", "--crlf", "-"]).unwrap();
+        assert_eq!(cli.export, Some(ExportFormat::Text));
+        assert!(cli.crlf);
+
+        assert!(Cli::try_parse_from(["mark", "--crlf", "test.md"]).is_err());
+    }
+
+    #[test]
+    fn test_show_urls_flag() {
+        let cli = Cli::try_parse_from(["mark", "--show-urls", "test.md"]).unwrap();
+        assert!(cli.show_urls);
+    }
+
+    #[test]
+    fn test_ignore_dir_flag_repeats() {
+        let cli =
+            Cli::try_parse_from(["mark", "--ignore-dir", "drafts", "--ignore-dir=build"]).unwrap();
+        assert_eq!(cli.ignore_dirs, vec!["drafts", "build"]);
+
+        let cli = Cli::try_parse_from(["mark"]).unwrap();
+        assert!(cli.ignore_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_download_flags() {
+        let cli = Cli::try_parse_from(["mark", "--yes"]).unwrap();
+        assert!(cli.yes);
+        assert!(!cli.no_download);
+
+        let cli = Cli::try_parse_from(["mark", "--no-download"]).unwrap();
+        assert!(cli.no_download);
+
+        assert!(Cli::try_parse_from(["mark", "--yes", "--no-download"]).is_err());
+
+        let cli = Cli::try_parse_from(["mark", "--no-config"]).unwrap();
+        assert!(cli.no_config);
+        assert!(Cli::try_parse_from(["mark", "--no-config", "-c", "a.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_as_code_flag() {
+        let cli = Cli::try_parse_from(["mark", "--as-code", "rust", "-"]).unwrap();
+        assert_eq!(cli.as_code.as_deref(), Some("rust"));
+        assert_eq!(cli.files, vec![PathBuf::from("-")]);
+    }
+
     #[test]
     fn test_no_file() {
-        let cli = Cli::try_parse_from(&["mark"]).unwrap();
-        assert_eq!(cli.file, None);
+        let cli = Cli::try_parse_from(["mark"]).unwrap();
+        assert!(cli.files.is_empty());
         assert_eq!(cli.width, 0);
     }
 }
@@ -0,0 +1,201 @@
+use crate::error::{MarkError, Result};
+use crate::search::expand_tilde_from;
+use crate::search::markdown::is_markdown_file;
+use std::path::{Path, PathBuf};
+
+/// Check whether a path argument contains glob metacharacters
+pub fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Expand a leading `~` and drop trailing separators, so `~/notes/` and `~/notes` match
+pub fn normalize_path(path: &Path) -> Result<PathBuf> {
+    normalize_path_from(path, env_lookup)
+}
+
+/// Like `normalize_path`, finding the home directory with `lookup` as in `expand_tilde_from`
+fn normalize_path_from<F>(path: &Path, lookup: F) -> Result<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    Ok(expand_tilde_from(path, lookup)?.components().collect())
+}
+
+fn env_lookup(key: &str) -> Option<String> {
+    std::env::var(key).ok()
+}
+
+/// Expand glob patterns into matching markdown files, keeping other paths as given
+///
+/// Every path is normalized first, including quoted ones the shell left alone.
+pub fn expand_globs(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    expand_globs_from(paths, env_lookup)
+}
+
+/// Like `expand_globs`, finding the home directory with `lookup` as in `expand_tilde_from`
+fn expand_globs_from<F>(paths: &[PathBuf], lookup: F) -> Result<Vec<PathBuf>>
+where
+    F: Fn(&str) -> Option<String> + Copy,
+{
+    let mut expanded = Vec::new();
+
+    for path in paths {
+        let path = normalize_path_from(path, lookup)?;
+        if !is_glob_pattern(&path) {
+            expanded.push(path);
+            continue;
+        }
+
+        let pattern = path.to_string_lossy();
+        let matches = glob::glob(&pattern)
+            .map_err(|e| MarkError::search(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+
+        let mut files: Vec<PathBuf> = matches
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file() && is_markdown_file(path))
+            .collect();
+        files.sort();
+        expanded.extend(files);
+    }
+
+    Ok(expanded)
+}
+
+/// Resolve the paths to open, browsing `default_directory` (or `.`) when none are given
+pub fn resolve_paths(paths: &[PathBuf], default_directory: Option<&str>) -> Result<Vec<PathBuf>> {
+    resolve_paths_from(paths, default_directory, env_lookup)
+}
+
+/// Like `resolve_paths`, finding the home directory with `lookup` as in `expand_tilde_from`
+pub fn resolve_paths_from<F>(
+    paths: &[PathBuf],
+    default_directory: Option<&str>,
+    lookup: F,
+) -> Result<Vec<PathBuf>>
+where
+    F: Fn(&str) -> Option<String> + Copy,
+{
+    if !paths.is_empty() {
+        return expand_globs_from(paths, lookup);
+    }
+
+    match default_directory.map(str::trim) {
+        Some(directory) if !directory.is_empty() => {
+            Ok(vec![normalize_path_from(Path::new(directory), lookup)?])
+        }
+        _ => Ok(vec![PathBuf::from(".")]),
+    }
+}
+
+/// Get the directory whose `.mark.toml` applies: the first directory given, or the first file's
+pub fn project_dir(paths: &[PathBuf]) -> Option<PathBuf> {
+    let path = paths.first()?;
+    if path.is_dir() {
+        return Some(path.clone());
+    }
+
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => Some(parent.to_path_buf()),
+        _ => Some(PathBuf::from(".")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_glob_pattern() {
+        assert!(is_glob_pattern(Path::new("docs/*.md")));
+        assert!(is_glob_pattern(Path::new("docs/**/file?.md")));
+        assert!(!is_glob_pattern(Path::new("docs/README.md")));
+    }
+
+    #[test]
+    fn test_expand_star_pattern() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("a.md")).unwrap();
+        File::create(temp_dir.path().join("b.markdown")).unwrap();
+        File::create(temp_dir.path().join("notes.txt")).unwrap();
+
+        let pattern = temp_dir.path().join("*");
+        let expanded = expand_globs(&[pattern]).unwrap();
+
+        assert_eq!(
+            expanded,
+            vec![
+                temp_dir.path().join("a.md"),
+                temp_dir.path().join("b.markdown")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_pattern_without_matches() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let pattern = temp_dir.path().join("*.md");
+        let expanded = expand_globs(&[pattern]).unwrap();
+
+        assert!(expanded.is_empty());
+    }
+
+    #[test]
+    fn test_plain_paths_are_kept() {
+        let paths = vec![PathBuf::from("missing.md"), PathBuf::from("docs")];
+        assert_eq!(expand_globs(&paths).unwrap(), paths);
+    }
+
+    #[test]
+    fn test_trailing_separators_are_dropped() {
+        let paths = vec![PathBuf::from("docs/"), PathBuf::from("/tmp/notes//")];
+        assert_eq!(
+            expand_globs(&paths).unwrap(),
+            vec![PathBuf::from("docs"), PathBuf::from("/tmp/notes")]
+        );
+    }
+
+    #[test]
+    fn test_no_args_use_default_directory() {
+        let resolved = resolve_paths(&[], Some("/home/user/notes")).unwrap();
+        assert_eq!(resolved, vec![PathBuf::from("/home/user/notes")]);
+    }
+
+    #[test]
+    fn test_no_args_without_default_directory_browse_current() {
+        assert_eq!(resolve_paths(&[], None).unwrap(), vec![PathBuf::from(".")]);
+        assert_eq!(
+            resolve_paths(&[], Some("  ")).unwrap(),
+            vec![PathBuf::from(".")]
+        );
+    }
+
+    #[test]
+    fn test_explicit_args_override_default_directory() {
+        let paths = vec![PathBuf::from("docs")];
+        assert_eq!(
+            resolve_paths(&paths, Some("/home/user/notes")).unwrap(),
+            paths
+        );
+    }
+
+    #[test]
+    fn test_project_dir_from_first_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("README.md");
+        File::create(&file).unwrap();
+
+        assert_eq!(
+            project_dir(&[temp_dir.path().to_path_buf()]),
+            Some(temp_dir.path().to_path_buf())
+        );
+        assert_eq!(project_dir(&[file]), Some(temp_dir.path().to_path_buf()));
+        assert_eq!(
+            project_dir(&[PathBuf::from("README.md")]),
+            Some(PathBuf::from("."))
+        );
+        assert_eq!(project_dir(&[]), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::error::Result;
+use crate::markdown_parser::AstNode;
+use crate::ui::renderer::Renderer;
+use ratatui::crossterm::style::{
+    Attribute, Color as CrosstermColor, SetAttribute, SetBackgroundColor, SetForegroundColor,
+};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use std::io::Write;
+
+/// Where documents are shown: the interactive viewer, or text printed to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Tui,
+    Plain,
+}
+
+/// Pick the output mode, printing plain text when stdout isn't a terminal
+///
+/// `--tui` and `--no-tui` override the detection either way.
+pub fn output_mode(force_tui: bool, force_plain: bool, stdout_is_terminal: bool) -> OutputMode {
+    if force_plain || (!force_tui && !stdout_is_terminal) {
+        OutputMode::Plain
+    } else {
+        OutputMode::Tui
+    }
+}
+
+/// Render `ast` with `renderer` and write it to `out`, colored with ANSI escape codes
+///
+/// Colors come from the rendered styles, so `NO_COLOR` leaves plain text.
+pub fn write_plain<W: Write>(ast: &AstNode, renderer: &Renderer, out: &mut W) -> Result<()> {
+    for line in renderer.render(ast) {
+        write_line(&line, out)?;
+    }
+    Ok(())
+}
+
+fn write_line<W: Write>(line: &Line, out: &mut W) -> Result<()> {
+    for span in &line.spans {
+        let style = line.style.patch(span.style);
+        if style == Style::default() {
+            write!(out, "{}", span.content)?;
+            continue;
+        }
+
+        if let Some(fg) = style.fg {
+            write!(out, "{}", SetForegroundColor(CrosstermColor::from(fg)))?;
+        }
+        if let Some(bg) = style.bg {
+            write!(out, "{}", SetBackgroundColor(CrosstermColor::from(bg)))?;
+        }
+        for (modifier, attribute) in [
+            (Modifier::BOLD, Attribute::Bold),
+            (Modifier::DIM, Attribute::Dim),
+            (Modifier::ITALIC, Attribute::Italic),
+            (Modifier::UNDERLINED, Attribute::Underlined),
+            (Modifier::REVERSED, Attribute::Reverse),
+            (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+        ] {
+            if style.add_modifier.contains(modifier) {
+                write!(out, "{}", SetAttribute(attribute))?;
+            }
+        }
+        write!(out, "{}{}", span.content, SetAttribute(Attribute::Reset))?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+    use crate::ui::renderer::Theme;
+    use crate::ui::style;
+
+    #[test]
+    fn test_non_terminal_stdout_prints_plain_text() {
+        assert_eq!(output_mode(false, false, false), OutputMode::Plain);
+        assert_eq!(output_mode(false, false, true), OutputMode::Tui);
+        assert_eq!(output_mode(true, false, false), OutputMode::Tui);
+        assert_eq!(output_mode(false, true, true), OutputMode::Plain);
+    }
+
+    #[test]
+    fn test_write_plain_colors_unless_no_color() {
+        let ast = parse_markdown("# Title\n\nSome **bold** text").unwrap();
+        let theme = Theme::default();
+
+        let mut out = Vec::new();
+        write_plain(&ast, &Renderer::new(&theme, 40), &mut out).unwrap();
+        let colored = String::from_utf8(out).unwrap();
+        assert!(colored.contains("\x1b["));
+        assert!(colored.contains("Title"));
+
+        style::set_no_color(true);
+        let mut out = Vec::new();
+        write_plain(&ast, &Renderer::new(&theme, 40), &mut out).unwrap();
+        style::set_no_color(false);
+        let plain = String::from_utf8(out).unwrap();
+        assert!(!plain.contains("\x1b[38"));
+        assert!(plain.contains("Some"));
+    }
+}
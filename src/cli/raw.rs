@@ -0,0 +1,86 @@
+use crate::cli::lines::LineRange;
+use crate::error::{MarkError, Result};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+/// Copy each file in `paths` to `out` byte for byte, reading `-` from `stdin`
+///
+/// With no paths, stdin is copied. Nothing is parsed, so this works for any
+/// input the renderer can't handle. With `lines`, only that range of each input is copied.
+pub fn write_raw(
+    paths: &[PathBuf],
+    lines: Option<LineRange>,
+    stdin: &mut dyn Read,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let stdin_only = [PathBuf::from("-")];
+    let paths = if paths.is_empty() {
+        &stdin_only[..]
+    } else {
+        paths
+    };
+
+    for path in paths {
+        if path.as_os_str() == "-" {
+            copy_lines(stdin, lines, out)?;
+        } else if path.is_file() {
+            copy_lines(&mut File::open(path)?, lines, out)?;
+        } else {
+            return Err(MarkError::FileNotFound { path: path.clone() });
+        }
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+fn copy_lines(input: &mut dyn Read, lines: Option<LineRange>, out: &mut dyn Write) -> Result<()> {
+    match lines {
+        Some(range) => {
+            let mut bytes = Vec::new();
+            input.read_to_end(&mut bytes)?;
+            out.write_all(range.slice_bytes(&bytes))?;
+        }
+        None => {
+            io::copy(input, out)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_raw_output_matches_file() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.md");
+        // Not valid markdown or UTF-8, so nothing may be decoded or rendered
+        let content = b"# Title\r\n\n```\nunclosed *fence\n\xff\xfe\n";
+        std::fs::write(&file, content).unwrap();
+
+        let mut out = Vec::new();
+        write_raw(&[file], None, &mut io::empty(), &mut out).unwrap();
+        assert_eq!(out, content);
+    }
+
+    #[test]
+    fn test_raw_reads_stdin_for_dash() {
+        let mut stdin: &[u8] = b"from stdin\n";
+        let mut out = Vec::new();
+        write_raw(&[PathBuf::from("-")], None, &mut stdin, &mut out).unwrap();
+        assert_eq!(out, b"from stdin\n");
+    }
+
+    #[test]
+    fn test_raw_line_range() {
+        let mut stdin: &[u8] = b"one\ntwo\nthree\n";
+        let mut out = Vec::new();
+        let lines = "2:5".parse().ok();
+        write_raw(&[PathBuf::from("-")], lines, &mut stdin, &mut out).unwrap();
+        assert_eq!(out, b"two\nthree\n");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::cli::command;
+use crate::config::Settings;
+use crate::error::{MarkError, Result};
+use crate::markdown_parser::{parse_markdown_or_default_with, stats::DocumentStats};
+use crate::search::{self, markdown::read_lossy};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct FileStats {
+    path: String,
+    #[serde(flatten)]
+    stats: DocumentStats,
+}
+
+/// Write statistics for the markdown file at `path`, or every markdown file below it
+///
+/// Directories get one line per file followed by a total; with `json` the
+/// result is an array of objects with a `path` and the counts instead.
+/// Directories are searched with the browser's filters from `settings`, or
+/// none of them with `show_all`.
+pub fn write_stats(
+    path: &Path,
+    json: bool,
+    settings: &Settings,
+    show_all: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let files = markdown_files(path, settings, show_all)?;
+
+    let mut results = Vec::new();
+    for file in files {
+        let (content, _) = read_lossy(&file)?;
+        let stats = DocumentStats::from_ast(&parse_markdown_or_default_with(
+            &content,
+            settings.strict_emphasis,
+        ));
+        results.push(FileStats {
+            path: file.display().to_string(),
+            stats,
+        });
+    }
+
+    if json {
+        let json = serde_json::to_string_pretty(&results)?;
+        writeln!(out, "{}", json)?;
+        return Ok(());
+    }
+
+    let mut total = DocumentStats::default();
+    for result in &results {
+        writeln!(out, "{}: {}", result.path, result.stats.summary())?;
+        total.add(&result.stats);
+    }
+    if results.len() > 1 {
+        writeln!(out, "Total: {}", total.summary())?;
+    }
+    Ok(())
+}
+
+/// The file itself, or the markdown files found below a directory
+fn markdown_files(path: &Path, settings: &Settings, show_all: bool) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        return Err(MarkError::FileNotFound {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let options = command::search_options(settings, show_all);
+    let files = search::iter_markdown_files(path, &options)?;
+    Ok(files.map(|file| file.path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_directory_stats_with_total() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.md"), "# One\n\nthree more words").unwrap();
+        std::fs::write(dir.path().join("b.md"), "# Two\n\n## Three").unwrap();
+
+        let mut out = Vec::new();
+        write_stats(dir.path(), false, &Settings::default(), false, &mut out).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines[0].ends_with("a.md: 4 words, 19 characters, 1 headings, 0 links, 0 code blocks")
+        );
+        assert!(lines[2].starts_with("Total: 6 words,"));
+        assert!(lines[2].contains("3 headings"));
+    }
+
+    #[test]
+    fn test_directory_stats_use_browser_filters() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.md"), "One").unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        std::fs::write(dir.path().join("node_modules").join("b.md"), "Two").unwrap();
+        std::fs::create_dir(dir.path().join(".drafts")).unwrap();
+        std::fs::write(dir.path().join(".drafts").join("c.md"), "Three").unwrap();
+
+        let mut out = Vec::new();
+        write_stats(dir.path(), false, &Settings::default(), false, &mut out).unwrap();
+        let output = String::from_utf8(out).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("a.md"));
+
+        let mut out = Vec::new();
+        write_stats(dir.path(), false, &Settings::default(), true, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_json_stats() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.md");
+        std::fs::write(&file, "See [docs](https://example.com)").unwrap();
+
+        let mut out = Vec::new();
+        write_stats(&file, true, &Settings::default(), false, &mut out).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(value[0]["links"], 1);
+        assert_eq!(value[0]["words"], 2);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::config::settings::COLOR_SCHEMES;
+use crate::config::MarkConfig;
+use crate::error::Result;
+use std::io::Write;
+
+/// Write the name of each color scheme a theme can select, marking the active one with `*`
+///
+/// Other `[color.NAME]` tables in `config` are left out, since no theme can select them.
+pub fn write_theme_list(config: &MarkConfig, out: &mut dyn Write) -> Result<()> {
+    let settings = &config.settings;
+    let active = settings.resolved_theme();
+    for name in COLOR_SCHEMES {
+        if name != active {
+            writeln!(out, "  {}", name)?;
+        } else if settings.is_auto_theme() {
+            writeln!(out, "* {} (auto)", name)?;
+        } else {
+            writeln!(out, "* {}", name)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: &str = r##"
+background = "#000000"
+text = "#ffffff"
+code_block = "#333333"
+h1 = "#ff0000"
+h2 = "#ff0000"
+h3 = "#ff0000"
+h4 = "#ff0000"
+h5 = "#ff0000"
+h6 = "#ff0000"
+link = "#0000ff"
+passive = "#888888"
+"##;
+
+    fn config(theme: &str) -> MarkConfig {
+        let content = format!(
+            "[settings]\ntheme = \"{theme}\"\nwidth = 80\nsyntax_highlighting = true\n\
+             hidden_files = false\nignored_dirs = []\n\n\
+             [color.dark]\n{COLORS}\n[color.light]\n{COLORS}\n[color.solarized]\n{COLORS}"
+        );
+        MarkConfig::from_toml(&content).unwrap()
+    }
+
+    #[test]
+    fn test_theme_list_marks_active_theme() {
+        let mut out = Vec::new();
+        write_theme_list(&config("light"), &mut out).unwrap();
+        // `solarized` can't be selected with --theme, so it isn't offered
+        assert_eq!(String::from_utf8(out).unwrap(), "  dark\n* light\n");
+    }
+
+    #[test]
+    fn test_theme_list_marks_detected_auto_theme() {
+        let mut config = config("auto");
+        config.settings.detected_theme = Some("light");
+        let mut out = Vec::new();
+        write_theme_list(&config, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  dark\n* light (auto)\n");
+    }
+}
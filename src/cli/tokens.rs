@@ -0,0 +1,60 @@
+use crate::error::Result;
+use crate::markdown_parser::{tokenize, tokenize_stats};
+use std::io::Write;
+
+/// Write the lexer's tokens for `input` to `out`, one per line with its line and column
+pub fn dump_tokens(input: &str, out: &mut dyn Write) -> Result<()> {
+    for token in tokenize(input)? {
+        writeln!(out, "{}:{}\t{:?}", token.line, token.column, token.token)?;
+    }
+    Ok(())
+}
+
+/// Write how many tokens of each kind `input` has, most common first
+pub fn dump_token_stats(input: &str, out: &mut dyn Write) -> Result<()> {
+    let mut stats: Vec<(&str, usize)> = tokenize_stats(input)?.into_iter().collect();
+    stats.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    for (kind, count) in stats {
+        writeln!(out, "{}\t{}", kind, count)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MarkError;
+
+    #[test]
+    fn test_dump_heading_tokens() {
+        let mut out = Vec::new();
+        dump_tokens("# Hi", &mut out).unwrap();
+
+        let dump = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines[0], "1:1\tHash(1)");
+        assert!(dump.contains("Text(\"Hi\")"));
+        assert_eq!(lines.last(), Some(&"1:5\tEof"));
+    }
+
+    #[test]
+    fn test_dump_token_stats() {
+        let mut out = Vec::new();
+        dump_token_stats("**a** *b*", &mut out).unwrap();
+
+        let dump = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["Asterisk\t4", "Text\t2", "Eof\t1", "Whitespace\t1"]
+        );
+    }
+
+    #[test]
+    fn test_dump_reports_lexer_errors() {
+        let mut out = Vec::new();
+        let error = dump_tokens("99999999999", &mut out).unwrap_err();
+        assert!(matches!(error, MarkError::Lexer(_)));
+        assert_eq!(error.position(), Some((1, 1)));
+    }
+}
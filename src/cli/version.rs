@@ -0,0 +1,36 @@
+/// Crate version from Cargo.toml
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Short git commit hash the binary was built from
+pub const GIT_HASH: &str = env!("MARK_GIT_HASH");
+
+/// Build version text, with target and profile details when `verbose` is set
+pub fn version_info(verbose: bool) -> String {
+    let mut info = format!("mark {} ({})", VERSION, GIT_HASH);
+
+    if verbose {
+        info.push_str(&format!("\ntarget:   {}", env!("MARK_BUILD_TARGET")));
+        info.push_str(&format!("\nprofile:  {}", env!("MARK_BUILD_PROFILE")));
+    }
+
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_info_short() {
+        let info = version_info(false);
+        assert!(info.starts_with(&format!("mark {}", env!("CARGO_PKG_VERSION"))));
+        assert!(!info.contains("target:"));
+    }
+
+    #[test]
+    fn test_version_info_verbose() {
+        let info = version_info(true);
+        assert!(info.contains("target:"));
+        assert!(info.contains("profile:"));
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Copies text to the system clipboard
+pub trait Clipboard {
+    fn copy(&self, text: &str) -> io::Result<()>;
+}
+
+/// Copies text by piping it to the platform's clipboard tool
+///
+/// Uses `pbcopy` on macOS, `clip` on Windows, and `wl-copy` or `xclip`
+/// elsewhere depending on whether a Wayland session is running.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClipboard;
+
+impl Clipboard for SystemClipboard {
+    fn copy(&self, text: &str) -> io::Result<()> {
+        let (program, args) = clipboard_command();
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        // Write everything before waiting, so large documents aren't cut short
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        let status = child.wait()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{} exited with {}", program, status),
+            ))
+        }
+    }
+}
+
+/// The command and arguments that read clipboard contents from stdin on this platform
+pub fn clipboard_command() -> (&'static str, &'static [&'static str]) {
+    if cfg!(target_os = "macos") {
+        ("pbcopy", &[])
+    } else if cfg!(windows) {
+        ("clip", &[])
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    }
+}
@@ -1,10 +1,14 @@
 use crate::error::{ConfigError, ConfigResult};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ColorTheme {
     pub dark: DarkColors,
     pub light: LightColors,
+    /// Any other `[color.NAME]` tables, kept as written; see `validate_custom`
+    #[serde(flatten)]
+    pub custom: BTreeMap<String, toml::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +24,12 @@ pub struct DarkColors {
     pub h6: String,
     pub link: String,
     pub passive: String,
+    #[serde(default = "default_dark_search_match")]
+    pub search_match: String,
+    #[serde(default = "default_dark_search_dim")]
+    pub search_dim: String,
+    #[serde(default = "default_dark_current_line")]
+    pub current_line: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +45,78 @@ pub struct LightColors {
     pub h6: String,
     pub link: String,
     pub passive: String,
+    #[serde(default = "default_light_search_match")]
+    pub search_match: String,
+    #[serde(default = "default_light_search_dim")]
+    pub search_dim: String,
+    #[serde(default = "default_light_current_line")]
+    pub current_line: String,
+}
+
+fn default_dark_search_match() -> String {
+    "#c8c8c8".to_string()
+}
+
+fn default_dark_search_dim() -> String {
+    "#646464".to_string()
+}
+
+fn default_light_search_match() -> String {
+    "#000000".to_string()
+}
+
+fn default_light_search_dim() -> String {
+    "#a0a0a0".to_string()
+}
+
+fn default_dark_current_line() -> String {
+    "#2a2a2a".to_string()
+}
+
+fn default_light_current_line() -> String {
+    "#ebebeb".to_string()
+}
+
+impl Default for DarkColors {
+    fn default() -> Self {
+        Self {
+            background: "#000000".to_string(),
+            text: "#ffffff".to_string(),
+            code_block: "#333333".to_string(),
+            h1: "#ff0000".to_string(),
+            h2: "#ff4444".to_string(),
+            h3: "#ff8888".to_string(),
+            h4: "#ffaaaa".to_string(),
+            h5: "#ffcccc".to_string(),
+            h6: "#ffeeee".to_string(),
+            link: "#0000ff".to_string(),
+            passive: "#888888".to_string(),
+            search_match: default_dark_search_match(),
+            search_dim: default_dark_search_dim(),
+            current_line: default_dark_current_line(),
+        }
+    }
+}
+
+impl Default for LightColors {
+    fn default() -> Self {
+        Self {
+            background: "#ffffff".to_string(),
+            text: "#000000".to_string(),
+            code_block: "#f0f0f0".to_string(),
+            h1: "#cc0000".to_string(),
+            h2: "#aa0000".to_string(),
+            h3: "#880000".to_string(),
+            h4: "#660000".to_string(),
+            h5: "#440000".to_string(),
+            h6: "#220000".to_string(),
+            link: "#0000cc".to_string(),
+            passive: "#666666".to_string(),
+            search_match: default_light_search_match(),
+            search_dim: default_light_search_dim(),
+            current_line: default_light_current_line(),
+        }
+    }
 }
 
 impl ColorTheme {
@@ -42,8 +124,41 @@ impl ColorTheme {
     pub fn validate(&self) -> ConfigResult<()> {
         self.dark.validate()?;
         self.light.validate()?;
+        self.validate_custom()
+    }
+
+    /// Check that every other key under `[color]` is a table of hex colors
+    ///
+    /// Without this, a stray `key = value` under `[color]` would be silently kept.
+    pub fn validate_custom(&self) -> ConfigResult<()> {
+        for (name, value) in &self.custom {
+            let Some(table) = value.as_table() else {
+                return Err(ConfigError::invalid_value(
+                    name.as_str(),
+                    "color",
+                    value.to_string().as_str(),
+                    "a [color.NAME] table of hex colors",
+                ));
+            };
+            for (field, color) in table {
+                let field = format!("{}.{}", name, field);
+                match color.as_str() {
+                    Some(color) => validate_hex_color(color, &field)?,
+                    None => return Err(ConfigError::invalid_color(&color.to_string(), &field)),
+                }
+            }
+        }
         Ok(())
     }
+
+    /// Validate the colors of the `dark` or `light` scheme only
+    pub fn validate_scheme(&self, name: &str) -> ConfigResult<()> {
+        match name {
+            "dark" => self.dark.validate(),
+            "light" => self.light.validate(),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl DarkColors {
@@ -61,6 +176,9 @@ impl DarkColors {
             ("h6", &self.h6),
             ("link", &self.link),
             ("passive", &self.passive),
+            ("search_match", &self.search_match),
+            ("search_dim", &self.search_dim),
+            ("current_line", &self.current_line),
         ];
 
         for (field_name, color_value) in colors {
@@ -84,6 +202,9 @@ impl DarkColors {
             ("h6", &self.h6),
             ("link", &self.link),
             ("passive", &self.passive),
+            ("search_match", &self.search_match),
+            ("search_dim", &self.search_dim),
+            ("current_line", &self.current_line),
         ]
     }
 }
@@ -103,6 +224,9 @@ impl LightColors {
             ("h6", &self.h6),
             ("link", &self.link),
             ("passive", &self.passive),
+            ("search_match", &self.search_match),
+            ("search_dim", &self.search_dim),
+            ("current_line", &self.current_line),
         ];
 
         for (field_name, color_value) in colors {
@@ -126,6 +250,9 @@ impl LightColors {
             ("h6", &self.h6),
             ("link", &self.link),
             ("passive", &self.passive),
+            ("search_match", &self.search_match),
+            ("search_dim", &self.search_dim),
+            ("current_line", &self.current_line),
         ]
     }
 }
@@ -188,6 +315,9 @@ mod tests {
             h6: "#ffeeee".to_string(),
             link: "#0000ff".to_string(),
             passive: "#888888".to_string(),
+            search_match: default_dark_search_match(),
+            search_dim: default_dark_search_dim(),
+            current_line: default_dark_current_line(),
         }
     }
 
@@ -204,6 +334,9 @@ mod tests {
             h6: "#220000".to_string(),
             link: "#0000cc".to_string(),
             passive: "#666666".to_string(),
+            search_match: default_light_search_match(),
+            search_dim: default_light_search_dim(),
+            current_line: default_light_current_line(),
         }
     }
 
@@ -256,11 +389,31 @@ mod tests {
         let theme = ColorTheme {
             dark: create_valid_dark_colors(),
             light: create_valid_light_colors(),
+            custom: BTreeMap::new(),
         };
 
         assert!(theme.validate().is_ok());
     }
 
+    #[test]
+    fn test_custom_schemes_must_be_tables_of_colors() {
+        let theme = |custom: &str| ColorTheme {
+            dark: create_valid_dark_colors(),
+            light: create_valid_light_colors(),
+            custom: toml::from_str(custom).unwrap(),
+        };
+
+        assert!(theme("[solarized]\ntext = \"#839496\"").validate().is_ok());
+        assert!(matches!(
+            theme("stray = 1").validate(),
+            Err(ConfigError::InvalidValue { ref field, .. }) if field == "stray"
+        ));
+        assert!(matches!(
+            theme("[solarized]\ntext = \"blue\"").validate(),
+            Err(ConfigError::InvalidColor { ref field, .. }) if field == "solarized.text"
+        ));
+    }
+
     #[test]
     fn test_invalid_color_in_theme() {
         let mut dark_colors = create_valid_dark_colors();
@@ -279,7 +432,7 @@ mod tests {
         let dark_colors = create_valid_dark_colors();
         let all_colors = dark_colors.all_colors();
 
-        assert_eq!(all_colors.len(), 11);
+        assert_eq!(all_colors.len(), 14);
         assert!(all_colors.iter().any(|(name, _)| *name == "background"));
         assert!(all_colors.iter().any(|(name, _)| *name == "text"));
         assert!(all_colors.iter().any(|(name, _)| *name == "h1"));
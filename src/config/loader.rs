@@ -12,19 +12,84 @@ const CONFIG_FILE_PATH: &str = "config/default.toml";
 #[cfg(not(test))]
 const GITHUB_RAW_URL: &str = "https://raw.githubusercontent.com";
 // TODO: Change to correct file
-#[cfg(not(test))]
 const DOCUMENTATION_URL: &str = "https://github.com/Pazl27/mark/blob/main/docs/configuration.md";
 
+/// Name of the per-directory config merged over the global one
+pub const PROJECT_CONFIG_FILE: &str = ".mark.toml";
+
+/// What to do when the config file is missing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownloadPolicy {
+    /// Ask whether to download the default config
+    #[default]
+    Prompt,
+    /// Download the default config without asking, e.g. from `--yes`
+    Accept,
+    /// Fail without downloading, e.g. from `--no-download`
+    Decline,
+}
+
+impl DownloadPolicy {
+    /// Turn `Prompt` into `Decline` when there's no terminal to answer the prompt
+    pub fn resolve(self, interactive: bool) -> Self {
+        match self {
+            Self::Prompt if !interactive => Self::Decline,
+            policy => policy,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct ConfigLoader {
     config_path: PathBuf,
+    project_dir: Option<PathBuf>,
+    theme_override: Option<String>,
+    /// Directories ignored on top of the config's `ignored_dirs`
+    extra_ignored_dirs: Vec<String>,
+    /// The theme `theme = "auto"` resolves to, kept when the config is reloaded
+    detected_theme: Option<&'static str>,
+    download_policy: DownloadPolicy,
+    /// Start from the built-in defaults instead of reading the config file
+    use_defaults: bool,
     config: Option<MarkConfig>,
 }
 
 impl ConfigLoader {
     /// Create config loader with custom path
     pub fn with_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::with_download_policy(path, DownloadPolicy::Prompt)
+    }
+
+    /// Create config loader with custom path, handling a missing file according to `policy`
+    pub fn with_download_policy<P: AsRef<Path>>(path: P, policy: DownloadPolicy) -> Result<Self> {
         let mut loader = Self {
             config_path: path.as_ref().to_path_buf(),
+            project_dir: None,
+            theme_override: None,
+            extra_ignored_dirs: Vec::new(),
+            detected_theme: None,
+            download_policy: policy,
+            use_defaults: false,
+            config: None,
+        };
+
+        loader.load_config()?;
+        Ok(loader)
+    }
+
+    /// Create a config loader that uses the built-in defaults, e.g. from `--no-config`
+    ///
+    /// The file at `path` is never read, but a project's `.mark.toml`, the
+    /// environment and overrides still apply on top of the defaults.
+    pub fn with_defaults<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut loader = Self {
+            config_path: path.as_ref().to_path_buf(),
+            project_dir: None,
+            theme_override: None,
+            extra_ignored_dirs: Vec::new(),
+            detected_theme: None,
+            download_policy: DownloadPolicy::Decline,
+            use_defaults: true,
             config: None,
         };
 
@@ -34,7 +99,7 @@ impl ConfigLoader {
 
     /// Load configuration from file
     fn load_config(&mut self) -> Result<()> {
-        if !self.config_path.exists() {
+        if !self.use_defaults && !self.config_path.exists() {
             return self.handle_missing_config();
         }
 
@@ -44,7 +109,10 @@ impl ConfigLoader {
                 Ok(())
             }
             Err(e) => {
-                self.handle_invalid_config(&e)?;
+                // A bad environment variable isn't the file's fault
+                if !matches!(e, ConfigError::InvalidEnvVar { .. }) {
+                    self.handle_invalid_config();
+                }
                 Err(MarkError::ConfigError(e))
             }
         }
@@ -52,109 +120,105 @@ impl ConfigLoader {
 
     /// Try to load and parse existing config file
     fn try_load_config(&self) -> ConfigResult<MarkConfig> {
-        let content =
+        let content = if self.use_defaults {
+            toml::to_string(&MarkConfig::default()).expect("the default config serializes")
+        } else {
             fs::read_to_string(&self.config_path).map_err(|_| ConfigError::FileNotFound {
                 path: self.config_path.clone(),
-            })?;
+            })?
+        };
 
-        MarkConfig::from_toml(&content)
+        let mut config = match self.project_config_path() {
+            Some(project_path) => {
+                let overrides = fs::read_to_string(&project_path)
+                    .map_err(|_| ConfigError::FileNotFound { path: project_path })?;
+                MarkConfig::from_toml_with_overrides(&content, &overrides)?
+            }
+            None => MarkConfig::from_toml(&content)?,
+        };
+        config.settings.apply_env_overrides()?;
+        if let Some(theme) = &self.theme_override {
+            config.settings.set_theme(theme)?;
+        }
+        config.settings.add_ignored_dirs(&self.extra_ignored_dirs);
+        config.settings.detected_theme = self.detected_theme;
+        // The environment or `--theme` may have switched to a scheme that wasn't checked
+        config.validate()?;
+        Ok(config)
     }
 
     /// Handle missing configuration file
+    #[cfg(test)]
     fn handle_missing_config(&mut self) -> Result<()> {
-        #[cfg(test)]
-        {
-            return Err(MarkError::ConfigError(ConfigError::FileNotFound {
-                path: self.config_path.clone(),
-            }));
-        }
-
-        #[cfg(not(test))]
-        {
-            eprintln!(
-                "Configuration file not found: {}",
-                self.config_path.display()
-            );
-            eprintln!();
-            eprintln!("Would you like to download the default configuration? [Y/n]");
-
-            if self.prompt_yes_no()? {
-                self.download_default_config()?;
-                self.load_config()?;
-            } else {
-                eprintln!();
-                eprintln!(
-                    "Please create a configuration file at: {}",
-                    self.config_path.display()
-                );
-                eprintln!("Documentation: {}", DOCUMENTATION_URL);
-                std::process::exit(1);
-            }
-        }
-
-        Ok(())
+        Err(MarkError::ConfigError(ConfigError::FileNotFound {
+            path: self.config_path.clone(),
+        }))
     }
 
-    /// Handle invalid configuration file
-    fn handle_invalid_config(&self, _error: &ConfigError) -> Result<()> {
-        #[cfg(test)]
-        {
-            return Ok(()); // Don't exit in test mode
-        }
+    /// Handle missing configuration file
+    #[cfg(not(test))]
+    fn handle_missing_config(&mut self) -> Result<()> {
+        use std::io::IsTerminal;
 
-        #[cfg(not(test))]
-        {
-            match _error {
-                ConfigError::TomlParseError { message, line, col } => {
-                    eprintln!(
-                        "Configuration parse error at line {}, column {}: {}",
-                        line, col, message
-                    );
-                }
-                ConfigError::MissingField { field, section } => {
-                    eprintln!(
-                        "Missing required field '{}' in section [{}]",
-                        field, section
-                    );
-                }
-                ConfigError::MissingSection { section } => {
-                    eprintln!("Missing required section [{}]", section);
-                }
-                ConfigError::InvalidValue {
-                    field,
-                    section,
-                    value,
-                    expected,
-                } => {
-                    eprintln!(
-                        "Invalid value '{}' for field '{}' in section [{}]. Expected: {}",
-                        value, field, section, expected
-                    );
-                }
-                ConfigError::InvalidColor { color, field } => {
+        eprintln!(
+            "Configuration file not found: {}",
+            self.config_path.display()
+        );
+        eprintln!();
+
+        let policy = self.download_policy.resolve(std::io::stdin().is_terminal());
+        let accepted = match policy {
+            DownloadPolicy::Accept => true,
+            DownloadPolicy::Decline => {
+                if self.download_policy == DownloadPolicy::Prompt {
                     eprintln!(
-                        "Invalid color '{}' for field '{}'. Expected hex format like '#ffffff'",
-                        color, field
+                        "Not downloading the default configuration: stdin is not a terminal."
                     );
+                    eprintln!("Run with --yes to download it without asking.");
                 }
-                ConfigError::InvalidTheme { theme } => {
-                    eprintln!("Invalid theme '{}'. Must be 'dark' or 'light'", theme);
-                }
-                _ => {
-                    eprintln!("Configuration error: {}", _error);
-                }
+                false
+            }
+            DownloadPolicy::Prompt => {
+                eprintln!("Would you like to download the default configuration? [Y/n]");
+                self.prompt_yes_no()?
             }
+        };
 
+        if accepted {
+            self.download_default_config()?;
+            self.load_config()?;
+        } else if policy == DownloadPolicy::Prompt {
             eprintln!();
-            eprintln!("Configuration file: {}", self.config_path.display());
+            eprintln!("Using the built-in default configuration for now.");
+            eprintln!(
+                "Create {} to customize Mark, or pass --no-config to skip this question.",
+                self.config_path.display()
+            );
             eprintln!("Documentation: {}", DOCUMENTATION_URL);
-
-            std::process::exit(78);
+            self.use_defaults = true;
+            self.load_config()?;
+        } else {
+            eprintln!();
+            eprintln!(
+                "Please create a configuration file at: {}",
+                self.config_path.display()
+            );
+            eprintln!("Documentation: {}", DOCUMENTATION_URL);
+            return Err(MarkError::ConfigError(ConfigError::DownloadDeclined));
         }
 
         Ok(())
     }
 
+    /// Point at the configuration file before an invalid config error is reported
+    ///
+    /// The error itself is returned, so `main` prints it and picks the exit code.
+    fn handle_invalid_config(&self) {
+        eprintln!("Configuration file: {}", self.config_path.display());
+        eprintln!("Documentation: {}", DOCUMENTATION_URL);
+        eprintln!();
+    }
+
     /// Prompt user for yes/no answer
     #[cfg(not(test))]
     fn prompt_yes_no(&self) -> Result<bool> {
@@ -180,29 +244,27 @@ impl ConfigLoader {
 
         // Use blocking client
         let response = reqwest::blocking::get(&url)
-            .map_err(|e| MarkError::network(format!("Failed to download config: {}", e)))?;
+            .map_err(|e| ConfigError::download_failed(e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(MarkError::network(format!(
-                "HTTP {}: Failed to download config",
-                response.status()
-            )));
+            return Err(ConfigError::download_failed(format!("HTTP {}", response.status())).into());
         }
 
         let content = response
             .text()
-            .map_err(|e| MarkError::network(format!("Failed to read response: {}", e)))?;
+            .map_err(|e| ConfigError::download_failed(format!("Failed to read response: {}", e)))?;
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = self.config_path.parent() {
             fs::create_dir_all(parent).map_err(|e| {
-                MarkError::network(format!("Failed to create config directory: {}", e))
+                ConfigError::download_failed(format!("Failed to create config directory: {}", e))
             })?;
         }
 
         // Write config file
-        fs::write(&self.config_path, content)
-            .map_err(|e| MarkError::network(format!("Failed to write config file: {}", e)))?;
+        fs::write(&self.config_path, content).map_err(|e| {
+            ConfigError::download_failed(format!("Failed to write config file: {}", e))
+        })?;
 
         eprintln!(
             "Configuration downloaded to: {}",
@@ -212,6 +274,75 @@ impl ConfigLoader {
         Ok(())
     }
 
+    /// Re-read and validate the config file, keeping the current config if it's invalid
+    pub fn reload(&mut self) -> Result<()> {
+        let config = self.try_load_config().map_err(MarkError::ConfigError)?;
+        self.config = Some(config);
+        Ok(())
+    }
+
+    /// Merge the `.mark.toml` in `dir`, if there is one, over the global config
+    ///
+    /// The previous config is kept if the merged result is invalid.
+    pub fn set_project_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<()> {
+        let previous = self.project_dir.replace(dir.as_ref().to_path_buf());
+        if let Err(e) = self.reload() {
+            self.project_dir = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Use `theme` regardless of the config files and environment, e.g. from `--theme`
+    ///
+    /// The previous config is kept if the theme name is unknown.
+    pub fn set_theme_override(&mut self, theme: &str) -> Result<()> {
+        let previous = self.theme_override.replace(theme.to_string());
+        if let Err(e) = self.reload() {
+            self.theme_override = previous;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Ignore `dirs` along with the config's `ignored_dirs`, e.g. from `--ignore-dir`
+    ///
+    /// The extra directories are kept when the config is reloaded.
+    pub fn add_ignored_dirs(&mut self, dirs: &[String]) {
+        self.extra_ignored_dirs.extend_from_slice(dirs);
+        if let Some(config) = self.config.as_mut() {
+            config.settings.add_ignored_dirs(dirs);
+        }
+    }
+
+    /// Resolve `theme = "auto"` to `theme`, e.g. as detected from the terminal background
+    ///
+    /// The detected theme is kept when the config is reloaded.
+    pub fn set_detected_theme(&mut self, theme: &'static str) {
+        self.detected_theme = Some(theme);
+        if let Some(config) = self.config.as_mut() {
+            config.settings.detected_theme = Some(theme);
+        }
+    }
+
+    /// Get the project config file in use, if any
+    pub fn project_config_path(&self) -> Option<PathBuf> {
+        self.project_dir
+            .as_ref()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .filter(|path| path.is_file())
+    }
+
+    /// Get the path the configuration is loaded from
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// How a missing config file is handled
+    pub fn download_policy(&self) -> DownloadPolicy {
+        self.download_policy
+    }
+
     /// Get loaded configuration
     pub fn config(&self) -> &MarkConfig {
         self.config.as_ref().expect("Configuration not loaded")
@@ -225,13 +356,10 @@ impl ConfigLoader {
 
 /// Get default configuration file path
 pub fn get_default_config_path() -> Result<PathBuf> {
-    let home = std::env::var("HOME")
-        .map_err(|_| MarkError::config("HOME environment variable not set"))?;
+    let home = crate::search::home_dir()
+        .ok_or_else(|| MarkError::config("HOME or USERPROFILE environment variable not set"))?;
 
-    Ok(PathBuf::from(home)
-        .join(".config")
-        .join("mark")
-        .join("config.toml"))
+    Ok(home.join(".config").join("mark").join("config.toml"))
 }
 
 #[cfg(test)]
@@ -304,6 +432,257 @@ passive = "#888888"
         assert_eq!(loader.config().settings.theme, "dark");
     }
 
+    const VALID_CONFIG: &str = r##"
+[settings]
+theme = "dark"
+width = 80
+syntax_highlighting = true
+hidden_files = false
+ignored_dirs = ["node_modules", "go", ".git"]
+
+[color.dark]
+background = "#000000"
+text = "#ffffff"
+code_block = "#333333"
+h1 = "#ff0000"
+h2 = "#ff0000"
+h3 = "#ff0000"
+h4 = "#ff0000"
+h5 = "#ff0000"
+h6 = "#ff0000"
+link = "#0000ff"
+passive = "#888888"
+
+[color.light]
+background = "#ffffff"
+text = "#000000"
+code_block = "#f0f0f0"
+h1 = "#ff0000"
+h2 = "#ff0000"
+h3 = "#ff0000"
+h4 = "#ff0000"
+h5 = "#ff0000"
+h6 = "#ff0000"
+link = "#0000ff"
+passive = "#888888"
+"##;
+
+    #[test]
+    fn test_download_policy_without_terminal() {
+        assert_eq!(
+            DownloadPolicy::Prompt.resolve(false),
+            DownloadPolicy::Decline
+        );
+        assert_eq!(
+            DownloadPolicy::Accept.resolve(false),
+            DownloadPolicy::Accept
+        );
+        assert_eq!(
+            DownloadPolicy::Decline.resolve(false),
+            DownloadPolicy::Decline
+        );
+    }
+
+    #[test]
+    fn test_download_policy_with_terminal() {
+        assert_eq!(DownloadPolicy::Prompt.resolve(true), DownloadPolicy::Prompt);
+        assert_eq!(DownloadPolicy::Accept.resolve(true), DownloadPolicy::Accept);
+        assert_eq!(
+            DownloadPolicy::Decline.resolve(true),
+            DownloadPolicy::Decline
+        );
+    }
+
+    #[test]
+    fn test_no_config_uses_built_in_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("nonexistent.toml");
+
+        let mut loader = ConfigLoader::with_defaults(&config_path).unwrap();
+        assert!(loader.is_loaded());
+        assert!(!config_path.exists());
+        let config = loader.config();
+        assert_eq!(config.settings.theme, "dark");
+        assert_eq!(config.color.dark.text, "#ffffff");
+        assert!(config.validate().is_ok());
+
+        // A project config still applies on top of the defaults
+        fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            "[settings]\nwidth = 100\n",
+        )
+        .unwrap();
+        loader.set_project_dir(temp_dir.path()).unwrap();
+        assert_eq!(loader.config().settings.width, 100);
+        loader.reload().unwrap();
+        assert_eq!(loader.config().settings.theme, "dark");
+    }
+
+    #[test]
+    fn test_reload_picks_up_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        assert_eq!(loader.config().settings.theme, "dark");
+
+        let light = VALID_CONFIG.replace("theme = \"dark\"", "theme = \"light\"");
+        fs::write(&config_path, light).unwrap();
+        loader.reload().unwrap();
+        assert_eq!(loader.config().settings.theme, "light");
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_previous_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        fs::write(&config_path, "[settings\ntheme = \"light\"").unwrap();
+
+        assert!(loader.reload().is_err());
+        assert_eq!(loader.config().settings.theme, "dark");
+    }
+
+    #[test]
+    fn test_project_config_overrides_present_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let project_dir = temp_dir.path().join("project");
+        fs::create_dir(&project_dir).unwrap();
+        fs::write(
+            project_dir.join(PROJECT_CONFIG_FILE),
+            "[settings]\nignored_dirs = [\"target\"]\n",
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        loader.set_project_dir(&project_dir).unwrap();
+
+        let config = loader.config();
+        assert_eq!(config.settings.ignored_dirs, vec!["target".to_string()]);
+        assert_eq!(config.settings.theme, "dark");
+        assert_eq!(config.settings.width, 80);
+        assert_eq!(config.color.dark.text, "#ffffff");
+    }
+
+    #[test]
+    fn test_missing_project_config_keeps_global() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        loader.set_project_dir(temp_dir.path()).unwrap();
+
+        assert!(loader.project_config_path().is_none());
+        assert_eq!(
+            loader.config().settings.ignored_dirs,
+            vec!["node_modules", "go", ".git"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_project_config_keeps_previous() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+        fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            "[settings]\nwidth = 5\n",
+        )
+        .unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        assert!(loader.set_project_dir(temp_dir.path()).is_err());
+        assert!(loader.project_config_path().is_none());
+        assert_eq!(loader.config().settings.width, 80);
+    }
+
+    #[test]
+    fn test_theme_override_replaces_loaded_theme() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        loader.set_theme_override("light").unwrap();
+        assert_eq!(loader.config().settings.theme, "light");
+
+        // The override survives reloading the file
+        loader.reload().unwrap();
+        assert_eq!(loader.config().settings.theme, "light");
+    }
+
+    #[test]
+    fn test_detected_theme_survives_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        loader.set_theme_override("auto").unwrap();
+        assert_eq!(loader.config().settings.resolved_theme(), "dark");
+
+        loader.set_detected_theme("light");
+        assert_eq!(loader.config().settings.resolved_theme(), "light");
+        loader.reload().unwrap();
+        assert_eq!(loader.config().settings.resolved_theme(), "light");
+    }
+
+    #[test]
+    fn test_extra_ignored_dirs_combine_with_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+        let notes = temp_dir.path().join("notes");
+        for dir in ["node_modules", "drafts", "guides"] {
+            fs::create_dir_all(notes.join(dir)).unwrap();
+            fs::write(notes.join(dir).join("page.md"), "# Page").unwrap();
+        }
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        loader.add_ignored_dirs(&["drafts".to_string(), "go".to_string()]);
+        loader.reload().unwrap();
+        let settings = &loader.config().settings;
+        assert_eq!(
+            settings.ignored_dirs,
+            vec!["node_modules", "go", ".git", "drafts"]
+        );
+
+        let paths = crate::search::list_markdown_paths(
+            notes.to_str().unwrap(),
+            &settings.ignored_dirs,
+            &settings.ignored_files,
+            settings.hidden_files,
+            false,
+            settings.follow_symlinks,
+            settings.max_file_size,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![PathBuf::from("guides/page.md")]);
+    }
+
+    #[test]
+    fn test_unknown_theme_override_is_invalid_theme() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, VALID_CONFIG).unwrap();
+
+        let mut loader = ConfigLoader::with_path(&config_path).unwrap();
+        let result = loader.set_theme_override("neon");
+
+        assert!(matches!(
+            result,
+            Err(MarkError::ConfigError(ConfigError::InvalidTheme { .. }))
+        ));
+        assert_eq!(loader.config().settings.theme, "dark");
+    }
+
     #[test]
     fn test_invalid_config_handling() {
         let temp_dir = TempDir::new().unwrap();
@@ -7,18 +7,16 @@ use std::path::PathBuf;
 
 // Re-export main types
 pub use colors::ColorTheme;
-pub use loader::ConfigLoader;
+pub use loader::{ConfigLoader, DownloadPolicy};
 pub use parser::MarkConfig;
 pub use settings::Settings;
 
 use crate::error::MarkError;
 
 pub fn get_default_config_path() -> std::result::Result<PathBuf, MarkError> {
-    let home = std::env::var("HOME")
-        .map_err(|_| crate::error::MarkError::config("HOME environment variable not set"))?;
+    let home = crate::search::home_dir().ok_or_else(|| {
+        crate::error::MarkError::config("HOME or USERPROFILE environment variable not set")
+    })?;
 
-    Ok(std::path::PathBuf::from(home)
-        .join(".config")
-        .join("mark")
-        .join("config.toml"))
+    Ok(home.join(".config").join("mark").join("config.toml"))
 }
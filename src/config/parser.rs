@@ -1,9 +1,12 @@
+use crate::config::settings::{strict_color_schemes, validate_date_format, THEMES};
 use crate::config::{ColorTheme, Settings};
 use crate::error::{ConfigError, ConfigResult};
 use serde::{Deserialize, Serialize};
 
 /// Complete Mark configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The default is the built-in configuration used when there is no config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MarkConfig {
     /// General settings
     pub settings: Settings,
@@ -16,18 +19,7 @@ impl MarkConfig {
     /// Parse configuration from TOML string with strict validation
     pub fn from_toml(content: &str) -> ConfigResult<Self> {
         // First parse as a generic value to check structure
-        let value: toml::Value = toml::from_str(content).map_err(|e| {
-            let (line, col) = if let Some(span) = e.span() {
-                (span.start, span.end)
-            } else {
-                (0, 0)
-            };
-            ConfigError::TomlParseError {
-                message: e.message().to_string(),
-                line,
-                col,
-            }
-        })?;
+        let value = Self::parse_value(content)?;
 
         // Validate structure before deserializing
         Self::validate_structure(&value)?;
@@ -46,6 +38,39 @@ impl MarkConfig {
         Ok(config)
     }
 
+    /// Parse a base configuration with a partial override file merged on top
+    ///
+    /// Only fields present in `overrides` replace those in `base`; tables are
+    /// merged key by key, so e.g. a single color can be changed.
+    pub fn from_toml_with_overrides(base: &str, overrides: &str) -> ConfigResult<Self> {
+        let mut merged = Self::parse_value(base)?;
+        let overrides = Self::parse_value(overrides)?;
+        merge_values(&mut merged, overrides);
+
+        let content = toml::to_string(&merged).map_err(|e| ConfigError::TomlParseError {
+            message: e.to_string(),
+            line: 0,
+            col: 0,
+        })?;
+        Self::from_toml(&content)
+    }
+
+    /// Parse TOML into a generic value
+    fn parse_value(content: &str) -> ConfigResult<toml::Value> {
+        toml::from_str(content).map_err(|e| {
+            let (line, col) = if let Some(span) = e.span() {
+                (span.start, span.end)
+            } else {
+                (0, 0)
+            };
+            ConfigError::TomlParseError {
+                message: e.message().to_string(),
+                line,
+                col,
+            }
+        })
+    }
+
     /// Serialize configuration to TOML string
     pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
         toml::to_string_pretty(self)
@@ -72,15 +97,32 @@ impl MarkConfig {
             .ok_or_else(|| ConfigError::missing_section("settings"))?;
         Self::validate_settings_section(settings)?;
 
-        // Validate color section
+        // Validate color section, checking color values only in the schemes that must be valid
         let color = table["color"]
             .as_table()
             .ok_or_else(|| ConfigError::missing_section("color"))?;
-        Self::validate_color_section(color)?;
+        let theme = settings.get("theme").and_then(|theme| theme.as_str());
+        let strict_colors = settings
+            .get("strict_colors")
+            .and_then(|strict| strict.as_bool())
+            .unwrap_or(false);
+        let strict = strict_color_schemes(theme.unwrap_or("dark"), strict_colors);
+        Self::validate_color_section(color, strict)?;
 
         Ok(())
     }
 
+    /// Check whether a TOML value matches the expected type name
+    fn has_type(value: &toml::Value, expected_type: &str) -> bool {
+        match expected_type {
+            "string" => value.is_str(),
+            "integer" => value.is_integer(),
+            "boolean" => value.is_bool(),
+            "array" => value.is_array(),
+            _ => false,
+        }
+    }
+
     /// Validate settings section
     fn validate_settings_section(settings: &toml::value::Table) -> ConfigResult<()> {
         let required_fields = vec![
@@ -97,15 +139,53 @@ impl MarkConfig {
             }
 
             let value = &settings[field];
-            let is_correct_type = match expected_type {
-                "string" => value.is_str(),
-                "integer" => value.is_integer(),
-                "boolean" => value.is_bool(),
-                "array" => value.is_array(),
-                _ => false,
+            if !Self::has_type(value, expected_type) {
+                return Err(ConfigError::invalid_value(
+                    field,
+                    "settings",
+                    &value.to_string(),
+                    expected_type,
+                ));
+            }
+        }
+
+        let optional_fields = vec![
+            ("ignored_files", "array"),
+            ("follow_symlinks", "boolean"),
+            ("default_directory", "string"),
+            ("max_file_size", "integer"),
+            ("cache_size", "integer"),
+            ("show_urls", "boolean"),
+            ("compact_list", "boolean"),
+            ("wrap_navigation", "boolean"),
+            ("multi_column_list", "boolean"),
+            ("date_format", "string"),
+            ("relative_dates", "boolean"),
+            ("match_filename_only", "boolean"),
+            ("path_display", "string"),
+            ("indent_by_heading", "boolean"),
+            ("use_text_modifiers", "boolean"),
+            ("wrap_code", "boolean"),
+            ("show_frontmatter", "boolean"),
+            ("heading_prefix", "string"),
+            ("conceal", "boolean"),
+            ("strict_colors", "boolean"),
+            ("inline_code_style", "string"),
+            ("code_block_border", "string"),
+            ("strict_emphasis", "boolean"),
+            ("scroll_step", "integer"),
+            ("collapse_blank_lines", "boolean"),
+            ("show_image_urls", "boolean"),
+            ("highlight_current_line", "boolean"),
+            ("auto_open_single", "boolean"),
+        ];
+
+        for (field, expected_type) in optional_fields {
+            let Some(value) = settings.get(field) else {
+                continue;
             };
 
-            if !is_correct_type {
+            if !Self::has_type(value, expected_type) {
                 return Err(ConfigError::invalid_value(
                     field,
                     "settings",
@@ -117,14 +197,19 @@ impl MarkConfig {
 
         // Validate theme value
         if let Some(theme) = settings["theme"].as_str() {
-            if theme != "dark" && theme != "light" {
+            if !THEMES.contains(&theme) {
                 return Err(ConfigError::invalid_theme(theme));
             }
         }
 
+        // Validate date format
+        if let Some(format) = settings.get("date_format").and_then(|v| v.as_str()) {
+            validate_date_format(format)?;
+        }
+
         // Validate width value
         if let Some(width) = settings["width"].as_integer() {
-            if width < 20 || width > 200 {
+            if !(20..=200).contains(&width) {
                 return Err(ConfigError::invalid_value(
                     "width",
                     "settings",
@@ -137,8 +222,8 @@ impl MarkConfig {
         Ok(())
     }
 
-    /// Validate color section
-    fn validate_color_section(color: &toml::value::Table) -> ConfigResult<()> {
+    /// Validate color section, checking the hex values of the `strict` schemes
+    fn validate_color_section(color: &toml::value::Table, strict: &[&str]) -> ConfigResult<()> {
         // Check required sub-sections
         if !color.contains_key("dark") {
             return Err(ConfigError::missing_section("color.dark"));
@@ -152,19 +237,23 @@ impl MarkConfig {
         let dark = color["dark"]
             .as_table()
             .ok_or_else(|| ConfigError::missing_section("color.dark"))?;
-        Self::validate_color_fields(dark, "color.dark")?;
+        Self::validate_color_fields(dark, "color.dark", strict.contains(&"dark"))?;
 
         // Validate light colors
         let light = color["light"]
             .as_table()
             .ok_or_else(|| ConfigError::missing_section("color.light"))?;
-        Self::validate_color_fields(light, "color.light")?;
+        Self::validate_color_fields(light, "color.light", strict.contains(&"light"))?;
 
         Ok(())
     }
 
-    /// Validate color fields in a theme
-    fn validate_color_fields(colors: &toml::value::Table, section: &str) -> ConfigResult<()> {
+    /// Validate color fields in a theme, including their hex values if `check_values`
+    fn validate_color_fields(
+        colors: &toml::value::Table,
+        section: &str,
+        check_values: bool,
+    ) -> ConfigResult<()> {
         let required_color_fields = vec![
             "background",
             "text",
@@ -193,7 +282,21 @@ impl MarkConfig {
                 )
             })?;
 
-            Self::validate_hex_color(color_value, field)?;
+            if check_values {
+                Self::validate_hex_color(color_value, field)?;
+            }
+        }
+
+        for field in ["search_match", "search_dim", "current_line"] {
+            let Some(value) = colors.get(field) else {
+                continue;
+            };
+            let color_value = value.as_str().ok_or_else(|| {
+                ConfigError::invalid_value(field, section, &value.to_string(), "string (hex color)")
+            })?;
+            if check_values {
+                Self::validate_hex_color(color_value, field)?;
+            }
         }
 
         Ok(())
@@ -218,19 +321,41 @@ impl MarkConfig {
     }
 
     /// Additional validation after deserialization
+    ///
+    /// Colors are only checked in the schemes the theme can use, see
+    /// [`MarkConfig::color_warnings`] for the others.
     pub fn validate(&self) -> ConfigResult<()> {
         // Validate settings
         self.settings.validate()?;
 
         // Validate colors
-        self.color.validate()?;
+        for scheme in self.strict_color_schemes() {
+            self.color.validate_scheme(scheme)?;
+        }
+        self.color.validate_custom()?;
 
         Ok(())
     }
 
+    /// Problems with colors in schemes the current theme doesn't use
+    ///
+    /// These don't stop the config from loading, but would if the theme changed.
+    pub fn color_warnings(&self) -> Vec<ConfigError> {
+        let strict = self.strict_color_schemes();
+        ["dark", "light"]
+            .into_iter()
+            .filter(|scheme| !strict.contains(scheme))
+            .filter_map(|scheme| self.color.validate_scheme(scheme).err())
+            .collect()
+    }
+
+    fn strict_color_schemes(&self) -> &'static [&'static str] {
+        strict_color_schemes(&self.settings.theme, self.settings.strict_colors)
+    }
+
     /// Get current theme colors based on settings
     pub fn current_colors(&self) -> Result<&dyn std::fmt::Debug, ConfigError> {
-        match self.settings.theme.as_str() {
+        match self.settings.resolved_theme() {
             "dark" => Ok(&self.color.dark),
             "light" => Ok(&self.color.light),
             theme => Err(ConfigError::invalid_theme(theme)),
@@ -275,6 +400,23 @@ impl MarkConfig {
     }
 }
 
+/// Recursively merge `overrides` into `base`, replacing non-table values
+fn merge_values(base: &mut toml::Value, overrides: toml::Value) {
+    match (base, overrides) {
+        (toml::Value::Table(base), toml::Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -383,6 +525,69 @@ mod tests {
         ));
     }
 
+    const INVALID_LIGHT_COLOR: &str = r##"
+        [color.dark]
+        background = "#000000"
+        text = "#ffffff"
+        code_block = "#333333"
+        h1 = "#ff0000"
+        h2 = "#ff0000"
+        h3 = "#ff0000"
+        h4 = "#ff0000"
+        h5 = "#ff0000"
+        h6 = "#ff0000"
+        link = "#0000ff"
+        passive = "#888888"
+
+        [color.light]
+        background = "#ffffff"
+        text = "#00000"
+        code_block = "#f0f0f0"
+        h1 = "#ff0000"
+        h2 = "#ff0000"
+        h3 = "#ff0000"
+        h4 = "#ff0000"
+        h5 = "#ff0000"
+        h6 = "#ff0000"
+        link = "#0000ff"
+        passive = "#888888"
+        "##;
+
+    fn config_with_invalid_light_color(settings: &str) -> String {
+        format!(
+            "[settings]\nwidth = 80\nsyntax_highlighting = true\nhidden_files = false\nignored_dirs = []\n{}\n{}",
+            settings, INVALID_LIGHT_COLOR
+        )
+    }
+
+    #[test]
+    fn test_invalid_inactive_theme_color_is_a_warning() {
+        let config =
+            MarkConfig::from_toml(&config_with_invalid_light_color("theme = \"dark\"")).unwrap();
+        let warnings = config.color_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(matches!(
+            &warnings[0],
+            ConfigError::InvalidColor { color, .. } if color == "#00000"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_active_theme_color_is_an_error() {
+        for settings in [
+            "theme = \"light\"",
+            "theme = \"auto\"",
+            "theme = \"dark\"\nstrict_colors = true",
+        ] {
+            let result = MarkConfig::from_toml(&config_with_invalid_light_color(settings));
+            assert!(
+                matches!(result, Err(ConfigError::InvalidColor { .. })),
+                "{}",
+                settings
+            );
+        }
+    }
+
     #[test]
     fn test_valid_config() {
         let valid_config = r##"
@@ -1,6 +1,59 @@
 use crate::error::{ConfigError, ConfigResult};
+use crate::search::markdown::is_valid_date_format;
 use serde::{Deserialize, Serialize};
 
+/// Theme names accepted in `[settings] theme`
+pub const THEMES: [&str; 3] = ["dark", "light", "auto"];
+
+/// Color schemes a theme can select, in the order `--theme-list` prints them
+pub const COLOR_SCHEMES: [&str; 2] = ["dark", "light"];
+
+/// Values accepted in `[settings] heading_prefix`
+pub const HEADING_PREFIXES: [&str; 3] = ["none", "hash", "bar"];
+
+/// Values accepted in `[settings] inline_code_style`
+pub const INLINE_CODE_STYLES: [&str; 2] = ["background", "backticks"];
+
+/// Values accepted in `[settings] code_block_border`
+pub const CODE_BLOCK_BORDERS: [&str; 3] = ["none", "bar", "box"];
+
+/// Values accepted in `[settings] path_display`
+pub const PATH_DISPLAYS: [&str; 3] = ["relative", "absolute", "name"];
+
+/// Color schemes whose colors must be valid for a config using `theme` to load
+///
+/// Only the scheme in use is checked unless `strict_colors` is set; `auto` may
+/// pick either one, so both are checked then.
+pub fn strict_color_schemes(theme: &str, strict_colors: bool) -> &'static [&'static str] {
+    match theme {
+        _ if strict_colors => &["dark", "light"],
+        "auto" => &["dark", "light"],
+        "light" => &["light"],
+        _ => &["dark"],
+    }
+}
+
+/// Check that `format` is a strftime format the file browser can show dates with
+pub fn validate_date_format(format: &str) -> ConfigResult<()> {
+    if is_valid_date_format(format) {
+        return Ok(());
+    }
+    Err(ConfigError::invalid_value(
+        "date_format",
+        "settings",
+        format,
+        "a strftime format",
+    ))
+}
+
+/// Pick a theme for a terminal background luminance, defaulting to dark when unknown
+pub fn theme_for_background(luminance: Option<f64>) -> &'static str {
+    match luminance {
+        Some(luminance) if luminance > 0.5 => "light",
+        _ => "dark",
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub theme: String,
@@ -8,13 +61,171 @@ pub struct Settings {
     pub syntax_highlighting: bool,
     pub hidden_files: bool,
     pub ignored_dirs: Vec<String>,
+    #[serde(default)]
+    pub ignored_files: Vec<String>,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_directory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    #[serde(default = "default_cache_size")]
+    pub cache_size: usize,
+    #[serde(default)]
+    pub show_urls: bool,
+    #[serde(default)]
+    pub compact_list: bool,
+    #[serde(default)]
+    pub wrap_navigation: bool,
+    #[serde(default)]
+    pub multi_column_list: bool,
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    #[serde(default)]
+    pub relative_dates: bool,
+    #[serde(default)]
+    pub match_filename_only: bool,
+    #[serde(default = "default_path_display")]
+    pub path_display: String,
+    #[serde(default)]
+    pub indent_by_heading: bool,
+    #[serde(default = "default_use_text_modifiers")]
+    pub use_text_modifiers: bool,
+    #[serde(default)]
+    pub wrap_code: bool,
+    #[serde(default)]
+    pub show_frontmatter: bool,
+    #[serde(default = "default_heading_prefix")]
+    pub heading_prefix: String,
+    #[serde(default)]
+    pub conceal: bool,
+    #[serde(default)]
+    pub strict_colors: bool,
+    #[serde(default = "default_inline_code_style")]
+    pub inline_code_style: String,
+    #[serde(default = "default_code_block_border")]
+    pub code_block_border: String,
+    #[serde(default)]
+    pub strict_emphasis: bool,
+    #[serde(default = "default_scroll_step")]
+    pub scroll_step: usize,
+    #[serde(default)]
+    pub collapse_blank_lines: bool,
+    #[serde(default = "default_show_image_urls")]
+    pub show_image_urls: bool,
+    #[serde(default)]
+    pub highlight_current_line: bool,
+    #[serde(default)]
+    pub auto_open_single: bool,
+    /// The theme `theme = "auto"` resolves to, detected from the terminal at startup
+    #[serde(skip)]
+    pub detected_theme: Option<&'static str>,
+}
+
+/// Directories skipped by default, matching the documented `config.toml`
+fn default_ignored_dirs() -> Vec<String> {
+    [
+        "node_modules",
+        "go",
+        ".git",
+        "target",
+        "build",
+        "dist",
+        ".vscode",
+        ".idea",
+        ".next",
+        "vendor",
+        "__pycache__",
+        ".pytest_cache",
+        "coverage",
+    ]
+    .iter()
+    .map(|dir| dir.to_string())
+    .collect()
+}
+
+fn default_cache_size() -> usize {
+    crate::markdown_parser::cache::DEFAULT_CACHE_SIZE
+}
+
+fn default_date_format() -> String {
+    crate::search::markdown::DEFAULT_DATE_FORMAT.to_string()
+}
+
+fn default_scroll_step() -> usize {
+    1
+}
+
+fn default_use_text_modifiers() -> bool {
+    true
+}
+
+fn default_show_image_urls() -> bool {
+    true
+}
+
+fn default_heading_prefix() -> String {
+    "none".to_string()
+}
+
+fn default_inline_code_style() -> String {
+    "background".to_string()
+}
+
+fn default_code_block_border() -> String {
+    "none".to_string()
+}
+
+fn default_path_display() -> String {
+    "relative".to_string()
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            width: 80,
+            syntax_highlighting: true,
+            hidden_files: false,
+            ignored_dirs: default_ignored_dirs(),
+            ignored_files: Vec::new(),
+            follow_symlinks: false,
+            default_directory: None,
+            max_file_size: None,
+            cache_size: default_cache_size(),
+            show_urls: false,
+            compact_list: false,
+            wrap_navigation: false,
+            multi_column_list: false,
+            date_format: default_date_format(),
+            relative_dates: false,
+            match_filename_only: false,
+            path_display: default_path_display(),
+            indent_by_heading: false,
+            use_text_modifiers: default_use_text_modifiers(),
+            wrap_code: false,
+            show_frontmatter: false,
+            heading_prefix: default_heading_prefix(),
+            conceal: false,
+            strict_colors: false,
+            inline_code_style: default_inline_code_style(),
+            code_block_border: default_code_block_border(),
+            strict_emphasis: false,
+            scroll_step: default_scroll_step(),
+            collapse_blank_lines: false,
+            show_image_urls: default_show_image_urls(),
+            highlight_current_line: false,
+            auto_open_single: false,
+            detected_theme: None,
+        }
+    }
 }
 
 impl Settings {
     /// Validate the settings configuration
     pub fn validate(&self) -> ConfigResult<()> {
         // Validate theme
-        if self.theme != "dark" && self.theme != "light" {
+        if !THEMES.contains(&self.theme.as_str()) {
             return Err(ConfigError::invalid_theme(self.theme.as_str()));
         }
 
@@ -28,23 +239,154 @@ impl Settings {
             ));
         }
 
+        if self.scroll_step == 0 || self.scroll_step > 100 {
+            return Err(ConfigError::invalid_value(
+                "scroll_step",
+                "settings",
+                &self.scroll_step.to_string(),
+                "1-100",
+            ));
+        }
+
+        validate_date_format(&self.date_format)?;
+
+        if !HEADING_PREFIXES.contains(&self.heading_prefix.as_str()) {
+            return Err(ConfigError::invalid_value(
+                "heading_prefix",
+                "settings",
+                &self.heading_prefix,
+                "none, hash or bar",
+            ));
+        }
+
+        if !INLINE_CODE_STYLES.contains(&self.inline_code_style.as_str()) {
+            return Err(ConfigError::invalid_value(
+                "inline_code_style",
+                "settings",
+                &self.inline_code_style,
+                "background or backticks",
+            ));
+        }
+
+        if !CODE_BLOCK_BORDERS.contains(&self.code_block_border.as_str()) {
+            return Err(ConfigError::invalid_value(
+                "code_block_border",
+                "settings",
+                &self.code_block_border,
+                "none, bar or box",
+            ));
+        }
+
+        if !PATH_DISPLAYS.contains(&self.path_display.as_str()) {
+            return Err(ConfigError::invalid_value(
+                "path_display",
+                "settings",
+                &self.path_display,
+                "relative, absolute or name",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Apply `MARK_THEME`, `MARK_WIDTH` and `MARK_HIDDEN_FILES` from the environment
+    pub fn apply_env_overrides(&mut self) -> ConfigResult<()> {
+        self.apply_overrides_from(|name| std::env::var(name).ok())
+    }
+
+    /// Apply overrides using `lookup` to read variables, then re-validate
+    pub fn apply_overrides_from<F: Fn(&str) -> Option<String>>(
+        &mut self,
+        lookup: F,
+    ) -> ConfigResult<()> {
+        if let Some(theme) = lookup("MARK_THEME") {
+            let theme = theme.trim();
+            if !THEMES.contains(&theme) {
+                return Err(ConfigError::invalid_env_var(
+                    "MARK_THEME",
+                    theme,
+                    "dark, light or auto",
+                ));
+            }
+            self.theme = theme.to_string();
+        }
+
+        if let Some(width) = lookup("MARK_WIDTH") {
+            self.width = width
+                .trim()
+                .parse()
+                .ok()
+                .filter(|width| (20..=200).contains(width))
+                .ok_or_else(|| {
+                    ConfigError::invalid_env_var("MARK_WIDTH", width.as_str(), "20-200")
+                })?;
+        }
+
+        if let Some(hidden) = lookup("MARK_HIDDEN_FILES") {
+            self.hidden_files = parse_bool(&hidden).ok_or_else(|| {
+                ConfigError::invalid_env_var("MARK_HIDDEN_FILES", hidden.as_str(), "boolean")
+            })?;
+        }
+
+        self.validate()
+    }
+
+    /// Ignore the directories in `dirs` as well, e.g. from `--ignore-dir`
+    pub fn add_ignored_dirs(&mut self, dirs: &[String]) {
+        for dir in dirs {
+            if !self.ignored_dirs.contains(dir) {
+                self.ignored_dirs.push(dir.clone());
+            }
+        }
+    }
+
+    /// Switch to the theme called `name`, rejecting unknown names
+    pub fn set_theme(&mut self, name: &str) -> ConfigResult<()> {
+        if !THEMES.contains(&name) {
+            return Err(ConfigError::invalid_theme(name));
+        }
+        self.theme = name.to_string();
         Ok(())
     }
 
+    /// Check if the theme should follow the terminal background
+    pub fn is_auto_theme(&self) -> bool {
+        self.theme == "auto"
+    }
+
+    /// Get the concrete theme in use, resolving `auto` to the detected one (dark if none was)
+    pub fn resolved_theme(&self) -> &str {
+        if self.is_auto_theme() {
+            self.detected_theme.unwrap_or("dark")
+        } else {
+            &self.theme
+        }
+    }
+
     /// Check if using dark theme
     pub fn is_dark_theme(&self) -> bool {
-        self.theme == "dark"
+        self.resolved_theme() == "dark"
     }
 
     /// Check if using light theme
     pub fn is_light_theme(&self) -> bool {
-        self.theme == "light"
+        self.resolved_theme() == "light"
+    }
+}
+
+/// Parse a boolean from an environment variable value
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::MarkConfig;
 
     #[test]
     fn test_valid_settings() {
@@ -54,6 +396,7 @@ mod tests {
             syntax_highlighting: true,
             hidden_files: false,
             ignored_dirs: vec!["node_modules".to_string(), "go".to_string()],
+            ..Default::default()
         };
 
         assert!(settings.validate().is_ok());
@@ -69,6 +412,7 @@ mod tests {
             syntax_highlighting: true,
             hidden_files: false,
             ignored_dirs: vec![],
+            ..Default::default()
         };
 
         let result = settings.validate();
@@ -87,6 +431,7 @@ mod tests {
             syntax_highlighting: true,
             hidden_files: false,
             ignored_dirs: vec![],
+            ..Default::default()
         };
 
         let result = settings.validate();
@@ -97,6 +442,77 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_invalid_scroll_step() {
+        let settings = Settings {
+            scroll_step: 0,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            settings.validate().unwrap_err(),
+            ConfigError::InvalidValue { .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_date_format() {
+        let settings = Settings {
+            date_format: "%Y-%Q".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            settings.validate().unwrap_err(),
+            ConfigError::InvalidValue { .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_heading_prefix() {
+        let settings = Settings {
+            heading_prefix: "stars".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            settings.validate().unwrap_err(),
+            ConfigError::InvalidValue { .. }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_code_styles() {
+        for settings in [
+            Settings {
+                inline_code_style: "quotes".to_string(),
+                ..Default::default()
+            },
+            Settings {
+                code_block_border: "double".to_string(),
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                settings.validate().unwrap_err(),
+                ConfigError::InvalidValue { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_path_display() {
+        let settings = Settings {
+            path_display: "full".to_string(),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            settings.validate().unwrap_err(),
+            ConfigError::InvalidValue { .. }
+        ));
+    }
+
     #[test]
     fn test_theme_helpers() {
         let dark_settings = Settings {
@@ -105,6 +521,7 @@ mod tests {
             syntax_highlighting: true,
             hidden_files: false,
             ignored_dirs: vec![],
+            ..Default::default()
         };
 
         let light_settings = Settings {
@@ -113,6 +530,7 @@ mod tests {
             syntax_highlighting: true,
             hidden_files: false,
             ignored_dirs: vec![],
+            ..Default::default()
         };
 
         assert!(dark_settings.is_dark_theme());
@@ -121,4 +539,105 @@ mod tests {
         assert!(!light_settings.is_dark_theme());
         assert!(light_settings.is_light_theme());
     }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let pairs: Vec<(String, String)> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        }
+    }
+
+    #[test]
+    fn test_default_ignored_dirs_match_documented_config() {
+        let documented =
+            MarkConfig::from_toml(include_str!("../../docs/src/user-guide/config.toml")).unwrap();
+        assert_eq!(
+            Settings::default().ignored_dirs,
+            documented.settings.ignored_dirs
+        );
+    }
+
+    #[test]
+    fn test_env_overrides_width() {
+        let mut settings = Settings::default();
+        settings
+            .apply_overrides_from(vars(&[("MARK_WIDTH", "120")]))
+            .unwrap();
+        assert_eq!(settings.width, 120);
+    }
+
+    #[test]
+    fn test_env_overrides_theme_and_hidden_files() {
+        let mut settings = Settings::default();
+        settings
+            .apply_overrides_from(vars(&[
+                ("MARK_THEME", "light"),
+                ("MARK_HIDDEN_FILES", "true"),
+            ]))
+            .unwrap();
+        assert!(settings.is_light_theme());
+        assert!(settings.hidden_files);
+    }
+
+    #[test]
+    fn test_env_override_out_of_range_width_errors() {
+        let mut settings = Settings::default();
+        let result = settings.apply_overrides_from(vars(&[("MARK_WIDTH", "500")]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnvVar { ref name, .. }) if name == "MARK_WIDTH"
+        ));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("environment variable MARK_WIDTH"));
+
+        let result = settings.apply_overrides_from(vars(&[("MARK_WIDTH", "wide")]));
+        assert!(matches!(result, Err(ConfigError::InvalidEnvVar { .. })));
+
+        let result = settings.apply_overrides_from(vars(&[("MARK_THEME", "neon")]));
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidEnvVar { ref name, .. }) if name == "MARK_THEME"
+        ));
+    }
+
+    #[test]
+    fn test_env_override_invalid_boolean_errors() {
+        let mut settings = Settings::default();
+        let result = settings.apply_overrides_from(vars(&[("MARK_HIDDEN_FILES", "maybe")]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_theme_for_background() {
+        assert_eq!(theme_for_background(Some(0.0)), "dark");
+        assert_eq!(theme_for_background(Some(0.02)), "dark");
+        assert_eq!(theme_for_background(Some(0.9)), "light");
+        assert_eq!(theme_for_background(Some(1.0)), "light");
+        assert_eq!(theme_for_background(None), "dark");
+    }
+
+    #[test]
+    fn test_auto_theme_resolves_to_detected() {
+        let mut settings = Settings {
+            theme: "auto".to_string(),
+            ..Default::default()
+        };
+        assert!(settings.validate().is_ok());
+        assert!(settings.is_dark_theme());
+
+        settings.detected_theme = Some(theme_for_background(Some(0.95)));
+        assert_eq!(settings.resolved_theme(), "light");
+        assert!(settings.is_light_theme());
+
+        settings.detected_theme = Some(theme_for_background(None));
+        assert!(settings.is_dark_theme());
+    }
 }
@@ -1,5 +1,7 @@
+use crate::markdown_parser::lexer::{BOM, DEFAULT_TAB_WIDTH};
 use std::path::PathBuf;
 use thiserror::Error;
+use unicode_width::UnicodeWidthChar;
 
 /// Application error types
 #[derive(Error, Debug)]
@@ -10,6 +12,9 @@ pub enum MarkError {
     #[error("File not found: {path}")]
     FileNotFound { path: PathBuf },
 
+    #[error("Not a file: {path}")]
+    NotAFile { path: PathBuf },
+
     #[error("Invalid file format: {path}. Expected markdown file (.md, .markdown)")]
     InvalidFileFormat { path: PathBuf },
 
@@ -28,11 +33,23 @@ pub enum MarkError {
     #[error("Search error: {message}")]
     Search { message: String },
 
+    #[error("Image error: {message}")]
+    Image { message: String },
+
     #[error("Lexer error: {0}")]
     Lexer(#[from] LexerError),
 
     #[error("Parser error: {0}")]
     Parser(#[from] ParseError),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Watch error: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[error("Found {problems} problem(s) in the markdown")]
+    Lint { problems: usize },
 }
 
 /// Configuration-specific error types
@@ -61,6 +78,13 @@ pub enum ConfigError {
         expected: String,
     },
 
+    #[error("Invalid value '{value}' for environment variable {name}. Expected: {expected}")]
+    InvalidEnvVar {
+        name: String,
+        value: String,
+        expected: String,
+    },
+
     #[error("Missing required section: [{section}]")]
     MissingSection { section: String },
 
@@ -69,7 +93,7 @@ pub enum ConfigError {
     )]
     InvalidColor { color: String, field: String },
 
-    #[error("Invalid theme: '{theme}'. Must be 'dark' or 'light'")]
+    #[error("Invalid theme: '{theme}'. Must be 'dark', 'light' or 'auto'")]
     InvalidTheme { theme: String },
 
     #[error("Failed to create config directory: {path}")]
@@ -141,6 +165,13 @@ pub enum ParseError {
         line: usize,
         column: usize,
     },
+
+    #[error("Nesting deeper than {limit} levels at line {line}, column {column}")]
+    NestingTooDeep {
+        limit: usize,
+        line: usize,
+        column: usize,
+    },
 }
 
 impl From<crate::error::LexerError> for ParseError {
@@ -263,19 +294,86 @@ impl MarkError {
         }
     }
 
+    /// Create a new image error
+    pub fn image<S: Into<String>>(message: S) -> Self {
+        Self::Image {
+            message: message.into(),
+        }
+    }
+
     /// Get the exit code for this error
     pub fn exit_code(&self) -> i32 {
         match self {
             Self::FileNotFound { .. } => 2,
-            Self::InvalidFileFormat { .. } | Self::InvalidWidth { .. } => 22,
+            Self::NotAFile { .. } | Self::InvalidFileFormat { .. } | Self::InvalidWidth { .. } => {
+                22
+            }
             Self::Config { .. } | Self::ConfigError(_) => 78,
             Self::Network { .. } => 7,
             Self::Search { .. } => 3,
             Self::Lexer(_) => 65,
             Self::Parser(_) => 66,
-            Self::Io(_) => 1,
+            Self::Io(_)
+            | Self::Json(_)
+            | Self::Watch(_)
+            | Self::Image { .. }
+            | Self::Lint { .. } => 1,
+        }
+    }
+
+    /// Line and column of a lexer or parser error, if it has one
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Lexer(error) => Some(error.position()),
+            Self::Parser(error) => error.position(),
+            _ => None,
+        }
+    }
+
+    /// The source line a lexer or parser error points at, with a caret under its column
+    pub fn snippet(&self, source: &str) -> Option<String> {
+        let (line, column) = self.position()?;
+        source_snippet(source, line, column)
+    }
+}
+
+/// Show line `line` of `source` with a `^` under `column`, the way rustc does
+///
+/// Columns are counted the way the lexer counts them, with tabs expanded to
+/// the next tab stop. Returns `None` if `source` has no such line.
+pub fn source_snippet(source: &str, line: usize, column: usize) -> Option<String> {
+    let source = source.strip_prefix(BOM).unwrap_or(source);
+    let text = source.lines().nth(line.checked_sub(1)?)?;
+
+    let mut expanded = String::new();
+    let mut offset = 0;
+    let mut current = 1;
+    for ch in text.chars() {
+        let (next, width) = if ch == '\t' {
+            let stop = ((current - 1) / DEFAULT_TAB_WIDTH + 1) * DEFAULT_TAB_WIDTH + 1;
+            expanded.push_str(&" ".repeat(stop - current));
+            (stop, stop - current)
+        } else {
+            expanded.push(ch);
+            (current + 1, ch.width().unwrap_or(0))
+        };
+        if current < column {
+            offset += width;
         }
+        current = next;
     }
+    // Errors at the end of a line point just past its last character
+    offset += column.saturating_sub(current);
+
+    let gutter = " ".repeat(line.to_string().len());
+    Some(format!(
+        "{} |\n{} | {}\n{} | {}^",
+        gutter,
+        line,
+        expanded,
+        gutter,
+        " ".repeat(offset)
+    ))
 }
 
 impl ConfigError {
@@ -297,6 +395,15 @@ impl ConfigError {
         }
     }
 
+    /// Create an invalid environment variable error
+    pub fn invalid_env_var<S: Into<String>>(name: S, value: S, expected: S) -> Self {
+        Self::InvalidEnvVar {
+            name: name.into(),
+            value: value.into(),
+            expected: expected.into(),
+        }
+    }
+
     /// Create a missing section error
     pub fn missing_section<S: Into<String>>(section: S) -> Self {
         Self::MissingSection {
@@ -328,6 +435,21 @@ impl ConfigError {
 }
 
 impl ParseError {
+    /// Line and column where the error occurred, if known
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::UnexpectedEndOfInput { .. } => None,
+            Self::UnexpectedToken { line, column, .. }
+            | Self::InvalidHeadingLevel { line, column, .. }
+            | Self::MalformedLink { line, column, .. }
+            | Self::MalformedImage { line, column, .. }
+            | Self::InvalidList { line, column, .. }
+            | Self::UnmatchedDelimiter { line, column, .. }
+            | Self::InvalidTable { line, column, .. }
+            | Self::NestingTooDeep { line, column, .. } => Some((*line, *column)),
+        }
+    }
+
     /// Create an unexpected token error
     pub fn unexpected_token<S: Into<String>>(
         expected: S,
@@ -403,9 +525,29 @@ impl ParseError {
             column,
         }
     }
+
+    /// Create a nesting too deep error
+    pub fn nesting_too_deep(limit: usize, line: usize, column: usize) -> Self {
+        Self::NestingTooDeep {
+            limit,
+            line,
+            column,
+        }
+    }
 }
 
 impl LexerError {
+    /// Line and column where the error occurred
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Self::UnexpectedCharacter { line, column, .. }
+            | Self::UnterminatedCodeBlock { line, column }
+            | Self::InvalidSyntax { line, column, .. }
+            | Self::InvalidUrl { line, column, .. }
+            | Self::NumberTooLarge { line, column, .. } => (*line, *column),
+        }
+    }
+
     /// Create an unexpected character error
     pub fn unexpected_character(character: char, line: usize, column: usize) -> Self {
         Self::UnexpectedCharacter {
@@ -459,6 +601,32 @@ mod tests {
         assert!(error.to_string().contains("test.txt"));
     }
 
+    #[test]
+    fn test_parse_error_snippet_points_at_column() {
+        let source = "# Notes\n\nSome text\nwith *unmatched emphasis\n";
+        let error = crate::markdown_parser::parse_markdown(source).unwrap_err();
+        assert_eq!(error.position(), Some((4, 6)));
+        assert_eq!(
+            error.snippet(source).unwrap(),
+            "  |\n4 | with *unmatched emphasis\n  |      ^"
+        );
+
+        assert_eq!(
+            source_snippet("\ta *b", 1, 7).unwrap(),
+            "  |\n1 |     a *b\n  |       ^"
+        );
+        assert_eq!(
+            source_snippet("日本 *", 1, 4).unwrap(),
+            "  |\n1 | 日本 *\n  |      ^"
+        );
+        assert_eq!(
+            source_snippet("one", 1, 4).unwrap(),
+            "  |\n1 | one\n  |    ^"
+        );
+        assert!(source_snippet("one", 2, 1).is_none());
+        assert!(MarkError::config("bad").snippet("one").is_none());
+    }
+
     #[test]
     fn test_config_error() {
         let error = MarkError::config("Invalid theme");
@@ -0,0 +1,202 @@
+use crate::error::{MarkError, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default number of files kept in the history
+pub const DEFAULT_CAPACITY: usize = 50;
+
+/// A file opened at a point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub path: PathBuf,
+    /// RFC 3339 timestamp of when the file was opened
+    pub opened_at: String,
+}
+
+impl HistoryEntry {
+    /// Format the timestamp for display, falling back to the raw value
+    pub fn display_time(&self) -> String {
+        DateTime::parse_from_rfc3339(&self.opened_at)
+            .map(|time| {
+                time.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.opened_at.clone())
+    }
+}
+
+/// Recently opened files, persisted to `~/.config/mark/history.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+    #[serde(skip, default = "default_capacity")]
+    capacity: usize,
+}
+
+fn default_capacity() -> usize {
+    DEFAULT_CAPACITY
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl History {
+    /// Create an empty history keeping at most `capacity` files
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Get the default history file path next to the config file
+    pub fn default_path() -> Result<PathBuf> {
+        let config_path = crate::config::get_default_config_path()?;
+        Ok(config_path.with_file_name("history.toml"))
+    }
+
+    /// Load history from `path`, starting empty if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let mut history: Self = toml::from_str(&content).map_err(|e| {
+            MarkError::config(format!(
+                "Failed to parse history file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        history.entries.truncate(history.capacity);
+        Ok(history)
+    }
+
+    /// Save history to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string(self)
+            .map_err(|e| MarkError::config(format!("Failed to serialize history: {}", e)))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Record that a file was opened now
+    pub fn record(&mut self, path: &Path) {
+        self.record_at(path, Local::now());
+    }
+
+    /// Record that a file was opened at `time`, moving it to the front if already present
+    pub fn record_at(&mut self, path: &Path, time: DateTime<Local>) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                path,
+                opened_at: time.to_rfc3339(),
+            },
+        );
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Get the entries, most recently opened first
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Check if the history is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Record `path` in the history file at the default location
+pub fn record_opened(path: &Path) -> Result<()> {
+    let history_path = History::default_path()?;
+    let mut history = History::load(&history_path)?;
+    history.record(path);
+    history.save(&history_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn at(seconds: i64) -> DateTime<Local> {
+        Local.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_record_appends_most_recent_first() {
+        let mut history = History::default();
+        history.record_at(Path::new("/docs/a.md"), at(1_000));
+        history.record_at(Path::new("/docs/b.md"), at(2_000));
+
+        let paths: Vec<_> = history.entries().iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/docs/b.md"), PathBuf::from("/docs/a.md")]
+        );
+        assert_eq!(history.entries()[0].opened_at, at(2_000).to_rfc3339());
+    }
+
+    #[test]
+    fn test_reopen_deduplicates() {
+        let mut history = History::default();
+        history.record_at(Path::new("/docs/a.md"), at(1_000));
+        history.record_at(Path::new("/docs/b.md"), at(2_000));
+        history.record_at(Path::new("/docs/a.md"), at(3_000));
+
+        assert_eq!(history.entries().len(), 2);
+        assert_eq!(history.entries()[0].path, PathBuf::from("/docs/a.md"));
+        assert_eq!(history.entries()[0].opened_at, at(3_000).to_rfc3339());
+    }
+
+    #[test]
+    fn test_truncates_at_capacity() {
+        let mut history = History::with_capacity(3);
+        for i in 0..5 {
+            history.record_at(Path::new(&format!("/docs/{}.md", i)), at(i));
+        }
+
+        let paths: Vec<_> = history.entries().iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/docs/4.md"),
+                PathBuf::from("/docs/3.md"),
+                PathBuf::from("/docs/2.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip_through_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("history.toml");
+
+        let mut history = History::default();
+        history.record_at(Path::new("/docs/a.md"), at(1_000));
+        history.record_at(Path::new("/docs/b.md"), at(2_000));
+        history.save(&path).unwrap();
+
+        assert_eq!(History::load(&path).unwrap(), history);
+        assert!(History::load(&temp_dir.path().join("missing.toml"))
+            .unwrap()
+            .is_empty());
+    }
+}
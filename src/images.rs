@@ -0,0 +1,325 @@
+use crate::error::{MarkError, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Largest image that will be loaded, to keep memory and escape sequences bounded
+pub const MAX_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// How long to wait for a remote image before giving up
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Size of the base64 payload in each Kitty graphics chunk
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Inline image protocols understood by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    Kitty,
+    ITerm,
+}
+
+/// Where an image is loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageSource {
+    Local(PathBuf),
+    Remote(String),
+}
+
+/// Detect the image protocol supported by the current terminal
+pub fn detect_protocol() -> Option<ImageProtocol> {
+    detect_protocol_from(|name| std::env::var(name).ok())
+}
+
+/// Detect the image protocol using `lookup` to read environment variables
+pub fn detect_protocol_from<F: Fn(&str) -> Option<String>>(lookup: F) -> Option<ImageProtocol> {
+    // Escape sequences don't make it through tmux/screen without passthrough
+    if lookup("TMUX").is_some_and(|value| !value.is_empty()) {
+        return None;
+    }
+
+    let term = lookup("TERM").unwrap_or_default();
+    let term_program = lookup("TERM_PROGRAM").unwrap_or_default();
+
+    if lookup("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || term_program == "ghostty"
+        || term_program == "WezTerm"
+    {
+        return Some(ImageProtocol::Kitty);
+    }
+
+    if term_program == "iTerm.app" || lookup("LC_TERMINAL").as_deref() == Some("iTerm2") {
+        return Some(ImageProtocol::ITerm);
+    }
+
+    None
+}
+
+/// Resolve an image URL from a document, treating relative paths as relative to `document_dir`
+pub fn resolve_source(url: &str, document_dir: &Path) -> ImageSource {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return ImageSource::Remote(url.to_string());
+    }
+
+    let path = url.strip_prefix("file://").unwrap_or(url);
+    let path = if path.starts_with('~') {
+        crate::search::expand_tilde(path).unwrap_or_else(|_| PathBuf::from(path))
+    } else {
+        PathBuf::from(path)
+    };
+
+    if path.is_absolute() {
+        ImageSource::Local(path)
+    } else {
+        ImageSource::Local(document_dir.join(path))
+    }
+}
+
+/// Load the raw bytes of an image
+pub fn load(source: &ImageSource) -> Result<Vec<u8>> {
+    match source {
+        ImageSource::Local(path) => {
+            let size = fs::metadata(path)?.len();
+            if size > MAX_IMAGE_BYTES {
+                return Err(MarkError::image(format!(
+                    "Image too large: {}",
+                    path.display()
+                )));
+            }
+            Ok(fs::read(path)?)
+        }
+        ImageSource::Remote(url) => fetch(url),
+    }
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| MarkError::network(format!("Failed to create HTTP client: {}", e)))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .map_err(|e| MarkError::network(format!("Failed to fetch image: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(MarkError::network(format!(
+            "HTTP {}: Failed to fetch image",
+            response.status()
+        )));
+    }
+
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_IMAGE_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| MarkError::network(format!("Failed to read image: {}", e)))?;
+
+    if bytes.len() as u64 > MAX_IMAGE_BYTES {
+        return Err(MarkError::image(format!("Image too large: {}", url)));
+    }
+    Ok(bytes)
+}
+
+/// Build the escape sequence that draws `data` over `rows` terminal rows
+///
+/// Returns `None` if the protocol can't display the image format.
+pub fn escape_sequence(protocol: ImageProtocol, data: &[u8], rows: usize) -> Option<String> {
+    if data.is_empty() {
+        return None;
+    }
+
+    match protocol {
+        ImageProtocol::Kitty => {
+            // Kitty only decodes PNG itself, other formats need raw pixel data
+            if !data.starts_with(&PNG_SIGNATURE) {
+                return None;
+            }
+
+            let encoded = STANDARD.encode(data);
+            let chunks: Vec<&str> = encoded
+                .as_bytes()
+                .chunks(KITTY_CHUNK_SIZE)
+                .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+                .collect();
+
+            let mut sequence = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                if i == 0 {
+                    sequence.push_str(&format!(
+                        "\x1b_Ga=T,f=100,q=2,C=1,r={},m={};{}\x1b\\",
+                        rows, more, chunk
+                    ));
+                } else {
+                    sequence.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                }
+            }
+            Some(sequence)
+        }
+        ImageProtocol::ITerm => Some(format!(
+            "\x1b]1337;File=inline=1;size={};height={};preserveAspectRatio=1:{}\x07",
+            data.len(),
+            rows,
+            STANDARD.encode(data)
+        )),
+    }
+}
+
+/// Escape sequence that removes every image drawn with `protocol`
+pub fn clear_sequence(protocol: ImageProtocol) -> &'static str {
+    match protocol {
+        ImageProtocol::Kitty => "\x1b_Ga=d,q=2\x1b\\",
+        // iTerm images are ordinary cell content and go away when redrawn
+        ImageProtocol::ITerm => "",
+    }
+}
+
+/// Load an image and encode it for `protocol`, or `None` if that isn't possible
+pub fn encode_image(
+    protocol: ImageProtocol,
+    url: &str,
+    document_dir: &Path,
+    rows: usize,
+) -> Option<String> {
+    let data = load(&resolve_source(url, document_dir)).ok()?;
+    escape_sequence(protocol, &data, rows)
+}
+
+/// Text shown in place of an image the terminal can't display, before its URL
+///
+/// An image without alt text is named after the file in its URL.
+pub fn fallback_text(alt: &str, title: Option<&str>, url: &str) -> String {
+    let alt = alt.trim();
+    let name = if alt.is_empty() { file_name(url) } else { alt };
+    match title.filter(|title| !title.is_empty()) {
+        Some(title) => format!("🖼 {} — {}", name, title),
+        None => format!("🖼 {}", name),
+    }
+}
+
+/// The last path segment of `url`, without any query or fragment
+fn file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let path = path.trim_end_matches('/');
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_fallback_text() {
+        assert_eq!(
+            fallback_text("diagram", Some("Request flow"), "img/flow.png"),
+            "🖼 diagram — Request flow"
+        );
+        assert_eq!(fallback_text("diagram", None, "img/flow.png"), "🖼 diagram");
+    }
+
+    #[test]
+    fn test_fallback_text_names_file_without_alt() {
+        assert_eq!(
+            fallback_text("", None, "https://example.com/img/logo.png?v=2#top"),
+            "🖼 logo.png"
+        );
+        assert_eq!(fallback_text(" ", Some("Logo"), "a.png"), "🖼 a.png — Logo");
+    }
+
+    #[test]
+    fn test_resolve_relative_to_document() {
+        let dir = Path::new("/notes/project");
+        assert_eq!(
+            resolve_source("img/flow.png", dir),
+            ImageSource::Local(PathBuf::from("/notes/project/img/flow.png"))
+        );
+        assert_eq!(
+            resolve_source("../shared/logo.png", dir),
+            ImageSource::Local(PathBuf::from("/notes/project/../shared/logo.png"))
+        );
+        assert_eq!(
+            resolve_source("/abs/logo.png", dir),
+            ImageSource::Local(PathBuf::from("/abs/logo.png"))
+        );
+        assert_eq!(
+            resolve_source("file:///abs/logo.png", dir),
+            ImageSource::Local(PathBuf::from("/abs/logo.png"))
+        );
+        assert_eq!(
+            resolve_source("https://example.com/a.png", dir),
+            ImageSource::Remote("https://example.com/a.png".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_protocol() {
+        assert_eq!(
+            detect_protocol_from(lookup(&[("TERM", "xterm-kitty")])),
+            Some(ImageProtocol::Kitty)
+        );
+        assert_eq!(
+            detect_protocol_from(lookup(&[("TERM_PROGRAM", "iTerm.app")])),
+            Some(ImageProtocol::ITerm)
+        );
+        assert_eq!(
+            detect_protocol_from(lookup(&[("TERM", "xterm-kitty"), ("TMUX", "/tmp/tmux")])),
+            None
+        );
+        assert_eq!(
+            detect_protocol_from(lookup(&[("TERM", "xterm-256color")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_or_unsupported_images_degrade() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(encode_image(ImageProtocol::Kitty, "missing.png", temp_dir.path(), 5).is_none());
+
+        fs::write(temp_dir.path().join("photo.jpg"), [0xff, 0xd8, 0xff]).unwrap();
+        assert!(encode_image(ImageProtocol::Kitty, "photo.jpg", temp_dir.path(), 5).is_none());
+        assert!(encode_image(ImageProtocol::ITerm, "photo.jpg", temp_dir.path(), 5).is_some());
+    }
+
+    #[test]
+    fn test_oversized_image_is_an_image_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("huge.png");
+        fs::File::create(&path)
+            .unwrap()
+            .set_len(MAX_IMAGE_BYTES + 1)
+            .unwrap();
+
+        let error = load(&ImageSource::Local(path)).unwrap_err();
+        assert!(matches!(error, MarkError::Image { .. }));
+        assert!(error.to_string().contains("Image too large"));
+    }
+
+    #[test]
+    fn test_kitty_sequence_is_chunked() {
+        let mut data = PNG_SIGNATURE.to_vec();
+        data.resize(KITTY_CHUNK_SIZE * 2, 0);
+
+        let sequence = escape_sequence(ImageProtocol::Kitty, &data, 4).unwrap();
+        assert!(sequence.starts_with("\x1b_Ga=T,f=100,q=2,C=1,r=4,m=1;"));
+        assert!(sequence.contains("\x1b_Gm=0;"));
+    }
+}
@@ -1,8 +1,30 @@
+//! Mark is a terminal markdown viewer that can also be used as a library.
+//!
+//! The items re-exported at the crate root are the stable API: parse a string
+//! into an [`AstNode`] tree, then turn it into HTML or plain text.
+//!
+//! ```
+//! let ast = mark::parse_markdown("# Hi").unwrap();
+//! assert_eq!(ast.to_html(), "<h1>Hi</h1>");
+//! assert_eq!(mark::to_plain_text("Some **bold** text").unwrap(), "Some bold text");
+//! ```
+
+pub mod bookmarks;
 pub mod cli;
+pub mod clipboard;
 pub mod config;
 pub mod error;
+pub mod history;
+pub mod images;
 pub mod markdown_parser;
+pub mod opener;
 pub mod search;
 pub mod ui;
 
+#[cfg(test)]
+mod test_support;
+
 pub use error::{MarkError, Result};
+pub use markdown_parser::{
+    parse_markdown, parse_markdown_or_default, to_html, to_plain_text, AstNode,
+};
@@ -1,13 +1,14 @@
 use mark::{
     cli,
-    error::{MarkError, Result},
+    error::{ConfigError, MarkError, Result},
 };
 use std::error::Error;
-use std::process;
+use std::process::ExitCode;
 
-fn main() {
-    if let Err(e) = run_application() {
-        handle_error(e);
+fn main() -> ExitCode {
+    match run_application() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => handle_error(e),
     }
 }
 
@@ -16,7 +17,7 @@ fn run_application() -> Result<()> {
     Ok(())
 }
 
-fn handle_error(error: MarkError) {
+fn handle_error(error: MarkError) -> ExitCode {
     eprintln!("Error: {}", error);
 
     let mut source = error.source();
@@ -27,45 +28,76 @@ fn handle_error(error: MarkError) {
 
     print_error_suggestions(&error);
 
-    process::exit(error.exit_code());
+    ExitCode::from(error.exit_code() as u8)
 }
 
 fn print_error_suggestions(error: &MarkError) {
+    let suggestions = error_suggestions(error);
+
+    eprintln!();
+    if suggestions.is_empty() {
+        eprintln!("For more help, run: mark --help");
+        return;
+    }
+
+    eprintln!("Suggestions:");
+    for suggestion in suggestions {
+        eprintln!("  • {}", suggestion);
+    }
+}
+
+/// Pick the hints shown below an error message
+fn error_suggestions(error: &MarkError) -> Vec<String> {
     match error {
-        MarkError::FileNotFound { path } => {
-            eprintln!();
-            eprintln!("Suggestions:");
-            eprintln!("  • Check if the file path is correct: {}", path.display());
-            eprintln!("  • Ensure the file exists and you have read permissions");
-        }
-        MarkError::InvalidFileFormat { path } => {
-            eprintln!();
-            eprintln!("Suggestions:");
-            eprintln!("  • Make sure '{}' is a markdown file", path.display());
-            eprintln!("  • Supported extensions: .md, .markdown");
+        MarkError::FileNotFound { path } => vec![
+            format!("Check if the file path is correct: {}", path.display()),
+            "Ensure the file exists and you have read permissions".to_string(),
+        ],
+        MarkError::NotAFile { path } => vec![
+            format!("'{}' is not a regular file", path.display()),
+            "Pass a markdown file, or a quoted glob such as 'docs/*.md'".to_string(),
+        ],
+        MarkError::InvalidFileFormat { path } => vec![
+            format!("Make sure '{}' is a markdown file", path.display()),
+            "Supported extensions: .md, .markdown".to_string(),
+        ],
+        MarkError::InvalidWidth { .. } => vec![
+            "Use a width value between 20 and 200 characters".to_string(),
+            "Example: mark --width 80 README.md".to_string(),
+        ],
+        MarkError::ConfigError(ConfigError::InvalidEnvVar { name, .. }) => {
+            vec![format!("Fix or unset the {} environment variable", name)]
         }
-        MarkError::InvalidWidth { .. } => {
-            eprintln!();
-            eprintln!("Suggestions:");
-            eprintln!("  • Use a width value between 20 and 200 characters");
-            eprintln!("  • Example: mark --width 80 README.md");
+        MarkError::Config { .. } | MarkError::ConfigError(_) => vec![
+            "Check your configuration file syntax".to_string(),
+            "Ensure the config file path is correct".to_string(),
+        ],
+        MarkError::Network { .. } => vec![
+            "Check your internet connection".to_string(),
+            "Create the configuration file manually instead".to_string(),
+        ],
+        MarkError::Search { .. } => vec![
+            "Check that the directory exists and is readable".to_string(),
+            "Quote glob patterns, e.g. mark 'docs/*.md'".to_string(),
+        ],
+        MarkError::Lexer(_) | MarkError::Parser(_) => vec![
+            "The markdown file contains syntax mark cannot parse".to_string(),
+            "Check the reported line and column for unclosed delimiters".to_string(),
+        ],
+        MarkError::Watch(_) => {
+            vec!["Run without --watch to view the file without reloading it".to_string()]
         }
-        MarkError::Config { .. } => {
-            eprintln!();
-            eprintln!("Suggestions:");
-            eprintln!("  • Check your configuration file syntax");
-            eprintln!("  • Ensure the config file path is correct");
-        }
-        _ => {
-            eprintln!();
-            eprintln!("For more help, run: mark --help");
+        MarkError::Lint { .. } => {
+            vec!["Fix the reported lines, or view the file anyway with mark".to_string()]
         }
+        MarkError::Image { .. } | MarkError::Json(_) | MarkError::Io(_) => Vec::new(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use mark::error::{LexerError, ParseError};
     use std::path::PathBuf;
 
     #[test]
@@ -77,5 +109,48 @@ mod tests {
 
         let invalid_width = MarkError::InvalidWidth { width: 300 };
         assert_eq!(invalid_width.exit_code(), 22);
+
+        let not_a_file = MarkError::NotAFile {
+            path: PathBuf::from("docs"),
+        };
+        assert_eq!(not_a_file.exit_code(), 22);
+        assert_eq!(not_a_file.to_string(), "Not a file: docs");
+
+        let json = MarkError::from(serde_json::from_str::<u8>("x").unwrap_err());
+        assert_eq!(json.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_error_suggestions_per_variant() {
+        let file_not_found = MarkError::FileNotFound {
+            path: PathBuf::from("nonexistent.md"),
+        };
+        assert!(error_suggestions(&file_not_found)[0].contains("nonexistent.md"));
+
+        let invalid_width = MarkError::InvalidWidth { width: 300 };
+        assert!(error_suggestions(&invalid_width)[0].contains("between 20 and 200"));
+
+        let env =
+            MarkError::ConfigError(ConfigError::invalid_env_var("MARK_WIDTH", "500", "20-200"));
+        assert!(error_suggestions(&env)[0].contains("MARK_WIDTH"));
+
+        let config = MarkError::config("bad");
+        assert!(error_suggestions(&config)[0].contains("configuration file"));
+
+        let search = MarkError::search("bad");
+        assert!(error_suggestions(&search)[0].contains("directory"));
+
+        let parser = MarkError::Parser(ParseError::unmatched_delimiter('*', 1, 1));
+        assert!(error_suggestions(&parser)[1].contains("line and column"));
+
+        let lexer = MarkError::Lexer(LexerError::number_too_large("9", 1, 1));
+        assert_eq!(error_suggestions(&lexer), error_suggestions(&parser));
+
+        let lint = MarkError::Lint { problems: 2 };
+        assert_eq!(lint.exit_code(), 1);
+        assert!(error_suggestions(&lint)[0].contains("reported lines"));
+
+        let io = MarkError::Io(std::io::Error::new(std::io::ErrorKind::Other, "boom"));
+        assert!(error_suggestions(&io).is_empty());
     }
 }
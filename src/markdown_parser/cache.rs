@@ -0,0 +1,227 @@
+use crate::error::Result;
+use crate::markdown_parser::{parse_markdown_or_default_with, AstNode};
+use crate::search::markdown::read_lossy;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Number of parsed documents kept unless configured otherwise
+pub const DEFAULT_CACHE_SIZE: usize = 32;
+
+/// A parsed file, noting whether invalid UTF-8 had to be replaced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub ast: AstNode,
+    pub lossy: bool,
+}
+
+struct CacheEntry {
+    modified: Option<SystemTime>,
+    document: Document,
+}
+
+/// Least-recently-used cache of parsed documents, keyed by path
+///
+/// Entries are re-parsed when the file's modified time changes.
+pub struct DocumentCache {
+    capacity: usize,
+    strict_emphasis: bool,
+    entries: HashMap<PathBuf, CacheEntry>,
+    /// Paths from least to most recently used
+    order: VecDeque<PathBuf>,
+    hits: usize,
+    misses: usize,
+}
+
+impl DocumentCache {
+    /// Create a cache holding up to `capacity` documents; 0 disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            strict_emphasis: false,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Treat unclosed emphasis as an error, as `[settings] strict_emphasis` asks
+    pub fn with_strict_emphasis(mut self, strict_emphasis: bool) -> Self {
+        self.strict_emphasis = strict_emphasis;
+        self
+    }
+
+    /// Return the parsed document at `path`, reading and parsing it only when needed
+    pub fn get_or_parse(&mut self, path: &Path) -> Result<Document> {
+        let modified = std::fs::metadata(path)?.modified().ok();
+        let strict_emphasis = self.strict_emphasis;
+        self.get_or_insert_with(path, modified, || {
+            let (content, lossy) = read_lossy(path)?;
+            Ok(Document {
+                ast: parse_markdown_or_default_with(&content, strict_emphasis),
+                lossy,
+            })
+        })
+    }
+
+    /// Return the cached document if it was stored for `modified`, otherwise `parse` it
+    ///
+    /// A `None` modified time can't prove the entry is fresh, so it always re-parses.
+    fn get_or_insert_with<F: FnOnce() -> Result<Document>>(
+        &mut self,
+        path: &Path,
+        modified: Option<SystemTime>,
+        parse: F,
+    ) -> Result<Document> {
+        if let Some(entry) = self.entries.get(path) {
+            if modified.is_some() && entry.modified == modified {
+                self.hits += 1;
+                let document = entry.document.clone();
+                self.touch(path);
+                return Ok(document);
+            }
+        }
+
+        self.misses += 1;
+        let document = parse()?;
+        self.insert(path, modified, document.clone());
+        Ok(document)
+    }
+
+    fn insert(&mut self, path: &Path, modified: Option<SystemTime>, document: Document) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.entries
+            .insert(path.to_path_buf(), CacheEntry { modified, document });
+        self.touch(path);
+
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Mark `path` as the most recently used entry
+    fn touch(&mut self, path: &Path) {
+        self.order.retain(|entry| entry != path);
+        self.order.push_back(path.to_path_buf());
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of lookups answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of lookups that had to parse the file
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+impl Default for DocumentCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unchanged_file_is_a_hit() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.md");
+        fs::write(&path, "# Title").unwrap();
+
+        let mut cache = DocumentCache::new(4);
+        let first = cache.get_or_parse(&path).unwrap();
+        let second = cache.get_or_parse(&path).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    }
+
+    #[test]
+    fn test_strict_emphasis_gives_an_empty_document() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.md");
+        fs::write(&path, "a * b").unwrap();
+
+        let lenient = DocumentCache::new(4).get_or_parse(&path).unwrap();
+        assert_eq!(lenient.ast.to_plain_text().trim(), "a * b");
+
+        let mut strict = DocumentCache::new(4).with_strict_emphasis(true);
+        assert_eq!(
+            strict.get_or_parse(&path).unwrap().ast,
+            AstNode::Document { children: vec![] }
+        );
+    }
+
+    #[test]
+    fn test_changed_modified_time_reparses() {
+        let path = Path::new("doc.md");
+        let text = |text: &str| {
+            Ok(Document {
+                ast: AstNode::Text(text.to_string()),
+                lossy: false,
+            })
+        };
+        let earlier = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let later = earlier + Duration::from_secs(10);
+
+        let mut cache = DocumentCache::new(4);
+        cache
+            .get_or_insert_with(path, Some(earlier), || text("old"))
+            .unwrap();
+        let cached = cache
+            .get_or_insert_with(path, Some(earlier), || text("unused"))
+            .unwrap();
+        assert_eq!(cached.ast, AstNode::Text("old".to_string()));
+
+        let reparsed = cache
+            .get_or_insert_with(path, Some(later), || text("new"))
+            .unwrap();
+        assert_eq!(reparsed.ast, AstNode::Text("new".to_string()));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["a.md", "b.md", "c.md"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        for path in &paths {
+            fs::write(path, "text").unwrap();
+        }
+
+        let mut cache = DocumentCache::new(2);
+        cache.get_or_parse(&paths[0]).unwrap();
+        cache.get_or_parse(&paths[1]).unwrap();
+        cache.get_or_parse(&paths[0]).unwrap();
+        cache.get_or_parse(&paths[2]).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // `b.md` was least recently used, so it was evicted
+        cache.get_or_parse(&paths[0]).unwrap();
+        cache.get_or_parse(&paths[1]).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (2, 4));
+    }
+}
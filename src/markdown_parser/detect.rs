@@ -0,0 +1,79 @@
+/// Non-blank lines looked at when guessing whether text is markdown
+const SAMPLE_LINES: usize = 200;
+
+/// Guess whether `input` is markdown rather than plain text such as a log
+///
+/// A heading, code fence or link anywhere in the first lines is enough. List
+/// items, quotes and table rows also appear in plain text now and then, so they
+/// only count when they make up a fifth of the lines. Empty input counts as
+/// markdown.
+pub fn detect_markdown(input: &str) -> bool {
+    let mut lines = 0;
+    let mut block_lines = 0;
+    for line in input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(SAMPLE_LINES)
+    {
+        lines += 1;
+        if is_heading(line) || line.starts_with("```") || line.starts_with("~~~") {
+            return true;
+        }
+        if line.contains("](") && line.contains('[') {
+            return true;
+        }
+        if is_list_item(line) || line.starts_with("> ") || is_table_row(line) {
+            block_lines += 1;
+        }
+    }
+    lines == 0 || block_lines * 5 >= lines
+}
+
+fn is_heading(line: &str) -> bool {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    (1..=6).contains(&hashes) && line[hashes..].starts_with(' ')
+}
+
+fn is_list_item(line: &str) -> bool {
+    if ["- ", "* ", "+ "]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+    {
+        return true;
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    digits > 0 && line[digits..].starts_with(". ")
+}
+
+fn is_table_row(line: &str) -> bool {
+    line.len() > 1 && line.starts_with('|') && line.ends_with('|')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_is_detected() {
+        assert!(detect_markdown("# Notes\n\nSome text.\n"));
+        assert!(detect_markdown("Shopping:\n\n- eggs\n- milk\n1. bread\n"));
+        assert!(detect_markdown("Run this:\n\n```sh\nmake\n```\n"));
+        assert!(detect_markdown("See [the docs](https://example.com)."));
+        assert!(detect_markdown(""));
+    }
+
+    #[test]
+    fn test_logs_are_not_markdown() {
+        let log = "\
+2024-05-01 12:00:01 INFO  server starting on port 8080
+2024-05-01 12:00:02 WARN  config key #timeout is deprecated
+2024-05-01 12:00:03 ERROR request failed: status=500 path=/api/items
+- retrying in 5s
+2024-05-01 12:00:08 INFO  request ok
+2024-05-01 12:00:09 INFO  shutting down
+";
+        assert!(!detect_markdown(log));
+        assert!(!detect_markdown("just one line of text"));
+    }
+}
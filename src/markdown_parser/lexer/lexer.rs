@@ -1,37 +1,84 @@
-use std::{iter::Peekable, str::Chars};
-
 use crate::error::LexerError;
-use crate::markdown_parser::lexer::tokens::Token;
+use crate::markdown_parser::lexer::tokens::{SpannedToken, Token};
+
+const URL_SCHEMES: [&str; 4] = ["http://", "https://", "ftp://", "mailto:"];
+
+/// Byte order mark some editors put at the start of UTF-8 files
+pub const BOM: char = '\u{FEFF}';
 
-pub struct Lexer<'a> {
-    input: Peekable<Chars<'a>>,
+/// Number of columns between tab stops unless configured otherwise
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+pub struct Lexer {
+    chars: Vec<char>,
+    index: usize,
     current_pos: usize,
     line: usize,
     column: usize,
+    tab_width: usize,
 }
 
-impl<'a> Lexer<'a> {
-    pub fn new(input: &'a str) -> Self {
-        Self {
-            input: input.chars().peekable(),
+impl Lexer {
+    pub fn new(input: &str) -> Self {
+        Self::with_tab_width(input, DEFAULT_TAB_WIDTH)
+    }
+
+    /// Create a lexer that expands tabs to the next multiple of `tab_width` columns
+    ///
+    /// A leading byte order mark is skipped, though spans still count its bytes.
+    pub fn with_tab_width(input: &str, tab_width: usize) -> Self {
+        let mut lexer = Self {
+            chars: Vec::new(),
+            index: 0,
             current_pos: 0,
             line: 1,
             column: 1,
-        }
+            tab_width: tab_width.max(1),
+        };
+        lexer.reset(input);
+        lexer
+    }
+
+    /// Start over on `input`, keeping the tab width and the character buffer
+    pub fn reset(&mut self, input: &str) {
+        let text = input.strip_prefix(BOM).unwrap_or(input);
+        self.chars.clear();
+        self.chars.extend(text.chars());
+        self.index = 0;
+        self.current_pos = input.len() - text.len();
+        self.line = 1;
+        self.column = 1;
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, LexerError> {
+    pub fn tokenize(&mut self) -> Result<Vec<SpannedToken>, LexerError> {
         let mut tokens = Vec::new();
+        self.tokenize_into(&mut tokens)?;
+        Ok(tokens)
+    }
+
+    /// Tokenize into `tokens`, replacing what it held but keeping its allocation
+    pub fn tokenize_into(&mut self, tokens: &mut Vec<SpannedToken>) -> Result<(), LexerError> {
+        tokens.clear();
+        loop {
+            let (start, line, column) = (self.current_pos, self.line, self.column);
+            let Some(token) = self.next_token()? else {
+                break;
+            };
+            let is_eof = matches!(token, Token::Eof);
+
+            tokens.push(SpannedToken {
+                token,
+                span: (start, self.current_pos),
+                line,
+                column,
+            });
 
-        while let Some(token) = self.next_token()? {
-            if matches!(token, Token::Eof) {
-                tokens.push(token);
+            if is_eof {
                 break;
             }
-            tokens.push(token);
         }
 
-        Ok(tokens)
+        Ok(())
     }
 
     fn next_token(&mut self) -> Result<Option<Token>, LexerError> {
@@ -99,16 +146,24 @@ impl<'a> Lexer<'a> {
                 self.advance();
                 Ok(Token::Pipe)
             }
+            '\\' if self.next_is('|') => {
+                self.advance();
+                self.advance();
+                Ok(Token::EscapedPipe)
+            }
             ':' => {
                 self.advance();
                 Ok(Token::Colon)
             }
             c if c.is_ascii_digit() => self.read_number(),
             '~' => Ok(self.read_tildes()),
+            '=' if self.next_is_equals() => Ok(self.read_equals()),
+            '$' => Ok(self.read_dollars()),
             '+' => {
                 self.advance();
                 Ok(Token::Plus)
             }
+            '<' if self.html_tag_len().is_some() => Ok(self.read_html_tag()),
             _ => self.read_text(ch),
         }
     }
@@ -182,6 +237,34 @@ impl<'a> Lexer<'a> {
         Token::Tilde(count)
     }
 
+    fn read_equals(&mut self) -> Token {
+        let mut count: u8 = 0;
+        while self.peek_char() == Some(&'=') {
+            self.advance();
+            count = count.saturating_add(1);
+        }
+        Token::Equals(count)
+    }
+
+    fn read_dollars(&mut self) -> Token {
+        let mut count: u8 = 0;
+        while self.peek_char() == Some(&'$') {
+            self.advance();
+            count = count.saturating_add(1);
+        }
+        Token::Dollar(count)
+    }
+
+    /// Check whether the character after the current one is also '='
+    fn next_is_equals(&self) -> bool {
+        self.next_is('=')
+    }
+
+    /// Check whether the character after the current one is `ch`
+    fn next_is(&self, ch: char) -> bool {
+        self.rest().nth(1) == Some(ch)
+    }
+
     fn read_text(&mut self, first_char: char) -> Result<Token, LexerError> {
         let mut text = String::new();
         text.push(first_char);
@@ -190,9 +273,24 @@ impl<'a> Lexer<'a> {
         // Read ahead to collect full potential URL or text
         while let Some(&ch) = self.peek_char() {
             match ch {
+                // Underscores inside a word are literal, as in `snake_case`
+                '_' if text.ends_with(char::is_alphanumeric)
+                    && self.underscores_continue_word() =>
+                {
+                    while self.peek_char() == Some(&'_') {
+                        text.push('_');
+                        self.advance();
+                    }
+                }
                 // Stop at markdown special characters
                 '\n' | '\r' | ' ' | '\t' | '#' | '*' | '`' | '_' | '~' | '[' | ']' | '(' | ')'
-                | '!' | '>' | '-' | '|' | '+' => break,
+                | '!' | '>' | '-' | '|' | '+' | '$' => break,
+                // A single '=' is plain text, only runs of them are markers
+                '=' if self.next_is_equals() => break,
+                '\\' if self.next_is('|') => break,
+                '<' if self.html_tag_len().is_some() => break,
+                // Colons delimit shortcodes unless they belong to a URL
+                ':' if !Self::continues_url(&text) => break,
                 _ => {
                     text.push(ch);
                     self.advance();
@@ -201,17 +299,95 @@ impl<'a> Lexer<'a> {
         }
 
         // Check if this looks like a URL
-        if text.starts_with("http://")
-            || text.starts_with("https://")
-            || text.starts_with("ftp://")
-            || text.starts_with("mailto:")
-        {
+        if URL_SCHEMES.iter().any(|scheme| text.starts_with(scheme)) {
             Ok(Token::Url(text))
         } else {
             Ok(Token::Text(text))
         }
     }
 
+    fn read_html_tag(&mut self) -> Token {
+        let len = self.html_tag_len().unwrap_or(0);
+        let tag: String = (0..len).filter_map(|_| self.advance()).collect();
+        Token::Html(tag)
+    }
+
+    /// Length in characters of the HTML tag starting at the current '<', if there is one
+    ///
+    /// Accepts opening tags with attributes, self-closing tags and closing tags on a
+    /// single line. Anything else, such as `a < b` or `<https://...>`, stays text.
+    fn html_tag_len(&self) -> Option<usize> {
+        let mut chars = self.rest().peekable();
+        let mut len = 1;
+        if chars.next() != Some('<') {
+            return None;
+        }
+
+        let closing = chars.peek() == Some(&'/');
+        if closing {
+            chars.next();
+            len += 1;
+        }
+        if !chars.next()?.is_ascii_alphabetic() {
+            return None;
+        }
+        len += 1;
+        while chars
+            .peek()
+            .is_some_and(|ch| ch.is_ascii_alphanumeric() || *ch == '-')
+        {
+            chars.next();
+            len += 1;
+        }
+
+        if closing {
+            for ch in chars {
+                len += 1;
+                match ch {
+                    '>' => return Some(len),
+                    ' ' | '\t' => {}
+                    _ => return None,
+                }
+            }
+            return None;
+        }
+
+        // The name must end at whitespace, '/' or '>'
+        if !matches!(chars.peek(), Some(' ' | '\t' | '/' | '>')) {
+            return None;
+        }
+        let mut quote = None;
+        for ch in chars {
+            len += 1;
+            match (ch, quote) {
+                ('\n' | '\r', _) => return None,
+                (_, Some(open)) if ch == open => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'', None) => quote = Some(ch),
+                ('>', None) => return Some(len),
+                ('<', None) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Check whether the upcoming run of underscores is followed by a word character
+    fn underscores_continue_word(&self) -> bool {
+        let mut rest = self.rest().skip_while(|&ch| ch == '_');
+        rest.next().is_some_and(char::is_alphanumeric)
+    }
+
+    /// Check whether a ':' after `text` is part of a URL rather than a delimiter
+    fn continues_url(text: &str) -> bool {
+        URL_SCHEMES.iter().any(|scheme| {
+            text.starts_with(scheme)
+                || scheme
+                    .strip_prefix(text)
+                    .is_some_and(|rest| rest.starts_with(':'))
+        })
+    }
+
     fn read_whitespace(&mut self) -> Token {
         while let Some(&ch) = self.peek_char() {
             match ch {
@@ -224,21 +400,34 @@ impl<'a> Lexer<'a> {
         Token::Whitespace
     }
 
+    /// The characters not consumed yet
+    fn rest(&self) -> impl Iterator<Item = char> + '_ {
+        self.chars[self.index..].iter().copied()
+    }
+
     fn peek_char(&mut self) -> Option<&char> {
-        self.input.peek()
+        self.chars.get(self.index)
     }
 
     fn advance(&mut self) -> Option<char> {
-        match self.input.next() {
+        let next = self.chars.get(self.index).copied();
+        self.index += usize::from(next.is_some());
+        match next {
             Some('\n') => {
                 self.line += 1;
                 self.column = 1;
                 self.current_pos += 1;
                 Some('\n')
             }
+            Some('\t') => {
+                // Move to the next tab stop so tabs and spaces indent alike
+                self.column = ((self.column - 1) / self.tab_width + 1) * self.tab_width + 1;
+                self.current_pos += 1;
+                Some('\t')
+            }
             Some(ch) => {
                 self.column += 1;
-                self.current_pos += 1;
+                self.current_pos += ch.len_utf8();
                 Some(ch)
             }
             None => None,
@@ -249,12 +438,12 @@ impl<'a> Lexer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::markdown_parser::lexer::tokens::Token;
+    use crate::markdown_parser::lexer::tokens::{strip_spans, Token};
 
     #[test]
     fn test_basic_tokens() {
         let mut lexer = Lexer::new("# Hello World");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Hash(1));
         assert_eq!(tokens[1], Token::Whitespace);
@@ -267,7 +456,7 @@ mod tests {
     #[test]
     fn test_multiple_hashes() {
         let mut lexer = Lexer::new("## Header");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Hash(2));
     }
@@ -275,7 +464,7 @@ mod tests {
     #[test]
     fn test_emphasis_tokens() {
         let mut lexer = Lexer::new("*bold* **italic** ***both***");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Asterisk(1));
         assert_eq!(tokens[2], Token::Asterisk(1));
@@ -287,7 +476,7 @@ mod tests {
     #[test]
     fn test_code_tokens() {
         let mut lexer = Lexer::new("`code` ```block```");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Backtick(1));
         assert_eq!(tokens[4], Token::Backtick(3));
@@ -297,7 +486,7 @@ mod tests {
     #[test]
     fn test_link_tokens() {
         let mut lexer = Lexer::new("[link](url) ![image](src)");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::LeftBracket);
         assert_eq!(tokens[2], Token::RightBracket);
@@ -307,10 +496,23 @@ mod tests {
         assert_eq!(tokens[8], Token::LeftBracket);
     }
 
+    #[test]
+    fn test_html_tag_tokens() {
+        let mut lexer = Lexer::new("a<br/> <img src=\"x > y\"> </sub > <1> a<b <https://x.com>");
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
+
+        assert_eq!(tokens[1], Token::Html("<br/>".to_string()));
+        assert_eq!(tokens[3], Token::Html("<img src=\"x > y\">".to_string()));
+        assert_eq!(tokens[5], Token::Html("</sub >".to_string()));
+        assert!(!tokens[6..]
+            .iter()
+            .any(|token| matches!(token, Token::Html(_))));
+    }
+
     #[test]
     fn test_list_tokens() {
         let mut lexer = Lexer::new("- item\n+ item\n1. numbered");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Hyphen);
         assert_eq!(tokens[4], Token::Plus);
@@ -321,7 +523,7 @@ mod tests {
     #[test]
     fn test_table_tokens() {
         let mut lexer = Lexer::new("| col1 | col2 |\n|:-----|-----:|");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Just check that we have pipe tokens
         let pipe_count = tokens.iter().filter(|t| matches!(t, Token::Pipe)).count();
@@ -334,16 +536,38 @@ mod tests {
     #[test]
     fn test_strikethrough_tokens() {
         let mut lexer = Lexer::new("~~strikethrough~~");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Tilde(2));
         assert_eq!(tokens[2], Token::Tilde(2));
     }
 
+    #[test]
+    fn test_equals_tokens() {
+        let mut lexer = Lexer::new("==mark== a=b");
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
+
+        assert_eq!(tokens[0], Token::Equals(2));
+        assert_eq!(tokens[1], Token::Text("mark".to_string()));
+        assert_eq!(tokens[2], Token::Equals(2));
+        assert_eq!(tokens[4], Token::Text("a=b".to_string()));
+    }
+
+    #[test]
+    fn test_dollar_tokens() {
+        let mut lexer = Lexer::new("$x$ $$");
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
+
+        assert_eq!(tokens[0], Token::Dollar(1));
+        assert_eq!(tokens[1], Token::Text("x".to_string()));
+        assert_eq!(tokens[2], Token::Dollar(1));
+        assert_eq!(tokens[4], Token::Dollar(2));
+    }
+
     #[test]
     fn test_url_detection() {
         let mut lexer = Lexer::new("https://example.com");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Url("https://example.com".to_string()));
     }
@@ -351,7 +575,7 @@ mod tests {
     #[test]
     fn test_blockquote() {
         let mut lexer = Lexer::new("> quote");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::GreaterThan);
     }
@@ -359,7 +583,7 @@ mod tests {
     #[test]
     fn test_newlines() {
         let mut lexer = Lexer::new("line1\nline2\r\nline3");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Find the newlines in the token stream
         let newline_positions: Vec<usize> = tokens
@@ -380,7 +604,7 @@ mod tests {
     #[test]
     fn test_underscore_emphasis() {
         let mut lexer = Lexer::new("_italic_ __bold__ ___both___");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Underscore(1));
         assert_eq!(tokens[2], Token::Underscore(1));
@@ -397,6 +621,32 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_token_spans_map_to_source() {
+        let input = "# Title";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+
+        assert_eq!(tokens[0].token, Token::Hash(1));
+        assert_eq!(tokens[0].source_text(input), "#");
+        assert_eq!(tokens[1].source_text(input), " ");
+        assert_eq!(tokens[2].token, Token::Text("Title".to_string()));
+        assert_eq!(tokens[2].span, (2, 7));
+        assert_eq!(tokens[2].source_text(input), "Title");
+        assert_eq!(tokens[3].token, Token::Eof);
+        assert_eq!(tokens[3].span, (7, 7));
+    }
+
+    #[test]
+    fn test_token_spans_are_byte_offsets() {
+        let input = "héllo\n## wörld";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+
+        assert_eq!(tokens[0].source_text(input), "héllo");
+        assert_eq!(tokens[2].source_text(input), "##");
+        assert_eq!((tokens[2].line, tokens[2].column), (2, 1));
+        assert_eq!(tokens[4].source_text(input), "wörld");
+    }
+
     #[test]
     fn test_position_tracking() {
         let mut lexer = Lexer::new("line1\nline2");
@@ -422,7 +672,7 @@ mod tests {
     #[test]
     fn test_empty_string() {
         let mut lexer = Lexer::new("");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens.len(), 1);
         assert_eq!(tokens[0], Token::Eof);
@@ -431,7 +681,7 @@ mod tests {
     #[test]
     fn test_only_whitespace() {
         let mut lexer = Lexer::new("   \t  \t ");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Whitespace);
         assert_eq!(tokens[1], Token::Eof);
@@ -440,7 +690,7 @@ mod tests {
     #[test]
     fn test_only_newlines() {
         let mut lexer = Lexer::new("\n\n\r\n\n");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let newline_count = tokens
             .iter()
@@ -452,7 +702,7 @@ mod tests {
     #[test]
     fn test_maximum_hashes() {
         let mut lexer = Lexer::new("######");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Hash(6));
     }
@@ -460,7 +710,7 @@ mod tests {
     #[test]
     fn test_excessive_hashes() {
         let mut lexer = Lexer::new("##########");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should stop at 6 hashes
         assert_eq!(tokens[0], Token::Hash(6));
@@ -470,7 +720,7 @@ mod tests {
     #[test]
     fn test_maximum_asterisks() {
         let mut lexer = Lexer::new("***");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Asterisk(3));
     }
@@ -478,7 +728,7 @@ mod tests {
     #[test]
     fn test_excessive_asterisks() {
         let mut lexer = Lexer::new("******");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should stop at 3 asterisks
         assert_eq!(tokens[0], Token::Asterisk(3));
@@ -488,7 +738,7 @@ mod tests {
     #[test]
     fn test_maximum_backticks() {
         let mut lexer = Lexer::new("````");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Backtick(4));
     }
@@ -496,7 +746,7 @@ mod tests {
     #[test]
     fn test_excessive_backticks() {
         let mut lexer = Lexer::new("``````");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should stop at 4 backticks
         assert_eq!(tokens[0], Token::Backtick(4));
@@ -506,7 +756,7 @@ mod tests {
     #[test]
     fn test_maximum_underscores() {
         let mut lexer = Lexer::new("___");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Underscore(3));
     }
@@ -514,7 +764,7 @@ mod tests {
     #[test]
     fn test_excessive_underscores() {
         let mut lexer = Lexer::new("______");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should stop at 3 underscores
         assert_eq!(tokens[0], Token::Underscore(3));
@@ -524,7 +774,7 @@ mod tests {
     #[test]
     fn test_maximum_tildes() {
         let mut lexer = Lexer::new("~~~");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Tilde(3));
     }
@@ -532,7 +782,7 @@ mod tests {
     #[test]
     fn test_excessive_tildes() {
         let mut lexer = Lexer::new("~~~~~~");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should stop at 3 tildes
         assert_eq!(tokens[0], Token::Tilde(3));
@@ -542,7 +792,7 @@ mod tests {
     #[test]
     fn test_zero_number() {
         let mut lexer = Lexer::new("0");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Number(0));
     }
@@ -550,7 +800,7 @@ mod tests {
     #[test]
     fn test_large_valid_number() {
         let mut lexer = Lexer::new("4294967295"); // Max u32
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Number(4294967295));
     }
@@ -558,7 +808,7 @@ mod tests {
     #[test]
     fn test_number_with_leading_zeros() {
         let mut lexer = Lexer::new("00123");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Number(123));
     }
@@ -566,7 +816,7 @@ mod tests {
     #[test]
     fn test_mixed_line_endings() {
         let mut lexer = Lexer::new("line1\nline2\r\nline3\rline4");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let newline_count = tokens
             .iter()
@@ -578,7 +828,7 @@ mod tests {
     #[test]
     fn test_carriage_return_only() {
         let mut lexer = Lexer::new("line1\rline2");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert!(tokens.iter().any(|t| matches!(t, Token::Newline)));
     }
@@ -586,7 +836,7 @@ mod tests {
     #[test]
     fn test_unicode_text() {
         let mut lexer = Lexer::new("Hello 世界 🌍");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Text("Hello".to_string()));
         assert_eq!(tokens[2], Token::Text("世界".to_string()));
@@ -596,7 +846,7 @@ mod tests {
     #[test]
     fn test_special_characters_in_text() {
         let mut lexer = Lexer::new("hello@world.com $100 %test &more");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should capture special characters as part of text
         assert!(tokens.iter().any(|t| {
@@ -621,7 +871,7 @@ mod tests {
 
         for (input, should_be_url) in test_cases {
             let mut lexer = Lexer::new(input);
-            let tokens = lexer.tokenize().unwrap();
+            let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
             if should_be_url {
                 assert!(matches!(tokens[0], Token::Url(_)), "Failed for: {}", input);
@@ -634,7 +884,7 @@ mod tests {
     #[test]
     fn test_complex_url() {
         let mut lexer = Lexer::new("https://example.com/path?param=value&other=123#anchor");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // URL should capture everything until whitespace/special chars
         if let Token::Url(url) = &tokens[0] {
@@ -648,7 +898,7 @@ mod tests {
     #[test]
     fn test_consecutive_special_characters() {
         let mut lexer = Lexer::new("**##__``~~");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Asterisk(2));
         assert_eq!(tokens[1], Token::Hash(2));
@@ -660,7 +910,7 @@ mod tests {
     #[test]
     fn test_mixed_brackets() {
         let mut lexer = Lexer::new("[({})]");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::LeftBracket);
         assert_eq!(tokens[1], Token::LeftParen);
@@ -678,7 +928,7 @@ mod tests {
     #[test]
     fn test_table_edge_cases() {
         let mut lexer = Lexer::new("||:|::|:::|");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let pipe_count = tokens.iter().filter(|t| matches!(t, Token::Pipe)).count();
         let colon_count = tokens.iter().filter(|t| matches!(t, Token::Colon)).count();
@@ -690,7 +940,7 @@ mod tests {
     #[test]
     fn test_list_markers_with_spaces() {
         let mut lexer = Lexer::new("- item\n+ item\n* item\n1. numbered\n42. another");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert!(tokens.iter().any(|t| matches!(t, Token::Hyphen)));
         assert!(tokens.iter().any(|t| matches!(t, Token::Plus)));
@@ -703,7 +953,7 @@ mod tests {
     #[test]
     fn test_emphasis_edge_cases() {
         let mut lexer = Lexer::new("*a*b* **c**d** ***e***f***");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Check for proper asterisk grouping
         let asterisk_tokens: Vec<_> = tokens
@@ -725,7 +975,7 @@ mod tests {
     #[test]
     fn test_blockquote_variations() {
         let mut lexer = Lexer::new("> quote\n>> nested\n> > spaced");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let gt_count = tokens
             .iter()
@@ -737,7 +987,7 @@ mod tests {
     #[test]
     fn test_code_block_edge_cases() {
         let mut lexer = Lexer::new("`inline` ``empty`` ```\nblock\n``` ````four````");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let backtick_tokens: Vec<_> = tokens
             .iter()
@@ -759,7 +1009,7 @@ mod tests {
     #[test]
     fn test_mixed_whitespace() {
         let mut lexer = Lexer::new("  \t  \t  text  \t  ");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should group consecutive whitespace
         assert_eq!(tokens[0], Token::Whitespace);
@@ -770,7 +1020,7 @@ mod tests {
     #[test]
     fn test_numbers_in_text() {
         let mut lexer = Lexer::new("version2.0 test123 abc");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Text with numbers should be handled properly
         let has_version_text = tokens.iter().any(|t| {
@@ -786,7 +1036,7 @@ mod tests {
     #[test]
     fn test_punctuation_combinations() {
         let mut lexer = Lexer::new("... !!! --- +++ |||");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should handle repeated punctuation
         let dot_count = tokens.iter().filter(|t| matches!(t, Token::Dot)).count();
@@ -808,7 +1058,7 @@ mod tests {
     #[test]
     fn test_url_in_complex_text() {
         let mut lexer = Lexer::new("Visit https://example.com for more info");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens[0], Token::Text("Visit".to_string()));
         assert_eq!(tokens[2], Token::Url("https://example.com".to_string()));
@@ -818,7 +1068,7 @@ mod tests {
     #[test]
     fn test_edge_case_combinations() {
         let mut lexer = Lexer::new("#*`_~[]()|:.-+123");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should tokenize each special character separately
         assert_eq!(tokens[0], Token::Hash(1));
@@ -891,7 +1141,7 @@ let code = "block";
 ---"#;
 
         let mut lexer = Lexer::new(complex_md);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Verify various token types exist
         assert!(tokens.iter().any(|t| matches!(t, Token::Hash(_))));
@@ -922,7 +1172,7 @@ let code = "block";
     fn test_very_long_text() {
         let long_text = "a".repeat(10000);
         let mut lexer = Lexer::new(&long_text);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         assert_eq!(tokens.len(), 2); // Text + EOF
         if let Token::Text(text) = &tokens[0] {
@@ -934,7 +1184,7 @@ let code = "block";
     fn test_many_consecutive_newlines() {
         let input = "\n".repeat(1000);
         let mut lexer = Lexer::new(&input);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let newline_count = tokens
             .iter()
@@ -946,7 +1196,7 @@ let code = "block";
     #[test]
     fn test_alternating_patterns() {
         let mut lexer = Lexer::new("*_*_*_*_*_*_*_*_*_");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should alternate between asterisks and underscores
         for (i, token) in tokens.iter().enumerate() {
@@ -959,7 +1209,7 @@ let code = "block";
     #[test]
     fn test_nested_emphasis_markers() {
         let mut lexer = Lexer::new("***___***___***");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let asterisk_count = tokens
             .iter()
@@ -978,7 +1228,7 @@ let code = "block";
     fn test_all_single_characters() {
         let chars = "!()[]|:~`";
         let mut lexer = Lexer::new(chars);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should handle all these characters (some as special tokens, some as text)
         assert!(tokens.len() > 1);
@@ -994,7 +1244,7 @@ let code = "block";
 
         // Test that other characters become text
         let mut lexer2 = Lexer::new("@#$%^&");
-        let tokens2 = lexer2.tokenize().unwrap();
+        let tokens2 = lexer2.tokenize().map(strip_spans).unwrap();
         assert!(tokens2.iter().any(|t| matches!(t, Token::Text(_))));
     }
 
@@ -1014,7 +1264,7 @@ let code = "block";
 
             match expected {
                 Some(num) => {
-                    let tokens = result.unwrap();
+                    let tokens = strip_spans(result.unwrap());
                     assert_eq!(tokens[0], Token::Number(num));
                 }
                 None => {
@@ -1027,12 +1277,12 @@ let code = "block";
     #[test]
     fn test_mixed_unicode_and_ascii() {
         let mut lexer = Lexer::new("Hello 世界! こんにちは # Header 🚀");
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Should handle Unicode properly
         let has_unicode = tokens.iter().any(|t| {
             if let Token::Text(text) = t {
-                text.chars().any(|c| !c.is_ascii())
+                !text.is_ascii()
             } else {
                 false
             }
@@ -1058,7 +1308,7 @@ let code = "block";
 
         for input in test_cases {
             let mut lexer = Lexer::new(input);
-            let tokens = lexer.tokenize().unwrap();
+            let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
             // Should tokenize without errors, even if markdown is malformed
             assert!(tokens.len() >= 2); // At least some tokens + EOF
@@ -1107,7 +1357,7 @@ let code = "block";
 
         for (input, should_be_url) in test_cases {
             let mut lexer = Lexer::new(input);
-            let tokens = lexer.tokenize().unwrap();
+            let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
             let is_url = matches!(tokens[0], Token::Url(_));
             assert_eq!(is_url, should_be_url, "Failed for input: {}", input);
@@ -1127,7 +1377,7 @@ let code = "block";
 
         for input in inputs {
             let mut lexer = Lexer::new(input);
-            let tokens = lexer.tokenize().unwrap();
+            let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
             assert_eq!(tokens[0], Token::Whitespace);
             assert_eq!(tokens[1], Token::Eof);
@@ -1138,7 +1388,7 @@ let code = "block";
     fn test_extreme_nesting() {
         let nested = "[[[[[[[[[text]]]]]]]]]";
         let mut lexer = Lexer::new(nested);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         let left_bracket_count = tokens
             .iter()
@@ -1157,7 +1407,7 @@ let code = "block";
     fn test_mixed_emphasis_with_text() {
         let input = "**bold** *italic* __underline__ _emphasis_ ~~strike~~ `code`";
         let mut lexer = Lexer::new(input);
-        let tokens = lexer.tokenize().unwrap();
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
 
         // Verify we have the right mix of tokens
         assert!(tokens.iter().any(|t| matches!(t, Token::Asterisk(2))));
@@ -1192,7 +1442,57 @@ let code = "block";
 
         // But we should be able to create a new lexer for the rest
         let mut lexer2 = Lexer::new("more text");
-        let tokens = lexer2.tokenize().unwrap();
+        let tokens = lexer2.tokenize().map(strip_spans).unwrap();
         assert!(tokens.iter().any(|t| matches!(t, Token::Text(_))));
     }
+
+    /// Tokens with their line and column, ignoring byte spans
+    fn positioned(input: &str, tab_width: usize) -> Vec<(Token, usize, usize)> {
+        Lexer::with_tab_width(input, tab_width)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.token, t.line, t.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_tab_expands_to_tab_stop() {
+        let tokens = positioned("\tx", 4);
+        assert_eq!(tokens[1], (Token::Text("x".to_string()), 1, 5));
+
+        // A tab after some spaces only moves to the next stop
+        let tokens = positioned("  \tx", 4);
+        assert_eq!(tokens[1], (Token::Text("x".to_string()), 1, 5));
+
+        let tokens = positioned("\tx", 8);
+        assert_eq!(tokens[1], (Token::Text("x".to_string()), 1, 9));
+    }
+
+    #[test]
+    fn test_tab_indented_item_matches_spaces() {
+        let tabs = "- parent\n\t- child\n";
+        let spaces = "- parent\n    - child\n";
+
+        assert_eq!(positioned(tabs, 4), positioned(spaces, 4));
+        assert_eq!(
+            crate::markdown_parser::parse_markdown(tabs).unwrap(),
+            crate::markdown_parser::parse_markdown(spaces).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_intraword_underscores_are_text() {
+        let mut lexer = Lexer::new("my_var__name _x_");
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
+        assert_eq!(tokens[0], Token::Text("my_var__name".to_string()));
+        assert_eq!(tokens[2], Token::Underscore(1));
+        assert_eq!(tokens[4], Token::Underscore(1));
+
+        // A trailing underscore is still a delimiter
+        let mut lexer = Lexer::new("word_ next");
+        let tokens = lexer.tokenize().map(strip_spans).unwrap();
+        assert_eq!(tokens[0], Token::Text("word".to_string()));
+        assert_eq!(tokens[1], Token::Underscore(1));
+    }
 }
@@ -1,12 +1,38 @@
+#[allow(clippy::module_inception)]
 mod lexer;
 pub mod tokens;
 
-pub use lexer::Lexer;
-pub use tokens::Token;
+pub use lexer::{Lexer, BOM, DEFAULT_TAB_WIDTH};
+pub use tokens::{strip_spans, SpannedToken, Token};
 
 use crate::error::LexerError;
+use std::collections::HashMap;
 
-pub fn tokenize(input: &str) -> Result<Vec<Token>, LexerError> {
+pub fn tokenize(input: &str) -> Result<Vec<SpannedToken>, LexerError> {
     let mut lexer = Lexer::new(input);
     lexer.tokenize()
 }
+
+/// Count the tokens of each kind in `input`, e.g. `"Asterisk"`, for diagnosing parser bugs
+pub fn tokenize_stats(input: &str) -> Result<HashMap<&'static str, usize>, LexerError> {
+    let mut stats = HashMap::new();
+    for spanned in tokenize(input)? {
+        *stats.entry(spanned.token.kind()).or_insert(0) += 1;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_stats() {
+        let stats = tokenize_stats("**a** *b*").unwrap();
+        assert_eq!(stats["Asterisk"], 4);
+        assert_eq!(stats["Text"], 2);
+        assert_eq!(stats["Whitespace"], 1);
+        assert_eq!(stats["Eof"], 1);
+        assert!(!stats.contains_key("Hash"));
+    }
+}
@@ -11,6 +11,7 @@ pub enum Token {
     Asterisk(u8),
     Underscore(u8),
     Tilde(u8),
+    Equals(u8),
     Backtick(u8),
     LeftBracket,
     RightBracket,
@@ -27,8 +28,102 @@ pub enum Token {
 
     // Tables
     Pipe,
+    /// `\|`, a literal pipe that doesn't separate table columns
+    EscapedPipe,
     Colon,
 
+    // Math
+    Dollar(u8),
+
     // Links and references
     Url(String),
+
+    /// A raw inline HTML tag such as `<br>`, `<sub>` or `</sub>`
+    Html(String),
+}
+
+/// A token together with where it was found in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    /// Byte range `(start, end)` into the source
+    pub span: (usize, usize),
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SpannedToken {
+    /// Get the exact source text covered by this token
+    pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.span.0..self.span.1]
+    }
+}
+
+/// Drop span information, keeping only the tokens
+pub fn strip_spans(tokens: Vec<SpannedToken>) -> Vec<Token> {
+    tokens.into_iter().map(|spanned| spanned.token).collect()
+}
+
+impl Token {
+    /// Name of the token's variant, without its contents
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Token::Text(_) => "Text",
+            Token::Newline => "Newline",
+            Token::Whitespace => "Whitespace",
+            Token::Eof => "Eof",
+            Token::Hash(_) => "Hash",
+            Token::Asterisk(_) => "Asterisk",
+            Token::Underscore(_) => "Underscore",
+            Token::Tilde(_) => "Tilde",
+            Token::Equals(_) => "Equals",
+            Token::Backtick(_) => "Backtick",
+            Token::LeftBracket => "LeftBracket",
+            Token::RightBracket => "RightBracket",
+            Token::LeftParen => "LeftParen",
+            Token::RightParen => "RightParen",
+            Token::Exclamation => "Exclamation",
+            Token::GreaterThan => "GreaterThan",
+            Token::Hyphen => "Hyphen",
+            Token::Number(_) => "Number",
+            Token::Dot => "Dot",
+            Token::Plus => "Plus",
+            Token::Pipe => "Pipe",
+            Token::EscapedPipe => "EscapedPipe",
+            Token::Colon => "Colon",
+            Token::Dollar(_) => "Dollar",
+            Token::Url(_) => "Url",
+            Token::Html(_) => "Html",
+        }
+    }
+
+    /// Get the source text this token represents
+    pub fn literal(&self) -> String {
+        match self {
+            Token::Text(text) | Token::Url(text) | Token::Html(text) => text.clone(),
+            Token::Newline => "\n".to_string(),
+            Token::Whitespace => " ".to_string(),
+            Token::Eof => String::new(),
+            Token::Hash(count) => "#".repeat(*count as usize),
+            Token::Asterisk(count) => "*".repeat(*count as usize),
+            Token::Underscore(count) => "_".repeat(*count as usize),
+            Token::Tilde(count) => "~".repeat(*count as usize),
+            Token::Equals(count) => "=".repeat(*count as usize),
+            Token::Backtick(count) => "`".repeat(*count as usize),
+            Token::Dollar(count) => "$".repeat(*count as usize),
+            Token::LeftBracket => "[".to_string(),
+            Token::RightBracket => "]".to_string(),
+            Token::LeftParen => "(".to_string(),
+            Token::RightParen => ")".to_string(),
+            Token::Exclamation => "!".to_string(),
+            Token::GreaterThan => ">".to_string(),
+            Token::Hyphen => "-".to_string(),
+            Token::Number(number) => number.to_string(),
+            Token::Dot => ".".to_string(),
+            Token::Plus => "+".to_string(),
+            Token::Pipe => "|".to_string(),
+            Token::EscapedPipe => "\\|".to_string(),
+            Token::Colon => ":".to_string(),
+        }
+    }
 }
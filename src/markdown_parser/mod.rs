@@ -1,25 +1,112 @@
+pub mod cache;
+pub mod detect;
 pub mod lexer;
 pub mod parser;
+pub mod stats;
+pub mod tasks;
 
 // Re-export main types and functions for easier access
-pub use lexer::{tokenize, Lexer, Token};
+pub use lexer::{strip_spans, tokenize, tokenize_stats, Lexer, SpannedToken, Token};
 pub use parser::{
-    parse_markdown as parser_parse_markdown,
-    parse_markdown_or_default as parser_parse_markdown_or_default, parse_tokens, AstNode, Parser,
+    generate_toc, parse_markdown as parser_parse_markdown,
+    parse_markdown_lenient as parser_parse_markdown_lenient,
+    parse_markdown_lenient_with as parser_parse_markdown_lenient_with,
+    parse_markdown_or_default as parser_parse_markdown_or_default, parse_spanned_tokens,
+    parse_tokens, AstNode, BatchParser, BlockParser, FrontMatterFormat, Parser, TocEntry,
 };
 
 use crate::error::MarkError;
 
 /// Parse markdown text into an AST
 pub fn parse_markdown(input: &str) -> Result<AstNode, MarkError> {
-    let tokens = tokenize(input)?;
-    let ast = parse_tokens(tokens)?;
-    Ok(ast)
+    Ok(parser_parse_markdown(input)?)
 }
 
-/// Parse markdown text into an AST, returning a default document on error
+/// Parse markdown text into an AST, letting unclosed code fences run to the end
+pub fn parse_markdown_lenient(input: &str) -> Result<AstNode, MarkError> {
+    Ok(parser_parse_markdown_lenient(input)?)
+}
+
+/// Parse markdown text leniently, failing on unclosed emphasis when `strict_emphasis` is set
+pub fn parse_markdown_lenient_with(
+    input: &str,
+    strict_emphasis: bool,
+) -> Result<AstNode, MarkError> {
+    Ok(parser_parse_markdown_lenient_with(input, strict_emphasis)?)
+}
+
+/// Parse markdown text leniently into an AST, returning a default document on error
 pub fn parse_markdown_or_default(input: &str) -> AstNode {
-    parse_markdown(input).unwrap_or_else(|_| AstNode::Document { children: vec![] })
+    parse_markdown_or_default_with(input, false)
+}
+
+/// Like `parse_markdown_or_default`, but unclosed emphasis gives the default document
+/// when `strict_emphasis` is set
+pub fn parse_markdown_or_default_with(input: &str, strict_emphasis: bool) -> AstNode {
+    parse_markdown_lenient_with(input, strict_emphasis)
+        .unwrap_or_else(|_| AstNode::Document { children: vec![] })
+}
+
+/// Convert markdown text to an HTML fragment
+pub fn to_html(input: &str) -> Result<String, MarkError> {
+    Ok(parse_markdown(input)?.to_html())
+}
+
+/// Convert markdown text to plain text, dropping inline markup
+pub fn to_plain_text(input: &str) -> Result<String, MarkError> {
+    Ok(parse_markdown(input)?.to_plain_text())
+}
+
+/// Use `\n` for every line break in `text`, or `\r\n` when `crlf` is set
+pub fn normalize_line_endings(text: &str, crlf: bool) -> String {
+    let lf = text.replace("\r\n", "\n").replace('\r', "\n");
+    if crlf {
+        lf.replace('\n', "\r\n")
+    } else {
+        lf
+    }
+}
+
+/// Wrap `code` in a document holding a single code block, without parsing it as markdown
+pub fn code_document(code: &str, language: &str) -> AstNode {
+    AstNode::Document {
+        children: vec![AstNode::CodeBlock {
+            language: Some(language.to_string()),
+            info: Some(language.to_string()),
+            code: code.to_string(),
+        }],
+    }
+}
+
+/// Wrap plain `text` in a document holding a single code block, keeping its line breaks
+pub fn text_document(text: &str) -> AstNode {
+    AstNode::Document {
+        children: vec![AstNode::CodeBlock {
+            language: None,
+            info: None,
+            code: text.to_string(),
+        }],
+    }
+}
+
+/// Parse markdown text and serialize the AST as pretty-printed JSON
+pub fn parse_to_json(input: &str, lenient: bool) -> Result<String, MarkError> {
+    let ast = if lenient {
+        parse_markdown_lenient(input)?
+    } else {
+        parse_markdown(input)?
+    };
+    Ok(serde_json::to_string_pretty(&ast)?)
+}
+
+/// Describe an error as pretty-printed JSON, including its line and column when known
+pub fn error_to_json(error: &MarkError) -> String {
+    let mut report = serde_json::json!({ "error": error.to_string() });
+    if let Some((line, column)) = error.position() {
+        report["line"] = line.into();
+        report["column"] = column.into();
+    }
+    serde_json::to_string_pretty(&report).unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -53,6 +140,20 @@ mod integration_tests {
         }
     }
 
+    #[test]
+    fn test_leading_bom_is_skipped() {
+        let with_bom = parse_markdown("\u{FEFF}# Title\n\nText").unwrap();
+        assert_eq!(with_bom, parse_markdown("# Title\n\nText").unwrap());
+        assert!(matches!(
+            with_bom.children()[0],
+            AstNode::Heading { level: 1, .. }
+        ));
+
+        let tokens = tokenize("\u{FEFF}# Title").unwrap();
+        assert_eq!(tokens[0].token, Token::Hash(1));
+        assert_eq!((tokens[0].span, tokens[0].column), ((3, 4), 1));
+    }
+
     #[test]
     fn test_multiple_heading_levels() {
         let markdown = r#"# H1
@@ -139,6 +240,234 @@ mod integration_tests {
         }
     }
 
+    #[test]
+    fn test_highlight() {
+        let markdown = "This is ==highlighted== text.";
+        let ast = parse_markdown(markdown).unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                assert!(content.contains(&AstNode::Highlight(vec![AstNode::Text(
+                    "highlighted".to_string()
+                )])));
+            }
+        }
+    }
+
+    #[test]
+    fn test_highlight_with_nested_emphasis() {
+        let markdown = "==**bold** and *italic*==";
+        let ast = parse_markdown(markdown).unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                if let AstNode::Highlight(inner) = &content[0] {
+                    assert!(matches!(inner[0], AstNode::Bold(_)));
+                    assert!(inner.iter().any(|node| matches!(node, AstNode::Italic(_))));
+                } else {
+                    panic!("Expected highlight node");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lone_equals_is_text() {
+        let ast = parse_markdown("a = b").unwrap();
+        assert_eq!(ast.text_content(), "a = b");
+
+        let ast = parse_markdown("Title\n=====").unwrap();
+        assert_eq!(ast.text_content(), "Title\n=====");
+    }
+
+    #[test]
+    fn test_inline_math() {
+        let ast = parse_markdown("Euler: $e^{i\\pi}+1=0$ holds").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                assert!(content.contains(&AstNode::InlineMath("e^{i\\pi}+1=0".to_string())));
+            }
+        }
+    }
+
+    #[test]
+    fn test_block_math_multiline() {
+        let markdown = "$$\n\\sum_{i=1}^n i\n= \\frac{n(n+1)}{2}\n$$\n\nAfter";
+        let ast = parse_markdown(markdown).unwrap();
+
+        if let AstNode::Document { children } = ast {
+            assert_eq!(
+                children[0],
+                AstNode::BlockMath("\\sum_{i=1}^n i\n= \\frac{n(n+1)}{2}".to_string())
+            );
+            assert!(matches!(children[1], AstNode::Paragraph { .. }));
+        }
+    }
+
+    #[test]
+    fn test_dollar_amounts_stay_text() {
+        let ast = parse_markdown("$5 and $10").unwrap();
+        assert_eq!(ast.text_content(), "$5 and $10");
+    }
+
+    #[test]
+    fn test_unhandled_tokens_stay_text() {
+        // Markers that don't start anything inline keep their source text
+        let markdown = "Call 555 (today) + 2 > 1 | ok, a.b!";
+        let ast = parse_markdown(markdown).unwrap();
+        assert_eq!(ast.text_content(), markdown);
+
+        let ast = parse_markdown("# Step 2 - done!").unwrap();
+        assert_eq!(ast.text_content(), "Step 2 - done!");
+    }
+
+    #[test]
+    fn test_emoji_shortcode() {
+        let ast = parse_markdown("Ship it :rocket:!").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                assert!(content.contains(&AstNode::Emoji("🚀".to_string())));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_emoji_stays_literal() {
+        let ast = parse_markdown("Hello :notanemoji: there").unwrap();
+        assert_eq!(ast.text_content(), "Hello :notanemoji: there");
+    }
+
+    #[test]
+    fn test_emoji_does_not_mangle_urls() {
+        let ast = parse_markdown("Visit http://example.com:8080/x :tada:").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                assert_eq!(
+                    content[2],
+                    AstNode::Text("http://example.com:8080/x".to_string())
+                );
+                assert!(content.contains(&AstNode::Emoji("🎉".to_string())));
+            }
+        }
+    }
+
+    #[test]
+    fn test_horizontal_rule_variants() {
+        for markdown in ["***", "___", "* * *", "_ _ _ _", "- - -"] {
+            let ast = parse_markdown(markdown).unwrap();
+
+            if let AstNode::Document { children } = ast {
+                assert_eq!(
+                    children,
+                    vec![AstNode::HorizontalRule],
+                    "input: {}",
+                    markdown
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_yaml_frontmatter() {
+        let ast = parse_markdown("---\ntitle: Notes\ndraft: true\n---\n# Notes\n\nBody").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            assert_eq!(
+                children[0],
+                AstNode::FrontMatter {
+                    format: FrontMatterFormat::Yaml,
+                    raw: "title: Notes\ndraft: true".to_string(),
+                }
+            );
+            assert!(matches!(children[1], AstNode::Heading { level: 1, .. }));
+            assert!(matches!(children[2], AstNode::Paragraph { .. }));
+            assert_eq!(children.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_toml_frontmatter() {
+        let ast = parse_markdown_lenient("+++\ntitle = \"Notes\"\n+++\n\nBody").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            assert_eq!(
+                children[0],
+                AstNode::FrontMatter {
+                    format: FrontMatterFormat::Toml,
+                    raw: "title = \"Notes\"".to_string(),
+                }
+            );
+            assert_eq!(children.len(), 2);
+        }
+        assert_eq!(
+            to_plain_text("+++\ntitle = \"Notes\"\n+++\nBody").unwrap(),
+            "Body"
+        );
+    }
+
+    #[test]
+    fn test_mid_document_rule_is_not_frontmatter() {
+        let ast = parse_markdown("Intro\n\n---\ntitle: Notes\n---\n").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            assert!(matches!(children[0], AstNode::Paragraph { .. }));
+            assert_eq!(children[1], AstNode::HorizontalRule);
+            assert!(!children
+                .iter()
+                .any(|child| matches!(child, AstNode::FrontMatter { .. })));
+        }
+    }
+
+    #[test]
+    fn test_horizontal_rule_after_paragraph_line() {
+        let ast = parse_markdown("Some text\n***\nMore text").unwrap();
+
+        if let AstNode::Document { children } = ast {
+            assert!(matches!(children[0], AstNode::Paragraph { .. }));
+            assert_eq!(children[1], AstNode::HorizontalRule);
+            assert!(matches!(children[2], AstNode::Paragraph { .. }));
+        }
+    }
+
+    #[test]
+    fn test_inline_triple_asterisk_is_not_rule() {
+        let ast = parse_markdown("Some ***bold italic*** text").unwrap();
+        let text = |text: &str| AstNode::Text(text.to_string());
+        assert_eq!(
+            ast,
+            AstNode::Document {
+                children: vec![AstNode::Paragraph {
+                    content: vec![
+                        text("Some"),
+                        text(" "),
+                        AstNode::Bold(vec![AstNode::Italic(vec![
+                            text("bold"),
+                            text(" "),
+                            text("italic"),
+                        ])]),
+                        text(" "),
+                        text("text"),
+                    ],
+                }],
+            }
+        );
+        assert_eq!(ast.to_plain_text(), "Some bold italic text");
+
+        for markdown in ["***x***", "___x___"] {
+            assert_eq!(
+                parse_markdown(markdown).unwrap(),
+                AstNode::Document {
+                    children: vec![AstNode::Paragraph {
+                        content: vec![AstNode::Bold(vec![AstNode::Italic(vec![text("x")])])],
+                    }],
+                }
+            );
+        }
+    }
+
     #[test]
     fn test_inline_code() {
         let markdown = "Use `console.log()` to debug.";
@@ -179,7 +508,7 @@ mod integration_tests {
 
         if let AstNode::Document { children } = ast {
             let has_ordered_list = children.iter().any(
-                |child| matches!(child, AstNode::List { ordered: true, items } if items.len() == 3),
+                |child| matches!(child, AstNode::List { ordered: true, items, .. } if items.len() == 3),
             );
             assert!(has_ordered_list, "Should contain ordered list with 3 items");
         }
@@ -194,7 +523,7 @@ mod integration_tests {
 
         if let AstNode::Document { children } = ast {
             let has_unordered_list = children.iter().any(|child| {
-                matches!(child, AstNode::List { ordered: false, items } if items.len() == 3)
+                matches!(child, AstNode::List { ordered: false, items, .. } if items.len() == 3)
             });
             assert!(
                 has_unordered_list,
@@ -212,7 +541,7 @@ mod integration_tests {
 
         if let AstNode::Document { children } = ast {
             let has_unordered_list = children.iter().any(|child| {
-                matches!(child, AstNode::List { ordered: false, items } if items.len() == 3)
+                matches!(child, AstNode::List { ordered: false, items, .. } if items.len() == 3)
             });
             assert!(
                 has_unordered_list,
@@ -246,7 +575,7 @@ fn main() {
 
         if let AstNode::Document { children } = ast {
             let has_code_block = children.iter().any(|child| {
-                if let AstNode::CodeBlock { language, code } = child {
+                if let AstNode::CodeBlock { language, code, .. } = child {
                     let lang_matches = language
                         .as_ref()
                         .map(|l| l.contains("rust"))
@@ -276,6 +605,38 @@ some code
         }
     }
 
+    fn fence_info(markdown: &str) -> (Option<String>, Option<String>) {
+        let ast = parse_markdown(markdown).unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        match &children[0] {
+            AstNode::CodeBlock { language, info, .. } => (language.clone(), info.clone()),
+            other => panic!("Expected code block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_code_fence_info_with_comma() {
+        let (language, info) = fence_info("```rust,ignore\nlet x = 1;\n```");
+        assert_eq!(language.as_deref(), Some("rust,ignore"));
+        assert_eq!(info.as_deref(), Some("rust,ignore"));
+    }
+
+    #[test]
+    fn test_code_fence_info_with_attributes() {
+        let (language, info) = fence_info("```js {.line-numbers}\nlet x = 1;\n```");
+        assert_eq!(language.as_deref(), Some("js"));
+        assert_eq!(info.as_deref(), Some("js {.line-numbers}"));
+    }
+
+    #[test]
+    fn test_bare_code_fence_has_no_info() {
+        let (language, info) = fence_info("```\ncode\n```");
+        assert_eq!(language, None);
+        assert_eq!(info, None);
+    }
+
     #[test]
     fn test_horizontal_rule() {
         let markdown = "---";
@@ -446,17 +807,45 @@ console.log("Hello");
         let markdown = "Paragraph 1\n\n\nParagraph 2";
         let ast = parse_markdown(markdown).unwrap();
 
-        if let AstNode::Document { children } = ast {
-            let paragraphs: Vec<_> = children
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        // Extra blank lines don't create empty paragraphs
+        assert_eq!(children.len(), 2);
+        assert!(children
+            .iter()
+            .all(|child| matches!(child, AstNode::Paragraph { .. })));
+        assert_eq!(children[0].text_content(), "Paragraph 1");
+        assert_eq!(children[1].text_content(), "Paragraph 2");
+    }
+
+    #[test]
+    fn test_blank_line_separates_paragraphs() {
+        let ast = parse_markdown("First line\nstill first\n\nSecond\n").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+
+        assert_eq!(children.len(), 2);
+        let AstNode::Paragraph { content } = &children[0] else {
+            panic!("Expected paragraph");
+        };
+        // A single newline is a soft break inside the paragraph
+        assert_eq!(
+            content
                 .iter()
-                .filter(|child| matches!(child, AstNode::Paragraph { .. }))
-                .collect();
-            assert!(
-                paragraphs.len() >= 1,
-                "Should contain at least one paragraph"
-            );
-            // The parser may combine or split paragraphs differently
-        }
+                .filter(|node| matches!(node, AstNode::LineBreak))
+                .count(),
+            1
+        );
+        assert_eq!(children[1].text_content(), "Second");
+
+        // Whitespace-only lines count as blank
+        let ast = parse_markdown("One\n   \nTwo").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 2);
     }
 
     #[test]
@@ -561,6 +950,172 @@ console.log("Hello");
         }
     }
 
+    #[test]
+    fn test_table_cells_keep_numbers_and_spaces() {
+        let markdown = "| Name | Age |\n|---|---|\n| Alice Smith | 30 |";
+        let ast = parse_markdown(markdown).unwrap();
+
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let Some(AstNode::Table { rows, .. }) = children.first() else {
+            panic!("Expected table");
+        };
+        let cells: Vec<String> = rows[0].iter().map(|cell| cell.to_plain_text()).collect();
+        assert_eq!(cells, vec!["Alice Smith", "30"]);
+    }
+
+    #[test]
+    fn test_table_cells_keep_punctuation_and_inline_markup() {
+        let markdown = "| Call | Note |\n|---|---|\n| f(x) ~~s~~ | a-b _c_ |";
+        let ast = parse_markdown(markdown).unwrap();
+
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let Some(AstNode::Table { rows, .. }) = children.first() else {
+            panic!("Expected table");
+        };
+        let cells: Vec<String> = rows[0].iter().map(|cell| cell.to_plain_text()).collect();
+        assert_eq!(cells, vec!["f(x) s", "a-b c"]);
+
+        let AstNode::TableCell { content } = &rows[0][0] else {
+            panic!("Expected cell");
+        };
+        assert!(content
+            .iter()
+            .any(|node| matches!(node, AstNode::Strikethrough(_))));
+        let AstNode::TableCell { content } = &rows[0][1] else {
+            panic!("Expected cell");
+        };
+        assert!(content
+            .iter()
+            .any(|node| matches!(node, AstNode::Italic(_))));
+    }
+
+    #[test]
+    fn test_indented_code_block() {
+        let markdown = "Intro\n\n    fn main() {\n        run();\n\n    }\n\nAfter";
+        let ast = parse_markdown(markdown).unwrap();
+
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 3);
+        assert_eq!(
+            children[1],
+            AstNode::CodeBlock {
+                language: None,
+                info: None,
+                code: "fn main() {\n    run();\n\n}\n".to_string(),
+            }
+        );
+        assert!(matches!(children[2], AstNode::Paragraph { .. }));
+    }
+
+    #[test]
+    fn test_indented_list_content_is_not_code() {
+        let markdown = "- item\n    more text\n    - nested\n\n    still the list";
+        let ast = parse_markdown(markdown).unwrap();
+
+        let mut has_code = false;
+        ast.walk(&mut |node| has_code |= matches!(node, AstNode::CodeBlock { .. }));
+        assert!(!has_code);
+        assert!(ast.to_plain_text().contains("still the list"));
+    }
+
+    #[test]
+    fn test_table_cell_escaped_pipe() {
+        let markdown = "| Expr | Note |\n|---|---|\n| a \\| b | or |";
+        let ast = parse_markdown(markdown).unwrap();
+
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let Some(AstNode::Table { headers, rows, .. }) = children.first() else {
+            panic!("Expected table");
+        };
+        assert_eq!(headers.len(), 2);
+        let cells: Vec<String> = rows[0].iter().map(|cell| cell.to_plain_text()).collect();
+        assert_eq!(cells, vec!["a | b", "or"]);
+
+        // Outside tables the escape still shows a plain pipe
+        let ast = parse_markdown("either \\| or").unwrap();
+        assert_eq!(ast.to_plain_text(), "either | or");
+    }
+
+    #[test]
+    fn test_malformed_table_separator() {
+        let error = parse_markdown("| a | b |\n|--x|---|\n| 1 | 2 |").unwrap_err();
+        assert!(matches!(
+            error,
+            MarkError::Parser(crate::error::ParseError::InvalidTable {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+
+        // Column counts have to line up with the header
+        let error = parse_markdown("| a | b |\n|---|\n| 1 | 2 |").unwrap_err();
+        assert!(error.to_string().contains("1 columns but the header has 2"));
+    }
+
+    #[test]
+    fn test_malformed_table_keeps_surrounding_text_when_lenient() {
+        let markdown = "# Title\n\nSome text\n\n| a | b |\n|---|\n| 1 | 2 |\n\nMore text";
+        let ast = parse_markdown_or_default(markdown);
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 4);
+        assert!(matches!(children[2], AstNode::Paragraph { .. }));
+        let text = ast.to_plain_text();
+        assert!(text.contains("Title"));
+        assert!(text.contains("| a | b |"));
+        assert!(text.contains("More text"));
+
+        let ast = parse_markdown_or_default("| a | b |\n|--x|---|\n\nafter");
+        assert!(ast.to_plain_text().contains("after"));
+    }
+
+    #[test]
+    fn test_table_rows_are_fitted_to_the_header() {
+        let ast = parse_markdown("| a | b |\n|---|---|\n| 1 |\n| 1 | 2 | 3 |").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let Some(AstNode::Table { rows, .. }) = children.first() else {
+            panic!("Expected table");
+        };
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_plain_text()).collect())
+            .collect();
+        assert_eq!(cells, vec![vec!["1", ""], vec!["1", "2"]]);
+    }
+
+    #[test]
+    fn test_pipe_paragraph_is_not_a_table() {
+        let ast = parse_markdown("| not a table\njust text | here").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 1);
+        assert!(matches!(children[0], AstNode::Paragraph { .. }));
+        assert_eq!(
+            children[0].to_plain_text(),
+            "| not a table\njust text | here"
+        );
+
+        // A list below a pipe line is still a list
+        let ast = parse_markdown("| note\n- item").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        assert!(matches!(children[1], AstNode::List { .. }));
+    }
+
     #[test]
     fn test_ast_text_content_method() {
         let markdown = "# Hello *World*";
@@ -693,7 +1248,7 @@ fn fibonacci(n: u32) -> u32 {
                 .find(|child| matches!(child, AstNode::CodeBlock { .. }));
             assert!(code_block.is_some(), "Should contain code block");
 
-            if let AstNode::CodeBlock { language, code } = code_block.unwrap() {
+            if let AstNode::CodeBlock { language, code, .. } = code_block.unwrap() {
                 // Validate language
                 assert!(language.is_some(), "Should have language specified");
                 assert_eq!(language.as_ref().unwrap(), "rust");
@@ -726,7 +1281,7 @@ ORDER BY name;
                 .find(|child| matches!(child, AstNode::CodeBlock { .. }));
             assert!(code_block.is_some(), "Should contain code block");
 
-            if let AstNode::CodeBlock { language, code } = code_block.unwrap() {
+            if let AstNode::CodeBlock { language, code, .. } = code_block.unwrap() {
                 // Should have no language
                 assert!(language.is_none(), "Should not have language specified");
 
@@ -794,7 +1349,7 @@ ORDER BY name;
                 .find(|child| matches!(child, AstNode::List { ordered: true, .. }));
             assert!(ordered_list.is_some(), "Should contain ordered list");
 
-            if let AstNode::List { ordered, items } = ordered_list.unwrap() {
+            if let AstNode::List { ordered, items, .. } = ordered_list.unwrap() {
                 assert!(*ordered, "Should be ordered list");
                 assert_eq!(items.len(), 3, "Should have 3 ordered items");
 
@@ -843,7 +1398,7 @@ ORDER BY name;
                 .find(|child| matches!(child, AstNode::List { ordered: false, .. }));
             assert!(unordered_list.is_some(), "Should contain unordered list");
 
-            if let AstNode::List { ordered, items } = unordered_list.unwrap() {
+            if let AstNode::List { ordered, items, .. } = unordered_list.unwrap() {
                 assert!(!*ordered, "Should be unordered list");
                 assert_eq!(items.len(), 3, "Should have 3 unordered items");
 
@@ -964,7 +1519,7 @@ ORDER BY name;
 
                 // Validate second link
                 let (second_text, second_url) = &links[1];
-                assert_eq!(*second_url, "https://doc.rustlang.org/book/");
+                assert_eq!(*second_url, "https://doc.rust-lang.org/book/");
                 if let AstNode::Text(text) = &second_text[0] {
                     assert_eq!(text, "Rust");
                 }
@@ -972,6 +1527,119 @@ ORDER BY name;
         }
     }
 
+    fn first_inline(markdown: &str) -> AstNode {
+        let ast = parse_markdown(markdown).unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        match &children[0] {
+            AstNode::Paragraph { content } => content[0].clone(),
+            other => panic!("Expected paragraph, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_intraword_underscores_stay_literal() {
+        assert_eq!(
+            first_inline("my_var_name"),
+            AstNode::Text("my_var_name".to_string())
+        );
+        assert_eq!(
+            first_inline("_italic_"),
+            AstNode::Italic(vec![AstNode::Text("italic".to_string())])
+        );
+        assert_eq!(
+            first_inline("_snake_case_"),
+            AstNode::Italic(vec![AstNode::Text("snake_case".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_intraword_asterisks_still_emphasize() {
+        let ast = parse_markdown("un*frigging*believable").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let AstNode::Paragraph { content } = &children[0] else {
+            panic!("Expected paragraph");
+        };
+        assert_eq!(
+            content[1],
+            AstNode::Italic(vec![AstNode::Text("frigging".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_link_url_keeps_hyphens_underscores_and_tildes() {
+        let link = first_inline("[docs](https://example.com/~user/my-page_v2/read_me.md)");
+        assert!(matches!(
+            link,
+            AstNode::Link { url, .. } if url == "https://example.com/~user/my-page_v2/read_me.md"
+        ));
+    }
+
+    #[test]
+    fn test_image_url_keeps_hyphens_and_underscores() {
+        let image = first_inline("![logo](assets/rust-logo_512x512.png)");
+        assert!(matches!(
+            image,
+            AstNode::Image { url, .. } if url == "assets/rust-logo_512x512.png"
+        ));
+    }
+
+    #[test]
+    fn test_image_title_is_split_from_url() {
+        let image = first_inline("![logo](img/logo.png \"The Rust logo\")");
+        assert!(matches!(
+            image,
+            AstNode::Image { url, title: Some(title), .. }
+                if url == "img/logo.png" && title == "The Rust logo"
+        ));
+        let image = first_inline("![logo](img/logo.png 'Logo')");
+        assert!(matches!(image, AstNode::Image { title: Some(title), .. } if title == "Logo"));
+        let image = first_inline("![logo](img/logo.png)");
+        assert!(matches!(image, AstNode::Image { title: None, .. }));
+        assert_eq!(
+            to_html("![a](b.png \"T\")").unwrap(),
+            "<p><img src=\"b.png\" alt=\"a\" title=\"T\"></p>"
+        );
+    }
+
+    #[test]
+    fn test_link_url_with_balanced_parens() {
+        let link = first_inline("[x](https://en.wikipedia.org/wiki/Foo_(bar)) after");
+        assert!(matches!(
+            link,
+            AstNode::Link { url, .. } if url == "https://en.wikipedia.org/wiki/Foo_(bar)"
+        ));
+    }
+
+    #[test]
+    fn test_link_url_with_unbalanced_paren_ends_at_first_close() {
+        let ast = parse_markdown("[x](https://example.com/a(b) and more text").unwrap();
+        let AstNode::Document { children } = ast else {
+            panic!("Expected document");
+        };
+        let AstNode::Paragraph { content } = &children[0] else {
+            panic!("Expected paragraph");
+        };
+
+        assert!(matches!(
+            &content[0],
+            AstNode::Link { url, .. } if url == "https://example.com/a(b"
+        ));
+        assert!(AstNode::Paragraph {
+            content: content.clone()
+        }
+        .to_plain_text()
+        .ends_with("and more text"));
+    }
+
+    #[test]
+    fn test_link_url_does_not_cross_blank_lines() {
+        assert!(parse_markdown("[x](https://example.com\n\nlater)").is_err());
+    }
+
     #[test]
     fn test_image_content_validation() {
         let markdown = r#"![Rust Logo](https://www.rust-lang.org/logos/rust-logo-512x512.png) and ![Alt Text](local-image.jpg)"#;
@@ -982,7 +1650,7 @@ ORDER BY name;
                 let images: Vec<(&Vec<AstNode>, &String)> = content
                     .iter()
                     .filter_map(|node| {
-                        if let AstNode::Image { alt, url } = node {
+                        if let AstNode::Image { alt, url, .. } = node {
                             Some((alt, url))
                         } else {
                             None
@@ -996,7 +1664,7 @@ ORDER BY name;
                 let (first_alt, first_url) = &images[0];
                 assert_eq!(
                     *first_url,
-                    "https://www.rustlang.org/logos/rustlogox512.png"
+                    "https://www.rust-lang.org/logos/rust-logo-512x512.png"
                 );
                 if let AstNode::Text(alt_text) = &first_alt[0] {
                     assert_eq!(alt_text, "Rust");
@@ -1004,7 +1672,7 @@ ORDER BY name;
 
                 // Validate second image
                 let (second_alt, second_url) = &images[1];
-                assert_eq!(*second_url, "localimage.jpg");
+                assert_eq!(*second_url, "local-image.jpg");
                 if let AstNode::Text(alt_text) = &second_alt[0] {
                     assert_eq!(alt_text, "Alt");
                 }
@@ -1012,6 +1680,112 @@ ORDER BY name;
         }
     }
 
+    #[test]
+    fn test_nested_blockquote() {
+        let ast = parse_markdown("> outer\n> > inner\n> > more\n> back").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 1);
+
+        let AstNode::BlockQuote { content } = &children[0] else {
+            panic!("Expected blockquote");
+        };
+        assert_eq!(content[0], AstNode::Text("outer".to_string()));
+        let AstNode::BlockQuote { content: inner } = &content[1] else {
+            panic!("Expected nested blockquote, got {:?}", content[1]);
+        };
+        assert_eq!(inner.last(), Some(&AstNode::Text("more".to_string())));
+        assert_eq!(content[2], AstNode::Text("back".to_string()));
+
+        assert_eq!(ast.to_plain_text(), "> outer\n> > inner\n> > more\n> back");
+    }
+
+    #[test]
+    fn test_asterisk_bullet_list() {
+        let ast = parse_markdown("Intro\n* one\n* *two*\n+ three").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 2);
+
+        let AstNode::List { ordered, items, .. } = &children[1] else {
+            panic!("Expected list, got {:?}", children[1]);
+        };
+        assert!(!ordered);
+        assert_eq!(items.len(), 3);
+        assert_eq!(
+            items[1],
+            AstNode::ListItem {
+                content: vec![AstNode::Italic(vec![AstNode::Text("two".to_string())])]
+            }
+        );
+    }
+
+    #[test]
+    fn test_line_leading_emphasis_is_not_a_bullet() {
+        let ast = parse_markdown("*emphasis* at the start\nthen *more* mid-line").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 1);
+        let AstNode::Paragraph { content } = &children[0] else {
+            panic!("Expected paragraph, got {:?}", children[0]);
+        };
+        assert_eq!(
+            content[0],
+            AstNode::Italic(vec![AstNode::Text("emphasis".to_string())])
+        );
+        assert_eq!(
+            ast.to_plain_text(),
+            "emphasis at the start\nthen more mid-line"
+        );
+    }
+
+    #[test]
+    fn test_definition_list() {
+        let ast = parse_markdown("Intro text\n\nTerm\n: Definition").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 2);
+        assert_eq!(
+            children[1],
+            AstNode::DefinitionList {
+                items: vec![(
+                    vec![AstNode::Text("Term".to_string())],
+                    vec![AstNode::Paragraph {
+                        content: vec![AstNode::Text("Definition".to_string())]
+                    }]
+                )]
+            }
+        );
+    }
+
+    #[test]
+    fn test_definition_list_multiple_definitions() {
+        let ast = parse_markdown("Apple\n: A fruit\n: A company\n\nPear\n: Another fruit").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("Expected document");
+        };
+        assert_eq!(children.len(), 1);
+        let AstNode::DefinitionList { items } = &children[0] else {
+            panic!("Expected definition list, got {:?}", children[0]);
+        };
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].1.len(), 2);
+        assert_eq!(
+            ast.to_plain_text(),
+            "Apple\n: A fruit\n: A company\n\nPear\n: Another fruit"
+        );
+    }
+
+    #[test]
+    fn test_leading_colon_stays_literal() {
+        let ast = parse_markdown(": not a definition\n\nTerm\n:tight").unwrap();
+        assert_eq!(ast.to_plain_text(), ": not a definition\n\nTerm\n:tight");
+    }
+
     #[test]
     fn test_blockquote_content_validation() {
         let markdown = r#"> This is a blockquote with **bold** text.
@@ -1079,7 +1853,7 @@ ORDER BY name;
                 .find(|child| matches!(child, AstNode::Table { .. }));
             assert!(table.is_some(), "Should contain table");
 
-            if let AstNode::Table { headers, rows } = table.unwrap() {
+            if let AstNode::Table { headers, rows, .. } = table.unwrap() {
                 // Validate headers
                 assert_eq!(headers.len(), 3, "Should have 3 headers");
 
@@ -1112,7 +1886,7 @@ ORDER BY name;
                 assert_eq!(rows.len(), 3, "Should have 3 data rows");
 
                 // Check first row content
-                if let Some(first_row) = rows.get(0) {
+                if let Some(first_row) = rows.first() {
                     assert_eq!(first_row.len(), 3, "First row should have 3 cells");
 
                     let cell_texts: Vec<String> = first_row
@@ -1286,7 +2060,7 @@ And some inline `code()` too.
             assert_eq!(blockquotes.len(), 1, "Should have one blockquote");
 
             // Validate code block content and language
-            if let AstNode::CodeBlock { language, code } = &code_blocks[0] {
+            if let AstNode::CodeBlock { language, code, .. } = &code_blocks[0] {
                 assert_eq!(language.as_ref().unwrap(), "rust");
                 assert!(code.contains("fn"));
                 assert!(code.contains("main"));
@@ -1332,4 +2106,59 @@ And some inline `code()` too.
             }
         }
     }
+
+    #[test]
+    fn test_code_document() {
+        let ast = code_document("# not a heading\nfn main() {}\n", "rust");
+        assert_eq!(
+            ast,
+            AstNode::Document {
+                children: vec![AstNode::CodeBlock {
+                    language: Some("rust".to_string()),
+                    info: Some("rust".to_string()),
+                    code: "# not a heading\nfn main() {}\n".to_string(),
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn test_unterminated_code_block_errors() {
+        let error = parse_markdown("Intro\n\n  ```rust\nfn main() {}\n").unwrap_err();
+        assert!(matches!(
+            error,
+            MarkError::Parser(crate::error::ParseError::UnmatchedDelimiter { delimiter: '`', .. })
+        ));
+        assert_eq!(error.position(), Some((3, 3)));
+
+        let ast = parse_markdown_lenient("```rust\nfn main() {}\n").unwrap();
+        assert_eq!(ast.to_plain_text(), "fn main() {}");
+    }
+
+    #[test]
+    fn test_parse_to_json_heading() {
+        let json = parse_to_json("## Title", false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let heading = &value["Document"]["children"][0]["Heading"];
+        assert_eq!(heading["level"], 2);
+        assert_eq!(heading["content"][0]["Text"], "Title");
+    }
+
+    #[test]
+    fn test_error_to_json_includes_position() {
+        let error = MarkError::Parser(crate::error::ParseError::unmatched_delimiter('*', 3, 7));
+        let value: serde_json::Value = serde_json::from_str(&error_to_json(&error)).unwrap();
+
+        assert_eq!(value["line"], 3);
+        assert_eq!(value["column"], 7);
+        assert!(value["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unmatched delimiter"));
+
+        let value: serde_json::Value =
+            serde_json::from_str(&error_to_json(&MarkError::config("bad"))).unwrap();
+        assert!(value.get("line").is_none());
+    }
 }
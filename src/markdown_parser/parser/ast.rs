@@ -1,4 +1,24 @@
-#[derive(Debug, Clone, PartialEq)]
+use super::frontmatter::FrontMatterFormat;
+use crate::markdown_parser::stats::prose;
+use serde::Serialize;
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// How a table column is aligned, from the colons in its separator row cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum ColumnAlignment {
+    /// No colons, e.g. `---`
+    #[default]
+    None,
+    /// `:--`
+    Left,
+    /// `:-:`
+    Center,
+    /// `--:`
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum AstNode {
     Document {
         children: Vec<AstNode>,
@@ -14,21 +34,38 @@ pub enum AstNode {
     },
     List {
         ordered: bool,
+        /// Number of the first item, always 1 for unordered lists
+        start: u32,
         items: Vec<AstNode>,
     },
     ListItem {
         content: Vec<AstNode>,
     },
+    /// A list item starting with a `[ ]` or `[x]` checkbox
+    TaskListItem {
+        checked: bool,
+        content: Vec<AstNode>,
+    },
     BlockQuote {
         content: Vec<AstNode>,
     },
+    /// Terms, each with one or more definitions (as paragraphs)
+    DefinitionList {
+        items: Vec<(Vec<AstNode>, Vec<AstNode>)>,
+    },
     CodeBlock {
+        /// First word of the fence info string
         language: Option<String>,
+        /// Full info string after the opening fence, e.g. `rust,ignore` or `js {.numbered}`
+        info: Option<String>,
         code: String,
     },
+    BlockMath(String),
     HorizontalRule,
     Table {
         headers: Vec<AstNode>,
+        /// One per header column
+        alignments: Vec<ColumnAlignment>,
         rows: Vec<Vec<AstNode>>,
     },
     TableCell {
@@ -43,7 +80,10 @@ pub enum AstNode {
     Bold(Vec<AstNode>),
     Italic(Vec<AstNode>),
     Strikethrough(Vec<AstNode>),
+    Highlight(Vec<AstNode>),
     InlineCode(String),
+    InlineMath(String),
+    Emoji(String),
     Link {
         text: Vec<AstNode>,
         url: String,
@@ -51,11 +91,61 @@ pub enum AstNode {
     Image {
         alt: Vec<AstNode>,
         url: String,
+        /// Quoted text after the URL, as in `![alt](url "title")`
+        title: Option<String>,
+    },
+    /// A raw inline HTML tag, kept verbatim
+    Html(String),
+    /// Metadata at the very start of the document, kept unparsed
+    FrontMatter {
+        format: FrontMatterFormat,
+        raw: String,
     },
 
     LineBreak,
 }
 
+/// Check whether a raw HTML tag is a line break, such as `<br>` or `<br/>`
+pub fn is_break_tag(tag: &str) -> bool {
+    let name: String = tag
+        .trim_start_matches('<')
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+    !tag.starts_with("</") && name.eq_ignore_ascii_case("br")
+}
+
+/// A heading listed in a document's table of contents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    pub level: u8,
+    pub title: String,
+    /// Index of the heading among the document's top-level blocks
+    pub block: usize,
+}
+
+/// List the document's top-level headings in order
+///
+/// Headings nested in quotes or lists aren't section titles and are left out.
+pub fn generate_toc(ast: &AstNode) -> Vec<TocEntry> {
+    let blocks = match ast {
+        AstNode::Document { children } => children.as_slice(),
+        other => std::slice::from_ref(other),
+    };
+    blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(block, node)| match node {
+            AstNode::Heading { level, content } => Some(TocEntry {
+                level: *level,
+                title: inline_plain_text(content).trim().to_string(),
+                block,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 impl AstNode {
     /// Check if this node is an inline element
     pub fn is_inline(&self) -> bool {
@@ -65,9 +155,13 @@ impl AstNode {
                 | AstNode::Bold(_)
                 | AstNode::Italic(_)
                 | AstNode::Strikethrough(_)
+                | AstNode::Highlight(_)
                 | AstNode::InlineCode(_)
+                | AstNode::InlineMath(_)
+                | AstNode::Emoji(_)
                 | AstNode::Link { .. }
                 | AstNode::Image { .. }
+                | AstNode::Html(_)
                 | AstNode::LineBreak
         )
     }
@@ -77,19 +171,78 @@ impl AstNode {
         !self.is_inline()
     }
 
+    /// The direct child nodes, in document order
+    pub fn children(&self) -> Vec<&AstNode> {
+        match self {
+            AstNode::Document { children }
+            | AstNode::Bold(children)
+            | AstNode::Italic(children)
+            | AstNode::Strikethrough(children)
+            | AstNode::Highlight(children)
+            | AstNode::Heading {
+                content: children, ..
+            }
+            | AstNode::Paragraph { content: children }
+            | AstNode::ListItem { content: children }
+            | AstNode::TaskListItem {
+                content: children, ..
+            }
+            | AstNode::BlockQuote { content: children }
+            | AstNode::TableCell { content: children }
+            | AstNode::TableRow { cells: children }
+            | AstNode::List {
+                items: children, ..
+            }
+            | AstNode::Link { text: children, .. }
+            | AstNode::Image { alt: children, .. } => children.iter().collect(),
+            AstNode::DefinitionList { items } => items
+                .iter()
+                .flat_map(|(term, definitions)| term.iter().chain(definitions))
+                .collect(),
+            AstNode::Table { headers, rows, .. } => {
+                headers.iter().chain(rows.iter().flatten()).collect()
+            }
+            AstNode::CodeBlock { .. }
+            | AstNode::BlockMath(_)
+            | AstNode::HorizontalRule
+            | AstNode::Text(_)
+            | AstNode::InlineCode(_)
+            | AstNode::InlineMath(_)
+            | AstNode::Emoji(_)
+            | AstNode::Html(_)
+            | AstNode::FrontMatter { .. }
+            | AstNode::LineBreak => Vec::new(),
+        }
+    }
+
+    /// Call `visit` on this node and every descendant, parents before children
+    pub fn walk<F: FnMut(&AstNode)>(&self, visit: &mut F) {
+        visit(self);
+        for child in self.children() {
+            child.walk(visit);
+        }
+    }
+
     /// Get the text content of this node recursively
     pub fn text_content(&self) -> String {
         match self {
             AstNode::Text(text) => text.clone(),
-            AstNode::InlineCode(code) => code.clone(),
+            AstNode::InlineCode(code)
+            | AstNode::InlineMath(code)
+            | AstNode::BlockMath(code)
+            | AstNode::Emoji(code) => code.clone(),
             AstNode::Bold(children)
             | AstNode::Italic(children)
             | AstNode::Strikethrough(children)
+            | AstNode::Highlight(children)
             | AstNode::Heading {
                 content: children, ..
             }
             | AstNode::Paragraph { content: children }
             | AstNode::ListItem { content: children }
+            | AstNode::TaskListItem {
+                content: children, ..
+            }
             | AstNode::BlockQuote { content: children }
             | AstNode::TableCell { content: children } => children
                 .iter()
@@ -106,7 +259,7 @@ impl AstNode {
                 .map(|item| item.text_content())
                 .collect::<Vec<_>>()
                 .join("\n"),
-            AstNode::Table { headers, rows } => {
+            AstNode::Table { headers, rows, .. } => {
                 let header_text = headers
                     .iter()
                     .map(|h| h.text_content())
@@ -123,6 +276,17 @@ impl AstNode {
                     .collect();
                 format!("{}\n{}", header_text, row_texts.join("\n"))
             }
+            AstNode::DefinitionList { items } => items
+                .iter()
+                .flat_map(|(term, definitions)| {
+                    std::iter::once(term.iter().map(|node| node.text_content()).collect()).chain(
+                        definitions
+                            .iter()
+                            .map(|definition| definition.text_content()),
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n"),
             AstNode::TableRow { cells } => cells
                 .iter()
                 .map(|cell| cell.text_content())
@@ -135,6 +299,94 @@ impl AstNode {
                 .collect::<Vec<_>>()
                 .join("\n"),
             AstNode::HorizontalRule => "---".to_string(),
+            AstNode::Html(tag) if is_break_tag(tag) => "\n".to_string(),
+            AstNode::Html(_) | AstNode::FrontMatter { .. } => String::new(),
+            AstNode::LineBreak => "\n".to_string(),
+        }
+    }
+
+    /// Render this node as readable plain text, keeping block structure
+    ///
+    /// Blocks are separated by blank lines, list items get `-` or `N.` markers
+    /// and quotes are prefixed with `> `, while inline markup is dropped.
+    pub fn to_plain_text(&self) -> String {
+        match self {
+            AstNode::Document { children } => children
+                .iter()
+                .map(|child| child.to_plain_text())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            AstNode::Heading { content, .. }
+            | AstNode::Paragraph { content }
+            | AstNode::ListItem { content }
+            | AstNode::TableCell { content } => inline_plain_text(content),
+            AstNode::TaskListItem { checked, content } => {
+                let marker = if *checked { "[x]" } else { "[ ]" };
+                format!("{} {}", marker, inline_plain_text(content))
+            }
+            AstNode::List {
+                ordered,
+                start,
+                items,
+            } => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let marker = if *ordered {
+                        format!("{}. ", *start as usize + i)
+                    } else {
+                        "- ".to_string()
+                    };
+                    format!("{}{}", marker, item.to_plain_text())
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            AstNode::BlockQuote { content } => quote_plain_text(content)
+                .lines()
+                .map(|line| format!("> {}", line))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            AstNode::DefinitionList { items } => items
+                .iter()
+                .map(|(term, definitions)| {
+                    std::iter::once(inline_plain_text(term))
+                        .chain(
+                            definitions
+                                .iter()
+                                .map(|definition| format!(": {}", definition.to_plain_text())),
+                        )
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            AstNode::CodeBlock { code, .. } => code.trim_end_matches('\n').to_string(),
+            AstNode::BlockMath(math) => math.clone(),
+            AstNode::HorizontalRule => "---".to_string(),
+            AstNode::Table {
+                headers,
+                alignments,
+                rows,
+            } => table_plain_text(headers, alignments, rows),
+            AstNode::TableRow { cells } => cells
+                .iter()
+                .map(|cell| cell.to_plain_text())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            AstNode::Bold(children)
+            | AstNode::Italic(children)
+            | AstNode::Strikethrough(children)
+            | AstNode::Highlight(children)
+            | AstNode::Link { text: children, .. }
+            | AstNode::Image { alt: children, .. } => inline_plain_text(children),
+            AstNode::Text(text)
+            | AstNode::InlineCode(text)
+            | AstNode::InlineMath(text)
+            | AstNode::Emoji(text) => text.clone(),
+            AstNode::Html(tag) if is_break_tag(tag) => "\n".to_string(),
+            // Metadata isn't part of the readable text
+            AstNode::Html(_) | AstNode::FrontMatter { .. } => String::new(),
             AstNode::LineBreak => "\n".to_string(),
         }
     }
@@ -146,11 +398,15 @@ impl AstNode {
             | AstNode::Bold(children)
             | AstNode::Italic(children)
             | AstNode::Strikethrough(children)
+            | AstNode::Highlight(children)
             | AstNode::Heading {
                 content: children, ..
             }
             | AstNode::Paragraph { content: children }
             | AstNode::ListItem { content: children }
+            | AstNode::TaskListItem {
+                content: children, ..
+            }
             | AstNode::BlockQuote { content: children }
             | AstNode::TableCell { content: children } => {
                 children.len()
@@ -173,7 +429,7 @@ impl AstNode {
                         .map(|item| item.count_children())
                         .sum::<usize>()
             }
-            AstNode::Table { headers, rows } => {
+            AstNode::Table { headers, rows, .. } => {
                 let header_count =
                     headers.len() + headers.iter().map(|h| h.count_children()).sum::<usize>();
                 let row_count = rows
@@ -184,6 +440,18 @@ impl AstNode {
                     .sum::<usize>();
                 header_count + row_count
             }
+            AstNode::DefinitionList { items } => items
+                .iter()
+                .map(|(term, definitions)| {
+                    term.len()
+                        + definitions.len()
+                        + term
+                            .iter()
+                            .chain(definitions)
+                            .map(|child| child.count_children())
+                            .sum::<usize>()
+                })
+                .sum(),
             AstNode::TableRow { cells } => {
                 cells.len()
                     + cells
@@ -194,11 +462,273 @@ impl AstNode {
             _ => 0, // Leaf nodes
         }
     }
+
+    /// Count the words of prose, leaving out code blocks, math blocks and image descriptions
+    pub fn word_count(&self) -> usize {
+        prose(self).split_whitespace().count()
+    }
+
+    /// Estimate how long the prose takes to read at `wpm` words per minute
+    pub fn reading_time(&self, wpm: usize) -> Duration {
+        let millis = self.word_count() as u64 * 60_000 / wpm.max(1) as u64;
+        Duration::from_millis(millis)
+    }
+}
+
+impl AstNode {
+    /// Merge adjacent `Text` nodes and drop empty ones, throughout the tree
+    ///
+    /// Whitespace at the start or end of a paragraph, heading, list item,
+    /// table cell or definition term is trimmed as well. Useful for
+    /// comparing trees without caring how the parser split up the text.
+    pub fn normalize(self) -> Self {
+        let inline = |nodes| normalize_nodes(nodes, true);
+        let nested = |nodes| normalize_nodes(nodes, false);
+
+        match self {
+            AstNode::Document { children } => AstNode::Document {
+                children: nested(children),
+            },
+            AstNode::Heading { level, content } => AstNode::Heading {
+                level,
+                content: inline(content),
+            },
+            AstNode::Paragraph { content } => AstNode::Paragraph {
+                content: inline(content),
+            },
+            AstNode::List {
+                ordered,
+                start,
+                items,
+            } => AstNode::List {
+                ordered,
+                start,
+                items: nested(items),
+            },
+            AstNode::ListItem { content } => AstNode::ListItem {
+                content: inline(content),
+            },
+            AstNode::TaskListItem { checked, content } => AstNode::TaskListItem {
+                checked,
+                content: inline(content),
+            },
+            AstNode::BlockQuote { content } => AstNode::BlockQuote {
+                content: nested(content),
+            },
+            AstNode::DefinitionList { items } => AstNode::DefinitionList {
+                items: items
+                    .into_iter()
+                    .map(|(term, definitions)| (inline(term), nested(definitions)))
+                    .collect(),
+            },
+            AstNode::Table {
+                headers,
+                alignments,
+                rows,
+            } => AstNode::Table {
+                headers: nested(headers),
+                alignments,
+                rows: rows.into_iter().map(nested).collect(),
+            },
+            AstNode::TableCell { content } => AstNode::TableCell {
+                content: inline(content),
+            },
+            AstNode::TableRow { cells } => AstNode::TableRow {
+                cells: nested(cells),
+            },
+            AstNode::Bold(content) => AstNode::Bold(nested(content)),
+            AstNode::Italic(content) => AstNode::Italic(nested(content)),
+            AstNode::Strikethrough(content) => AstNode::Strikethrough(nested(content)),
+            AstNode::Highlight(content) => AstNode::Highlight(nested(content)),
+            AstNode::Link { text, url } => AstNode::Link {
+                text: nested(text),
+                url,
+            },
+            AstNode::Image { alt, url, title } => AstNode::Image {
+                alt: nested(alt),
+                url,
+                title,
+            },
+            leaf => leaf,
+        }
+    }
+}
+
+/// Normalize `nodes`, merging adjacent text and optionally trimming whitespace at the ends
+fn normalize_nodes(nodes: Vec<AstNode>, trim: bool) -> Vec<AstNode> {
+    let mut merged: Vec<AstNode> = Vec::with_capacity(nodes.len());
+    for node in nodes.into_iter().map(AstNode::normalize) {
+        match (merged.last_mut(), node) {
+            (_, AstNode::Text(text)) if text.is_empty() => {}
+            (Some(AstNode::Text(previous)), AstNode::Text(text)) => previous.push_str(&text),
+            (_, node) => merged.push(node),
+        }
+    }
+
+    if trim {
+        if let Some(AstNode::Text(text)) = merged.first_mut() {
+            *text = text.trim_start().to_string();
+        }
+        if let Some(AstNode::Text(text)) = merged.last_mut() {
+            *text = text.trim_end().to_string();
+        }
+        merged.retain(|node| !matches!(node, AstNode::Text(text) if text.is_empty()));
+    }
+    merged
+}
+
+/// Concatenate the plain text of inline nodes
+fn inline_plain_text(nodes: &[AstNode]) -> String {
+    nodes.iter().map(|node| node.to_plain_text()).collect()
+}
+
+/// Render a table with pipes and dashes, padding each column to its widest cell
+///
+/// Cells are padded according to the column's alignment, and the separator row
+/// keeps the colons so the output is still a valid markdown table.
+fn table_plain_text(
+    headers: &[AstNode],
+    alignments: &[ColumnAlignment],
+    rows: &[Vec<AstNode>],
+) -> String {
+    let cell_text = |cell: &AstNode| {
+        cell.to_plain_text()
+            .replace('\n', " ")
+            .replace('|', "\\|")
+            .trim()
+            .to_string()
+    };
+    let header_cells: Vec<String> = headers.iter().map(cell_text).collect();
+    let row_cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(cell_text).collect())
+        .collect();
+
+    let columns = row_cells
+        .iter()
+        .map(Vec::len)
+        .chain(std::iter::once(header_cells.len()))
+        .max()
+        .unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            std::iter::once(&header_cells)
+                .chain(&row_cells)
+                .filter_map(|cells| cells.get(column))
+                .map(|cell| cell.width())
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        })
+        .collect();
+    let alignment = |column: usize| alignments.get(column).copied().unwrap_or_default();
+
+    let format_row = |cells: &[String]| {
+        let padded: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(column, &width)| {
+                let cell = cells.get(column).map(String::as_str).unwrap_or("");
+                let padding = width.saturating_sub(cell.width());
+                match alignment(column) {
+                    ColumnAlignment::Right => format!("{}{}", " ".repeat(padding), cell),
+                    ColumnAlignment::Center => format!(
+                        "{}{}{}",
+                        " ".repeat(padding / 2),
+                        cell,
+                        " ".repeat(padding - padding / 2)
+                    ),
+                    ColumnAlignment::None | ColumnAlignment::Left => {
+                        format!("{}{}", cell, " ".repeat(padding))
+                    }
+                }
+            })
+            .collect();
+        format!("| {} |", padded.join(" | "))
+    };
+    let separator: Vec<String> = widths
+        .iter()
+        .enumerate()
+        .map(|(column, &width)| {
+            let (left, right) = match alignment(column) {
+                ColumnAlignment::None => ('-', '-'),
+                ColumnAlignment::Left => (':', '-'),
+                ColumnAlignment::Center => (':', ':'),
+                ColumnAlignment::Right => ('-', ':'),
+            };
+            format!("{}{}{}", left, "-".repeat(width), right)
+        })
+        .collect();
+
+    std::iter::once(format_row(&header_cells))
+        .chain(std::iter::once(format!("|{}|", separator.join("|"))))
+        .chain(row_cells.iter().map(|cells| format_row(cells)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Plain text of a quote's content, putting nested quotes on their own lines
+fn quote_plain_text(nodes: &[AstNode]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        if let AstNode::BlockQuote { .. } = node {
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&node.to_plain_text());
+            text.push('\n');
+        } else {
+            text.push_str(&node.to_plain_text());
+        }
+    }
+    text.trim_end_matches('\n').to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::markdown_parser::parse_markdown;
+
+    #[test]
+    fn test_generate_toc() {
+        let ast =
+            parse_markdown("# Guide\n\nIntro\n\n## Install **now**\n\n> # Quoted\n\n### Usage")
+                .unwrap();
+        let entries = generate_toc(&ast);
+        let toc: Vec<(u8, &str, usize)> = entries
+            .iter()
+            .map(|entry| (entry.level, entry.title.as_str(), entry.block))
+            .collect();
+        assert_eq!(
+            toc,
+            vec![(1, "Guide", 0), (2, "Install now", 2), (3, "Usage", 4)]
+        );
+    }
+
+    #[test]
+    fn test_word_count_skips_code_blocks() {
+        let ast = parse_markdown(
+            "# Notes\n\nSome *short* text here.\n\n```rust\nlet skipped = \"words\";\n```",
+        )
+        .unwrap();
+        assert_eq!(ast.word_count(), 5);
+        assert_eq!(AstNode::Document { children: vec![] }.word_count(), 0);
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let words = vec!["word"; 500].join(" ");
+        let ast = parse_markdown(&words).unwrap();
+        assert_eq!(ast.word_count(), 500);
+        assert_eq!(ast.reading_time(200), Duration::from_secs(150));
+        assert_eq!(
+            parse_markdown("").unwrap().reading_time(200),
+            Duration::ZERO
+        );
+    }
 
     #[test]
     fn test_is_inline() {
@@ -242,6 +772,60 @@ mod tests {
         assert_eq!(heading.text_content(), "Title");
     }
 
+    #[test]
+    fn test_to_plain_text() {
+        let document = AstNode::Document {
+            children: vec![
+                AstNode::Heading {
+                    level: 1,
+                    content: vec![AstNode::Text("Title".to_string())],
+                },
+                AstNode::Paragraph {
+                    content: vec![
+                        AstNode::Text("Some ".to_string()),
+                        AstNode::Bold(vec![AstNode::Text("bold".to_string())]),
+                        AstNode::LineBreak,
+                        AstNode::Link {
+                            text: vec![AstNode::Text("a link".to_string())],
+                            url: "https://example.com".to_string(),
+                        },
+                    ],
+                },
+                AstNode::List {
+                    ordered: true,
+                    start: 1,
+                    items: vec![
+                        AstNode::ListItem {
+                            content: vec![AstNode::Text("one".to_string())],
+                        },
+                        AstNode::ListItem {
+                            content: vec![AstNode::InlineCode("two".to_string())],
+                        },
+                    ],
+                },
+                AstNode::BlockQuote {
+                    content: vec![AstNode::Text("quoted".to_string())],
+                },
+            ],
+        };
+
+        assert_eq!(
+            document.to_plain_text(),
+            "Title\n\nSome bold\na link\n\n1. one\n2. two\n\n> quoted"
+        );
+    }
+
+    #[test]
+    fn test_table_plain_text_alignment() {
+        let markdown = "| Fruit | Count |\n|---|--:|\n| apple | 3 |\n| kiwi | 120 |";
+        let ast = crate::markdown_parser::parse_markdown(markdown).unwrap();
+
+        assert_eq!(
+            ast.to_plain_text(),
+            "| Fruit | Count |\n|-------|------:|\n| apple |     3 |\n| kiwi  |   120 |"
+        );
+    }
+
     #[test]
     fn test_count_children() {
         let simple_text = AstNode::Text("hello".to_string());
@@ -260,4 +844,44 @@ mod tests {
         };
         assert_eq!(document.count_children(), 4); // 1 direct + 3 nested
     }
+
+    #[test]
+    fn test_normalize_merges_text() {
+        let text = |s: &str| AstNode::Text(s.to_string());
+        let node = AstNode::Paragraph {
+            content: vec![
+                text(" "),
+                text("a"),
+                text(" "),
+                text("b"),
+                AstNode::Bold(vec![text("c"), text(""), text(" d")]),
+                text(" "),
+            ],
+        };
+
+        assert_eq!(
+            node.normalize(),
+            AstNode::Paragraph {
+                content: vec![text("a b"), AstNode::Bold(vec![text("c d")])],
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalize_parsed_document() {
+        let ast =
+            crate::markdown_parser::parse_markdown("The definition of **bold text**").unwrap();
+
+        assert_eq!(
+            ast.normalize(),
+            AstNode::Document {
+                children: vec![AstNode::Paragraph {
+                    content: vec![
+                        AstNode::Text("The definition of ".to_string()),
+                        AstNode::Bold(vec![AstNode::Text("bold text".to_string())]),
+                    ],
+                }],
+            }
+        );
+    }
 }
@@ -0,0 +1,96 @@
+use crate::error::ParseError;
+use crate::markdown_parser::lexer::{Lexer, SpannedToken};
+use crate::markdown_parser::parser::{parse_with_frontmatter, AstNode, Parser};
+
+/// Parses one document after another, reusing the lexer, token and parser buffers
+///
+/// Each document parses to the same AST as a fresh `parse_markdown` (or
+/// `parse_markdown_lenient`) call, without allocating the buffers again.
+pub struct BatchParser {
+    lexer: Lexer,
+    tokens: Vec<SpannedToken>,
+    parser: Parser,
+}
+
+impl BatchParser {
+    /// Parse like `parse_markdown`
+    pub fn new() -> Self {
+        Self {
+            lexer: Lexer::new(""),
+            tokens: Vec::new(),
+            parser: Parser::new(Vec::new()),
+        }
+    }
+
+    /// Parse like `parse_markdown_lenient`
+    pub fn lenient() -> Self {
+        Self {
+            lexer: Lexer::new(""),
+            tokens: Vec::new(),
+            parser: Parser::new(Vec::new())
+                .with_lenient(true)
+                .with_strict_emphasis(false),
+        }
+    }
+
+    /// Parse the next document
+    pub fn parse(&mut self, input: &str) -> Result<AstNode, ParseError> {
+        let Self {
+            lexer,
+            tokens,
+            parser,
+        } = self;
+        parse_with_frontmatter(input, |body| {
+            lexer.reset(body);
+            lexer.tokenize_into(tokens)?;
+            parser.reset(tokens);
+            parser.parse()
+        })
+    }
+}
+
+impl Default for BatchParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::lexer::tokenize;
+    use crate::markdown_parser::parser::{parse_markdown, parse_markdown_lenient};
+
+    const DOCUMENTS: [&str; 4] = [
+        "# Title\n\nSome *text* and a [link](https://example.com).\n\n- one\n    - two\n",
+        "---\ntitle: Notes\n---\n    indented code\n\n| a | b |\n|---|---|\n| 1 | 2 |\n",
+        "Unclosed ``` fence\n\n```rust\nfn main() {}\n",
+        "> quote\n\n1. [ ] task\n2. [x] done\n\nTrailing `code`",
+    ];
+
+    #[test]
+    fn test_reused_parser_matches_fresh_parsers() {
+        let mut strict = BatchParser::new();
+        let mut lenient = BatchParser::lenient();
+        for _ in 0..2 {
+            for document in DOCUMENTS {
+                assert_eq!(strict.parse(document).ok(), parse_markdown(document).ok());
+                assert_eq!(
+                    lenient.parse(document).unwrap(),
+                    parse_markdown_lenient(document).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_reset_lexer_matches_fresh_lexer() {
+        let mut lexer = Lexer::new(DOCUMENTS[0]);
+        let mut tokens = Vec::new();
+        for document in DOCUMENTS {
+            lexer.reset(document);
+            lexer.tokenize_into(&mut tokens).unwrap();
+            assert_eq!(tokens, tokenize(document).unwrap());
+        }
+    }
+}
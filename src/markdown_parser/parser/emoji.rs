@@ -0,0 +1,60 @@
+/// Known emoji shortcodes, without the surrounding colons
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("bug", "🐛"),
+    ("book", "📖"),
+    ("bulb", "💡"),
+    ("check", "✔️"),
+    ("clap", "👏"),
+    ("construction", "🚧"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("heart", "❤️"),
+    ("information_source", "ℹ️"),
+    ("joy", "😂"),
+    ("laughing", "😆"),
+    ("memo", "📝"),
+    ("no_entry", "⛔"),
+    ("pencil", "📝"),
+    ("question", "❓"),
+    ("rocket", "🚀"),
+    ("smile", "😄"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("warning", "⚠️"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+/// Look up the emoji for a shortcode like `rocket`
+pub fn lookup(shortcode: &str) -> Option<&'static str> {
+    SHORTCODES
+        .iter()
+        .find(|(name, _)| *name == shortcode)
+        .map(|(_, emoji)| *emoji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_known_shortcode() {
+        assert_eq!(lookup("rocket"), Some("🚀"));
+        assert_eq!(lookup("white_check_mark"), Some("✅"));
+    }
+
+    #[test]
+    fn test_lookup_unknown_shortcode() {
+        assert_eq!(lookup("not_an_emoji"), None);
+        assert_eq!(lookup(""), None);
+    }
+}
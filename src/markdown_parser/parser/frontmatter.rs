@@ -0,0 +1,107 @@
+use crate::markdown_parser::lexer::BOM;
+use serde::Serialize;
+
+/// Metadata syntax of a frontmatter block, chosen by its delimiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FrontMatterFormat {
+    /// Between `---` lines, optionally closed by `...`
+    Yaml,
+    /// Between `+++` lines
+    Toml,
+}
+
+impl FrontMatterFormat {
+    /// Name used as the language when the block is shown as code
+    pub fn name(self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "yaml",
+            FrontMatterFormat::Toml => "toml",
+        }
+    }
+}
+
+/// Frontmatter found at the very start of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontMatter<'a> {
+    pub format: FrontMatterFormat,
+    /// Text between the delimiter lines, without them
+    pub raw: &'a str,
+    /// Number of lines taken up, including both delimiters
+    pub lines: usize,
+    /// The rest of the document after the closing delimiter
+    pub rest: &'a str,
+}
+
+/// Split frontmatter off the start of `input`
+///
+/// The opening delimiter must be the first line and be followed directly by
+/// content, so a document that merely starts with a `---` rule is left alone.
+/// Without a closing delimiter there is no frontmatter. A leading byte order
+/// mark is skipped.
+pub fn split_frontmatter(input: &str) -> Option<FrontMatter<'_>> {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    let mut lines = input.split_inclusive('\n');
+    let format = match lines.next()?.trim_end() {
+        "---" => FrontMatterFormat::Yaml,
+        "+++" => FrontMatterFormat::Toml,
+        _ => return None,
+    };
+    let start = input.find('\n')? + 1;
+
+    let mut end = start;
+    for (index, line) in lines.enumerate() {
+        let trimmed = line.trim_end();
+        if index == 0 && trimmed.is_empty() {
+            return None;
+        }
+        let closes = match format {
+            FrontMatterFormat::Yaml => trimmed == "---" || trimmed == "...",
+            FrontMatterFormat::Toml => trimmed == "+++",
+        };
+        if closes {
+            return Some(FrontMatter {
+                format,
+                raw: input[start..end].trim_end_matches(['\n', '\r']),
+                lines: index + 2,
+                rest: &input[end + line.len()..],
+            });
+        }
+        end += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_yaml_frontmatter() {
+        let input = "---\ntitle: Notes\ntags: [a, b]\n---\n# Notes\n";
+        let front = split_frontmatter(input).unwrap();
+        assert_eq!(front.format, FrontMatterFormat::Yaml);
+        assert_eq!(front.raw, "title: Notes\ntags: [a, b]");
+        assert_eq!(front.lines, 4);
+        assert_eq!(front.rest, "# Notes\n");
+    }
+
+    #[test]
+    fn test_split_toml_frontmatter() {
+        let input = "+++\r\ntitle = \"Notes\"\r\n+++\r\nBody";
+        let front = split_frontmatter(input).unwrap();
+        assert_eq!(front.format, FrontMatterFormat::Toml);
+        assert_eq!(front.raw, "title = \"Notes\"");
+        assert_eq!(front.rest, "Body");
+
+        let front = split_frontmatter("\u{FEFF}---\ntitle: Notes\n---\nBody").unwrap();
+        assert_eq!(front.raw, "title: Notes");
+    }
+
+    #[test]
+    fn test_rules_are_not_frontmatter() {
+        assert!(split_frontmatter("---\n\n# Title\n\n---\n").is_none());
+        assert!(split_frontmatter("---\ntitle: Notes\n").is_none());
+        assert!(split_frontmatter("# Title\n---\nkey: value\n---\n").is_none());
+        assert!(split_frontmatter("+++\ntitle: Notes\n---\n").is_none());
+    }
+}
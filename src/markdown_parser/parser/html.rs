@@ -0,0 +1,241 @@
+use super::ast::AstNode;
+
+impl AstNode {
+    /// Render this node as an HTML fragment
+    ///
+    /// Blocks are placed on their own lines and all text is escaped.
+    pub fn to_html(&self) -> String {
+        match self {
+            AstNode::Document { children } => children
+                .iter()
+                .filter(|child| !matches!(child, AstNode::FrontMatter { .. }))
+                .map(|child| child.to_html())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            AstNode::Heading { level, content } => {
+                format!("<h{level}>{}</h{level}>", inline_html(content))
+            }
+            AstNode::Paragraph { content } => format!("<p>{}</p>", inline_html(content)),
+            AstNode::List {
+                ordered,
+                start,
+                items,
+            } => {
+                let (tag, open) = match (*ordered, *start) {
+                    (false, _) => ("ul", "<ul>".to_string()),
+                    (true, 1) => ("ol", "<ol>".to_string()),
+                    (true, start) => ("ol", format!("<ol start=\"{start}\">")),
+                };
+                let items = items
+                    .iter()
+                    .map(|item| match item {
+                        AstNode::ListItem { .. } | AstNode::TaskListItem { .. } => item.to_html(),
+                        other => format!("<li>{}</li>", other.to_html()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!("{open}\n{items}\n</{tag}>")
+            }
+            AstNode::ListItem { content } => format!("<li>{}</li>", inline_html(content)),
+            AstNode::TaskListItem { checked, content } => {
+                let checked = if *checked { " checked" } else { "" };
+                format!(
+                    "<li><input type=\"checkbox\" disabled{}> {}</li>",
+                    checked,
+                    inline_html(content)
+                )
+            }
+            AstNode::BlockQuote { content } => {
+                let mut parts = Vec::new();
+                for group in
+                    content.split_inclusive(|node| matches!(node, AstNode::BlockQuote { .. }))
+                {
+                    let (inline, nested) = match group.split_last() {
+                        Some((nested @ AstNode::BlockQuote { .. }, inline)) => {
+                            (inline, Some(nested))
+                        }
+                        _ => (group, None),
+                    };
+                    if !inline.is_empty() {
+                        parts.push(format!("<p>{}</p>", inline_html(inline)));
+                    }
+                    if let Some(nested) = nested {
+                        parts.push(nested.to_html());
+                    }
+                }
+                format!("<blockquote>\n{}\n</blockquote>", parts.join("\n"))
+            }
+            AstNode::DefinitionList { items } => {
+                let mut parts = Vec::new();
+                for (term, definitions) in items {
+                    parts.push(format!("<dt>{}</dt>", inline_html(term)));
+                    for definition in definitions {
+                        let content = match definition {
+                            AstNode::Paragraph { content } => inline_html(content),
+                            other => other.to_html(),
+                        };
+                        parts.push(format!("<dd>{content}</dd>"));
+                    }
+                }
+                format!("<dl>\n{}\n</dl>", parts.join("\n"))
+            }
+            AstNode::CodeBlock { language, code, .. } => {
+                let class = language
+                    .as_ref()
+                    .map(|language| format!(" class=\"language-{}\"", escape_html(language)))
+                    .unwrap_or_default();
+                format!("<pre><code{class}>{}</code></pre>", escape_html(code))
+            }
+            AstNode::BlockMath(math) => format!("<div class=\"math\">{}</div>", escape_html(math)),
+            AstNode::HorizontalRule => "<hr>".to_string(),
+            AstNode::Table { headers, rows, .. } => {
+                let row_html = |cells: &[AstNode], tag: &str| {
+                    let cells: String = cells
+                        .iter()
+                        .map(|cell| format!("<{tag}>{}</{tag}>", cell.to_html()))
+                        .collect();
+                    format!("<tr>{cells}</tr>")
+                };
+                let body = rows
+                    .iter()
+                    .map(|row| row_html(row, "td"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(
+                    "<table>\n<thead>\n{}\n</thead>\n<tbody>\n{}\n</tbody>\n</table>",
+                    row_html(headers, "th"),
+                    body
+                )
+            }
+            AstNode::TableCell { content } => inline_html(content),
+            AstNode::TableRow { cells } => {
+                let cells: String = cells
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", cell.to_html()))
+                    .collect();
+                format!("<tr>{cells}</tr>")
+            }
+            AstNode::Text(text) | AstNode::Emoji(text) => escape_html(text),
+            AstNode::Bold(children) => format!("<strong>{}</strong>", inline_html(children)),
+            AstNode::Italic(children) => format!("<em>{}</em>", inline_html(children)),
+            AstNode::Strikethrough(children) => format!("<del>{}</del>", inline_html(children)),
+            AstNode::Highlight(children) => format!("<mark>{}</mark>", inline_html(children)),
+            AstNode::InlineCode(code) => format!("<code>{}</code>", escape_html(code)),
+            AstNode::InlineMath(math) => {
+                format!("<span class=\"math\">{}</span>", escape_html(math))
+            }
+            AstNode::Link { text, url } => {
+                format!("<a href=\"{}\">{}</a>", escape_html(url), inline_html(text))
+            }
+            AstNode::Image { alt, url, title } => format!(
+                "<img src=\"{}\" alt=\"{}\"{}>",
+                escape_html(url),
+                escape_html(
+                    &alt.iter()
+                        .map(|node| node.to_plain_text())
+                        .collect::<String>()
+                ),
+                title
+                    .as_ref()
+                    .map(|title| format!(" title=\"{}\"", escape_html(title)))
+                    .unwrap_or_default()
+            ),
+            AstNode::Html(tag) => tag.clone(),
+            AstNode::FrontMatter { .. } => String::new(),
+            AstNode::LineBreak => "<br>\n".to_string(),
+        }
+    }
+}
+
+/// Concatenate the HTML of inline nodes
+fn inline_html(nodes: &[AstNode]) -> String {
+    nodes.iter().map(|node| node.to_html()).collect()
+}
+
+/// Escape the characters HTML treats specially in text and attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+
+    #[test]
+    fn test_blocks_to_html() {
+        let ast = parse_markdown("# Title\n\nSome **bold** and `code`\n\n- one\n- two").unwrap();
+        assert_eq!(
+            ast.to_html(),
+            "<h1>Title</h1>\n\
+             <p>Some <strong>bold</strong> and <code>code</code></p>\n\
+             <ul>\n<li>one</li>\n<li>two</li>\n</ul>"
+        );
+    }
+
+    #[test]
+    fn test_code_and_links_are_escaped() {
+        let ast = parse_markdown("```rust\nif a < b && c {}\n```").unwrap();
+        assert_eq!(
+            ast.to_html(),
+            "<pre><code class=\"language-rust\">if a &lt; b &amp;&amp; c {}\n</code></pre>"
+        );
+
+        let link = AstNode::Link {
+            text: vec![AstNode::Text("a \"quote\"".to_string())],
+            url: "https://example.com/?a=1&b=2".to_string(),
+        };
+        assert_eq!(
+            link.to_html(),
+            "<a href=\"https://example.com/?a=1&amp;b=2\">a &quot;quote&quot;</a>"
+        );
+    }
+
+    #[test]
+    fn test_inline_html_passes_through() {
+        let ast = parse_markdown("one<br>two H<sub>2</sub>O").unwrap();
+        let AstNode::Document { children } = &ast else {
+            panic!("expected a document");
+        };
+        let AstNode::Paragraph { content } = &children[0] else {
+            panic!("expected a paragraph");
+        };
+        assert_eq!(content[1], AstNode::Html("<br>".to_string()));
+
+        assert_eq!(ast.to_html(), "<p>one<br>two H<sub>2</sub>O</p>");
+        assert_eq!(
+            parse_markdown("a < b > c").unwrap().to_html(),
+            "<p>a &lt; b &gt; c</p>"
+        );
+    }
+
+    #[test]
+    fn test_ordered_list_start() {
+        let ast = parse_markdown("3. three\n4. four").unwrap();
+        assert_eq!(
+            ast.to_html(),
+            "<ol start=\"3\">\n<li>three</li>\n<li>four</li>\n</ol>"
+        );
+    }
+
+    #[test]
+    fn test_table_to_html() {
+        let ast = parse_markdown("| A | B |\n|---|---|\n| 1 | 2 |").unwrap();
+        assert_eq!(
+            ast.to_html(),
+            "<table>\n<thead>\n<tr><th>A</th><th>B</th></tr>\n</thead>\n\
+             <tbody>\n<tr><td>1</td><td>2</td></tr>\n</tbody>\n</table>"
+        );
+    }
+}
@@ -1,34 +1,95 @@
 pub mod ast;
+pub mod batch;
+pub mod emoji;
+pub mod frontmatter;
+pub mod html;
+#[allow(clippy::module_inception)]
 pub mod parser;
 
-pub use ast::AstNode;
-pub use parser::Parser;
+pub use ast::{generate_toc, AstNode, ColumnAlignment, TocEntry};
+pub use batch::BatchParser;
+pub use frontmatter::{split_frontmatter, FrontMatterFormat};
+pub use parser::{BlockParser, Parser};
 
 use crate::error::ParseError;
-use crate::markdown_parser::lexer::tokenize;
+use crate::markdown_parser::lexer::{tokenize, SpannedToken};
 
 /// Parse tokens into an AST
 pub fn parse_tokens(
     tokens: Vec<crate::markdown_parser::lexer::Token>,
 ) -> Result<AstNode, ParseError> {
-    let mut parser = Parser::new(tokens);
-    parser.parse()
+    let children = BlockParser::new(tokens).collect::<Result<Vec<_>, _>>()?;
+    Ok(AstNode::Document { children })
+}
+
+/// Parse tokens with source positions into an AST
+pub fn parse_spanned_tokens(tokens: Vec<SpannedToken>) -> Result<AstNode, ParseError> {
+    let children = Parser::from_spanned(tokens)
+        .blocks()
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(AstNode::Document { children })
 }
 
 /// Parse markdown text into an AST
 pub fn parse_markdown(input: &str) -> Result<AstNode, ParseError> {
-    let tokens = tokenize(input)?;
-    parse_tokens(tokens)
+    parse_with_frontmatter(input, |body| parse_spanned_tokens(tokenize(body)?))
+}
+
+/// Parse markdown text into an AST, letting unclosed code fences run to the end
+///
+/// Unclosed emphasis is kept as text too.
+pub fn parse_markdown_lenient(input: &str) -> Result<AstNode, ParseError> {
+    parse_markdown_lenient_with(input, false)
+}
+
+/// Parse markdown text leniently, failing on unclosed emphasis when `strict_emphasis` is set
+pub fn parse_markdown_lenient_with(
+    input: &str,
+    strict_emphasis: bool,
+) -> Result<AstNode, ParseError> {
+    parse_with_frontmatter(input, |body| {
+        let children = Parser::from_spanned(tokenize(body)?)
+            .with_lenient(true)
+            .with_strict_emphasis(strict_emphasis)
+            .blocks()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AstNode::Document { children })
+    })
+}
+
+/// Parse the document after any frontmatter with `parse`, putting the frontmatter first
+fn parse_with_frontmatter<F>(input: &str, parse: F) -> Result<AstNode, ParseError>
+where
+    F: FnOnce(&str) -> Result<AstNode, ParseError>,
+{
+    let Some(front) = split_frontmatter(input) else {
+        return parse(input);
+    };
+
+    // Blank lines in place of the frontmatter keep error line numbers right
+    let body = format!("{}{}", "\n".repeat(front.lines), front.rest);
+    let mut ast = parse(&body)?;
+    if let AstNode::Document { children } = &mut ast {
+        children.insert(
+            0,
+            AstNode::FrontMatter {
+                format: front.format,
+                raw: front.raw.to_string(),
+            },
+        );
+    }
+    Ok(ast)
 }
 
-/// Parse markdown text into an AST, returning a default document on error
+/// Parse markdown text leniently into an AST, returning a default document on error
 pub fn parse_markdown_or_default(input: &str) -> AstNode {
-    parse_markdown(input).unwrap_or_else(|_| AstNode::Document { children: vec![] })
+    parse_markdown_lenient(input).unwrap_or_else(|_| AstNode::Document { children: vec![] })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::markdown_parser::lexer::strip_spans;
 
     #[test]
     fn test_parse_simple_markdown() {
@@ -46,6 +107,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_block_parser_matches_parse() {
+        let markdown =
+            "# Title\n\nSome *text* here.\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n\n> quote";
+        let tokens = strip_spans(tokenize(markdown).unwrap());
+
+        let streamed: Vec<AstNode> = BlockParser::new(tokens.clone())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let parsed = Parser::new(tokens).parse().unwrap();
+
+        assert_eq!(streamed.len(), 5);
+        assert_eq!(AstNode::Document { children: streamed }, parsed);
+    }
+
     #[test]
     fn test_parse_or_default() {
         let invalid_markdown = "";
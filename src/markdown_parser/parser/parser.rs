@@ -1,22 +1,104 @@
-use crate::error::ParseError;
-use crate::markdown_parser::lexer::Token;
-use crate::markdown_parser::parser::ast::AstNode;
+use crate::error::{LexerError, ParseError};
+use crate::markdown_parser::lexer::{SpannedToken, Token};
+use crate::markdown_parser::parser::ast::{AstNode, ColumnAlignment};
+use crate::markdown_parser::parser::emoji;
+
+/// Default maximum nesting depth for inline elements
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Columns of indentation that turn a line into an indented code block
+pub const CODE_INDENT: usize = 4;
 
 pub struct Parser {
     tokens: Vec<Token>,
+    /// Source line and column of each token, when known
+    positions: Vec<(usize, usize)>,
     current: usize,
     line: usize,
     column: usize,
+    depth: usize,
+    max_depth: usize,
+    /// Accept unterminated code fences and malformed tables instead of failing
+    lenient: bool,
+    /// Fail on unclosed emphasis, strikethrough and code delimiters instead of
+    /// keeping them as text
+    strict_emphasis: bool,
+    /// Whether indented lines belong to a list above them rather than a code block
+    in_list: bool,
+    /// Index of the token between the brackets of each task list item's checkbox
+    task_marks: Vec<usize>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
             tokens,
+            positions: Vec::new(),
             current: 0,
             line: 1,
             column: 1,
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            lenient: false,
+            strict_emphasis: true,
+            in_list: false,
+            task_marks: Vec::new(),
+        }
+    }
+
+    /// Create a parser that reports exact source positions in errors
+    pub fn from_spanned(tokens: Vec<SpannedToken>) -> Self {
+        let positions = tokens.iter().map(|t| (t.line, t.column)).collect();
+        let mut parser = Self::new(tokens.into_iter().map(|t| t.token).collect());
+        parser.positions = positions;
+        parser.sync_position();
+        parser
+    }
+
+    /// Start over on `tokens`, keeping the options and the buffers of the last document
+    ///
+    /// The tokens are moved out, leaving `tokens` empty but with its allocation
+    /// so it can be filled again for the next document.
+    pub fn reset(&mut self, tokens: &mut Vec<SpannedToken>) {
+        self.tokens.clear();
+        self.positions.clear();
+        for spanned in tokens.drain(..) {
+            self.positions.push((spanned.line, spanned.column));
+            self.tokens.push(spanned.token);
         }
+        self.current = 0;
+        self.line = 1;
+        self.column = 1;
+        self.depth = 0;
+        self.in_list = false;
+        self.task_marks.clear();
+        self.sync_position();
+    }
+
+    /// Set the maximum nesting depth before parsing fails
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Let code fences run to the end of input instead of failing when unclosed,
+    /// and read tables with a bad separator row as paragraphs
+    pub fn with_lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Keep an unclosed `*`, `_`, `~~` or `` ` `` as text when false, instead of failing
+    pub fn with_strict_emphasis(mut self, strict_emphasis: bool) -> Self {
+        self.strict_emphasis = strict_emphasis;
+        self
+    }
+
+    /// Token index of the mark inside each parsed task's checkbox, in document order
+    ///
+    /// The `n`th entry belongs to the `n`th `TaskListItem` in the AST.
+    pub fn task_marks(&self) -> &[usize] {
+        &self.task_marks
     }
 
     pub fn parse(&mut self) -> Result<AstNode, ParseError> {
@@ -31,7 +113,31 @@ impl Parser {
         Ok(AstNode::Document { children })
     }
 
+    /// Consume the parser, yielding one top-level block at a time
+    pub fn blocks(self) -> BlockParser {
+        BlockParser {
+            parser: self,
+            finished: false,
+        }
+    }
+
     fn parse_block(&mut self) -> Result<Option<AstNode>, ParseError> {
+        let indent = self.indent_at(self.current);
+        if indent >= CODE_INDENT && !self.in_list {
+            return Ok(Some(self.parse_indented_code_block()));
+        }
+
+        let block = self.parse_block_content()?;
+        match block {
+            Some(AstNode::List { .. }) => self.in_list = true,
+            // Anything that isn't indented ends the list's context
+            Some(_) if indent < CODE_INDENT => self.in_list = false,
+            _ => {}
+        }
+        Ok(block)
+    }
+
+    fn parse_block_content(&mut self) -> Result<Option<AstNode>, ParseError> {
         // Skip whitespace at the beginning of blocks
         self.skip_whitespace();
 
@@ -45,16 +151,28 @@ impl Parser {
                     Ok(Some(self.parse_unordered_list()?))
                 }
             }
+            Some(Token::Asterisk(_)) | Some(Token::Underscore(_)) if self.is_horizontal_rule() => {
+                Ok(Some(self.parse_horizontal_rule()?))
+            }
             Some(Token::Plus) => Ok(Some(self.parse_unordered_list()?)),
+            Some(Token::Asterisk(1)) if self.is_list_marker_at(self.current) => {
+                Ok(Some(self.parse_unordered_list()?))
+            }
             Some(Token::GreaterThan) => Ok(Some(self.parse_blockquote()?)),
             Some(Token::Backtick(amount)) if amount >= 3 => {
                 Ok(Some(self.parse_code_block(amount)?))
             }
-            Some(Token::Pipe) => Ok(Some(self.parse_table()?)),
+            Some(Token::Pipe) if self.is_table_start_at(self.current) => {
+                Ok(Some(self.parse_table()?))
+            }
+            Some(Token::Dollar(2)) => Ok(Some(self.parse_math_block()?)),
             Some(Token::Newline) => {
                 self.advance();
                 Ok(None)
             }
+            Some(_) if self.is_definition_term_at(self.current) => {
+                Ok(Some(self.parse_definition_list()?))
+            }
             Some(_) => Ok(Some(self.parse_paragraph()?)),
             None => Ok(None),
         }
@@ -81,11 +199,14 @@ impl Parser {
         while let Some(token) = self.current_token() {
             match token {
                 Token::Newline => {
-                    // Check if next line starts a new block
-                    if self.peek_next_is_block_start() {
+                    // A blank line or the start of a new block ends the paragraph
+                    if self.next_line_is_blank()
+                        || self.peek_next_is_block_start()
+                        || self.is_definition_term_at(self.current + 1)
+                    {
                         break;
                     }
-                    // If not, treat as line break within paragraph
+                    // Otherwise a single newline is a soft break within the paragraph
                     content.push(AstNode::LineBreak);
                     self.advance();
                 }
@@ -100,8 +221,49 @@ impl Parser {
         Ok(AstNode::Paragraph { content })
     }
 
+    fn parse_definition_list(&mut self) -> Result<AstNode, ParseError> {
+        let mut items = Vec::new();
+
+        while self.is_definition_term_at(self.current) {
+            self.skip_whitespace();
+            let term = self.parse_inline_content_until_newline()?;
+            self.advance(); // Consume newline
+
+            let mut definitions = Vec::new();
+            while self.is_definition_marker_at(self.current) {
+                self.advance(); // Consume ':'
+                self.skip_whitespace();
+                let content = self.parse_inline_content_until_newline()?;
+                definitions.push(AstNode::Paragraph { content });
+
+                if matches!(self.current_token(), Some(Token::Newline)) {
+                    self.advance();
+                }
+            }
+            items.push((term, definitions));
+
+            // Terms may be separated by blank lines
+            let mut pos = self.current;
+            while matches!(self.tokens.get(pos), Some(Token::Newline)) {
+                pos += 1;
+            }
+            if !self.is_definition_term_at(pos) {
+                break;
+            }
+            while self.current < pos {
+                self.advance();
+            }
+        }
+
+        Ok(AstNode::DefinitionList { items })
+    }
+
     fn parse_ordered_list(&mut self) -> Result<AstNode, ParseError> {
         let mut items = Vec::new();
+        let start = match self.current_token() {
+            Some(Token::Number(number)) => *number,
+            _ => 1,
+        };
 
         while let Some(Token::Number(_)) = self.current_token() {
             self.advance(); // Consume number
@@ -117,8 +279,9 @@ impl Parser {
             self.advance(); // Consume dot
             self.skip_whitespace();
 
+            let checked = self.take_task_marker();
             let content = self.parse_inline_content_until_newline()?;
-            items.push(AstNode::ListItem { content });
+            items.push(list_item(checked, content));
 
             // Skip newlines between items
             while matches!(self.current_token(), Some(Token::Newline)) {
@@ -128,6 +291,7 @@ impl Parser {
 
         Ok(AstNode::List {
             ordered: true,
+            start,
             items,
         })
     }
@@ -135,15 +299,13 @@ impl Parser {
     fn parse_unordered_list(&mut self) -> Result<AstNode, ParseError> {
         let mut items = Vec::new();
 
-        while matches!(
-            self.current_token(),
-            Some(Token::Hyphen) | Some(Token::Plus)
-        ) {
+        while self.is_list_marker_at(self.current) {
             self.advance(); // Consume list marker
             self.skip_whitespace();
 
+            let checked = self.take_task_marker();
             let content = self.parse_inline_content_until_newline()?;
-            items.push(AstNode::ListItem { content });
+            items.push(list_item(checked, content));
 
             // Skip newlines between items
             while matches!(self.current_token(), Some(Token::Newline)) {
@@ -153,22 +315,88 @@ impl Parser {
 
         Ok(AstNode::List {
             ordered: false,
+            start: 1,
             items,
         })
     }
 
+    /// Consume a `[ ]`, `[x]` or `[X]` checkbox at the start of a list item
+    ///
+    /// Returns whether it is checked, or `None` if the item isn't a task.
+    fn take_task_marker(&mut self) -> Option<bool> {
+        let checked = match self.tokens.get(self.current..self.current + 3)? {
+            [Token::LeftBracket, Token::Whitespace, Token::RightBracket] => false,
+            [Token::LeftBracket, Token::Text(mark), Token::RightBracket]
+                if mark.eq_ignore_ascii_case("x") =>
+            {
+                true
+            }
+            _ => return None,
+        };
+        if !matches!(
+            self.tokens.get(self.current + 3),
+            None | Some(Token::Whitespace | Token::Newline | Token::Eof)
+        ) {
+            return None;
+        }
+
+        self.task_marks.push(self.current + 1);
+        for _ in 0..3 {
+            self.advance();
+        }
+        self.skip_whitespace();
+        Some(checked)
+    }
+
+    /// Check whether `pos` holds an unordered list marker: `-`, `+` or a `*` followed by a space
+    fn is_list_marker_at(&self, pos: usize) -> bool {
+        match self.tokens.get(pos) {
+            Some(Token::Hyphen | Token::Plus) => true,
+            Some(Token::Asterisk(1)) => matches!(self.tokens.get(pos + 1), Some(Token::Whitespace)),
+            _ => false,
+        }
+    }
+
     fn parse_blockquote(&mut self) -> Result<AstNode, ParseError> {
+        self.parse_blockquote_at(1)
+    }
+
+    /// Parse quote lines with at least `level` markers, nesting deeper lines as inner quotes
+    fn parse_blockquote_at(&mut self, level: usize) -> Result<AstNode, ParseError> {
+        if level > self.max_depth {
+            return Err(ParseError::nesting_too_deep(
+                self.max_depth,
+                self.line,
+                self.column,
+            ));
+        }
+
         let mut content = Vec::new();
 
-        while matches!(self.current_token(), Some(Token::GreaterThan)) {
-            self.advance(); // Consume '>'
+        loop {
+            let markers = self.quote_markers_at(self.current);
+            if markers < level {
+                break;
+            }
+            if markers > level {
+                content.push(self.parse_blockquote_at(level + 1)?);
+                continue;
+            }
+
+            for _ in 0..level {
+                self.skip_whitespace();
+                self.advance(); // Consume '>'
+            }
             self.skip_whitespace();
 
+            // Lines of the same quote are separated by line breaks
+            if !matches!(content.last(), None | Some(AstNode::BlockQuote { .. })) {
+                content.push(AstNode::LineBreak);
+            }
             let line_content = self.parse_inline_content_until_newline()?;
             content.extend(line_content);
 
-            // Skip newlines
-            while matches!(self.current_token(), Some(Token::Newline)) {
+            if matches!(self.current_token(), Some(Token::Newline)) {
                 self.advance();
             }
         }
@@ -176,31 +404,50 @@ impl Parser {
         Ok(AstNode::BlockQuote { content })
     }
 
+    /// Count the `>` markers at the start of the line beginning at `pos`
+    fn quote_markers_at(&self, mut pos: usize) -> usize {
+        let mut markers = 0;
+        loop {
+            while let Some(Token::Whitespace) = self.tokens.get(pos) {
+                pos += 1;
+            }
+            if !matches!(self.tokens.get(pos), Some(Token::GreaterThan)) {
+                return markers;
+            }
+            markers += 1;
+            pos += 1;
+        }
+    }
+
     fn parse_code_block(&mut self, _fence_length: u8) -> Result<AstNode, ParseError> {
+        let (line, column) = (self.line, self.column);
         self.advance(); // Consume opening backticks
 
-        // Parse optional language
-        let mut language = None;
-        if let Some(Token::Text(lang)) = self.current_token() {
-            language = Some(lang.clone());
-            self.advance();
-        }
-
-        // Skip to end of line
-        while !matches!(
-            self.current_token(),
-            Some(Token::Newline) | Some(Token::Eof)
-        ) {
+        // The rest of the fence line is the info string
+        let mut info = String::new();
+        while let Some(token) = self.current_token() {
+            if matches!(token, Token::Newline | Token::Eof) {
+                break;
+            }
+            info.push_str(&token.literal());
             self.advance();
         }
         self.advance(); // Consume newline
 
+        let info = Some(info.trim().to_string()).filter(|info| !info.is_empty());
+        let language = info
+            .as_deref()
+            .and_then(|info| info.split_whitespace().next())
+            .map(str::to_string);
+
         // Collect code content until closing fence
         let mut code = String::new();
+        let mut closed = false;
         while let Some(token) = self.current_token() {
             match token {
                 Token::Backtick(3) | Token::Backtick(4) | Token::Backtick(5) => {
                     self.advance(); // Consume closing fence
+                    closed = true;
                     break;
                 }
                 Token::Text(text) => {
@@ -214,36 +461,135 @@ impl Parser {
                 Token::Eof => break,
                 _ => {
                     // Include other tokens as text in code block
-                    code.push_str(&format!("{:?}", token));
+                    code.push_str(&token.literal());
                     self.advance();
                 }
             }
         }
 
-        Ok(AstNode::CodeBlock { language, code })
+        if !closed && !self.lenient {
+            return Err(LexerError::unterminated_code_block(line, column).into());
+        }
+
+        Ok(AstNode::CodeBlock {
+            language,
+            info,
+            code,
+        })
     }
 
-    fn parse_horizontal_rule(&mut self) -> Result<AstNode, ParseError> {
-        // Consume the three or more hyphens
-        let mut count = 0;
-        while matches!(self.current_token(), Some(Token::Hyphen)) {
-            count += 1;
-            self.advance();
+    /// Parse lines indented by `CODE_INDENT` or more columns, and blank lines between them
+    fn parse_indented_code_block(&mut self) -> AstNode {
+        let mut lines = Vec::new();
+        loop {
+            if self.indent_at(self.current) >= CODE_INDENT {
+                lines.push(self.take_indented_line());
+            } else if self.is_blank_line_at(self.current)
+                && self.indent_at(self.next_line_at(self.current)) >= CODE_INDENT
+            {
+                self.current_line_text();
+                lines.push(String::new());
+            } else {
+                break;
+            }
+        }
+
+        let mut code = lines.join("\n");
+        code.push('\n');
+        AstNode::CodeBlock {
+            language: None,
+            info: None,
+            code,
+        }
+    }
+
+    /// Consume an indented line, returning it without the code block indentation
+    fn take_indented_line(&mut self) -> String {
+        let indent = self.indent_at(self.current);
+        self.advance(); // Consume the indentation
+        " ".repeat(indent - CODE_INDENT) + &self.current_line_text()
+    }
+
+    /// Consume the rest of the line and its newline, keeping the width of whitespace runs
+    fn current_line_text(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(token) = self.current_token() {
+            match token {
+                Token::Newline => {
+                    self.advance();
+                    break;
+                }
+                Token::Eof => break,
+                Token::Whitespace => {
+                    let width = match (
+                        self.positions.get(self.current),
+                        self.positions.get(self.current + 1),
+                    ) {
+                        (Some(&(line, start)), Some(&(next_line, end))) if line == next_line => {
+                            end - start
+                        }
+                        _ => 1,
+                    };
+                    text.push_str(&" ".repeat(width));
+                    self.advance();
+                }
+                token => {
+                    text.push_str(&token.literal());
+                    self.advance();
+                }
+            }
+        }
+        text
+    }
+
+    fn parse_math_block(&mut self) -> Result<AstNode, ParseError> {
+        let (start, line, column) = (self.current, self.line, self.column);
+        self.advance(); // Consume opening `$$`
+
+        let mut math = String::new();
+        while let Some(token) = self.current_token() {
+            match token {
+                Token::Dollar(2) => {
+                    self.advance(); // Consume closing `$$`
+                    return Ok(AstNode::BlockMath(math.trim().to_string()));
+                }
+                Token::Eof => break,
+                _ => {
+                    math.push_str(&token.literal());
+                    self.advance();
+                }
+            }
         }
 
-        if count < 3 {
+        // No closing `$$`, so the opener is just paragraph text
+        self.current = start;
+        self.line = line;
+        self.column = column;
+        self.parse_paragraph()
+    }
+
+    fn parse_horizontal_rule(&mut self) -> Result<AstNode, ParseError> {
+        if !self.is_horizontal_rule() {
             return Err(ParseError::invalid_list(
-                "Horizontal rule requires at least 3 hyphens".to_string(),
+                "Horizontal rule requires at least 3 '-', '*' or '_' characters".to_string(),
                 self.line,
                 self.column,
             ));
         }
 
+        // Consume the rule markers up to the end of the line
+        while !matches!(
+            self.current_token(),
+            Some(Token::Newline) | Some(Token::Eof) | None
+        ) {
+            self.advance();
+        }
+
         Ok(AstNode::HorizontalRule)
     }
 
     fn parse_table(&mut self) -> Result<AstNode, ParseError> {
-        // For now, parse a simple table
+        let start = (self.current, self.line, self.column);
         let mut headers = Vec::new();
         let mut rows = Vec::new();
 
@@ -272,7 +618,34 @@ impl Parser {
             self.advance();
         }
 
-        // Parse separator row (skip for now)
+        // The separator row must match the header's shape
+        let (line, column) = (self.line, self.column);
+        let separator = match self.separator_row_at(self.current) {
+            SeparatorRow::Valid(alignments) if alignments.len() == headers.len() => Ok(alignments),
+            SeparatorRow::Valid(alignments) => Err(ParseError::invalid_table(
+                format!(
+                    "separator row has {} columns but the header has {}",
+                    alignments.len(),
+                    headers.len()
+                ),
+                line,
+                column,
+            )),
+            SeparatorRow::Malformed | SeparatorRow::Missing => Err(ParseError::invalid_table(
+                "separator row may only contain '-', ':', '|' and spaces",
+                line,
+                column,
+            )),
+        };
+        let alignments = match separator {
+            Ok(alignments) => alignments,
+            // Like GFM, lines that don't make a table are just text
+            Err(_) if self.lenient => {
+                (self.current, self.line, self.column) = start;
+                return self.parse_paragraph();
+            }
+            Err(error) => return Err(error),
+        };
         while !matches!(
             self.current_token(),
             Some(Token::Newline) | Some(Token::Eof)
@@ -303,6 +676,10 @@ impl Parser {
                 });
             }
 
+            // Rows are cut or padded to the header's width, as in GFM
+            row_cells.resize_with(headers.len(), || AstNode::TableCell {
+                content: Vec::new(),
+            });
             rows.push(row_cells);
 
             if matches!(self.current_token(), Some(Token::Newline)) {
@@ -310,36 +687,34 @@ impl Parser {
             }
         }
 
-        Ok(AstNode::Table { headers, rows })
+        Ok(AstNode::Table {
+            headers,
+            alignments,
+            rows,
+        })
     }
 
     fn parse_table_cell_content(&mut self) -> Result<Vec<AstNode>, ParseError> {
         let mut content = Vec::new();
 
-        while !matches!(
-            self.current_token(),
-            Some(Token::Pipe) | Some(Token::Newline) | Some(Token::Eof)
-        ) {
-            match self.current_token() {
-                Some(Token::Text(text)) => {
-                    content.push(AstNode::Text(text.clone()));
+        while let Some(token) = self.current_token() {
+            match token {
+                Token::Pipe | Token::Newline | Token::Eof => break,
+                Token::Whitespace => {
+                    // Keep spacing between words, but not the padding around the cell
+                    if !content.is_empty() {
+                        content.push(AstNode::Text(" ".to_string()));
+                    }
                     self.advance();
                 }
-                Some(Token::Asterisk(count)) => {
-                    content.push(self.parse_emphasis(*count)?);
-                }
-                Some(Token::LeftBracket) => {
-                    content.push(self.parse_link_or_image()?);
-                }
-                Some(Token::Backtick(1)) => {
-                    content.push(self.parse_inline_code()?);
-                }
-                _ => {
-                    self.advance(); // Skip unknown tokens
-                }
+                _ => content.extend(self.parse_inline_content()?),
             }
         }
 
+        if matches!(content.last(), Some(AstNode::Text(text)) if text == " ") {
+            content.pop();
+        }
+
         Ok(content)
     }
 
@@ -347,40 +722,33 @@ impl Parser {
         let mut content = Vec::new();
 
         while let Some(token) = self.current_token() {
-            match token {
-                Token::Newline | Token::Eof => break,
-                Token::Text(text) => {
-                    content.push(AstNode::Text(text.clone()));
-                    self.advance();
-                }
-                Token::Asterisk(count) => {
-                    content.push(self.parse_emphasis(*count)?);
-                }
-                Token::Underscore(count) => {
-                    content.push(self.parse_underscore_emphasis(*count)?);
-                }
-                Token::LeftBracket => {
-                    content.push(self.parse_link_or_image()?);
-                }
-                Token::Backtick(1) => {
-                    content.push(self.parse_inline_code()?);
-                }
-                Token::Tilde(2) => {
-                    content.push(self.parse_strikethrough()?);
-                }
-                Token::Whitespace => {
-                    content.push(AstNode::Text(" ".to_string()));
-                    self.advance();
-                }
-                _ => {
-                    self.advance(); // Skip unhandled tokens for now
-                }
+            if matches!(token, Token::Newline | Token::Eof) {
+                break;
             }
+            content.extend(self.parse_inline_content()?);
         }
         Ok(content)
     }
 
     fn parse_inline_content(&mut self) -> Result<Vec<AstNode>, ParseError> {
+        if self.depth >= self.max_depth {
+            return Err(ParseError::nesting_too_deep(
+                self.max_depth,
+                self.line,
+                self.column,
+            ));
+        }
+
+        self.depth += 1;
+        let content = self.parse_inline_element();
+        self.depth -= 1;
+        content
+    }
+
+    /// Parse the inline element at the current token, for every kind of inline content
+    ///
+    /// Tokens with no inline meaning are kept as the text they were written as.
+    fn parse_inline_element(&mut self) -> Result<Vec<AstNode>, ParseError> {
         let mut content = Vec::new();
 
         match self.current_token() {
@@ -388,6 +756,10 @@ impl Parser {
                 content.push(AstNode::Text(text.clone()));
                 self.advance();
             }
+            Some(Token::Html(tag)) => {
+                content.push(AstNode::Html(tag.clone()));
+                self.advance();
+            }
             Some(Token::Asterisk(count)) => {
                 content.push(self.parse_emphasis(*count)?);
             }
@@ -403,19 +775,39 @@ impl Parser {
             Some(Token::Tilde(2)) => {
                 content.push(self.parse_strikethrough()?);
             }
+            Some(Token::Equals(count)) => {
+                content.push(self.parse_highlight(*count)?);
+            }
+            Some(Token::Dollar(count)) => {
+                content.push(self.parse_inline_math(*count)?);
+            }
+            Some(Token::Colon) => {
+                content.push(self.parse_emoji());
+            }
             Some(Token::Whitespace) => {
                 content.push(AstNode::Text(" ".to_string()));
                 self.advance();
             }
-            _ => {
-                self.advance(); // Skip unhandled tokens for now
+            Some(Token::EscapedPipe) => {
+                content.push(AstNode::Text("|".to_string()));
+                self.advance();
+            }
+            Some(Token::Exclamation) if matches!(self.peek_next(), Some(Token::LeftBracket)) => {
+                self.advance(); // Image marker, handled by parse_link_or_image
+            }
+            // Anything else, like a stray `|` or `(`, is shown as written
+            Some(other) => {
+                content.push(AstNode::Text(other.literal()));
+                self.advance();
             }
+            None => {}
         }
 
         Ok(content)
     }
 
     fn parse_emphasis(&mut self, count: u8) -> Result<AstNode, ParseError> {
+        let opener = (self.current, self.line, self.column);
         self.advance(); // Consume opening asterisks
 
         let mut content = Vec::new();
@@ -437,17 +829,19 @@ impl Parser {
         }
 
         if !found_closing {
-            return Err(ParseError::unmatched_delimiter('*', self.line, self.column));
+            return self.unmatched_delimiter(opener, '*', count);
         }
 
         match count {
             1 => Ok(AstNode::Italic(content)),
             2 => Ok(AstNode::Bold(content)),
-            _ => Ok(AstNode::Text("*".repeat(count as usize))), // Fallback for unexpected counts
+            // `***text***` is bold and italic at once
+            _ => Ok(AstNode::Bold(vec![AstNode::Italic(content)])),
         }
     }
 
     fn parse_underscore_emphasis(&mut self, count: u8) -> Result<AstNode, ParseError> {
+        let opener = (self.current, self.line, self.column);
         self.advance(); // Consume opening underscores
 
         let mut content = Vec::new();
@@ -469,17 +863,19 @@ impl Parser {
         }
 
         if !found_closing {
-            return Err(ParseError::unmatched_delimiter('_', self.line, self.column));
+            return self.unmatched_delimiter(opener, '_', count);
         }
 
         match count {
             1 => Ok(AstNode::Italic(content)),
             2 => Ok(AstNode::Bold(content)),
-            _ => Ok(AstNode::Text("_".repeat(count as usize))), // Fallback for unexpected counts
+            // `___text___` is bold and italic at once
+            _ => Ok(AstNode::Bold(vec![AstNode::Italic(content)])),
         }
     }
 
     fn parse_strikethrough(&mut self) -> Result<AstNode, ParseError> {
+        let opener = (self.current, self.line, self.column);
         self.advance(); // Consume opening tildes
 
         let mut content = Vec::new();
@@ -501,13 +897,116 @@ impl Parser {
         }
 
         if !found_closing {
-            return Err(ParseError::unmatched_delimiter('~', self.line, self.column));
+            return self.unmatched_delimiter(opener, '~', 2);
         }
 
         Ok(AstNode::Strikethrough(content))
     }
 
+    fn parse_highlight(&mut self, count: u8) -> Result<AstNode, ParseError> {
+        let literal = AstNode::Text("=".repeat(count as usize));
+        if count != 2 {
+            // Only `==` opens a highlight; other runs (e.g. Setext underlines) stay literal
+            self.advance();
+            return Ok(literal);
+        }
+
+        let (start, line, column) = (self.current, self.line, self.column);
+        self.advance(); // Consume opening equals
+
+        let mut content = Vec::new();
+
+        while let Some(token) = self.current_token() {
+            match token {
+                Token::Equals(2) => {
+                    self.advance();
+                    return Ok(AstNode::Highlight(content));
+                }
+                Token::Newline | Token::Eof => break,
+                _ => {
+                    let inline_nodes = self.parse_inline_content()?;
+                    content.extend(inline_nodes);
+                }
+            }
+        }
+
+        // No closing `==` on this line, so treat the opener as plain text
+        self.current = start + 1;
+        self.line = line;
+        self.column = column + 1;
+        self.sync_position();
+        Ok(literal)
+    }
+
+    fn parse_inline_math(&mut self, count: u8) -> Result<AstNode, ParseError> {
+        // `$...$` must hug its content, so prices like `$5 and $10` stay literal
+        let opens = count == 1
+            && !matches!(
+                self.peek_next(),
+                Some(Token::Whitespace) | Some(Token::Newline) | Some(Token::Eof) | None
+            );
+
+        if opens {
+            let mut pos = self.current + 1;
+            while let Some(token) = self.tokens.get(pos) {
+                match token {
+                    Token::Dollar(1)
+                        if !matches!(self.tokens.get(pos - 1), Some(Token::Whitespace))
+                            && !matches!(self.tokens.get(pos + 1), Some(Token::Number(_))) =>
+                    {
+                        let math: String = self.tokens[self.current + 1..pos]
+                            .iter()
+                            .map(Token::literal)
+                            .collect();
+                        while self.current <= pos {
+                            self.advance();
+                        }
+                        return Ok(AstNode::InlineMath(math));
+                    }
+                    Token::Newline | Token::Eof => break,
+                    _ => pos += 1,
+                }
+            }
+        }
+
+        self.advance();
+        Ok(AstNode::Text("$".repeat(count as usize)))
+    }
+
+    fn parse_emoji(&mut self) -> AstNode {
+        let mut name = String::new();
+        let mut pos = self.current + 1;
+
+        while let Some(token) = self.tokens.get(pos) {
+            match token {
+                Token::Text(_)
+                | Token::Underscore(_)
+                | Token::Hyphen
+                | Token::Plus
+                | Token::Number(_) => {
+                    name.push_str(&token.literal());
+                    pos += 1;
+                }
+                _ => break,
+            }
+        }
+
+        if matches!(self.tokens.get(pos), Some(Token::Colon)) {
+            if let Some(emoji) = emoji::lookup(&name) {
+                while self.current <= pos {
+                    self.advance();
+                }
+                return AstNode::Emoji(emoji.to_string());
+            }
+        }
+
+        // Unknown shortcode, keep the colon as text
+        self.advance();
+        AstNode::Text(":".to_string())
+    }
+
     fn parse_inline_code(&mut self) -> Result<AstNode, ParseError> {
+        let opener = (self.current, self.line, self.column);
         self.advance(); // Consume opening backtick
 
         let mut code = String::new();
@@ -520,10 +1019,14 @@ impl Parser {
                     found_closing = true;
                     break;
                 }
-                Token::Text(text) => {
+                Token::Text(text) | Token::Html(text) => {
                     code.push_str(text);
                     self.advance();
                 }
+                Token::EscapedPipe => {
+                    code.push_str(&token.literal());
+                    self.advance();
+                }
                 Token::Whitespace => {
                     code.push(' ');
                     self.advance();
@@ -538,12 +1041,33 @@ impl Parser {
         }
 
         if !found_closing {
-            return Err(ParseError::unmatched_delimiter('`', self.line, self.column));
+            return self.unmatched_delimiter(opener, '`', 1);
         }
 
         Ok(AstNode::InlineCode(code))
     }
 
+    /// Handle a delimiter run at `opener` that is never closed on its line
+    ///
+    /// With strict emphasis this is an error; otherwise parsing resumes just
+    /// after the run, which is kept as `count` literal `delimiter`s.
+    fn unmatched_delimiter(
+        &mut self,
+        opener: (usize, usize, usize),
+        delimiter: char,
+        count: u8,
+    ) -> Result<AstNode, ParseError> {
+        let (start, line, column) = opener;
+        if self.strict_emphasis {
+            return Err(ParseError::unmatched_delimiter(delimiter, line, column));
+        }
+        self.current = start + 1;
+        self.line = line;
+        self.column = column + count as usize;
+        self.sync_position();
+        Ok(AstNode::Text(delimiter.to_string().repeat(count as usize)))
+    }
+
     fn parse_link_or_image(&mut self) -> Result<AstNode, ParseError> {
         // Check if this is an image (starts with ![)
         if matches!(self.peek_previous(), Some(Token::Exclamation)) {
@@ -588,34 +1112,13 @@ impl Parser {
         }
         self.advance();
 
-        // Parse URL
-        let mut url = String::new();
-        while let Some(token) = self.current_token() {
-            match token {
-                Token::RightParen => {
-                    self.advance();
-                    break;
-                }
-                Token::Text(text_content) => {
-                    url.push_str(text_content);
-                    self.advance();
-                }
-                Token::Url(url_content) => {
-                    url.push_str(url_content);
-                    self.advance();
-                }
-                Token::Eof => {
-                    return Err(ParseError::malformed_link(
-                        "Unexpected end of input in link URL".to_string(),
-                        self.line,
-                        self.column,
-                    ));
-                }
-                _ => {
-                    self.advance(); // Skip unexpected tokens
-                }
-            }
-        }
+        let Some(url) = self.parse_url() else {
+            return Err(ParseError::malformed_link(
+                "Unexpected end of input in link URL".to_string(),
+                self.line,
+                self.column,
+            ));
+        };
 
         Ok(AstNode::Link { text, url })
     }
@@ -655,36 +1158,92 @@ impl Parser {
         }
         self.advance();
 
-        // Parse URL
-        let mut url = String::new();
+        let Some((url, title)) = self.parse_url_and_title() else {
+            return Err(ParseError::malformed_image(
+                "Unexpected end of input in image URL".to_string(),
+                self.line,
+                self.column,
+            ));
+        };
+
+        Ok(AstNode::Image { alt, url, title })
+    }
+
+    /// Collect a link URL up to the closing `)`, or `None` if there isn't one
+    ///
+    /// Characters such as `-`, `_` and `~` are lexed as their own tokens, so the
+    /// literal text of every token is kept rather than just text tokens. Nested
+    /// `()` pairs stay part of the URL; if they never balance, the URL ends at the
+    /// first `)` instead.
+    fn parse_url(&mut self) -> Option<String> {
+        self.scan_url(false)
+    }
+
+    /// Collect a URL like [`Parser::parse_url`], splitting off a `"title"` or `'title'` after it
+    fn parse_url_and_title(&mut self) -> Option<(String, Option<String>)> {
+        let raw = self.scan_url(true)?;
+        let raw = raw.trim();
+        if let Some(split) = raw.find(char::is_whitespace) {
+            let (url, rest) = (&raw[..split], raw[split..].trim_start());
+            for quote in ['"', '\''] {
+                if rest.len() >= 2 && rest.starts_with(quote) && rest.ends_with(quote) {
+                    let title = rest[1..rest.len() - 1].to_string();
+                    return Some((url.to_string(), Some(title)));
+                }
+            }
+        }
+        let url = raw.split_whitespace().collect();
+        Some((url, None))
+    }
+
+    /// Collect the text up to the `)` that ends the URL, consuming it, in one pass
+    ///
+    /// Newlines become spaces when `keep_whitespace` is set and are dropped along
+    /// with other whitespace otherwise. If the parentheses never balance, the
+    /// parser steps back to just after the first `)` and returns the text before it;
+    /// without any `)` it steps back to where it started and returns `None`.
+    fn scan_url(&mut self, keep_whitespace: bool) -> Option<String> {
+        let start = (self.current, 0, self.line, self.column);
+        let mut text = String::new();
+        let mut depth = 0;
+        let mut first_close = None;
+
         while let Some(token) = self.current_token() {
             match token {
-                Token::RightParen => {
-                    self.advance();
-                    break;
-                }
-                Token::Text(text_content) => {
-                    url.push_str(text_content);
-                    self.advance();
-                }
-                Token::Url(url_content) => {
-                    url.push_str(url_content);
-                    self.advance();
+                Token::LeftParen => depth += 1,
+                Token::RightParen if depth == 0 => {
+                    self.advance(); // Consume ')'
+                    return Some(text);
                 }
-                Token::Eof => {
-                    return Err(ParseError::malformed_image(
-                        "Unexpected end of input in image URL".to_string(),
-                        self.line,
-                        self.column,
-                    ));
+                Token::RightParen => {
+                    first_close.get_or_insert((self.current, text.len(), self.line, self.column));
+                    depth -= 1;
                 }
-                _ => {
-                    self.advance(); // Skip unexpected tokens
+                // An open paren doesn't carry the URL onto the next line, and
+                // nothing carries it past the end of the paragraph
+                Token::Newline if depth > 0 || matches!(self.peek_next(), Some(Token::Newline)) => {
+                    break
                 }
+                Token::Eof => break,
+                _ => {}
+            }
+            match self.current_token() {
+                Some(Token::Newline) if keep_whitespace => text.push(' '),
+                Some(Token::Whitespace | Token::Newline) if !keep_whitespace => {}
+                Some(token) => text.push_str(&token.literal()),
+                None => {}
             }
+            self.advance();
         }
 
-        Ok(AstNode::Image { alt, url })
+        let (close, len, line, column) = first_close.unwrap_or(start);
+        self.current = close;
+        self.line = line;
+        self.column = column;
+        first_close?;
+        self.advance(); // Consume ')'
+        text.truncate(len);
+        Some(text)
     }
 
     // Helper methods
@@ -713,6 +1272,15 @@ impl Parser {
                 self.column += 1;
             }
             self.current += 1;
+            self.sync_position();
+        }
+    }
+
+    /// Take line and column from the source positions, when available
+    fn sync_position(&mut self) {
+        if let Some(&(line, column)) = self.positions.get(self.current) {
+            self.line = line;
+            self.column = column;
         }
     }
 
@@ -727,18 +1295,179 @@ impl Parser {
     }
 
     fn is_horizontal_rule(&self) -> bool {
-        // Check if we have at least 3 consecutive hyphens
+        self.is_horizontal_rule_at(self.current)
+    }
+
+    /// Check if the line starting at `pos` is only 3+ of one rule marker, optionally spaced
+    fn is_horizontal_rule_at(&self, mut pos: usize) -> bool {
+        let mut marker = None;
         let mut count = 0;
-        let mut pos = self.current;
 
-        while let Some(Token::Hyphen) = self.tokens.get(pos) {
-            count += 1;
+        while let Some(token) = self.tokens.get(pos) {
+            let (kind, amount) = match token {
+                Token::Whitespace => {
+                    pos += 1;
+                    continue;
+                }
+                Token::Newline | Token::Eof => break,
+                Token::Hyphen => ('-', 1),
+                Token::Asterisk(n) => ('*', *n as usize),
+                Token::Underscore(n) => ('_', *n as usize),
+                _ => return false,
+            };
+
+            if *marker.get_or_insert(kind) != kind {
+                return false;
+            }
+            count += amount;
             pos += 1;
         }
 
         count >= 3
     }
 
+    /// Check whether `pos` starts a `: ` definition line
+    fn is_definition_marker_at(&self, pos: usize) -> bool {
+        matches!(self.tokens.get(pos), Some(Token::Colon))
+            && matches!(self.tokens.get(pos + 1), Some(Token::Whitespace))
+    }
+
+    /// Check whether the line at `pos` is a term followed by a `: ` definition line
+    fn is_definition_term_at(&self, mut pos: usize) -> bool {
+        while let Some(Token::Whitespace) = self.tokens.get(pos) {
+            pos += 1;
+        }
+        if matches!(
+            self.tokens.get(pos),
+            None | Some(Token::Newline | Token::Eof | Token::Colon)
+        ) {
+            return false;
+        }
+
+        while !matches!(
+            self.tokens.get(pos),
+            None | Some(Token::Newline | Token::Eof)
+        ) {
+            pos += 1;
+        }
+        matches!(self.tokens.get(pos), Some(Token::Newline))
+            && self.is_definition_marker_at(pos + 1)
+    }
+
+    /// Columns of indentation before the content of the line starting at `pos`
+    ///
+    /// Tabs are already expanded in the token columns. Blank lines, and tokens
+    /// without source positions, count as not indented.
+    fn indent_at(&self, pos: usize) -> usize {
+        let at_line_start = pos == 0 || matches!(self.tokens.get(pos - 1), Some(Token::Newline));
+        if !at_line_start
+            || !matches!(self.tokens.get(pos), Some(Token::Whitespace))
+            || self.is_blank_line_at(pos)
+        {
+            return 0;
+        }
+        match (self.positions.get(pos), self.positions.get(pos + 1)) {
+            (Some(&(_, start)), Some(&(_, content))) => content - start,
+            _ => 0,
+        }
+    }
+
+    /// Whether the line starting at `pos` has nothing but whitespace
+    fn is_blank_line_at(&self, mut pos: usize) -> bool {
+        while let Some(Token::Whitespace) = self.tokens.get(pos) {
+            pos += 1;
+        }
+        matches!(self.tokens.get(pos), Some(Token::Newline | Token::Eof))
+    }
+
+    /// Position of the first token on the line after the one containing `pos`
+    fn next_line_at(&self, mut pos: usize) -> usize {
+        while !matches!(
+            self.tokens.get(pos),
+            None | Some(Token::Newline | Token::Eof)
+        ) {
+            pos += 1;
+        }
+        pos + 1
+    }
+
+    /// Check whether the line after the current newline is empty or the end of input
+    fn next_line_is_blank(&self) -> bool {
+        let mut pos = self.current + 1;
+        while let Some(Token::Whitespace) = self.tokens.get(pos) {
+            pos += 1;
+        }
+        matches!(
+            self.tokens.get(pos),
+            None | Some(Token::Newline | Token::Eof)
+        )
+    }
+
+    /// Check whether a `|` line at `pos` is followed by something shaped like a separator row
+    fn is_table_start_at(&self, pos: usize) -> bool {
+        let mut next_line = pos;
+        while !matches!(
+            self.tokens.get(next_line),
+            None | Some(Token::Newline | Token::Eof)
+        ) {
+            next_line += 1;
+        }
+        matches!(self.tokens.get(next_line), Some(Token::Newline))
+            && !matches!(self.separator_row_at(next_line + 1), SeparatorRow::Missing)
+    }
+
+    /// Classify the line starting at `pos` as a table separator row
+    fn separator_row_at(&self, mut pos: usize) -> SeparatorRow {
+        while let Some(Token::Whitespace) = self.tokens.get(pos) {
+            pos += 1;
+        }
+        let starts_with_pipe = matches!(self.tokens.get(pos), Some(Token::Pipe));
+        if !starts_with_pipe && !matches!(self.tokens.get(pos), Some(Token::Hyphen | Token::Colon))
+        {
+            return SeparatorRow::Missing;
+        }
+
+        // Each cell's hyphens and colons, e.g. ":--" or "---:"
+        let mut cells = vec![String::new()];
+        let mut has_hyphen = false;
+        let mut only_separator_tokens = true;
+        while let Some(token) = self.tokens.get(pos) {
+            match token {
+                Token::Newline | Token::Eof => break,
+                Token::Pipe => cells.push(String::new()),
+                Token::Hyphen => {
+                    has_hyphen = true;
+                    cells.last_mut().unwrap().push('-');
+                }
+                Token::Colon => cells.last_mut().unwrap().push(':'),
+                Token::Whitespace => {}
+                _ => only_separator_tokens = false,
+            }
+            pos += 1;
+        }
+
+        // A rule or list item below a `|` line isn't a separator row
+        if !has_hyphen || cells.len() == 1 || (!starts_with_pipe && !only_separator_tokens) {
+            return SeparatorRow::Missing;
+        }
+        if !only_separator_tokens {
+            return SeparatorRow::Malformed;
+        }
+
+        // Outer pipes leave empty cells at the ends, every inner cell needs a hyphen
+        if cells.len() > 1 && !cells[0].contains('-') {
+            cells.remove(0);
+        }
+        if cells.len() > 1 && !cells[cells.len() - 1].contains('-') {
+            cells.pop();
+        }
+        if cells.iter().all(|cell| cell.contains('-')) {
+            SeparatorRow::Valid(cells.iter().map(|cell| column_alignment(cell)).collect())
+        } else {
+            SeparatorRow::Malformed
+        }
+    }
+
     fn peek_next_is_block_start(&self) -> bool {
         // Look ahead to see if the next non-whitespace token starts a block
         let mut pos = self.current + 1;
@@ -751,9 +1480,13 @@ impl Parser {
                 | Token::Number(_)
                 | Token::Hyphen
                 | Token::Plus
-                | Token::GreaterThan
-                | Token::Pipe => return true,
+                | Token::GreaterThan => return true,
+                Token::Pipe => return self.is_table_start_at(pos),
                 Token::Backtick(count) if *count >= 3 => return true,
+                Token::Dollar(2) => return true,
+                Token::Asterisk(_) | Token::Underscore(_) => {
+                    return self.is_horizontal_rule_at(pos) || self.is_list_marker_at(pos)
+                }
                 _ => return false,
             }
         }
@@ -762,6 +1495,66 @@ impl Parser {
     }
 }
 
+/// What the line after a table header looks like
+enum SeparatorRow {
+    /// Only separator characters, with each column's alignment
+    Valid(Vec<ColumnAlignment>),
+    /// Has dashes but also other content or empty columns
+    Malformed,
+    /// Not a separator row at all, so the header line isn't a table
+    Missing,
+}
+
+/// A list item, or a task list item when it started with a checkbox
+fn list_item(checked: Option<bool>, content: Vec<AstNode>) -> AstNode {
+    match checked {
+        Some(checked) => AstNode::TaskListItem { checked, content },
+        None => AstNode::ListItem { content },
+    }
+}
+
+/// Alignment of a separator row cell such as `:--`, `:-:` or `--:`
+fn column_alignment(cell: &str) -> ColumnAlignment {
+    match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => ColumnAlignment::Center,
+        (false, true) => ColumnAlignment::Right,
+        (true, false) => ColumnAlignment::Left,
+        (false, false) => ColumnAlignment::None,
+    }
+}
+
+/// Streaming parser that yields top-level blocks as they are parsed
+pub struct BlockParser {
+    parser: Parser,
+    finished: bool,
+}
+
+impl BlockParser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Parser::new(tokens).blocks()
+    }
+}
+
+impl Iterator for BlockParser {
+    type Item = Result<AstNode, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished && !self.parser.is_at_end() {
+            match self.parser.parse_block() {
+                Ok(Some(node)) => return Some(Ok(node)),
+                Ok(None) => continue,
+                Err(e) => {
+                    // Positions after an error are unreliable, so stop here
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -936,6 +1729,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_deep_nesting_errors_gracefully() {
+        let mut tokens = vec![Token::Text("deep".to_string())];
+        tokens.extend((0..10_000).map(|i| Token::Asterisk(if i % 2 == 0 { 1 } else { 2 })));
+        tokens.push(Token::Eof);
+
+        let mut parser = Parser::new(tokens);
+        let result = parser.parse();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::NestingTooDeep {
+                limit: DEFAULT_MAX_DEPTH,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_custom_max_depth() {
+        let tokens = vec![
+            Token::Asterisk(1),
+            Token::Tilde(2),
+            Token::Text("deep".to_string()),
+            Token::Tilde(2),
+            Token::Asterisk(1),
+            Token::Eof,
+        ];
+
+        assert!(Parser::new(tokens.clone()).parse().is_ok());
+        assert!(Parser::new(tokens).with_max_depth(1).parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_highlight() {
+        let tokens = vec![
+            Token::Equals(2),
+            Token::Text("marked".to_string()),
+            Token::Equals(2),
+            Token::Eof,
+        ];
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+
+        if let AstNode::Document { children } = ast {
+            if let AstNode::Paragraph { content } = &children[0] {
+                assert_eq!(
+                    content[0],
+                    AstNode::Highlight(vec![AstNode::Text("marked".to_string())])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_unclosed_highlight_is_literal() {
+        let tokens = vec![
+            Token::Text("a".to_string()),
+            Token::Whitespace,
+            Token::Equals(2),
+            Token::Whitespace,
+            Token::Text("b".to_string()),
+            Token::Eof,
+        ];
+        let mut parser = Parser::new(tokens);
+        let ast = parser.parse().unwrap();
+
+        assert_eq!(ast.text_content(), "a == b");
+    }
+
     #[test]
     fn test_parse_inline_code() {
         let tokens = vec![
@@ -976,6 +1839,29 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_unclosed_delimiters_are_text_unless_strict() {
+        let parse = |input: &str, strict: bool| {
+            Parser::from_spanned(crate::markdown_parser::tokenize(input).unwrap())
+                .with_strict_emphasis(strict)
+                .parse()
+        };
+
+        assert!(matches!(
+            parse("a * b", true),
+            Err(ParseError::UnmatchedDelimiter { delimiter: '*', .. })
+        ));
+        let ast = parse("a * b", false).unwrap();
+        assert_eq!(ast.to_plain_text().trim(), "a * b");
+
+        for input in ["a _b", "a ~~b", "a `b"] {
+            assert!(parse(input, true).is_err(), "{}", input);
+            assert_eq!(parse(input, false).unwrap().to_plain_text().trim(), input);
+        }
+        let ast = parse("*a* and **b *c* d", false).unwrap();
+        assert!(ast.to_html().contains("<em>a</em> and **b <em>c</em> d"));
+    }
+
     #[test]
     fn test_parse_ordered_list() {
         let tokens = vec![
@@ -994,7 +1880,7 @@ mod tests {
         let ast = parser.parse().unwrap();
 
         if let AstNode::Document { children } = ast {
-            if let AstNode::List { ordered, items } = &children[0] {
+            if let AstNode::List { ordered, items, .. } = &children[0] {
                 assert!(*ordered);
                 assert_eq!(items.len(), 2);
 
@@ -1025,7 +1911,7 @@ mod tests {
         let ast = parser.parse().unwrap();
 
         if let AstNode::Document { children } = ast {
-            if let AstNode::List { ordered, items } = &children[0] {
+            if let AstNode::List { ordered, items, .. } = &children[0] {
                 assert!(!*ordered);
                 assert_eq!(items.len(), 2);
             } else {
@@ -1073,7 +1959,7 @@ mod tests {
         let ast = parser.parse().unwrap();
 
         if let AstNode::Document { children } = ast {
-            if let AstNode::CodeBlock { language, code } = &children[0] {
+            if let AstNode::CodeBlock { language, code, .. } = &children[0] {
                 assert_eq!(language.as_ref().unwrap(), "rust");
                 assert!(code.contains("fn main() {}"));
             } else {
@@ -0,0 +1,118 @@
+use crate::markdown_parser::parser::ast::is_break_tag;
+use crate::markdown_parser::AstNode;
+use serde::Serialize;
+
+/// Counts describing a parsed document
+///
+/// Words and characters cover the prose only: code blocks, math blocks and
+/// image descriptions are left out, and line breaks aren't characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DocumentStats {
+    pub words: usize,
+    pub characters: usize,
+    pub headings: usize,
+    pub links: usize,
+    pub code_blocks: usize,
+}
+
+impl DocumentStats {
+    /// Count the words, characters, headings, links and code blocks in `ast`
+    pub fn from_ast(ast: &AstNode) -> Self {
+        let mut stats = Self::default();
+        ast.walk(&mut |node| match node {
+            AstNode::Heading { .. } => stats.headings += 1,
+            AstNode::Link { .. } => stats.links += 1,
+            AstNode::CodeBlock { .. } => stats.code_blocks += 1,
+            _ => {}
+        });
+
+        let text = prose(ast);
+        stats.words = text.split_whitespace().count();
+        stats.characters = text.chars().filter(|&ch| ch != '\n').count();
+        stats
+    }
+
+    /// Add the counts of `other`, e.g. to total several files
+    pub fn add(&mut self, other: &DocumentStats) {
+        self.words += other.words;
+        self.characters += other.characters;
+        self.headings += other.headings;
+        self.links += other.links;
+        self.code_blocks += other.code_blocks;
+    }
+
+    /// One-line human readable summary
+    pub fn summary(&self) -> String {
+        format!(
+            "{} words, {} characters, {} headings, {} links, {} code blocks",
+            self.words, self.characters, self.headings, self.links, self.code_blocks
+        )
+    }
+}
+
+/// The prose of `node`, with each block on its own line
+pub(crate) fn prose(node: &AstNode) -> String {
+    let mut text = String::new();
+    collect_prose(node, &mut text);
+    text
+}
+
+/// Append the prose of `node` to `text`, starting each block on a new line
+fn collect_prose(node: &AstNode, text: &mut String) {
+    match node {
+        AstNode::Text(content)
+        | AstNode::InlineCode(content)
+        | AstNode::InlineMath(content)
+        | AstNode::Emoji(content) => text.push_str(content),
+        AstNode::LineBreak => text.push('\n'),
+        AstNode::Html(tag) if is_break_tag(tag) => text.push('\n'),
+        AstNode::CodeBlock { .. } | AstNode::BlockMath(_) | AstNode::Image { .. } => {}
+        AstNode::DefinitionList { items } => {
+            for (term, definitions) in items {
+                text.push('\n');
+                for child in term.iter().chain(definitions) {
+                    collect_prose(child, text);
+                }
+            }
+        }
+        _ => {
+            if node.is_block() {
+                text.push('\n');
+            }
+            for child in node.children() {
+                collect_prose(child, text);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+
+    #[test]
+    fn test_counts_words_and_headings() {
+        let ast = parse_markdown(
+            "# Getting Started\n\nInstall **mark** with cargo.\n\n## Usage\n\n- Run it\n- Read [docs](https://example.com)",
+        )
+        .unwrap();
+        let stats = DocumentStats::from_ast(&ast);
+
+        assert_eq!(stats.headings, 2);
+        assert_eq!(stats.links, 1);
+        assert_eq!(stats.code_blocks, 0);
+        // Getting Started / Install mark with cargo. / Usage / Run it / Read docs
+        assert_eq!(stats.words, 11);
+    }
+
+    #[test]
+    fn test_code_is_counted_as_blocks_not_words() {
+        let ast = parse_markdown("Two words\n\n```rust\nfn main() {}\n```").unwrap();
+        let stats = DocumentStats::from_ast(&ast);
+
+        assert_eq!(stats.code_blocks, 1);
+        assert_eq!(stats.words, 2);
+        assert_eq!(stats.characters, "Two words".len());
+    }
+}
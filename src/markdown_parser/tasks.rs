@@ -0,0 +1,110 @@
+use crate::markdown_parser::lexer::tokenize;
+use crate::markdown_parser::parser::{split_frontmatter, Parser};
+
+/// The checkbox of a task list item, as found in the markdown source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskMarker {
+    pub checked: bool,
+    /// Byte range of the text between the brackets
+    pub span: (usize, usize),
+}
+
+/// Find the checkbox of every task list item in `source`, in document order
+///
+/// The source is parsed the way the viewer parses it and the parser reports
+/// where each task's checkbox is, so the `n`th marker belongs to the `n`th
+/// `TaskListItem`. Nothing is found if the source can't be parsed.
+pub fn find_task_markers(source: &str) -> Vec<TaskMarker> {
+    let (offset, body) = match split_frontmatter(source) {
+        Some(front) => (source.len() - front.rest.len(), front.rest),
+        None => (0, source),
+    };
+    let Ok(tokens) = tokenize(body) else {
+        return Vec::new();
+    };
+
+    let spans: Vec<_> = tokens.iter().map(|spanned| spanned.span).collect();
+    let mut parser = Parser::from_spanned(tokens)
+        .with_lenient(true)
+        .with_strict_emphasis(false);
+    if parser.parse().is_err() {
+        return Vec::new();
+    }
+    parser
+        .task_marks()
+        .iter()
+        .map(|&index| {
+            let (start, end) = spans[index];
+            TaskMarker {
+                checked: !body[start..end].trim().is_empty(),
+                span: (start + offset, end + offset),
+            }
+        })
+        .collect()
+}
+
+/// Check or uncheck the `index`th task in `source`, returning the new source
+///
+/// Returns `None` if there are fewer tasks than that.
+pub fn toggle_task(source: &str, index: usize) -> Option<String> {
+    let marker = find_task_markers(source).into_iter().nth(index)?;
+    let mark = if marker.checked { " " } else { "x" };
+    let mut toggled = source.to_string();
+    toggled.replace_range(marker.span.0..marker.span.1, mark);
+    Some(toggled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_unchecked_task() {
+        let source = "# Todo\n\n- [x] write\n- [ ] test\n1. [ ] ship\n";
+        let markers = find_task_markers(source);
+        assert_eq!(markers.len(), 3);
+        assert!(markers[0].checked);
+        assert_eq!(markers[1].span, (23, 24));
+
+        let toggled = toggle_task(source, 1).unwrap();
+        assert_eq!(toggled, "# Todo\n\n- [x] write\n- [x] test\n1. [ ] ship\n");
+        assert_eq!(&toggled[22..25], "[x]");
+
+        let toggled = toggle_task(&toggled, 0).unwrap();
+        assert!(toggled.contains("- [ ] write"));
+        assert_eq!(toggle_task(source, 3), None);
+    }
+
+    #[test]
+    fn test_code_and_links_are_not_tasks() {
+        let source = "```\n- [ ] code\n```\n\n- [link](https://example.com)\n- [x]not a task\n";
+        assert!(find_task_markers(source).is_empty());
+    }
+
+    #[test]
+    fn test_markers_follow_the_parsers_fences() {
+        // Any run of three to five backticks closes a fence, as in the parser
+        let source = "````\n```\n- [ ] after\n````\n- [ ] code\n";
+        let markers = find_task_markers(source);
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].span.0, source.find("[ ]").unwrap() + 1);
+    }
+
+    #[test]
+    fn test_tilde_lines_are_not_fences() {
+        let source = "~~~\n- [ ] a\n~~~\n\n    - [ ] c\n";
+        let markers = find_task_markers(source);
+        assert_eq!(markers.len(), 1);
+
+        let toggled = toggle_task(source, 0).unwrap();
+        assert_eq!(toggled, "~~~\n- [x] a\n~~~\n\n    - [ ] c\n");
+    }
+
+    #[test]
+    fn test_indented_code_is_not_a_task() {
+        let source = "Text\n\n    - [ ] code\n\n- [ ] task\n";
+        let toggled = toggle_task(source, 0).unwrap();
+        assert_eq!(toggled, "Text\n\n    - [ ] code\n\n- [x] task\n");
+        assert_eq!(toggle_task(source, 1), None);
+    }
+}
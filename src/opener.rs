@@ -0,0 +1,47 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Opens paths with an external application
+pub trait Opener {
+    fn open(&self, path: &Path) -> io::Result<()>;
+}
+
+/// Opens paths with the platform's default handler (`xdg-open`, `open` or `start`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemOpener;
+
+impl Opener for SystemOpener {
+    fn open(&self, path: &Path) -> io::Result<()> {
+        let (program, args) = opener_command();
+        // Keep the handler's output from drawing over the TUI
+        let status = Command::new(program)
+            .args(args)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("{} exited with {}", program, status),
+            ))
+        }
+    }
+}
+
+/// The command and leading arguments used to open a path on this platform
+pub fn opener_command() -> (&'static str, &'static [&'static str]) {
+    if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if cfg!(windows) {
+        // `start` is a shell builtin; its first quoted argument is the window title
+        ("cmd", &["/C", "start", ""])
+    } else {
+        ("xdg-open", &[])
+    }
+}
@@ -1,19 +1,44 @@
 use crate::error::Result;
-use crate::search::{expand_tilde, MarkdownFile};
+use crate::search::{expand_tilde, is_listed_markdown, walk_tree, IgnoreMatcher, MarkdownFile};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
-use walkdir::WalkDir;
+
+/// How many directories to walk between progress messages
+const PROGRESS_INTERVAL: usize = 10;
+
+/// State shared by the walks of every searched directory
+#[derive(Default)]
+struct WalkState {
+    seen: HashSet<PathBuf>,
+    scanned: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl WalkState {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum SearchMessage {
     FileFound(MarkdownFile),
+    /// Number of directories walked so far, across every searched directory
+    Progress {
+        scanned: usize,
+    },
     Finished,
     Error(String),
 }
 
 pub struct BackgroundSearcher {
     receiver: Receiver<SearchMessage>,
-    _handle: thread::JoinHandle<()>,
+    handle: thread::JoinHandle<()>,
+    cancelled: Arc<AtomicBool>,
     pub is_complete: bool,
 }
 
@@ -23,31 +48,89 @@ impl BackgroundSearcher {
         ignored_dirs: Vec<String>,
         show_hidden: bool,
         show_all: bool,
+    ) -> Result<Self> {
+        Self::with_directories(
+            vec![PathBuf::from(directory)],
+            ignored_dirs,
+            Vec::new(),
+            show_hidden,
+            show_all,
+            false,
+            None,
+        )
+    }
+
+    /// Search several directories, merging their results into one stream
+    pub fn with_directories(
+        directories: Vec<PathBuf>,
+        ignored_dirs: Vec<String>,
+        ignored_files: Vec<String>,
+        show_hidden: bool,
+        show_all: bool,
+        follow_symlinks: bool,
+        max_file_size: Option<u64>,
     ) -> Result<Self> {
         let (tx, rx) = mpsc::channel();
-        let dir = directory.to_string();
+        let ignore = IgnoreMatcher::with_files(&ignored_dirs, &ignored_files)?
+            .with_max_file_size(max_file_size);
+        let cancelled = Arc::new(AtomicBool::new(false));
 
+        let worker_cancelled = Arc::clone(&cancelled);
         let handle = thread::spawn(move || {
-            if let Err(e) = Self::search_files(&tx, &dir, ignored_dirs, show_hidden, show_all) {
-                let _ = tx.send(SearchMessage::Error(e.to_string()));
+            let mut state = WalkState {
+                cancelled: worker_cancelled,
+                ..WalkState::default()
+            };
+            for dir in &directories {
+                if state.is_cancelled() {
+                    return;
+                }
+                if let Err(e) = Self::search_files(
+                    &tx,
+                    dir,
+                    &ignore,
+                    show_hidden,
+                    show_all,
+                    follow_symlinks,
+                    &mut state,
+                ) {
+                    let _ = tx.send(SearchMessage::Error(e.to_string()));
+                }
+                let _ = tx.send(SearchMessage::Progress {
+                    scanned: state.scanned,
+                });
             }
             let _ = tx.send(SearchMessage::Finished);
         });
 
         Ok(Self {
             receiver: rx,
-            _handle: handle,
+            handle,
+            cancelled,
             is_complete: false,
         })
     }
 
+    /// Stop the search, discarding any results that haven't been received yet
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // Swap in a disconnected receiver so the worker's next send fails too
+        let (_, receiver) = mpsc::channel();
+        self.receiver = receiver;
+        self.is_complete = true;
+    }
+
+    /// Check whether the worker thread is still walking directories
+    pub fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+
     pub fn try_recv(&mut self) -> Vec<SearchMessage> {
         let mut messages = Vec::new();
 
         while let Ok(message) = self.receiver.try_recv() {
-            match &message {
-                SearchMessage::Finished => self.is_complete = true,
-                _ => {}
+            if let SearchMessage::Finished = message {
+                self.is_complete = true;
             }
             messages.push(message);
         }
@@ -57,58 +140,61 @@ impl BackgroundSearcher {
 
     fn search_files(
         tx: &Sender<SearchMessage>,
-        directory: &str,
-        ignored_dirs: Vec<String>,
+        directory: &Path,
+        ignore: &IgnoreMatcher,
         show_hidden: bool,
         show_all: bool,
+        follow_symlinks: bool,
+        state: &mut WalkState,
     ) -> Result<()> {
-        let expanded_dir = expand_tilde(directory)?;
-        let search_root = expanded_dir.clone();
+        let search_root = expand_tilde(directory)?;
+        // Showing everything drops every filter, as in `list_markdown_paths`
+        let ignore = if show_all {
+            IgnoreMatcher::new(&[])?
+        } else {
+            ignore.clone()
+        };
+        let skip_hidden = !show_all && !show_hidden;
+
+        let entries = walk_tree(
+            search_root.clone(),
+            ignore.clone(),
+            skip_hidden,
+            follow_symlinks,
+        );
+        for entry in entries {
+            if state.is_cancelled() {
+                break;
+            }
 
-        for entry in WalkDir::new(expanded_dir) {
             let entry = match entry {
                 Ok(e) => e,
-                Err(_) => continue, // Skip inaccessible files/directories
+                Err(_) => continue, // Skip inaccessible files/directories and symlink loops
             };
 
-            let path = entry.path();
-
-            // Check if it's a markdown file
-            if !path.extension().map(|ext| ext == "md").unwrap_or(false) {
+            if entry.file_type().is_dir() {
+                state.scanned += 1;
+                if state.scanned % PROGRESS_INTERVAL == 0 {
+                    let progress = SearchMessage::Progress {
+                        scanned: state.scanned,
+                    };
+                    if tx.send(progress).is_err() {
+                        break;
+                    }
+                }
                 continue;
             }
 
-            // Skip if path contains any ignored directories (unless show_all is true)
-            if !show_all
-                && path.components().any(|component| {
-                    component
-                        .as_os_str()
-                        .to_str()
-                        .map(|s| ignored_dirs.contains(&s.to_string()))
-                        .unwrap_or(false)
-                })
-            {
+            if !is_listed_markdown(&entry, &search_root, &ignore) {
                 continue;
             }
 
-            // Handle hidden files unless show_all is true
-            if !show_all && !show_hidden {
-                // Skip if the file is inside a hidden directory (relative to search root)
-                if let Ok(relative_path) = path.strip_prefix(&search_root) {
-                    // Check if any component in the relative path is a hidden directory
-                    let mut should_skip = false;
-                    for component in relative_path.components() {
-                        if let Some(name_str) = component.as_os_str().to_str() {
-                            if name_str.starts_with('.') && name_str != "." && name_str != ".." {
-                                should_skip = true;
-                                break;
-                            }
-                        }
-                    }
-                    if should_skip {
-                        continue;
-                    }
-                }
+            let path = entry.path();
+
+            // Skip files already found through an overlapping directory
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            if !state.seen.insert(canonical) {
+                continue;
             }
 
             // Create MarkdownFile and send it
@@ -4,7 +4,7 @@ use std::time::Duration;
 use tempfile::TempDir;
 
 use super::background::{BackgroundSearcher, SearchMessage};
-use super::MarkdownFile;
+use super::{iter_markdown_files, MarkdownFile};
 
 #[cfg(test)]
 mod tests {
@@ -47,6 +47,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -106,6 +107,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -165,6 +167,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files_no_hidden.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -212,6 +215,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files_with_hidden.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -279,6 +283,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -325,6 +330,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -416,6 +422,7 @@ mod tests {
                     SearchMessage::FileFound(file) => {
                         found_files.push(file);
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         completed = true;
                         break;
@@ -455,6 +462,7 @@ mod tests {
                         received_error = true;
                         break;
                     }
+                    SearchMessage::Progress { .. } => {}
                     SearchMessage::Finished => {
                         break;
                     }
@@ -471,6 +479,162 @@ mod tests {
         // Error handling in walkdir will just skip inaccessible paths
     }
 
+    #[test]
+    fn test_progress_reported_before_finished() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        for i in 0..12 {
+            let dir = first.join(format!("dir{}", i));
+            fs::create_dir_all(&dir).unwrap();
+            File::create(dir.join("note.md")).unwrap();
+        }
+        fs::create_dir_all(second.join("nested")).unwrap();
+        File::create(second.join("nested").join("other.md")).unwrap();
+
+        let mut searcher = BackgroundSearcher::with_directories(
+            vec![first, second],
+            vec![],
+            vec![],
+            false,
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let mut progress = Vec::new();
+        let mut completed = false;
+        for _ in 0..200 {
+            for message in searcher.try_recv() {
+                match message {
+                    SearchMessage::Progress { scanned } => {
+                        assert!(!completed, "progress after Finished");
+                        progress.push(scanned);
+                    }
+                    SearchMessage::Finished => completed = true,
+                    _ => {}
+                }
+            }
+            if completed {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(completed);
+        assert!(progress.len() >= 2);
+        assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+        // 13 directories under `first`, 2 under `second`
+        assert_eq!(progress.last(), Some(&15));
+    }
+
+    #[test]
+    fn test_background_search_skips_large_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small.md"), "# Small\n").unwrap();
+        fs::write(temp_dir.path().join("large.md"), "x".repeat(2048)).unwrap();
+
+        let mut searcher = BackgroundSearcher::with_directories(
+            vec![temp_dir.path().to_path_buf()],
+            vec![],
+            vec![],
+            false,
+            false,
+            false,
+            Some(1024),
+        )
+        .unwrap();
+
+        let mut found = Vec::new();
+        for _ in 0..100 {
+            for message in searcher.try_recv() {
+                if let SearchMessage::FileFound(file) = message {
+                    found.push(file.name);
+                }
+            }
+            if searcher.is_complete {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(searcher.is_complete);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].ends_with("small.md"));
+    }
+
+    #[test]
+    fn test_background_search_walks_in_list_order() {
+        let temp_dir = TempDir::new().unwrap();
+        for dir in ["b", "a", "c/d"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        for file in ["z.md", "b/y.md", "a/x.md", "a/w.md", "c/d/v.md", "c/u.md"] {
+            File::create(temp_dir.path().join(file)).unwrap();
+        }
+
+        let mut searcher = BackgroundSearcher::with_directories(
+            vec![temp_dir.path().to_path_buf()],
+            vec![],
+            vec![],
+            false,
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        let mut found = Vec::new();
+        for _ in 0..100 {
+            for message in searcher.try_recv() {
+                if let SearchMessage::FileFound(file) = message {
+                    found.push(file.path);
+                }
+            }
+            if searcher.is_complete {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let listed: Vec<_> = iter_markdown_files(temp_dir.path(), &Default::default())
+            .unwrap()
+            .map(|file| file.path)
+            .collect();
+        assert!(searcher.is_complete);
+        assert_eq!(found.len(), 6);
+        assert_eq!(found, listed);
+    }
+
+    #[test]
+    fn test_cancel_stops_search() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..50 {
+            File::create(temp_dir.path().join(format!("file{}.md", i))).unwrap();
+        }
+
+        let mut searcher =
+            BackgroundSearcher::new(temp_dir.path().to_str().unwrap(), vec![], false, false)
+                .unwrap();
+        thread::sleep(Duration::from_millis(20));
+        searcher.cancel();
+        assert!(searcher.is_complete);
+
+        for _ in 0..100 {
+            if !searcher.is_running() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!searcher.is_running(), "worker should stop after cancel");
+
+        let messages = searcher.try_recv();
+        assert!(!messages
+            .iter()
+            .any(|m| matches!(m, SearchMessage::FileFound(_))));
+    }
+
     #[test]
     fn test_search_message_debug() {
         // Test that SearchMessage implements Debug properly
@@ -0,0 +1,224 @@
+use crate::error::{MarkError, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Matches paths against the `ignored_dirs` patterns
+///
+/// Patterns without a `/` match any single path component, so plain names like
+/// `node_modules` keep working and globs like `build-*` match at any depth.
+/// Patterns containing a `/` match a leading portion of the path relative to the
+/// search root, e.g. `docs/drafts` or `**/target`.
+///
+/// File patterns from `ignored_files` match the file name, or the whole relative
+/// path if they contain a `/`, e.g. `CHANGELOG.md` or `docs/*-draft.md`.
+/// Files larger than `max_file_size` can be skipped as well.
+#[derive(Debug, Clone)]
+pub struct IgnoreMatcher {
+    components: GlobSet,
+    paths: GlobSet,
+    file_names: GlobSet,
+    file_paths: GlobSet,
+    max_file_size: Option<u64>,
+}
+
+impl IgnoreMatcher {
+    /// Compile the given directory ignore patterns
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        Self::with_files(patterns, &[])
+    }
+
+    /// Compile directory and file ignore patterns
+    pub fn with_files(patterns: &[String], file_patterns: &[String]) -> Result<Self> {
+        let mut components = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+
+        for pattern in patterns {
+            let pattern = pattern.trim_end_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let glob = compile(pattern).map_err(|e| {
+                MarkError::search(format!("Invalid ignore pattern '{}': {}", pattern, e))
+            })?;
+
+            if pattern.contains('/') {
+                paths.add(glob);
+            } else {
+                components.add(glob);
+            }
+        }
+
+        let build = |builder: GlobSetBuilder| {
+            builder
+                .build()
+                .map_err(|e| MarkError::search(format!("Invalid ignore patterns: {}", e)))
+        };
+
+        let mut file_names = GlobSetBuilder::new();
+        let mut file_paths = GlobSetBuilder::new();
+        for pattern in file_patterns {
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let glob = compile(pattern).map_err(|e| {
+                MarkError::search(format!("Invalid ignored file pattern '{}': {}", pattern, e))
+            })?;
+
+            if pattern.contains('/') {
+                file_paths.add(glob);
+            } else {
+                file_names.add(glob);
+            }
+        }
+
+        Ok(Self {
+            components: build(components)?,
+            paths: build(paths)?,
+            file_names: build(file_names)?,
+            file_paths: build(file_paths)?,
+            max_file_size: None,
+        })
+    }
+
+    /// Also skip files larger than `max_file_size` bytes
+    pub fn with_max_file_size(mut self, max_file_size: Option<u64>) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Check if a file of `size` bytes is over the size limit
+    pub fn is_too_large(&self, size: u64) -> bool {
+        self.max_file_size.is_some_and(|max| size > max)
+    }
+
+    /// Check if a file path relative to the search root should be ignored
+    ///
+    /// The `ignored_dirs` patterns only see the directories above the file.
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        if relative_path
+            .file_name()
+            .is_some_and(|name| self.file_names.is_match(name))
+            || self.file_paths.is_match(relative_path)
+        {
+            return true;
+        }
+
+        relative_path
+            .parent()
+            .is_some_and(|parent| self.is_ignored_dir(parent))
+    }
+
+    /// Check if a directory relative to the search root matches the `ignored_dirs` patterns
+    ///
+    /// Unlike [`is_ignored`](Self::is_ignored), the `ignored_files` patterns don't apply.
+    pub fn is_ignored_dir(&self, relative_path: &Path) -> bool {
+        if relative_path
+            .components()
+            .any(|component| self.components.is_match(component.as_os_str()))
+        {
+            return true;
+        }
+
+        if self.paths.is_empty() {
+            return false;
+        }
+
+        relative_path
+            .ancestors()
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .any(|ancestor| self.paths.is_match(ancestor))
+    }
+}
+
+/// Compile a pattern whose `*` and `?` stay within one path component, like a shell glob
+fn compile(pattern: &str) -> std::result::Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(patterns: &[&str]) -> IgnoreMatcher {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        IgnoreMatcher::new(&patterns).unwrap()
+    }
+
+    #[test]
+    fn test_exact_name() {
+        let matcher = matcher(&["node_modules"]);
+
+        assert!(matcher.is_ignored(Path::new("node_modules/pkg/readme.md")));
+        assert!(matcher.is_ignored(Path::new("a/b/node_modules/readme.md")));
+        assert!(!matcher.is_ignored(Path::new("node_modules_backup/readme.md")));
+        assert!(!matcher.is_ignored(Path::new("docs/readme.md")));
+    }
+
+    #[test]
+    fn test_star_glob() {
+        let matcher = matcher(&["build-*", "*.cache"]);
+
+        assert!(matcher.is_ignored(Path::new("build-debug/notes.md")));
+        assert!(matcher.is_ignored(Path::new("src/pip.cache/readme.md")));
+        assert!(!matcher.is_ignored(Path::new("build/notes.md")));
+    }
+
+    #[test]
+    fn test_dir_pattern_skips_file_name() {
+        let matcher = matcher(&["build-*", "notes/build-notes.md"]);
+
+        assert!(!matcher.is_ignored(Path::new("notes/build-notes.md")));
+        assert!(!matcher.is_ignored(Path::new("build-notes.md")));
+        assert!(matcher.is_ignored(Path::new("notes/build-a/notes.md")));
+    }
+
+    #[test]
+    fn test_path_segment_pattern() {
+        let matcher = matcher(&["docs/drafts", "**/target"]);
+
+        assert!(matcher.is_ignored(Path::new("docs/drafts/todo.md")));
+        assert!(matcher.is_ignored(Path::new("a/b/target/doc.md")));
+        assert!(!matcher.is_ignored(Path::new("other/docs/drafts/todo.md")));
+        assert!(!matcher.is_ignored(Path::new("docs/published/todo.md")));
+    }
+
+    #[test]
+    fn test_path_star_stays_in_one_component() {
+        let matcher = matcher(&["docs/*/drafts"]);
+
+        assert!(matcher.is_ignored(Path::new("docs/v1/drafts/todo.md")));
+        assert!(!matcher.is_ignored(Path::new("docs/v1/old/drafts/todo.md")));
+    }
+
+    #[test]
+    fn test_ignored_files() {
+        let files = vec!["CHANGELOG.md".to_string(), "docs/*-draft.md".to_string()];
+        let matcher = IgnoreMatcher::with_files(&[], &files).unwrap();
+
+        assert!(matcher.is_ignored(Path::new("CHANGELOG.md")));
+        assert!(matcher.is_ignored(Path::new("sub/CHANGELOG.md")));
+        assert!(matcher.is_ignored(Path::new("docs/intro-draft.md")));
+        assert!(!matcher.is_ignored(Path::new("other/intro-draft.md")));
+        assert!(!matcher.is_ignored(Path::new("CHANGELOG/notes.md")));
+        assert!(!matcher.is_ignored(Path::new("docs/a/intro-draft.md")));
+    }
+
+    #[test]
+    fn test_ignored_dir_skips_file_patterns() {
+        let dirs = vec!["node_modules".to_string()];
+        let files = vec!["CHANGELOG*".to_string()];
+        let matcher = IgnoreMatcher::with_files(&dirs, &files).unwrap();
+
+        assert!(matcher.is_ignored_dir(Path::new("a/node_modules")));
+        assert!(!matcher.is_ignored_dir(Path::new("CHANGELOG")));
+        assert!(matcher.is_ignored(Path::new("CHANGELOG")));
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let result = IgnoreMatcher::new(&["[unclosed".to_string()]);
+        assert!(matches!(result, Err(MarkError::Search { .. })));
+    }
+}
@@ -1,7 +1,44 @@
+use chrono::format::{Item, StrftimeItems};
 use chrono::{DateTime, Local};
 
 use crate::error::Result;
-use std::{env::current_dir, io::Read, path::PathBuf};
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Format used for file dates unless `date_format` is configured
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Characters of context kept on each side of the match in a search snippet
+pub const SNIPPET_CONTEXT: usize = 30;
+
+/// Extensions of the files searches and glob expansion pick up
+pub const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+
+/// How file paths are shown in the browser, set by `[settings] path_display`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathDisplay {
+    /// Relative to the directory the file was found in
+    #[default]
+    Relative,
+    /// The full path from the filesystem root
+    Absolute,
+    /// Only the file name
+    Name,
+}
+
+impl PathDisplay {
+    /// Parse a `path_display` setting, falling back to `Relative` for unknown values
+    pub fn from_setting(value: &str) -> Self {
+        match value {
+            "absolute" => PathDisplay::Absolute,
+            "name" => PathDisplay::Name,
+            _ => PathDisplay::Relative,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct MarkdownFile {
@@ -9,6 +46,12 @@ pub struct MarkdownFile {
     pub name: String,
     pub content: Option<String>,
     pub created_at: Option<String>,
+    /// Creation time, or the modification time where the platform lacks one
+    pub created: Option<SystemTime>,
+    /// File size in bytes, if the metadata could be read
+    pub size: Option<u64>,
+    /// Whether invalid UTF-8 in the loaded content was replaced with `U+FFFD`
+    pub lossy: bool,
 }
 
 impl MarkdownFile {
@@ -28,27 +71,157 @@ impl MarkdownFile {
             name
         };
 
-        let created_at = std::fs::metadata(&path)
-            .and_then(|meta| meta.created())
-            .ok()
-            .and_then(|time| {
-                let datetime: DateTime<Local> = time.into();
-                Some(datetime.format("%Y-%m-%d %H:%M:%S").to_string())
-            });
+        let created = created_time(&path);
+        let size = std::fs::metadata(&path).ok().map(|metadata| metadata.len());
+        let created_at = created.map(|time| format_date(time, DEFAULT_DATE_FORMAT));
 
         Self {
             path: path.clone(),
             name,
             content: None,
             created_at,
+            created,
+            size,
+            lossy: false,
         }
     }
 
     pub fn load_content(&mut self) -> Result<()> {
-        let mut file = std::fs::File::open(&self.path)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
+        let (content, lossy) = read_lossy(&self.path)?;
         self.content = Some(content);
+        self.lossy = lossy;
         Ok(())
     }
+
+    /// The path to show for this file in the given `display` mode
+    ///
+    /// Relative paths are taken from the deepest of the search `roots` the file
+    /// is under, falling back to `name` when it is under none of them.
+    pub fn display_path(&self, display: PathDisplay, roots: &[PathBuf]) -> String {
+        match display {
+            PathDisplay::Relative => roots
+                .iter()
+                .filter_map(|root| self.path.strip_prefix(root).ok())
+                .filter(|relative| !relative.as_os_str().is_empty())
+                .min_by_key(|relative| relative.components().count())
+                .map(|relative| relative.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.name.clone()),
+            PathDisplay::Absolute => {
+                let path = if self.path.is_absolute() {
+                    self.path.clone()
+                } else {
+                    current_dir().unwrap_or_default().join(&self.path)
+                };
+                // Collecting the components drops `.` segments like `/home/./notes`
+                path.components()
+                    .collect::<PathBuf>()
+                    .to_string_lossy()
+                    .into_owned()
+            }
+            PathDisplay::Name => self
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| self.name.clone()),
+        }
+    }
+
+    /// The first line of the loaded content containing `query`, ignoring case
+    ///
+    /// Long lines are cut to the match and `SNIPPET_CONTEXT` characters either
+    /// side of it, with `…` where text was dropped. Returns `None` if the content
+    /// isn't loaded or nothing matches.
+    pub fn matching_snippet(&self, query: &str) -> Option<String> {
+        let needle: Vec<char> = query.chars().map(fold_case).collect();
+        if needle.is_empty() {
+            return None;
+        }
+
+        self.content.as_deref()?.lines().find_map(|line| {
+            let chars: Vec<char> = line.trim().chars().collect();
+            let folded: Vec<char> = chars.iter().copied().map(fold_case).collect();
+            let position = folded
+                .windows(needle.len())
+                .position(|window| window == needle.as_slice())?;
+
+            let start = position.saturating_sub(SNIPPET_CONTEXT);
+            let end = (position + needle.len() + SNIPPET_CONTEXT).min(chars.len());
+            let mut snippet = String::new();
+            if start > 0 {
+                snippet.push('…');
+            }
+            snippet.extend(&chars[start..end]);
+            if end < chars.len() {
+                snippet.push('…');
+            }
+            Some(snippet)
+        })
+    }
+}
+
+/// Lowercase `c` for case-insensitive matching, keeping one char per char
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Whether `path` has one of the `MARKDOWN_EXTENSIONS`, in any case
+pub fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| MARKDOWN_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// When `path` was created, falling back to its modification time
+pub fn created_time(path: &Path) -> Option<SystemTime> {
+    let metadata = std::fs::metadata(path).ok()?;
+    metadata.created().or_else(|_| metadata.modified()).ok()
+}
+
+/// Whether `format` is a strftime format chrono can apply
+pub fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+/// Format `time` in local time, using the default format if `format` is invalid
+pub fn format_date(time: SystemTime, format: &str) -> String {
+    let format = if is_valid_date_format(format) {
+        format
+    } else {
+        DEFAULT_DATE_FORMAT
+    };
+    let datetime: DateTime<Local> = time.into();
+    datetime.format(format).to_string()
+}
+
+/// Describe how long before `now` `time` was, e.g. "just now" or "2 days ago"
+///
+/// Times in the future, as happens with clock skew, count as just now.
+pub fn format_relative(time: SystemTime, now: SystemTime) -> String {
+    let seconds = now
+        .duration_since(time)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (seconds / 60, "minute"),
+        3_600..=86_399 => (seconds / 3_600, "hour"),
+        86_400..=2_591_999 => (seconds / 86_400, "day"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{} ago", count, unit, plural)
+}
+
+/// Read a file as text, replacing invalid UTF-8 instead of failing
+///
+/// The flag is `true` when any bytes had to be replaced.
+pub fn read_lossy(path: &Path) -> Result<(String, bool)> {
+    let bytes = std::fs::read(path)?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(content) => (content, false),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+    })
 }
@@ -1,18 +1,22 @@
 pub mod background;
+pub mod ignore;
 pub mod markdown;
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests;
 
 #[cfg(test)]
 mod background_tests;
 
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::Result;
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 
+pub use crate::search::ignore::IgnoreMatcher;
+use crate::search::markdown::is_markdown_file;
 pub use crate::search::markdown::MarkdownFile;
 
 pub fn find_markdown_files(dir: &str) -> Result<Vec<MarkdownFile>> {
@@ -23,36 +27,12 @@ pub fn find_markdown_files_with_ignored(
     dir: &str,
     ignored_dirs: &[String],
 ) -> Result<Vec<MarkdownFile>> {
-    let expanded_dir = expand_tilde(dir)?;
-    let paths: Vec<PathBuf> = WalkDir::new(expanded_dir)
-        .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|e| {
-            // Skip if path contains any ignored directories
-            !e.path().components().any(|component| {
-                component
-                    .as_os_str()
-                    .to_str()
-                    .map(|s| ignored_dirs.contains(&s.to_string()))
-                    .unwrap_or(false)
-            })
-        })
-        .filter(|e| e.path().extension().map(|ext| ext == "md").unwrap_or(false))
-        .map(|e| e.path().to_path_buf())
-        .collect();
-
+    let paths = collect_markdown_paths(dir, ignored_dirs, &[], false, false, None)?;
     Ok(convert_to_files(paths))
 }
 
 pub fn find_all_markdown_files_unfiltered(dir: &str) -> Result<Vec<MarkdownFile>> {
-    let expanded_dir = expand_tilde(dir)?;
-    let paths: Vec<PathBuf> = WalkDir::new(expanded_dir)
-        .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|e| e.path().extension().map(|ext| ext == "md").unwrap_or(false))
-        .map(|e| e.path().to_path_buf())
-        .collect();
-
+    let paths = collect_markdown_paths(dir, &[], &[], false, false, None)?;
     Ok(convert_to_files(paths))
 }
 
@@ -64,62 +44,206 @@ pub fn find_markdown_files_without_hidden_with_ignored(
     dir: &str,
     ignored_dirs: &[String],
 ) -> Result<Vec<MarkdownFile>> {
-    let expanded_dir = expand_tilde(dir)?;
-    let search_root = expanded_dir.clone();
-    let paths: Vec<PathBuf> = WalkDir::new(expanded_dir)
+    let paths = collect_markdown_paths(dir, ignored_dirs, &[], true, false, None)?;
+    Ok(convert_to_files(paths))
+}
+
+/// Filters for [`iter_markdown_files`]
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub ignored_dirs: Vec<String>,
+    pub ignored_files: Vec<String>,
+    pub skip_hidden: bool,
+    pub follow_symlinks: bool,
+    pub max_file_size: Option<u64>,
+}
+
+/// Walk `dir` for markdown files lazily, yielding each one as it is found
+///
+/// Directories are only read as the iterator reaches them, so stopping early
+/// skips the rest of the tree. Entries are visited in file name order.
+pub fn iter_markdown_files<P: AsRef<Path>>(
+    dir: P,
+    options: &SearchOptions,
+) -> Result<impl Iterator<Item = MarkdownFile>> {
+    let paths = markdown_paths(
+        dir,
+        &options.ignored_dirs,
+        &options.ignored_files,
+        options.skip_hidden,
+        options.follow_symlinks,
+        options.max_file_size,
+    )?;
+    Ok(paths.map(MarkdownFile::new))
+}
+
+/// Walk `dir` for markdown files, optionally following symlinked directories
+fn collect_markdown_paths<P: AsRef<Path>>(
+    dir: P,
+    ignored_dirs: &[String],
+    ignored_files: &[String],
+    skip_hidden: bool,
+    follow_symlinks: bool,
+    max_file_size: Option<u64>,
+) -> Result<Vec<PathBuf>> {
+    let paths = markdown_paths(
+        dir,
+        ignored_dirs,
+        ignored_files,
+        skip_hidden,
+        follow_symlinks,
+        max_file_size,
+    )?;
+    Ok(paths.collect())
+}
+
+fn markdown_paths<P: AsRef<Path>>(
+    dir: P,
+    ignored_dirs: &[String],
+    ignored_files: &[String],
+    skip_hidden: bool,
+    follow_symlinks: bool,
+    max_file_size: Option<u64>,
+) -> Result<impl Iterator<Item = PathBuf>> {
+    let search_root = expand_tilde(dir)?;
+    let ignore =
+        IgnoreMatcher::with_files(ignored_dirs, ignored_files)?.with_max_file_size(max_file_size);
+    // Symlink loops surface as walk errors, which are skipped like unreadable entries
+    let paths = walk_tree(
+        search_root.clone(),
+        ignore.clone(),
+        skip_hidden,
+        follow_symlinks,
+    )
+    .filter_map(std::result::Result::ok)
+    .filter(move |e| is_listed_markdown(e, &search_root, &ignore))
+    .map(|e| e.path().to_path_buf());
+
+    Ok(paths)
+}
+
+/// Walk `root` in file name order, directories included, pruning hidden and ignored directories
+///
+/// Hidden entries are only pruned when `skip_hidden` is set. Pruned
+/// directories are never read, however large they are.
+fn walk_tree(
+    root: PathBuf,
+    ignore: IgnoreMatcher,
+    skip_hidden: bool,
+    follow_symlinks: bool,
+) -> impl Iterator<Item = walkdir::Result<DirEntry>> {
+    WalkDir::new(&root)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
         .into_iter()
-        .filter_map(std::result::Result::ok)
-        .filter(|e| {
-            // Skip if the file is inside a hidden directory (relative to search root)
-            let path = e.path();
-
-            // Get the relative path from search root
-            if let Ok(relative_path) = path.strip_prefix(&search_root) {
-                // Check if any component in the relative path is a hidden directory
-                for component in relative_path.components() {
-                    if let Some(name_str) = component.as_os_str().to_str() {
-                        if name_str.starts_with('.') && name_str != "." && name_str != ".." {
-                            return false; // Skip this file - it's in a hidden directory
-                        }
-                    }
-                }
+        .filter_entry(move |e| {
+            if e.depth() == 0 {
+                return true;
             }
-            true // Include this file
-        })
-        .filter(|e| {
-            // Skip if path contains any ignored directories
-            !e.path().components().any(|component| {
-                component
-                    .as_os_str()
-                    .to_str()
-                    .map(|s| ignored_dirs.contains(&s.to_string()))
-                    .unwrap_or(false)
-            })
+            if skip_hidden && e.file_name().to_string_lossy().starts_with('.') {
+                return false;
+            }
+            let relative_path = e.path().strip_prefix(&root).unwrap_or(e.path());
+            !(e.file_type().is_dir() && ignore.is_ignored_dir(relative_path))
         })
-        .filter(|e| e.path().extension().map(|ext| ext == "md").unwrap_or(false))
-        .map(|e| e.path().to_path_buf())
+}
+
+/// Check if an entry of [`walk_tree`] is a markdown file that `ignore` keeps
+fn is_listed_markdown(entry: &DirEntry, root: &Path, ignore: &IgnoreMatcher) -> bool {
+    if entry.file_type().is_dir() || !is_markdown_file(entry.path()) {
+        return false;
+    }
+    let relative_path = entry.path().strip_prefix(root).unwrap_or(entry.path());
+    !ignore.is_ignored(relative_path)
+        && !entry
+            .metadata()
+            .is_ok_and(|metadata| ignore.is_too_large(metadata.len()))
+}
+
+/// List markdown file paths relative to `dir`, using the same filters as the browser
+pub fn list_markdown_paths<P: AsRef<Path>>(
+    dir: P,
+    ignored_dirs: &[String],
+    ignored_files: &[String],
+    show_hidden: bool,
+    show_all: bool,
+    follow_symlinks: bool,
+    max_file_size: Option<u64>,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    let files = if show_all {
+        collect_markdown_paths(dir, &[], &[], false, follow_symlinks, None)?
+    } else {
+        collect_markdown_paths(
+            dir,
+            ignored_dirs,
+            ignored_files,
+            !show_hidden,
+            follow_symlinks,
+            max_file_size,
+        )?
+    };
+
+    let root = expand_tilde(dir)?;
+    let mut paths: Vec<PathBuf> = files
+        .into_iter()
+        .map(|path| path.strip_prefix(&root).map(PathBuf::from).unwrap_or(path))
         .collect();
+    paths.sort();
 
-    Ok(convert_to_files(paths))
+    Ok(paths)
 }
 
 fn convert_to_files(paths: Vec<PathBuf>) -> Vec<MarkdownFile> {
     paths.into_iter().map(MarkdownFile::new).collect()
 }
 
+/// Get the user's home directory from the environment
+pub fn home_dir() -> Option<PathBuf> {
+    home_dir_from(|key| env::var(key).ok())
+}
+
+/// Resolve the home directory from `HOME`, falling back to the Windows
+/// `USERPROFILE` and `HOMEDRIVE`+`HOMEPATH` variables
+pub fn home_dir_from<F>(lookup: F) -> Option<PathBuf>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let non_empty = |key: &str| lookup(key).filter(|value| !value.is_empty());
+
+    if let Some(home) = non_empty("HOME").or_else(|| non_empty("USERPROFILE")) {
+        return Some(PathBuf::from(home));
+    }
+
+    match (non_empty("HOMEDRIVE"), non_empty("HOMEPATH")) {
+        (Some(drive), Some(path)) => Some(PathBuf::from(format!("{}{}", drive, path))),
+        _ => None,
+    }
+}
+
 /// Expand tilde (~) to home directory path
-pub fn expand_tilde(path: &str) -> Result<PathBuf> {
-    if path.starts_with("~/") {
-        let home = env::var("HOME").map_err(|_| {
-            crate::error::MarkError::search("Could not find HOME environment variable")
-        })?;
-        Ok(PathBuf::from(home).join(&path[2..]))
-    } else if path == "~" {
-        let home = env::var("HOME").map_err(|_| {
-            crate::error::MarkError::search("Could not find HOME environment variable")
-        })?;
-        Ok(PathBuf::from(home))
+pub fn expand_tilde<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    expand_tilde_from(path, |key| env::var(key).ok())
+}
+
+/// Expand tilde (~) to the home directory found with `lookup`, as in `home_dir_from`
+pub fn expand_tilde_from<P, F>(path: P, lookup: F) -> Result<PathBuf>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> Option<String>,
+{
+    let path = path.as_ref();
+    let Ok(rest) = path.strip_prefix("~") else {
+        return Ok(path.to_path_buf());
+    };
+
+    let home = home_dir_from(lookup).ok_or_else(|| {
+        crate::error::MarkError::search("Could not find HOME or USERPROFILE environment variable")
+    })?;
+
+    if rest.as_os_str().is_empty() {
+        Ok(home)
     } else {
-        Ok(PathBuf::from(path))
+        Ok(home.join(rest))
     }
 }
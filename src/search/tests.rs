@@ -51,6 +51,56 @@ mod tests {
         assert_eq!(md_file.content, None);
     }
 
+    #[test]
+    fn test_markdown_file_new_reads_created_at() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.md");
+        File::create(&file_path).unwrap();
+
+        let md_file = MarkdownFile::new(file_path);
+        assert!(md_file.created.is_some());
+        let created_at = md_file.created_at.unwrap();
+        // e.g. 2024-06-15 12:00:00
+        assert_eq!(created_at.len(), 19);
+    }
+
+    #[test]
+    fn test_format_date_applies_format() {
+        use std::time::{Duration, UNIX_EPOCH};
+        // 2024-06-15 12:00:00 UTC, far enough from midnight for any time zone
+        let time = UNIX_EPOCH + Duration::from_secs(1_718_452_800);
+
+        assert_eq!(markdown::format_date(time, "%Y-%m"), "2024-06");
+        assert_eq!(markdown::format_date(time, "%d/%m/%Y"), "15/06/2024");
+        assert!(!markdown::is_valid_date_format("%Q"));
+        assert_eq!(
+            markdown::format_date(time, "%Q").len(),
+            "2024-06-15 12:00:00".len()
+        );
+    }
+
+    #[test]
+    fn test_format_relative_buckets() {
+        use std::time::{Duration, UNIX_EPOCH};
+        let now = UNIX_EPOCH + Duration::from_secs(1_718_452_800);
+        let ago = |seconds: u64| markdown::format_relative(now - Duration::from_secs(seconds), now);
+
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(45 * 60), "45 minutes ago");
+        assert_eq!(ago(3_600), "1 hour ago");
+        assert_eq!(ago(23 * 3_600), "23 hours ago");
+        assert_eq!(ago(86_400), "1 day ago");
+        assert_eq!(ago(2 * 86_400 + 5), "2 days ago");
+        assert_eq!(ago(90 * 86_400), "3 months ago");
+        assert_eq!(ago(800 * 86_400), "2 years ago");
+
+        // Clock skew shouldn't produce a negative age
+        let future = now + Duration::from_secs(30);
+        assert_eq!(markdown::format_relative(future, now), "just now");
+    }
+
     #[test]
     fn test_convert_to_files_empty() {
         let paths = vec![];
@@ -100,6 +150,20 @@ mod tests {
 
         assert!(result.is_ok());
         assert_eq!(md_file.content, Some(test_content.to_string()));
+        assert!(!md_file.lossy);
+    }
+
+    #[test]
+    fn test_markdown_file_load_content_invalid_utf8() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("latin1.md");
+        std::fs::write(&file_path, b"# Caf\xe9\n").unwrap();
+
+        let mut md_file = MarkdownFile::new(file_path);
+        md_file.load_content().unwrap();
+
+        assert!(md_file.lossy);
+        assert_eq!(md_file.content, Some("# Caf\u{FFFD}\n".to_string()));
     }
 
     #[test]
@@ -128,6 +192,69 @@ mod tests {
         assert_eq!(md_file.content, Some(String::new()));
     }
 
+    #[test]
+    fn test_matching_snippet_returns_first_matching_line() {
+        let mut md_file = MarkdownFile::new(PathBuf::from("notes.md"));
+        assert_eq!(md_file.matching_snippet("install"), None);
+
+        md_file.content = Some(format!(
+            "# Notes\n\n  Run the Installer first\n\ninstall again\n{}needle{}\n",
+            "a".repeat(40),
+            "b".repeat(40)
+        ));
+        assert_eq!(
+            md_file.matching_snippet("install").as_deref(),
+            Some("Run the Installer first")
+        );
+        assert_eq!(
+            md_file.matching_snippet("NEEDLE"),
+            Some(format!("…{}needle{}…", "a".repeat(30), "b".repeat(30)))
+        );
+    }
+
+    #[test]
+    fn test_matching_snippet_without_match_is_none() {
+        let mut md_file = MarkdownFile::new(PathBuf::from("notes.md"));
+        md_file.content = Some("# Notes\n\nNothing here".to_string());
+        assert_eq!(md_file.matching_snippet("missing"), None);
+        assert_eq!(md_file.matching_snippet(""), None);
+    }
+
+    #[test]
+    fn test_relative_path_display_strips_search_root() {
+        use crate::search::markdown::PathDisplay;
+
+        let md_file = MarkdownFile::new(PathBuf::from("/home/user/notes/docs/setup.md"));
+        let roots = [
+            PathBuf::from("/home/user"),
+            PathBuf::from("/home/user/notes"),
+        ];
+        assert_eq!(
+            md_file.display_path(PathDisplay::Relative, &roots),
+            "docs/setup.md"
+        );
+        // Outside every root the usual name is shown
+        assert_eq!(
+            md_file.display_path(PathDisplay::Relative, &[PathBuf::from("/srv")]),
+            "/home/user/notes/docs/setup.md"
+        );
+        assert_eq!(
+            md_file.display_path(PathDisplay::Absolute, &roots),
+            "/home/user/notes/docs/setup.md"
+        );
+    }
+
+    #[test]
+    fn test_name_path_display_shows_file_name() {
+        use crate::search::markdown::PathDisplay;
+
+        let md_file = MarkdownFile::new(PathBuf::from("/home/user/notes/docs/setup.md"));
+        let roots = [PathBuf::from("/home/user/notes")];
+        assert_eq!(md_file.display_path(PathDisplay::Name, &roots), "setup.md");
+        assert_eq!(PathDisplay::from_setting("name"), PathDisplay::Name);
+        assert_eq!(PathDisplay::from_setting("other"), PathDisplay::Relative);
+    }
+
     #[test]
     fn test_find_markdown_files_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -201,10 +328,11 @@ mod tests {
         assert!(result.is_ok());
         let files = result.unwrap();
 
-        // Only .md files should be found (not .markdown in current implementation)
-        assert_eq!(files.len(), 1);
+        // Both .md and .markdown files should be found
+        assert_eq!(files.len(), 2);
         // The name will be the full path since we're not in the temp directory
         assert!(files[0].name.ends_with("doc.md"));
+        assert!(files[1].name.ends_with("readme.markdown"));
     }
 
     #[test]
@@ -307,6 +435,32 @@ mod tests {
         std::env::remove_var("HOME");
     }
 
+    #[test]
+    fn test_expand_tilde_ignores_trailing_separator() {
+        let home = |key: &str| (key == "HOME").then(|| "/home/testuser".to_string());
+
+        let plain = super::expand_tilde_from("~/notes", home).unwrap();
+        let trailing = super::expand_tilde_from("~/notes/", home).unwrap();
+        assert_eq!(plain, PathBuf::from("/home/testuser/notes"));
+        assert_eq!(plain, trailing);
+
+        let resolved = crate::cli::paths::resolve_paths(
+            &[PathBuf::from("notes"), PathBuf::from("notes/")],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            resolved,
+            vec![PathBuf::from("notes"), PathBuf::from("notes")]
+        );
+
+        let resolve = |path: &str| {
+            crate::cli::paths::resolve_paths_from(&[PathBuf::from(path)], None, home).unwrap()
+        };
+        assert_eq!(resolve("~/x/"), resolve("~/x"));
+        assert_eq!(resolve("~/x/"), vec![PathBuf::from("/home/testuser/x")]);
+    }
+
     #[test]
     fn test_expand_tilde_no_expansion_needed() {
         let result = super::expand_tilde("/absolute/path");
@@ -320,11 +474,15 @@ mod tests {
 
     #[test]
     fn test_expand_tilde_missing_home_env() {
-        // Save original HOME value
-        let original_home = std::env::var("HOME").ok();
+        let vars = ["HOME", "USERPROFILE", "HOMEDRIVE", "HOMEPATH"];
 
-        // Remove HOME environment variable
-        std::env::remove_var("HOME");
+        // Save original values
+        let originals: Vec<_> = vars.iter().map(|var| std::env::var(var).ok()).collect();
+
+        // Remove every home directory variable
+        for var in vars {
+            std::env::remove_var(var);
+        }
 
         let result = super::expand_tilde("~");
         assert!(result.is_err());
@@ -332,12 +490,58 @@ mod tests {
         let result = super::expand_tilde("~/Documents");
         assert!(result.is_err());
 
-        // Restore original HOME value
-        if let Some(home) = original_home {
-            std::env::set_var("HOME", home);
+        // Restore original values
+        for (var, original) in vars.iter().zip(originals) {
+            if let Some(value) = original {
+                std::env::set_var(var, value);
+            }
         }
     }
 
+    #[test]
+    fn test_home_dir_prefers_home() {
+        let home = super::home_dir_from(|key| match key {
+            "HOME" => Some("/home/testuser".to_string()),
+            "USERPROFILE" => Some("C:\\Users\\test".to_string()),
+            _ => None,
+        });
+        assert_eq!(home, Some(PathBuf::from("/home/testuser")));
+    }
+
+    #[test]
+    fn test_home_dir_falls_back_to_userprofile() {
+        let home = super::home_dir_from(|key| match key {
+            "USERPROFILE" => Some("C:\\Users\\test".to_string()),
+            _ => None,
+        });
+        assert_eq!(home, Some(PathBuf::from("C:\\Users\\test")));
+
+        // An empty HOME is treated as unset
+        let home = super::home_dir_from(|key| match key {
+            "HOME" => Some(String::new()),
+            "USERPROFILE" => Some("C:\\Users\\test".to_string()),
+            _ => None,
+        });
+        assert_eq!(home, Some(PathBuf::from("C:\\Users\\test")));
+    }
+
+    #[test]
+    fn test_home_dir_falls_back_to_homedrive_homepath() {
+        let home = super::home_dir_from(|key| match key {
+            "HOMEDRIVE" => Some("C:".to_string()),
+            "HOMEPATH" => Some("\\Users\\test".to_string()),
+            _ => None,
+        });
+        assert_eq!(home, Some(PathBuf::from("C:\\Users\\test")));
+
+        // HOMEPATH alone is not enough to build a path
+        let home = super::home_dir_from(|key| match key {
+            "HOMEPATH" => Some("\\Users\\test".to_string()),
+            _ => None,
+        });
+        assert_eq!(home, None);
+    }
+
     #[test]
     fn test_integration_full_workflow() {
         let temp_dir = TempDir::new().unwrap();
@@ -524,4 +728,161 @@ mod tests {
         assert!(names.iter().any(|name| name.ends_with("root.md")));
         assert!(names.iter().any(|name| name.ends_with("docs/public.md")));
     }
+
+    #[test]
+    fn test_list_markdown_paths_respects_ignored_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir_all(dir_path.join("docs")).unwrap();
+        fs::create_dir_all(dir_path.join("node_modules")).unwrap();
+        fs::create_dir_all(dir_path.join(".hidden")).unwrap();
+        File::create(dir_path.join("root.md")).unwrap();
+        File::create(dir_path.join("docs/guide.md")).unwrap();
+        File::create(dir_path.join("docs/notes.txt")).unwrap();
+        File::create(dir_path.join("node_modules/package.md")).unwrap();
+        File::create(dir_path.join(".hidden/secret.md")).unwrap();
+
+        let ignored_dirs = vec!["node_modules".to_string()];
+        let paths = super::super::list_markdown_paths(
+            dir_path.to_str().unwrap(),
+            &ignored_dirs,
+            &[],
+            false,
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("docs/guide.md"), PathBuf::from("root.md")]
+        );
+    }
+
+    #[test]
+    fn test_list_markdown_paths_ignored_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir_all(dir_path.join("docs")).unwrap();
+        File::create(dir_path.join("README.md")).unwrap();
+        File::create(dir_path.join("CHANGELOG.md")).unwrap();
+        File::create(dir_path.join("docs/CHANGELOG.md")).unwrap();
+        File::create(dir_path.join("docs/LICENSE.md")).unwrap();
+        File::create(dir_path.join("docs/guide.md")).unwrap();
+
+        let ignored_files = vec!["CHANGELOG.md".to_string(), "LICENSE*".to_string()];
+        let list = |show_all| {
+            super::super::list_markdown_paths(
+                dir_path.to_str().unwrap(),
+                &[],
+                &ignored_files,
+                false,
+                show_all,
+                false,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            list(false),
+            vec![PathBuf::from("README.md"), PathBuf::from("docs/guide.md")]
+        );
+        // --all shows everything, including ignored files
+        assert_eq!(list(true).len(), 5);
+    }
+
+    #[test]
+    fn test_list_markdown_paths_max_file_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::write(dir_path.join("small.md"), "# Small\n").unwrap();
+        fs::write(dir_path.join("large.md"), "x".repeat(2048)).unwrap();
+
+        let list = |show_all| {
+            super::super::list_markdown_paths(
+                dir_path.to_str().unwrap(),
+                &[],
+                &[],
+                false,
+                show_all,
+                false,
+                Some(1024),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(list(false), vec![PathBuf::from("small.md")]);
+        // --all shows everything, including oversized files
+        assert_eq!(list(true).len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_markdown_paths_follow_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        File::create(dir_path.join("root.md")).unwrap();
+        File::create(target_dir.path().join("linked.md")).unwrap();
+        std::os::unix::fs::symlink(target_dir.path(), dir_path.join("docs")).unwrap();
+        // A self-referential link must not hang the walk
+        std::os::unix::fs::symlink(dir_path, dir_path.join("loop")).unwrap();
+
+        let list = |follow_symlinks| {
+            super::super::list_markdown_paths(
+                dir_path.to_str().unwrap(),
+                &[],
+                &[],
+                false,
+                false,
+                follow_symlinks,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(list(false), vec![PathBuf::from("root.md")]);
+        assert_eq!(
+            list(true),
+            vec![PathBuf::from("docs/linked.md"), PathBuf::from("root.md")]
+        );
+    }
+
+    #[test]
+    fn test_iter_markdown_files_stops_early() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        File::create(dir_path.join("a.md")).unwrap();
+        File::create(dir_path.join("b.md")).unwrap();
+        fs::create_dir(dir_path.join("z")).unwrap();
+        for index in 0..50 {
+            File::create(dir_path.join("z").join(format!("{}.md", index))).unwrap();
+        }
+
+        let options = super::super::SearchOptions::default();
+        let mut files = super::super::iter_markdown_files(dir_path, &options).unwrap();
+        let first: Vec<PathBuf> = files.by_ref().take(2).map(|file| file.path).collect();
+        assert_eq!(first, vec![dir_path.join("a.md"), dir_path.join("b.md")]);
+
+        // The subdirectory hasn't been read yet, so removing it leaves nothing to find
+        fs::remove_dir_all(dir_path.join("z")).unwrap();
+        assert_eq!(files.count(), 0);
+
+        let options = super::super::SearchOptions {
+            ignored_files: vec!["a.md".to_string()],
+            ..Default::default()
+        };
+        let names: Vec<PathBuf> = super::super::iter_markdown_files(dir_path, &options)
+            .unwrap()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(names, vec![dir_path.join("b.md")]);
+    }
 }
@@ -0,0 +1,37 @@
+//! Test doubles shared by unit tests across the crate
+
+use crate::clipboard::Clipboard;
+use crate::opener::Opener;
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Records what it is asked to open or copy, and fails when asked to
+pub struct Recording<T> {
+    pub calls: Rc<RefCell<Vec<T>>>,
+    pub fail: bool,
+}
+
+impl<T> Recording<T> {
+    fn record(&self, value: T) -> io::Result<()> {
+        self.calls.borrow_mut().push(value);
+        if self.fail {
+            Err(io::Error::new(io::ErrorKind::NotFound, "no handler"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Opener for Recording<PathBuf> {
+    fn open(&self, path: &Path) -> io::Result<()> {
+        self.record(path.to_path_buf())
+    }
+}
+
+impl Clipboard for Recording<String> {
+    fn copy(&self, text: &str) -> io::Result<()> {
+        self.record(text.to_string())
+    }
+}
@@ -1,47 +1,174 @@
+use crate::bookmarks::Bookmarks;
+use crate::config::ConfigLoader;
 use crate::error::Result;
-use crate::search::MarkdownFile;
-use crate::ui::{events::EventHandler, file_browser::FileBrowser, Event};
+use crate::images::{self, ImageProtocol};
+use crate::markdown_parser::{cache::DocumentCache, AstNode};
+use crate::search::markdown::PathDisplay;
+use crate::ui::renderer::{CodeBlockBorder, HeadingPrefix, InlineCodeStyle, Theme};
+use crate::ui::watcher::FileWatcher;
+use crate::ui::{events::EventHandler, file_browser::FileBrowser, viewer::MarkdownViewer, Event};
 use crossterm::event::KeyEvent;
 use ratatui::Frame;
+use std::path::{Path, PathBuf};
 
 pub struct App {
-    file_browser: FileBrowser,
+    file_browser: Option<FileBrowser>,
+    viewer: Option<MarkdownViewer>,
+    theme: Theme,
+    max_width: usize,
+    show_urls: bool,
+    indent_by_heading: bool,
+    text_modifiers: bool,
+    wrap_code: bool,
+    show_frontmatter: bool,
+    heading_prefix: HeadingPrefix,
+    conceal: bool,
+    inline_code_style: InlineCodeStyle,
+    code_block_border: CodeBlockBorder,
+    scroll_step: usize,
+    collapse_blank_lines: bool,
+    show_image_urls: bool,
+    highlight_current_line: bool,
+    strict_emphasis: bool,
+    max_file_size: Option<u64>,
+    /// An oversized file the user was warned about, opened if selected again
+    large_file_warned: Option<PathBuf>,
+    image_protocol: Option<ImageProtocol>,
+    config_loader: Option<ConfigLoader>,
+    /// Re-render files in the viewer when they change on disk
+    watch: bool,
+    watcher: Option<FileWatcher>,
+    document_cache: DocumentCache,
     event_handler: EventHandler,
     running: bool,
 }
 
 impl App {
     pub fn new(
-        directory: &str,
+        directories: Vec<PathBuf>,
         config: &crate::config::MarkConfig,
         show_all: bool,
     ) -> Result<Self> {
         let file_browser = FileBrowser::new_with_background_search(
-            directory,
+            directories,
             config.settings.ignored_dirs.clone(),
+            config.settings.ignored_files.clone(),
             config.settings.hidden_files,
             show_all,
+            config.settings.follow_symlinks,
+            config.settings.max_file_size,
         )?;
-        let event_handler = EventHandler::new(50); // 50ms tick rate for responsive loading indicator
+        let mut app = Self::empty(config);
+        let file_browser = match load_bookmarks() {
+            Some((bookmarks, path)) => file_browser.with_bookmarks(bookmarks, path),
+            None => file_browser,
+        };
+        let file_browser = file_browser
+            .with_theme(&app.theme)
+            .with_compact_list(config.settings.compact_list)
+            .with_wrap_navigation(config.settings.wrap_navigation)
+            .with_multi_column_list(config.settings.multi_column_list)
+            .with_date_format(&config.settings.date_format)
+            .with_relative_dates(config.settings.relative_dates)
+            .with_match_filename_only(config.settings.match_filename_only)
+            .with_strict_emphasis(config.settings.strict_emphasis)
+            .with_path_display(PathDisplay::from_setting(&config.settings.path_display));
+        app.file_browser = Some(file_browser);
+        Ok(app)
+    }
+
+    /// Open a single file in the viewer, without a browser to return to
+    pub fn with_file(path: &Path, config: &crate::config::MarkConfig) -> Result<Self> {
+        let mut app = Self::empty(config);
+        app.viewer = Some(app.load_viewer(path)?);
+        Ok(app)
+    }
+
+    /// Show an already built document, without a browser to return to
+    pub fn with_document(name: &Path, ast: AstNode, config: &crate::config::MarkConfig) -> Self {
+        let mut app = Self::empty(config);
+        app.viewer = Some(app.build_viewer(name, ast));
+        app
+    }
 
-        Ok(Self {
-            file_browser,
-            event_handler,
+    fn empty(config: &crate::config::MarkConfig) -> Self {
+        Self {
+            file_browser: None,
+            viewer: None,
+            theme: Theme::from_config(config),
+            max_width: config.settings.width,
+            show_urls: config.settings.show_urls,
+            indent_by_heading: config.settings.indent_by_heading,
+            text_modifiers: config.settings.use_text_modifiers,
+            wrap_code: config.settings.wrap_code,
+            show_frontmatter: config.settings.show_frontmatter,
+            heading_prefix: HeadingPrefix::from_setting(&config.settings.heading_prefix),
+            conceal: config.settings.conceal,
+            inline_code_style: InlineCodeStyle::from_setting(&config.settings.inline_code_style),
+            code_block_border: CodeBlockBorder::from_setting(&config.settings.code_block_border),
+            scroll_step: config.settings.scroll_step,
+            collapse_blank_lines: config.settings.collapse_blank_lines,
+            show_image_urls: config.settings.show_image_urls,
+            highlight_current_line: config.settings.highlight_current_line,
+            strict_emphasis: config.settings.strict_emphasis,
+            max_file_size: config.settings.max_file_size,
+            large_file_warned: None,
+            image_protocol: images::detect_protocol(),
+            config_loader: None,
+            watch: false,
+            watcher: None,
+            document_cache: DocumentCache::new(config.settings.cache_size)
+                .with_strict_emphasis(config.settings.strict_emphasis),
+            event_handler: EventHandler::new(50), // 50ms tick rate for responsive loading indicator
             running: true,
-        })
+        }
+    }
+
+    /// Override the maximum width of rendered documents
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.max_width = width;
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.set_max_width(width);
+        }
+        self
+    }
+
+    /// Show link URLs after their text, overriding the config
+    pub fn with_show_urls(mut self, show_urls: bool) -> Self {
+        self.show_urls = show_urls;
+        if let Some(viewer) = self.viewer.take() {
+            self.viewer = Some(viewer.with_show_urls(show_urls));
+        }
+        self
+    }
+
+    /// Allow reloading the config from `loader` while running
+    pub fn with_config_loader(mut self, loader: ConfigLoader) -> Self {
+        self.config_loader = Some(loader);
+        self
+    }
+
+    /// Reload the file in the viewer whenever it changes on disk
+    pub fn with_watch(mut self, watch: bool) -> Result<Self> {
+        self.watch = watch;
+        if let Some(path) = self
+            .viewer
+            .as_ref()
+            .map(|viewer| viewer.path().to_path_buf())
+        {
+            self.start_watching(&path)?;
+        }
+        Ok(self)
     }
 
-    pub fn run(&mut self, terminal: &mut crate::ui::Tui) -> Result<Option<MarkdownFile>> {
+    pub fn run(&mut self, terminal: &mut crate::ui::Tui) -> Result<()> {
         while self.running {
             terminal.draw(|frame| self.render(frame))?;
+            self.draw_images(terminal)?;
 
             if let Some(event) = self.event_handler.poll()? {
                 match event {
-                    Event::Key(key_event) => {
-                        if let Some(selected_file) = self.handle_key_event(key_event)? {
-                            return Ok(Some(selected_file.clone()));
-                        }
-                    }
+                    Event::Key(key_event) => self.handle_key_event(key_event)?,
                     Event::Resize(_, _) => {
                         // Terminal will handle resize automatically
                     }
@@ -50,24 +177,237 @@ impl App {
                     }
                 }
             }
+            self.reload_watched_file();
+        }
+
+        Ok(())
+    }
 
-            if self.file_browser.should_quit() {
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.handle_key_event(key_event)?;
+
+            if viewer.take_reload_request() {
+                self.reload_config();
+            } else if viewer.should_quit() {
                 self.running = false;
+            } else if viewer.should_close() {
+                viewer.clear_images(&mut std::io::stdout())?;
+                self.viewer = None;
+                self.watcher = None;
+                // Without a browser there is nothing to go back to
+                if self.file_browser.is_none() {
+                    self.running = false;
+                }
+            }
+            return Ok(());
+        }
+
+        let Some(file_browser) = self.file_browser.as_mut() else {
+            self.running = false;
+            return Ok(());
+        };
+
+        if let Some(selected_file) = file_browser.handle_key_event(key_event)? {
+            let path = selected_file.path.clone();
+            if let Some(size) = oversized(&path, self.max_file_size) {
+                if self.large_file_warned.as_ref() != Some(&path) {
+                    let message = format!(
+                        "{} is {} bytes, over max_file_size; open it again to load it anyway",
+                        selected_file.name, size
+                    );
+                    file_browser.set_message(message);
+                    self.large_file_warned = Some(path);
+                    return Ok(());
+                }
+            }
+            self.large_file_warned = None;
+            self.open_file(&path)?;
+        } else if file_browser.take_reload_request() {
+            self.reload_config();
+        } else if file_browser.should_quit() {
+            self.running = false;
+        }
+
+        Ok(())
+    }
+
+    /// Reload the config and re-apply its colors, keeping the old config if it's invalid
+    fn reload_config(&mut self) {
+        let message = match self.config_loader.as_mut() {
+            None => "Config reload is not available".to_string(),
+            Some(loader) => match loader.reload() {
+                Ok(()) => {
+                    self.theme = Theme::from_config(loader.config());
+                    if let Some(viewer) = self.viewer.as_mut() {
+                        viewer.set_theme(self.theme.clone());
+                    }
+                    self.max_file_size = loader.config().settings.max_file_size;
+                    if let Some(file_browser) = self.file_browser.as_mut() {
+                        let settings = &loader.config().settings;
+                        file_browser.set_theme(&self.theme);
+                        file_browser.set_compact_list(settings.compact_list);
+                        file_browser.set_wrap_navigation(settings.wrap_navigation);
+                        file_browser.set_multi_column_list(settings.multi_column_list);
+                        file_browser.set_date_format(&settings.date_format);
+                        file_browser.set_relative_dates(settings.relative_dates);
+                        file_browser.set_match_filename_only(settings.match_filename_only);
+                        file_browser
+                            .set_path_display(PathDisplay::from_setting(&settings.path_display));
+                    }
+                    format!("Reloaded {}", loader.config_path().display())
+                }
+                Err(e) => format!("Config not reloaded: {}", e),
+            },
+        };
+
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.set_message(message);
+        } else if let Some(file_browser) = self.file_browser.as_mut() {
+            file_browser.set_message(message);
+        }
+    }
+
+    /// Build a viewer showing `ast` under `name`, with every viewer setting from the config
+    fn build_viewer(&self, name: &Path, ast: AstNode) -> MarkdownViewer {
+        MarkdownViewer::with_ast(name, ast, self.theme.clone(), self.max_width)
+            .with_image_protocol(self.image_protocol)
+            .with_show_urls(self.show_urls)
+            .with_indent_by_heading(self.indent_by_heading)
+            .with_text_modifiers(self.text_modifiers)
+            .with_wrap_code(self.wrap_code)
+            .with_show_frontmatter(self.show_frontmatter)
+            .with_heading_prefix(self.heading_prefix)
+            .with_conceal(self.conceal)
+            .with_inline_code_style(self.inline_code_style)
+            .with_code_block_border(self.code_block_border)
+            .with_collapse_blank_lines(self.collapse_blank_lines)
+            .with_show_image_urls(self.show_image_urls)
+            .with_highlight_current_line(self.highlight_current_line)
+            .with_scroll_step(self.scroll_step)
+            .with_strict_emphasis(self.strict_emphasis)
+    }
+
+    /// Build a viewer for `path`, warning when the file isn't valid UTF-8
+    fn load_viewer(&mut self, path: &Path) -> Result<MarkdownViewer> {
+        let document = self.document_cache.get_or_parse(path)?;
+        let mut viewer = self.build_viewer(path, document.ast).with_editable(true);
+        if document.lossy {
+            viewer.set_message("File is not valid UTF-8; invalid bytes are shown as �");
+        }
+        Ok(viewer)
+    }
+
+    /// Open `path` in the viewer, or say in the browser why it couldn't be read
+    fn open_file(&mut self, path: &Path) -> Result<()> {
+        let viewer = match self.load_viewer(path) {
+            Ok(viewer) => viewer,
+            Err(e) => {
+                if let Some(file_browser) = self.file_browser.as_mut() {
+                    file_browser.set_message(format!("Could not open {}: {}", path.display(), e));
+                }
+                return Ok(());
             }
+        };
+        self.viewer = Some(viewer);
+        self.start_watching(path)?;
+        // History is a convenience, so a failed write shouldn't interrupt viewing
+        let _ = crate::history::record_opened(path);
+        Ok(())
+    }
+
+    /// Watch `path` if watching is enabled and it's a file on disk, not stdin
+    fn start_watching(&mut self, path: &Path) -> Result<()> {
+        self.watcher = None;
+        if self.watch && path.is_file() {
+            self.watcher = Some(FileWatcher::new(path)?);
         }
+        Ok(())
+    }
 
-        Ok(None)
+    /// Re-parse the viewed file once its changes have settled
+    fn reload_watched_file(&mut self) {
+        let (Some(watcher), Some(viewer)) = (self.watcher.as_mut(), self.viewer.as_mut()) else {
+            return;
+        };
+        if !watcher.poll() {
+            return;
+        }
+
+        match self.document_cache.get_or_parse(viewer.path()) {
+            Ok(document) => viewer.set_ast(document.ast),
+            Err(e) => viewer.set_message(format!("Could not reload the file: {}", e)),
+        }
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<Option<&MarkdownFile>> {
-        self.file_browser.handle_key_event(key_event)
+    /// Draw inline images on top of the frame that was just rendered
+    fn draw_images(&mut self, terminal: &mut crate::ui::Tui) -> Result<()> {
+        let Some(viewer) = self.viewer.as_mut() else {
+            return Ok(());
+        };
+
+        if viewer.poll_images() || viewer.images_changed() {
+            // The terminal buffer doesn't know about images, so repaint everything
+            terminal.clear()?;
+            terminal.draw(|frame| viewer.render(frame))?;
+            viewer.draw_images(terminal.backend_mut())?;
+        }
+        Ok(())
     }
 
     fn render(&mut self, frame: &mut Frame) {
-        self.file_browser.render(frame);
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.render(frame);
+        } else if let Some(file_browser) = self.file_browser.as_mut() {
+            file_browser.render(frame);
+        }
     }
 
     pub fn is_running(&self) -> bool {
         self.running
     }
 }
+
+/// The size of `path` if it's larger than `max_file_size` bytes
+fn oversized(path: &Path, max_file_size: Option<u64>) -> Option<u64> {
+    let max = max_file_size?;
+    let size = std::fs::metadata(path).ok()?.len();
+    (size > max).then_some(size)
+}
+
+/// Load the saved bookmarks, warning and going without them if they can't be read
+///
+/// Without a path, bookmarks toggled in the browser aren't saved, so a
+/// corrupt file is left alone rather than overwritten.
+fn load_bookmarks() -> Option<(Bookmarks, PathBuf)> {
+    let loaded = Bookmarks::default_path()
+        .and_then(|path| Bookmarks::load(&path).map(|bookmarks| (bookmarks, path)));
+    match loaded {
+        Ok(loaded) => Some(loaded),
+        Err(e) => {
+            eprintln!("Warning: Failed to load bookmarks: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MarkConfig;
+    use crate::search::MarkdownFile;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    #[test]
+    fn test_unreadable_file_keeps_the_browser_open() {
+        let mut app = App::empty(&MarkConfig::default());
+        app.file_browser = Some(FileBrowser::new(vec![MarkdownFile::new(PathBuf::from(
+            "does/not/exist.md",
+        ))]));
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        assert!(app.is_running());
+        assert!(app.viewer.is_none());
+    }
+}
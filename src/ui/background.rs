@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+/// How long to wait for the terminal to answer a background color query
+pub const QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Parse an OSC 11 reply such as `\x1b]11;rgb:ffff/ffff/ffff\x07` into 8-bit RGB
+pub fn parse_osc11_response(response: &str) -> Option<(u8, u8, u8)> {
+    let start = response.find("rgb:")? + "rgb:".len();
+    let body = &response[start..];
+    let end = body
+        .find(|c: char| !(c.is_ascii_hexdigit() || c == '/'))
+        .unwrap_or(body.len());
+
+    let mut channels = body[..end].split('/').map(parse_channel);
+    let rgb = (channels.next()??, channels.next()??, channels.next()??);
+    if channels.next().is_some() {
+        return None;
+    }
+    Some(rgb)
+}
+
+/// Scale a 1-4 digit hex channel to 8 bits
+fn parse_channel(hex: &str) -> Option<u8> {
+    if hex.is_empty() || hex.len() > 4 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let max = (1u32 << (4 * hex.len())) - 1;
+    Some((value * 255 / max) as u8)
+}
+
+/// Relative luminance of a color, from 0.0 (black) to 1.0 (white)
+pub fn luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linear = |channel: u8| {
+        let c = channel as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// Ask the terminal for its background color, returning `None` if it doesn't answer
+#[cfg(unix)]
+pub fn query_background_color(timeout: Duration) -> Option<(u8, u8, u8)> {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled};
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+
+    let was_raw = is_raw_mode_enabled().unwrap_or(false);
+    if !was_raw {
+        enable_raw_mode().ok()?;
+    }
+
+    let mut response = Vec::new();
+    if tty.write_all(b"\x1b]11;?\x07").and(tty.flush()).is_ok() {
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0u8; 64];
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+
+            let mut fd = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `fd` is a valid pollfd for the open tty and outlives the call
+            let ready = unsafe { libc::poll(&mut fd, 1, remaining.as_millis() as libc::c_int) };
+            if ready <= 0 {
+                break;
+            }
+
+            match tty.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => response.extend_from_slice(&buffer[..n]),
+            }
+
+            // The reply ends with BEL or ST
+            if response.ends_with(b"\x07") || response.ends_with(b"\x1b\\") {
+                break;
+            }
+        }
+    }
+
+    if !was_raw {
+        let _ = disable_raw_mode();
+    }
+
+    parse_osc11_response(&String::from_utf8_lossy(&response))
+}
+
+/// Ask the terminal for its background color, returning `None` if it doesn't answer
+#[cfg(not(unix))]
+pub fn query_background_color(_timeout: Duration) -> Option<(u8, u8, u8)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_osc11_response() {
+        assert_eq!(
+            parse_osc11_response("\x1b]11;rgb:ffff/ffff/ffff\x07"),
+            Some((255, 255, 255))
+        );
+        assert_eq!(
+            parse_osc11_response("\x1b]11;rgb:1e1e/2020/2a2a\x1b\\"),
+            Some((30, 32, 42))
+        );
+        assert_eq!(
+            parse_osc11_response("\x1b]11;rgb:f/8/0\x07"),
+            Some((255, 136, 0))
+        );
+        assert_eq!(parse_osc11_response(""), None);
+        assert_eq!(parse_osc11_response("\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+
+    #[test]
+    fn test_luminance() {
+        assert_eq!(luminance((0, 0, 0)), 0.0);
+        assert!((luminance((255, 255, 255)) - 1.0).abs() < 1e-9);
+        assert!(luminance((30, 32, 42)) < 0.05);
+    }
+}
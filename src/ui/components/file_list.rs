@@ -1,23 +1,115 @@
+use crate::bookmarks::Bookmarks;
+use crate::search::markdown::{self, PathDisplay, DEFAULT_DATE_FORMAT};
 use crate::search::MarkdownFile;
+use crate::ui::style::{self, StyleExt};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use ratatui::{
-    layout::Rect,
+    layout::{Alignment, Constraint, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{List, ListItem, ListState},
+    widgets::{List, ListItem, ListState, Paragraph},
     Frame,
 };
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use unicode_width::UnicodeWidthStr;
+
+/// Minimum width of each column when the list is split into columns
+pub const COLUMN_WIDTH: usize = 60;
+
+/// Row and column of the `index`th entry on a page filled column by column
+pub fn grid_position(index: usize, rows: usize) -> (usize, usize) {
+    let rows = rows.max(1);
+    (index % rows, index / rows)
+}
+
+/// Shorten `path` to `width` columns by replacing directories in its middle with `…`
+///
+/// Whole directory names are dropped, keeping the top-level directory and as many
+/// of the innermost ones as fit, e.g. `docs/…/api/intro.md`. The file name is
+/// always kept, even if it alone is wider than `width`.
+pub fn middle_ellipsis(path: &str, width: usize) -> String {
+    if path.width() <= width {
+        return path.to_string();
+    }
+    let separator = if path.contains('/') { '/' } else { '\\' };
+    let parts: Vec<&str> = path.split(separator).collect();
+    let (name, directories) = parts.split_last().expect("split yields at least one part");
+    let Some((first, inner)) = directories.split_first() else {
+        return name.to_string();
+    };
+
+    let join = |parts: &[&str]| {
+        let mut joined = String::new();
+        for part in parts {
+            joined.push_str(part);
+            joined.push(separator);
+        }
+        joined
+    };
+    let head = format!("{}{}…{}", first, separator, separator);
+    let mut tail = name.to_string();
+    if head.width() + tail.width() > width {
+        let short = format!("…{}{}", separator, name);
+        return if short.width() <= width {
+            short
+        } else {
+            name.to_string()
+        };
+    }
+    for kept in (1..=inner.len()).rev() {
+        let candidate = format!("{}{}", join(&inner[inner.len() - kept..]), name);
+        if head.width() + candidate.width() <= width {
+            tail = candidate;
+            break;
+        }
+    }
+    format!("{}{}", head, tail)
+}
 
 pub struct FileList {
     files: Vec<MarkdownFile>,
     filtered_files: Vec<MarkdownFile>,
     state: ListState,
     items_per_page: usize,
+    rows_per_column: usize,
+    /// Number of columns files are laid out in, filled top to bottom
+    columns: usize,
+    /// Split the list into columns when the terminal is wide enough
+    multi_column: bool,
     current_page: usize,
     search_query: String,
     is_searching: bool,
     search_input_mode: bool,
+    bookmarks: Bookmarks,
+    bookmarks_only: bool,
+    tree_view: bool,
+    /// Show one line per file, without the date and spacing
+    compact: bool,
+    /// Wrap from the last file to the first and back when moving the selection
+    wrap_navigation: bool,
+    /// strftime format for the dates shown under each file
+    date_format: String,
+    /// Show file dates as "2 days ago" instead of using `date_format`
+    relative_dates: bool,
+    search_match: Color,
+    search_dim: Color,
+    /// Search the file name only, ignoring the directories above it
+    match_filename_only: bool,
+    /// Directories whose files are hidden in the tree view
+    collapsed: HashSet<PathBuf>,
+    /// How each file's path is shown and searched
+    path_display: PathDisplay,
+    /// Directories the files were found in, for relative paths
+    search_roots: Vec<PathBuf>,
+    /// Whether the search for files is still running
+    loading: bool,
+    /// Whether hidden and ignored files are already listed
+    show_all: bool,
+    /// Files marked with Space, for acting on several at once
+    marked: HashSet<PathBuf>,
 }
 
 impl FileList {
@@ -34,26 +126,318 @@ impl FileList {
             filtered_files,
             state,
             items_per_page: 10, // Will be updated based on screen size
+            rows_per_column: 10,
+            columns: 1,
+            multi_column: false,
             current_page: 0,
             search_query: String::new(),
             is_searching: false,
             search_input_mode: false,
+            bookmarks: Bookmarks::default(),
+            bookmarks_only: false,
+            tree_view: false,
+            compact: false,
+            wrap_navigation: false,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            relative_dates: false,
+            search_match: Color::Rgb(200, 200, 200),
+            search_dim: Color::Rgb(100, 100, 100),
+            match_filename_only: false,
+            collapsed: HashSet::new(),
+            path_display: PathDisplay::default(),
+            search_roots: Vec::new(),
+            loading: false,
+            show_all: false,
+            marked: HashSet::new(),
+        }
+    }
+
+    /// Replace the set of bookmarked files
+    pub fn set_bookmarks(&mut self, bookmarks: Bookmarks) {
+        self.bookmarks = bookmarks;
+        if self.bookmarks_only {
+            self.refilter();
+        }
+    }
+
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    pub fn is_bookmarked(&self, file: &MarkdownFile) -> bool {
+        self.bookmarks.contains(&file.path)
+    }
+
+    /// Toggle the bookmark on the selected file, returning whether it is now bookmarked
+    pub fn toggle_current_bookmark(&mut self) -> Option<bool> {
+        let path = self.get_current_file()?.path.clone();
+        let bookmarked = self.bookmarks.toggle(&path);
+
+        if self.bookmarks_only && !bookmarked {
+            let selected = self.state.selected().unwrap_or(0);
+            self.refilter();
+            self.select_index(selected);
+        }
+
+        Some(bookmarked)
+    }
+
+    pub fn is_marked(&self, file: &MarkdownFile) -> bool {
+        self.marked.contains(&file.path)
+    }
+
+    /// Mark or unmark the selected file, returning whether it is now marked
+    pub fn toggle_current_mark(&mut self) -> Option<bool> {
+        let path = self.get_current_file()?.path.clone();
+        if self.marked.remove(&path) {
+            return Some(false);
+        }
+        self.marked.insert(path);
+        Some(true)
+    }
+
+    /// Paths of the marked files, in the order they are listed
+    ///
+    /// Files hidden by a search or the bookmark filter are left out, though
+    /// they stay marked for when they are listed again.
+    pub fn marked_paths(&self) -> Vec<PathBuf> {
+        self.current_files()
+            .iter()
+            .filter(|file| self.is_marked(file))
+            .map(|file| file.path.clone())
+            .collect()
+    }
+
+    /// Unmark `paths`, keeping any other marks
+    pub fn unmark(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            self.marked.remove(path);
+        }
+    }
+
+    /// Switch between showing all files and only bookmarked ones
+    pub fn toggle_bookmarks_only(&mut self) {
+        self.bookmarks_only = !self.bookmarks_only;
+        self.refilter();
+        self.current_page = 0;
+        if self.search_input_mode || self.get_file_count() == 0 {
+            self.state.select(None);
+        } else {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn is_bookmarks_only(&self) -> bool {
+        self.bookmarks_only
+    }
+
+    /// Whether the list shows `filtered_files` instead of every file
+    fn is_filtered(&self) -> bool {
+        self.is_searching || self.bookmarks_only
+    }
+
+    /// Score a file against the bookmark filter and the search query
+    ///
+    /// Returns `None` for files that are filtered out and `Some(0)` for every
+    /// kept file when there is no query.
+    fn filter_score(&self, file: &MarkdownFile) -> Option<i64> {
+        if self.bookmarks_only && !self.is_bookmarked(file) {
+            return None;
+        }
+
+        if !self.is_searching || self.search_query.is_empty() {
+            return Some(0);
+        }
+
+        let path_str = if self.match_filename_only {
+            file.path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            self.display_path(file)
+        };
+        SkimMatcherV2::default()
+            .fuzzy_match(&path_str, remove_whitespace(&self.search_query).as_str())
+    }
+
+    /// Check a file against the bookmark filter and the search query
+    fn matches_filters(&self, file: &MarkdownFile) -> bool {
+        self.filter_score(file).is_some()
+    }
+
+    /// Rebuild `filtered_files`, best search matches first
+    fn refilter(&mut self) {
+        let mut scored: Vec<(i64, &MarkdownFile)> = self
+            .files
+            .iter()
+            .filter_map(|file| Some((self.filter_score(file)?, file)))
+            .collect();
+        // Stable sort, so files keep their original order without a query
+        if self.is_searching && !self.search_query.is_empty() {
+            scored.sort_by(|(a_score, a), (b_score, b)| {
+                b_score.cmp(a_score).then_with(|| a.path.cmp(&b.path))
+            });
+        }
+        self.filtered_files = scored.into_iter().map(|(_, file)| file.clone()).collect();
+    }
+
+    /// Select `index`, clamped to the current list, and move to its page
+    fn select_index(&mut self, index: usize) {
+        let count = self.entry_count();
+        if count == 0 {
+            self.current_page = 0;
+            self.state.select(None);
+            return;
+        }
+
+        let index = index.min(count - 1);
+        self.current_page = index / self.items_per_page;
+        self.state.select(Some(index));
+    }
+
+    /// Switch between three lines per file and a single line without the date
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+        if let Some(selected) = self.state.selected() {
+            self.select_index(selected);
+        }
+    }
+
+    pub fn set_wrap_navigation(&mut self, wrap: bool) {
+        self.wrap_navigation = wrap;
+    }
+
+    /// Lay files out in columns of at least `COLUMN_WIDTH` on wide terminals
+    pub fn set_multi_column(&mut self, multi_column: bool) {
+        self.multi_column = multi_column;
+        if !multi_column {
+            self.set_columns(1);
+        }
+    }
+
+    /// Fit as many columns as `width` allows, or one when multi-column is off
+    pub fn update_columns(&mut self, width: usize) {
+        let columns = if self.multi_column {
+            width / COLUMN_WIDTH
+        } else {
+            1
+        };
+        self.set_columns(columns);
+    }
+
+    fn set_columns(&mut self, columns: usize) {
+        self.columns = columns.max(1);
+        self.items_per_page = self.rows_per_column * self.columns;
+        if let Some(selected) = self.state.selected() {
+            self.select_index(selected);
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn set_date_format(&mut self, format: &str) {
+        self.date_format = format.to_string();
+    }
+
+    pub fn set_relative_dates(&mut self, relative: bool) {
+        self.relative_dates = relative;
+    }
+
+    /// Show paths relative to the search roots, in full, or as file names only
+    pub fn set_path_display(&mut self, display: PathDisplay) {
+        self.path_display = display;
+        if self.is_searching {
+            let query = self.search_query.clone();
+            self.update_search(&query);
+        }
+    }
+
+    /// Directories relative paths are shown from
+    pub fn set_search_roots(&mut self, roots: Vec<PathBuf>) {
+        self.search_roots = roots;
+    }
+
+    /// Mark the file search as running, so an empty list isn't reported as empty yet
+    pub fn set_loading(&mut self, loading: bool) {
+        self.loading = loading;
+    }
+
+    /// Whether hidden and ignored files are listed, to leave out the `--all` hint
+    pub fn set_show_all(&mut self, show_all: bool) {
+        self.show_all = show_all;
+    }
+
+    /// The message and hint shown in place of an empty list
+    ///
+    /// Tells apart a search that found no files from filters that hide them all.
+    /// Returns `None` while files are listed or still being searched for.
+    pub fn empty_message(&self) -> Option<(String, Option<&'static str>)> {
+        if self.files.is_empty() {
+            if self.loading {
+                return None;
+            }
+            let roots: Vec<String> = self
+                .search_roots
+                .iter()
+                .map(|root| root.display().to_string())
+                .collect();
+            let message = if roots.is_empty() {
+                "No markdown files found".to_string()
+            } else {
+                format!("No markdown files found in {}", roots.join(", "))
+            };
+            let hint = (!self.show_all)
+                .then_some("Press . or run with --all to include hidden and ignored files");
+            return Some((message, hint));
+        }
+
+        if self.get_file_count() > 0 {
+            return None;
+        }
+        if self.is_searching && !self.search_query.is_empty() {
+            Some((
+                format!("No files match \"{}\"", self.search_query),
+                Some("Press Esc to clear the search"),
+            ))
+        } else if self.bookmarks_only {
+            Some((
+                "No bookmarked files".to_string(),
+                Some("Press B to show all files"),
+            ))
+        } else {
+            None
         }
     }
 
+    /// The path shown for `file` in the list
+    pub fn display_path(&self, file: &MarkdownFile) -> String {
+        file.display_path(self.path_display, &self.search_roots)
+    }
+
+    /// Colors for matched and unmatched characters while typing a search
+    pub fn set_search_colors(&mut self, matched: Color, dim: Color) {
+        self.search_match = matched;
+        self.search_dim = dim;
+    }
+
     pub fn update_items_per_page(&mut self, height: usize) {
-        // Reserve space for header (4), help (1), pagination (1)
-        // Each file takes 3 lines (path + created_at + empty space)
+        // Reserve space for header (4), help (1), status line (1)
+        // Each file takes 3 lines (path + created_at + empty space), or 1 when compact
         let available_height = height.saturating_sub(6);
-        self.items_per_page = (available_height / 3).max(1);
+        let lines_per_item = if self.compact { 1 } else { 3 };
+        self.rows_per_column = (available_height / lines_per_item).max(1);
+        self.items_per_page = self.rows_per_column * self.columns;
+        if let Some(selected) = self.state.selected() {
+            self.current_page = selected / self.items_per_page;
+        }
     }
 
     pub fn total_pages(&self) -> usize {
-        let file_count = if self.is_searching {
-            self.filtered_files.len()
-        } else {
-            self.files.len()
-        };
+        let file_count = self.entry_count();
 
         if file_count == 0 {
             1
@@ -67,11 +451,7 @@ impl FileList {
     }
 
     pub fn next(&mut self) {
-        let file_count = if self.is_searching {
-            self.filtered_files.len()
-        } else {
-            self.files.len()
-        };
+        let file_count = self.entry_count();
 
         let current_selection = self.state.selected().unwrap_or(0);
         let start_index = self.current_page * self.items_per_page;
@@ -86,6 +466,8 @@ impl FileList {
             self.current_page += 1;
             let new_start = self.current_page * self.items_per_page;
             self.state.select(Some(new_start));
+        } else if self.wrap_navigation {
+            self.go_to_top();
         }
     }
 
@@ -100,9 +482,10 @@ impl FileList {
         } else if self.current_page > 0 {
             // Move to previous page and go to bottom
             self.current_page -= 1;
-            let _new_start = self.current_page * self.items_per_page;
-            let new_end = ((self.current_page + 1) * self.items_per_page).min(self.files.len());
+            let new_end = ((self.current_page + 1) * self.items_per_page).min(self.entry_count());
             self.state.select(Some(new_end - 1));
+        } else if self.wrap_navigation {
+            self.go_to_bottom();
         }
     }
 
@@ -112,23 +495,19 @@ impl FileList {
             return None;
         }
 
-        if let Some(selected) = self.state.selected() {
-            if self.is_searching {
-                self.filtered_files.get(selected)
-            } else {
-                self.files.get(selected)
+        let selected = self.state.selected()?;
+        if self.tree_view {
+            match self.tree_rows().get(selected) {
+                Some(TreeRow::File(file)) => Some(*file),
+                _ => None,
             }
         } else {
-            None
+            self.current_files().get(selected)
         }
     }
 
     pub fn go_to_top(&mut self) {
-        let file_count = if self.is_searching {
-            self.filtered_files.len()
-        } else {
-            self.files.len()
-        };
+        let file_count = self.entry_count();
 
         if file_count > 0 {
             self.current_page = 0;
@@ -136,12 +515,13 @@ impl FileList {
         }
     }
 
+    /// Select the file at `index`, or the last file if the list is shorter
+    pub fn go_to(&mut self, index: usize) {
+        self.select_index(index);
+    }
+
     pub fn go_to_bottom(&mut self) {
-        let file_count = if self.is_searching {
-            self.filtered_files.len()
-        } else {
-            self.files.len()
-        };
+        let file_count = self.entry_count();
 
         if file_count > 0 {
             let last_page = self.total_pages().saturating_sub(1);
@@ -152,11 +532,7 @@ impl FileList {
 
     pub fn next_page(&mut self) {
         if self.current_page + 1 < self.total_pages() {
-            let file_count = if self.is_searching {
-                self.filtered_files.len()
-            } else {
-                self.files.len()
-            };
+            let file_count = self.entry_count();
 
             let current_selection = self.state.selected().unwrap_or(0);
             let start_index = self.current_page * self.items_per_page;
@@ -187,23 +563,148 @@ impl FileList {
         }
     }
 
-    fn get_visible_files(&self) -> &[MarkdownFile] {
-        let files = if self.is_searching {
+    /// Move the selection to the same row in the next column, or the next page
+    pub fn next_column(&mut self) {
+        let Some(selected) = self.state.selected() else {
+            return;
+        };
+        let rows = self.rows_per_column;
+        let start = self.current_page * self.items_per_page;
+        let end = (start + self.items_per_page).min(self.entry_count());
+        let (row, column) = grid_position(selected - start, rows);
+
+        if column + 1 < (end - start + rows - 1) / rows {
+            // A shorter last column keeps its bottom entry selected
+            self.state.select(Some((selected + rows).min(end - 1)));
+        } else if self.current_page + 1 < self.total_pages() {
+            self.select_index(end + row);
+        }
+    }
+
+    /// Move the selection to the same row in the previous column, or the previous page
+    pub fn previous_column(&mut self) {
+        let Some(selected) = self.state.selected() else {
+            return;
+        };
+        let rows = self.rows_per_column;
+        let start = self.current_page * self.items_per_page;
+        let (row, column) = grid_position(selected - start, rows);
+
+        if column > 0 {
+            self.state.select(Some(selected - rows));
+        } else if self.current_page > 0 {
+            // Earlier pages are always full
+            self.select_index(start - rows + row);
+        }
+    }
+
+    /// The files currently listed, after any search or bookmark filter
+    fn current_files(&self) -> &[MarkdownFile] {
+        if self.is_filtered() {
             &self.filtered_files
         } else {
             &self.files
+        }
+    }
+
+    /// Number of selectable rows: files, plus directory headers in the tree view
+    fn entry_count(&self) -> usize {
+        if self.tree_view {
+            self.tree_rows().len()
+        } else {
+            self.get_file_count()
+        }
+    }
+
+    /// Rows of the tree view, skipping the files of collapsed directories
+    pub fn tree_rows(&self) -> Vec<TreeRow<'_>> {
+        let mut rows = Vec::new();
+        for (directory, files) in group_by_directory(self.current_files()) {
+            let collapsed = self.collapsed.contains(directory);
+            rows.push(TreeRow::Directory {
+                path: directory,
+                files: files.len(),
+                collapsed,
+            });
+            if !collapsed {
+                rows.extend(files.into_iter().map(TreeRow::File));
+            }
+        }
+        rows
+    }
+
+    /// Switch between the flat list and files grouped under their directories
+    pub fn toggle_tree_view(&mut self) {
+        let selected = self.get_current_file().map(|file| file.path.clone());
+        self.tree_view = !self.tree_view;
+
+        let index = selected.and_then(|path| {
+            if self.tree_view {
+                self.tree_rows()
+                    .iter()
+                    .position(|row| matches!(row, TreeRow::File(file) if file.path == path))
+            } else {
+                self.current_files()
+                    .iter()
+                    .position(|file| file.path == path)
+            }
+        });
+        if !self.search_input_mode {
+            self.select_index(index.unwrap_or(0));
+        }
+    }
+
+    /// Search file names only instead of whole paths, re-running an active search
+    pub fn set_match_filename_only(&mut self, filename_only: bool) {
+        self.match_filename_only = filename_only;
+        if self.is_searching {
+            let query = self.search_query.clone();
+            self.update_search(&query);
+        }
+    }
+
+    pub fn is_match_filename_only(&self) -> bool {
+        self.match_filename_only
+    }
+
+    pub fn is_tree_view(&self) -> bool {
+        self.tree_view
+    }
+
+    /// Collapse or expand the directory of the selected row in the tree view
+    pub fn toggle_collapse(&mut self) {
+        if !self.tree_view {
+            return;
+        }
+        let Some(selected) = self.state.selected() else {
+            return;
         };
 
-        let start = self.current_page * self.items_per_page;
-        let end = ((self.current_page + 1) * self.items_per_page).min(files.len());
-        &files[start..end]
+        let rows = self.tree_rows();
+        let directory = match rows.get(selected) {
+            Some(TreeRow::Directory { path, .. }) => path.to_path_buf(),
+            Some(TreeRow::File(file)) => file_directory(file).to_path_buf(),
+            None => return,
+        };
+
+        if !self.collapsed.remove(&directory) {
+            self.collapsed.insert(directory.clone());
+        }
+
+        // Keep the selection on the directory that was toggled
+        let index = self
+            .tree_rows()
+            .iter()
+            .position(|row| matches!(row, TreeRow::Directory { path, .. } if *path == directory))
+            .unwrap_or(0);
+        self.select_index(index);
     }
 
     pub fn start_search(&mut self) {
         self.is_searching = true;
         self.search_input_mode = true;
         self.search_query.clear();
-        self.filtered_files = self.files.clone();
+        self.refilter();
         self.current_page = 0;
         // Don't select anything during search input
         self.state.select(None);
@@ -213,30 +714,16 @@ impl FileList {
         self.is_searching = false;
         self.search_input_mode = false;
         self.search_query.clear();
+        self.refilter();
         self.current_page = 0;
-        if !self.files.is_empty() {
+        if self.get_file_count() > 0 {
             self.state.select(Some(0));
         }
     }
 
     pub fn update_search(&mut self, query: &str) {
         self.search_query = query.to_string();
-
-        if query.is_empty() {
-            self.filtered_files = self.files.clone();
-        } else {
-            let matcher = SkimMatcherV2::default();
-            self.filtered_files = self
-                .files
-                .iter()
-                .filter_map(|file| {
-                    let path_str = file.path.to_string_lossy();
-                    matcher
-                        .fuzzy_match(&path_str, remove_whitespace(query).as_str())
-                        .map(|_| file.clone())
-                })
-                .collect();
-        }
+        self.refilter();
 
         self.current_page = 0;
         // Only select when search is not in input mode (i.e., when query is not empty)
@@ -261,7 +748,7 @@ impl FileList {
     }
 
     pub fn get_file_count(&self) -> usize {
-        if self.is_searching {
+        if self.is_filtered() {
             self.filtered_files.len()
         } else {
             self.files.len()
@@ -275,30 +762,41 @@ impl FileList {
     pub fn add_file(&mut self, file: MarkdownFile) {
         self.files.push(file.clone());
 
-        // If we're currently searching, also check if this file matches the search
-        if self.is_searching && !self.search_query.is_empty() {
-            let matcher = SkimMatcherV2::default();
-            if matcher
-                .fuzzy_match(&file.name, remove_whitespace(&self.search_query).as_str())
-                .is_some()
-            {
+        // If we're currently filtering, also check if this file matches the filters
+        if self.is_filtered() && self.matches_filters(&file) {
+            if self.is_searching && !self.search_query.is_empty() {
+                // Keep search results ranked as files stream in
+                self.refilter();
+            } else {
                 self.filtered_files.push(file);
             }
-        } else if self.is_searching {
-            // If searching but no query yet, add to filtered files too
-            self.filtered_files.push(file);
         }
 
         // If this is the first file and nothing is selected, select it
-        if self.files.len() == 1 && self.state.selected().is_none() {
+        if !self.search_input_mode && self.get_file_count() == 1 && self.state.selected().is_none()
+        {
             self.state.select(Some(0));
         }
     }
 
+    /// Remove every file, keeping the search query and bookmark filter
+    pub fn clear(&mut self) {
+        self.files.clear();
+        self.filtered_files.clear();
+        self.current_page = 0;
+        self.state.select(None);
+    }
+
+    /// The listed file, if exactly one is left after filtering
+    pub fn only_file(&self) -> Option<&MarkdownFile> {
+        match self.current_files() {
+            [file] => Some(file),
+            _ => None,
+        }
+    }
+
     pub fn select_first(&mut self) {
-        if self.is_searching && !self.filtered_files.is_empty() {
-            self.state.select(Some(0));
-        } else if !self.files.is_empty() {
+        if self.entry_count() > 0 {
             self.state.select(Some(0));
         }
     }
@@ -308,17 +806,22 @@ impl FileList {
     }
 
     pub fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let visible_files = self.get_visible_files();
+        if let Some((message, hint)) = self.empty_message() {
+            self.render_empty(frame, area, message, hint);
+            return;
+        }
 
-        // Create a local state for the current page
-        let current_selection = self.state.selected().unwrap_or(0);
-        let start = self.current_page * self.items_per_page;
-        let files = if self.is_searching {
-            &self.filtered_files
+        let rows = if self.tree_view {
+            self.tree_rows()
         } else {
-            &self.files
+            self.current_files().iter().map(TreeRow::File).collect()
         };
-        let end = ((self.current_page + 1) * self.items_per_page).min(files.len());
+
+        // Create a local state for the current page
+        let current_selection = self.state.selected().unwrap_or(0);
+        let start = (self.current_page * self.items_per_page).min(rows.len());
+        let end = ((self.current_page + 1) * self.items_per_page).min(rows.len());
+        let visible_rows = &rows[start..end];
 
         let relative_selection = if self.search_input_mode {
             // During search input, don't show any selection
@@ -335,10 +838,12 @@ impl FileList {
             local_state.select(relative_selection);
         }
 
-        let items: Vec<ListItem> = visible_files
+        // Room for a path beside the selector bar, in each column
+        let path_width = (area.width as usize / self.columns.max(1)).saturating_sub(2);
+        let items: Vec<ListItem> = visible_rows
             .iter()
             .enumerate()
-            .map(|(i, file)| {
+            .map(|(i, row)| {
                 // During search input, nothing should be selected
                 let is_selected = if self.search_input_mode {
                     false
@@ -346,82 +851,253 @@ impl FileList {
                     relative_selection == Some(i)
                 };
 
+                let file = match row {
+                    TreeRow::File(file) => *file,
+                    TreeRow::Directory {
+                        path,
+                        files,
+                        collapsed,
+                    } => return self.directory_item(path, *files, *collapsed, is_selected),
+                };
+
                 // Path styling - greyed out only during search input, normal after Enter is pressed
                 let path_style = if self.search_input_mode {
-                    Style::default().fg(Color::Rgb(100, 100, 100)) // Greyed out during search input
+                    Style::default().tint(Color::Rgb(100, 100, 100)) // Greyed out during search input
                 } else if is_selected {
                     Style::default()
-                        .fg(Color::Rgb(100, 200, 255))
+                        .tint(Color::Rgb(100, 200, 255))
                         .add_modifier(Modifier::BOLD)
                 } else {
-                    Style::default().fg(Color::Rgb(200, 200, 200)) // Normal color after search applied
+                    Style::default().tint(Color::Rgb(200, 200, 200)) // Normal color after search applied
                 };
 
                 // Creation date styling (dimmer)
                 let date_style = if self.search_input_mode {
-                    Style::default().fg(Color::Rgb(60, 60, 60)) // Much darker grey during search input
+                    Style::default().tint(Color::Rgb(60, 60, 60)) // Much darker grey during search input
                 } else if is_selected {
-                    Style::default().fg(Color::Rgb(70, 140, 180)) // Darker than path when selected
+                    Style::default().tint(Color::Rgb(70, 140, 180)) // Darker than path when selected
                 } else {
-                    Style::default().fg(Color::Rgb(120, 120, 120)) // Normal grey after search applied
+                    Style::default().tint(Color::Rgb(120, 120, 120)) // Normal grey after search applied
                 };
 
-                let path_display = file.name.as_str();
+                // The tree view shows the directory once, in its header
+                let path_display = if self.tree_view {
+                    Path::new(&file.name)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(&file.name)
+                        .to_string()
+                } else {
+                    self.display_path(file)
+                };
+                let indent = if self.tree_view { "  " } else { "" };
+                let marker_width =
+                    2 * (usize::from(self.is_marked(file)) + usize::from(self.is_bookmarked(file)));
+                let path_display = middle_ellipsis(
+                    &path_display,
+                    path_width.saturating_sub(indent.len() + marker_width),
+                );
                 let created_text = file
-                    .created_at
-                    .as_ref()
-                    .map(|d| d.to_string())
+                    .created
+                    .map(|time| {
+                        if self.relative_dates {
+                            markdown::format_relative(time, SystemTime::now())
+                        } else {
+                            markdown::format_date(time, &self.date_format)
+                        }
+                    })
                     .unwrap_or_else(|| "Unknown".to_string());
 
+                let mark_marker = if self.is_marked(file) {
+                    Span::styled("✓ ", Style::default().tint(Color::Rgb(100, 255, 100)))
+                } else {
+                    Span::raw("")
+                };
+                let bookmark_marker = if self.is_bookmarked(file) {
+                    Span::styled("★ ", Style::default().tint(Color::Rgb(255, 200, 100)))
+                } else {
+                    Span::raw("")
+                };
+
                 let selector_line1 = if is_selected { "│ " } else { "  " };
                 let selector_line2 = if is_selected { "│ " } else { "  " };
 
+                // Only the part that was searched gets highlighted
+                let (directory, searched) = if self.match_filename_only {
+                    path_display.split_at(path_display.rfind(['/', '\\']).map_or(0, |i| i + 1))
+                } else {
+                    ("", path_display.as_str())
+                };
+
                 // Create highlighted path spans during search input mode, or underlined spans after search applied
                 let path_spans = if self.search_input_mode && !self.search_query.is_empty() {
-                    self.create_highlighted_spans(&path_display, &self.search_query)
+                    let mut spans = vec![Span::styled(
+                        directory.to_string(),
+                        Style::default().tint(self.search_dim),
+                    )];
+                    spans.extend(Self::create_highlighted_spans(
+                        searched,
+                        &self.search_query,
+                        self.search_match,
+                        self.search_dim,
+                    ));
+                    spans
                 } else if self.is_searching && !self.search_query.is_empty() {
                     // After Enter is pressed, show underlined matches
-                    self.create_underlined_spans(&path_display, &self.search_query, path_style)
+                    let mut spans = vec![Span::styled(directory.to_string(), path_style)];
+                    spans.extend(self.create_underlined_spans(
+                        searched,
+                        &self.search_query,
+                        path_style,
+                    ));
+                    spans
                 } else {
                     vec![Span::styled(path_display, path_style)]
                 };
 
-                let content = vec![
+                let mut content = vec![
                     Line::from({
-                        let mut spans = vec![Span::styled(
-                            selector_line1,
-                            Style::default().fg(Color::Rgb(100, 200, 255)),
-                        )];
+                        let mut spans = vec![
+                            Span::styled(
+                                selector_line1,
+                                Style::default().tint(Color::Rgb(100, 200, 255)),
+                            ),
+                            Span::raw(indent),
+                            mark_marker,
+                            bookmark_marker,
+                        ];
                         spans.extend(path_spans);
                         spans
                     }),
                     Line::from(vec![
                         Span::styled(
                             selector_line2,
-                            Style::default().fg(Color::Rgb(100, 200, 255)),
+                            Style::default().tint(Color::Rgb(100, 200, 255)),
                         ),
+                        Span::raw(indent),
                         Span::styled(created_text, date_style),
                     ]),
                     Line::from(vec![]), // Empty line for spacing between files
                 ];
+                if self.compact {
+                    content.truncate(1);
+                }
 
                 ListItem::new(content)
             })
             .collect();
 
-        let list = List::new(items);
+        // Fill the columns top to bottom, each with its own part of the selection
+        let rows = self.rows_per_column;
+        let areas = Layout::horizontal(vec![
+            Constraint::Ratio(1, self.columns as u32);
+            self.columns
+        ])
+        .split(area);
+        let mut items = items.into_iter();
+        for (column, column_area) in areas.iter().enumerate() {
+            let column_items: Vec<ListItem> = items.by_ref().take(rows).collect();
+            let mut column_state = ListState::default();
+            column_state.select(
+                local_state
+                    .selected()
+                    .map(|i| grid_position(i, rows))
+                    .filter(|&(_, selected_column)| selected_column == column)
+                    .map(|(row, _)| row),
+            );
+            frame.render_stateful_widget(List::new(column_items), *column_area, &mut column_state);
+        }
+    }
+
+    /// Draw `message` and `hint` centered in `area`
+    fn render_empty(&self, frame: &mut Frame, area: Rect, message: String, hint: Option<&str>) {
+        let mut lines = vec![Line::from(Span::styled(
+            message,
+            Style::default().tint(Color::Rgb(200, 200, 200)),
+        ))];
+        if let Some(hint) = hint {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                hint.to_string(),
+                Style::default().tint(Color::Rgb(120, 120, 120)),
+            )));
+        }
+
+        let height = (lines.len() as u16).min(area.height);
+        let centered = Rect {
+            y: area.y + (area.height - height) / 2,
+            height,
+            ..area
+        };
+        frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), centered);
+    }
+
+    /// A tree view header for `directory`, with its file count below
+    fn directory_item(
+        &self,
+        directory: &Path,
+        files: usize,
+        collapsed: bool,
+        is_selected: bool,
+    ) -> ListItem<'static> {
+        let selector = if is_selected { "│ " } else { "  " };
+        let selector_style = Style::default().tint(Color::Rgb(100, 200, 255));
+        let marker = if collapsed { "▸ " } else { "▾ " };
+        let name = if directory.as_os_str().is_empty() {
+            "./".to_string()
+        } else {
+            format!("{}/", directory.display())
+        };
+        let name_style = if is_selected {
+            Style::default()
+                .tint(Color::Rgb(100, 200, 255))
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+                .tint(Color::Rgb(255, 200, 100))
+                .add_modifier(Modifier::BOLD)
+        };
+        let count = if files == 1 {
+            "1 file".to_string()
+        } else {
+            format!("{} files", files)
+        };
+
+        let count_style = Style::default().tint(Color::Rgb(120, 120, 120));
+        if self.compact {
+            return ListItem::new(Line::from(vec![
+                Span::styled(selector, selector_style),
+                Span::styled(marker, name_style),
+                Span::styled(name, name_style),
+                Span::styled(format!(" ({})", count), count_style),
+            ]));
+        }
 
-        frame.render_stateful_widget(list, area, &mut local_state);
+        ListItem::new(vec![
+            Line::from(vec![
+                Span::styled(selector, selector_style),
+                Span::styled(marker, name_style),
+                Span::styled(name, name_style),
+            ]),
+            Line::from(vec![
+                Span::styled(selector, selector_style),
+                Span::styled(count, count_style),
+            ]),
+            Line::from(vec![]),
+        ])
     }
 
-    fn create_highlighted_spans(&self, text: &str, query: &str) -> Vec<Span> {
+    fn create_highlighted_spans(
+        text: &str,
+        query: &str,
+        matched: Color,
+        dim: Color,
+    ) -> Vec<Span<'static>> {
         let mut spans = Vec::new();
 
-        if query.is_empty() {
-            return vec![Span::styled(
-                text.to_string(),
-                Style::default().fg(Color::Rgb(100, 100, 100)),
-            )];
+        // Without colors the match highlighting has nothing to show
+        if query.is_empty() || style::no_color() {
+            return vec![Span::styled(text.to_string(), Style::default().tint(dim))];
         }
 
         let matcher = SkimMatcherV2::default();
@@ -433,7 +1109,7 @@ impl FileList {
                 if index > last_end {
                     spans.push(Span::styled(
                         text[last_end..index].to_string(),
-                        Style::default().fg(Color::Rgb(100, 100, 100)),
+                        Style::default().tint(dim),
                     ));
                 }
 
@@ -445,7 +1121,7 @@ impl FileList {
                     .unwrap_or(text.len());
                 spans.push(Span::styled(
                     text[index..char_end].to_string(),
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(matched),
                 ));
 
                 last_end = char_end;
@@ -455,21 +1131,18 @@ impl FileList {
             if last_end < text.len() {
                 spans.push(Span::styled(
                     text[last_end..].to_string(),
-                    Style::default().fg(Color::Rgb(100, 100, 100)),
+                    Style::default().tint(dim),
                 ));
             }
         } else {
             // No fuzzy match found, return the whole text greyed out
-            spans.push(Span::styled(
-                text.to_string(),
-                Style::default().fg(Color::Rgb(100, 100, 100)),
-            ));
+            spans.push(Span::styled(text.to_string(), Style::default().tint(dim)));
         }
 
         spans
     }
 
-    fn create_underlined_spans(&self, text: &str, query: &str, base_style: Style) -> Vec<Span> {
+    fn create_underlined_spans(&self, text: &str, query: &str, base_style: Style) -> Vec<Span<'_>> {
         let mut spans = Vec::new();
 
         if query.is_empty() {
@@ -513,6 +1186,497 @@ impl FileList {
     }
 }
 
+/// A row of the tree view: a directory header or a file beneath it
+#[derive(Debug, Clone)]
+pub enum TreeRow<'a> {
+    Directory {
+        path: &'a Path,
+        files: usize,
+        collapsed: bool,
+    },
+    File(&'a MarkdownFile),
+}
+
+/// The directory a file is listed under, from its displayed name
+fn file_directory(file: &MarkdownFile) -> &Path {
+    Path::new(&file.name).parent().unwrap_or(Path::new(""))
+}
+
+/// Group files by directory, sorted by directory and keeping the order of files within each
+pub fn group_by_directory(files: &[MarkdownFile]) -> Vec<(&Path, Vec<&MarkdownFile>)> {
+    let mut groups: BTreeMap<&Path, Vec<&MarkdownFile>> = BTreeMap::new();
+    for file in files {
+        groups.entry(file_directory(file)).or_default().push(file);
+    }
+    groups.into_iter().collect()
+}
+
 fn remove_whitespace(s: &str) -> String {
     s.chars().filter(|c| !c.is_whitespace()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::path::PathBuf;
+
+    fn rendered_text(list: &mut FileList) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal
+            .draw(|frame| list.render(frame, frame.area()))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_list_shows_empty_state() {
+        let mut list = FileList::new(Vec::new());
+        list.set_search_roots(vec![PathBuf::from("notes")]);
+        list.set_loading(true);
+        assert_eq!(list.empty_message(), None);
+
+        list.set_loading(false);
+        let text = rendered_text(&mut list);
+        assert!(text.contains("No markdown files found in notes"));
+        assert!(text.contains("--all"));
+
+        list.set_show_all(true);
+        assert_eq!(list.empty_message().unwrap().1, None);
+    }
+
+    #[test]
+    fn test_search_without_matches_shows_empty_state() {
+        let mut list = FileList::new(vec![MarkdownFile::new(PathBuf::from("readme.md"))]);
+        assert_eq!(list.empty_message(), None);
+
+        list.start_search();
+        list.update_search("zzz");
+        let text = rendered_text(&mut list);
+        assert!(text.contains("No files match \"zzz\""));
+        assert!(!text.contains("No markdown files found"));
+    }
+
+    #[test]
+    fn test_no_color_spans_have_no_colors() {
+        let list = FileList::new(vec![MarkdownFile::new(PathBuf::from("docs/readme.md"))]);
+
+        style::set_no_color(true);
+        let highlighted =
+            FileList::create_highlighted_spans("docs/readme.md", "rdm", Color::White, Color::Gray);
+        let underlined = list.create_underlined_spans(
+            "docs/readme.md",
+            "rdm",
+            Style::default().tint(Color::Red),
+        );
+        style::set_no_color(false);
+
+        assert_eq!(highlighted.len(), 1);
+        assert_eq!(highlighted[0].content, "docs/readme.md");
+        for span in highlighted.iter().chain(underlined.iter()) {
+            assert_eq!(span.style.fg, None);
+            assert_eq!(span.style.bg, None);
+        }
+    }
+
+    #[test]
+    fn test_marking_files() {
+        let mut list = numbered_list(4);
+        assert!(list.marked_paths().is_empty());
+
+        list.go_to(2);
+        assert_eq!(list.toggle_current_mark(), Some(true));
+        list.go_to(0);
+        assert_eq!(list.toggle_current_mark(), Some(true));
+        list.go_to(3);
+        assert_eq!(list.toggle_current_mark(), Some(true));
+        assert_eq!(list.toggle_current_mark(), Some(false));
+
+        // Listed order, not the order they were marked in
+        assert_eq!(
+            list.marked_paths(),
+            vec![PathBuf::from("00.md"), PathBuf::from("02.md")]
+        );
+        assert!(rendered_text(&mut list).contains("✓"));
+
+        // Marks on files a search hides are kept but not listed
+        list.is_searching = true;
+        list.search_query = "02".to_string();
+        list.refilter();
+        assert_eq!(list.marked_paths(), vec![PathBuf::from("02.md")]);
+
+        list.unmark(&[PathBuf::from("02.md")]);
+        assert!(list.marked_paths().is_empty());
+        list.is_searching = false;
+        list.refilter();
+        assert_eq!(list.marked_paths(), vec![PathBuf::from("00.md")]);
+        assert_eq!(FileList::new(Vec::new()).toggle_current_mark(), None);
+    }
+
+    #[test]
+    fn test_bookmarks_only_filter() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["a.md", "b.md", "c.md"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for path in &paths {
+            std::fs::File::create(path).unwrap();
+        }
+
+        let mut list = FileList::new(paths.iter().cloned().map(MarkdownFile::new).collect());
+        list.next();
+        assert_eq!(list.toggle_current_bookmark(), Some(true));
+        assert!(list.bookmarks().contains(&paths[1]));
+
+        list.toggle_bookmarks_only();
+        assert!(list.is_bookmarks_only());
+        assert_eq!(list.get_file_count(), 1);
+        assert_eq!(list.get_current_file().unwrap().path, paths[1]);
+
+        // Files found later are filtered too
+        list.add_file(MarkdownFile::new(temp_dir.path().join("d.md")));
+        assert_eq!(list.get_file_count(), 1);
+
+        // Removing the last bookmark empties the filtered list
+        assert_eq!(list.toggle_current_bookmark(), Some(false));
+        assert_eq!(list.get_file_count(), 0);
+        assert!(list.get_current_file().is_none());
+
+        list.toggle_bookmarks_only();
+        assert_eq!(list.get_file_count(), 4);
+    }
+
+    fn filtered_paths(list: &FileList) -> Vec<String> {
+        list.filtered_files
+            .iter()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_match_filename_only_ignores_directories() {
+        let paths = ["readme/setup.md", "docs/readme.md", "guide.md"];
+        let mut list = FileList::new(
+            paths
+                .iter()
+                .map(|path| MarkdownFile::new(PathBuf::from(path)))
+                .collect(),
+        );
+
+        list.start_search();
+        list.update_search("readme");
+        let mut matched = filtered_paths(&list);
+        matched.sort();
+        assert_eq!(matched, vec!["docs/readme.md", "readme/setup.md"]);
+
+        list.set_match_filename_only(true);
+        assert_eq!(filtered_paths(&list), vec!["docs/readme.md"]);
+
+        list.set_match_filename_only(false);
+        assert_eq!(filtered_paths(&list).len(), 2);
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let paths = [
+            "notes/reading-list.md",
+            "archive/r/e/a/d/m/e.md",
+            "readme.md",
+            "src/main.md",
+        ];
+        let mut list = FileList::new(
+            paths
+                .iter()
+                .map(|path| MarkdownFile::new(PathBuf::from(path)))
+                .collect(),
+        );
+
+        list.start_search();
+        list.update_search("readme");
+        let ranked = filtered_paths(&list);
+        assert_eq!(ranked[0], "readme.md");
+        assert!(!ranked.contains(&"src/main.md".to_string()));
+        assert_eq!(
+            list.get_current_file().unwrap().path,
+            PathBuf::from("readme.md")
+        );
+    }
+
+    #[test]
+    fn test_empty_query_keeps_original_order() {
+        let paths = ["b.md", "c.md", "a.md"];
+        let mut list = FileList::new(
+            paths
+                .iter()
+                .map(|path| MarkdownFile::new(PathBuf::from(path)))
+                .collect(),
+        );
+
+        list.start_search();
+        list.update_search("md");
+        list.update_search("");
+        assert_eq!(filtered_paths(&list), paths);
+
+        list.end_search();
+        assert_eq!(list.get_current_file().unwrap().path, PathBuf::from("b.md"));
+    }
+
+    #[test]
+    fn test_compact_list_fits_more_items() {
+        let mut list = FileList::new(Vec::new());
+        list.update_items_per_page(36);
+        assert_eq!(list.items_per_page, 10);
+
+        list.set_compact(true);
+        list.update_items_per_page(36);
+        assert_eq!(list.items_per_page, 30);
+    }
+
+    #[test]
+    fn test_compact_list_keeps_selection_on_its_page() {
+        let files = (0..40)
+            .map(|i| MarkdownFile::new(PathBuf::from(format!("{i:02}.md"))))
+            .collect();
+        let mut list = FileList::new(files);
+        list.update_items_per_page(36);
+        list.go_to(25);
+        assert_eq!(list.current_page(), 3);
+
+        list.set_compact(true);
+        list.update_items_per_page(36);
+        assert_eq!(list.current_page(), 1);
+        assert_eq!(list.get_current_file().unwrap().name, "25.md");
+    }
+
+    fn numbered_list(count: usize) -> FileList {
+        let files = (0..count)
+            .map(|i| MarkdownFile::new(PathBuf::from(format!("{i:02}.md"))))
+            .collect();
+        let mut list = FileList::new(files);
+        // Two files per page
+        list.update_items_per_page(12);
+        list
+    }
+
+    #[test]
+    fn test_navigation_stops_at_the_ends_by_default() {
+        let mut list = numbered_list(5);
+        list.previous();
+        assert_eq!(list.get_current_file().unwrap().name, "00.md");
+
+        list.go_to_bottom();
+        list.next();
+        assert_eq!(list.get_current_file().unwrap().name, "04.md");
+        assert_eq!(list.current_page(), 3);
+    }
+
+    #[test]
+    fn test_wrap_navigation() {
+        let mut list = numbered_list(5);
+        list.set_wrap_navigation(true);
+
+        list.previous();
+        assert_eq!(list.get_current_file().unwrap().name, "04.md");
+        assert_eq!(list.current_page(), 3);
+
+        list.next();
+        assert_eq!(list.get_current_file().unwrap().name, "00.md");
+        assert_eq!(list.current_page(), 1);
+
+        // Moving within and across pages is unchanged
+        list.next();
+        list.next();
+        assert_eq!(list.get_current_file().unwrap().name, "02.md");
+    }
+
+    #[test]
+    fn test_middle_ellipsis_keeps_file_name() {
+        let path = "docs/guide/reference/api/intro.md";
+        assert_eq!(middle_ellipsis(path, 40), path);
+        assert_eq!(middle_ellipsis(path, 30), "docs/…/reference/api/intro.md");
+        assert_eq!(middle_ellipsis(path, 20), "docs/…/api/intro.md");
+        assert_eq!(middle_ellipsis(path, 16), "docs/…/intro.md");
+        assert_eq!(middle_ellipsis(path, 12), "…/intro.md");
+        assert_eq!(middle_ellipsis(path, 4), "intro.md");
+        assert_eq!(middle_ellipsis("intro.md", 4), "intro.md");
+        assert_eq!(
+            middle_ellipsis("docs\\guide\\api\\intro.md", 20),
+            "docs\\…\\api\\intro.md"
+        );
+    }
+
+    #[test]
+    fn test_long_paths_fit_the_list() {
+        let path = PathBuf::from("docs/guide/reference/api/intro.md");
+        let mut list = FileList::new(vec![MarkdownFile::new(path)]);
+        let mut terminal = Terminal::new(TestBackend::new(24, 4)).unwrap();
+        terminal
+            .draw(|frame| list.render(frame, frame.area()))
+            .unwrap();
+        let first_row: String = terminal.backend().buffer().content()[..24]
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert_eq!(first_row.trim_end(), "│ docs/…/api/intro.md");
+    }
+
+    #[test]
+    fn test_grid_position() {
+        assert_eq!(grid_position(0, 3), (0, 0));
+        assert_eq!(grid_position(2, 3), (2, 0));
+        assert_eq!(grid_position(3, 3), (0, 1));
+        assert_eq!(grid_position(7, 3), (1, 2));
+        assert_eq!(grid_position(4, 0), (0, 4));
+    }
+
+    fn name(list: &FileList) -> &str {
+        &list.get_current_file().unwrap().name
+    }
+
+    #[test]
+    fn test_column_navigation() {
+        // Three columns of two files, so pages hold 00-05 and 06-10
+        let mut list = numbered_list(11);
+        list.update_columns(3 * COLUMN_WIDTH);
+        assert_eq!(list.columns(), 1);
+        list.set_multi_column(true);
+        list.update_columns(3 * COLUMN_WIDTH);
+        assert_eq!(list.columns(), 3);
+        assert_eq!(list.total_pages(), 2);
+
+        list.next_column();
+        assert_eq!(name(&list), "02.md");
+        list.next();
+        list.next_column();
+        assert_eq!(name(&list), "05.md");
+
+        // Past the last column the same row on the next page is selected
+        list.next_column();
+        assert_eq!(name(&list), "07.md");
+        assert_eq!(list.current_page(), 2);
+        list.previous_column();
+        assert_eq!(name(&list), "05.md");
+        assert_eq!(list.current_page(), 1);
+        list.next_column();
+
+        // Moving down from the bottom of a column continues in the next one
+        list.next();
+        assert_eq!(name(&list), "08.md");
+
+        // The shorter last column keeps its only file selected
+        list.next();
+        list.next_column();
+        assert_eq!(name(&list), "10.md");
+        list.next_column();
+        assert_eq!(name(&list), "10.md");
+
+        list.previous_column();
+        list.previous_column();
+        assert_eq!(name(&list), "06.md");
+    }
+
+    fn tree_files() -> Vec<MarkdownFile> {
+        ["docs/b.md", "readme.md", "docs/a.md", "docs/api/c.md"]
+            .iter()
+            .map(|path| MarkdownFile::new(PathBuf::from(path)))
+            .collect()
+    }
+
+    #[test]
+    fn test_group_by_directory() {
+        let files = tree_files();
+        let groups: Vec<(String, Vec<String>)> = group_by_directory(&files)
+            .into_iter()
+            .map(|(directory, files)| {
+                (
+                    directory.display().to_string(),
+                    files.iter().map(|file| file.name.clone()).collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            groups,
+            vec![
+                (String::new(), vec!["readme.md".to_string()]),
+                (
+                    "docs".to_string(),
+                    vec!["docs/b.md".to_string(), "docs/a.md".to_string()]
+                ),
+                ("docs/api".to_string(), vec!["docs/api/c.md".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collapse_hides_directory_files() {
+        let mut list = FileList::new(tree_files());
+        list.toggle_tree_view();
+        // ./, readme.md, docs/, b.md, a.md, docs/api/, c.md
+        assert_eq!(list.tree_rows().len(), 7);
+        // The selected file stays selected when switching views
+        assert_eq!(list.get_current_file().unwrap().name, "docs/b.md");
+
+        list.toggle_collapse();
+        assert_eq!(list.tree_rows().len(), 5);
+        assert!(matches!(
+            list.tree_rows()[2],
+            TreeRow::Directory {
+                files: 2,
+                collapsed: true,
+                ..
+            }
+        ));
+        // The collapsed directory's header is selected, which isn't a file
+        assert!(list.get_current_file().is_none());
+
+        list.next();
+        list.next();
+        assert_eq!(list.get_current_file().unwrap().name, "docs/api/c.md");
+
+        list.previous();
+        list.previous();
+        list.toggle_collapse();
+        assert_eq!(list.tree_rows().len(), 7);
+
+        list.toggle_tree_view();
+        assert_eq!(list.get_file_count(), 4);
+    }
+
+    #[test]
+    fn test_highlighted_spans_are_colored_by_default() {
+        let spans =
+            FileList::create_highlighted_spans("readme.md", "rd", Color::White, Color::Gray);
+
+        assert!(spans.len() > 1);
+        assert!(spans.iter().all(|span| span.style.fg.is_some()));
+    }
+
+    #[test]
+    fn test_highlighted_spans_use_given_colors() {
+        let matched = Color::Rgb(10, 20, 30);
+        let dim = Color::Rgb(200, 210, 220);
+        let spans = FileList::create_highlighted_spans("readme.md", "rd", matched, dim);
+
+        let colors: Vec<_> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.fg))
+            .collect();
+        assert_eq!(
+            colors,
+            vec![
+                ("r", Some(matched)),
+                ("ea", Some(dim)),
+                ("d", Some(matched)),
+                ("me.md", Some(dim)),
+            ]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::ui::components::spinner::Spinner;
+use crate::ui::style::StyleExt;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -8,24 +9,28 @@ use ratatui::{
 };
 
 pub struct Header {
-    title: String,
     file_count: usize,
     original_count: usize,
     search_query: String,
     is_searching: bool,
     is_loading: bool,
+    scanned_dirs: usize,
+    bookmarks_only: bool,
+    show_all: bool,
     spinner: Spinner,
 }
 
 impl Header {
     pub fn new(file_count: usize) -> Self {
         Self {
-            title: "Mark".to_string(),
             file_count,
             original_count: file_count,
             search_query: String::new(),
             is_searching: false,
             is_loading: false,
+            scanned_dirs: 0,
+            bookmarks_only: false,
+            show_all: false,
             spinner: Spinner::new(),
         }
     }
@@ -47,10 +52,24 @@ impl Header {
         }
     }
 
+    pub fn set_bookmarks_only(&mut self, bookmarks_only: bool) {
+        self.bookmarks_only = bookmarks_only;
+    }
+
+    /// Mark the listing as including hidden and ignored files
+    pub fn set_show_all(&mut self, show_all: bool) {
+        self.show_all = show_all;
+    }
+
     pub fn set_loading(&mut self, is_loading: bool) {
         self.is_loading = is_loading;
     }
 
+    /// Record how many directories the background search has walked
+    pub fn set_scanned_dirs(&mut self, scanned: usize) {
+        self.scanned_dirs = scanned;
+    }
+
     pub fn update_file_count(&mut self, count: usize) {
         self.file_count = count;
         if !self.is_searching || self.search_query.is_empty() {
@@ -62,6 +81,24 @@ impl Header {
         self.spinner.tick();
     }
 
+    /// Spinner and scan progress shown while the background search runs
+    fn loading_spans(&self) -> Vec<Span<'_>> {
+        if !self.is_loading {
+            return Vec::new();
+        }
+        vec![
+            Span::raw(" "),
+            self.spinner.render_inline(),
+            Span::styled(
+                format!(
+                    " scanning… {} found / {} dirs",
+                    self.original_count, self.scanned_dirs
+                ),
+                Style::default().tint(Color::Rgb(100, 100, 100)),
+            ),
+        ]
+    }
+
     pub fn render(&self, frame: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -78,7 +115,7 @@ impl Header {
             let count_text = format!("  {} elements", self.file_count);
             let count_line = Line::from(Span::styled(
                 count_text,
-                Style::default().fg(Color::Rgb(100, 100, 100)),
+                Style::default().tint(Color::Rgb(100, 100, 100)),
             ));
             let count = Paragraph::new(count_line).alignment(Alignment::Left);
             frame.render_widget(count, chunks[2]);
@@ -88,25 +125,25 @@ impl Header {
                 Span::styled(
                     "M",
                     Style::default()
-                        .fg(Color::Rgb(255, 100, 150))
+                        .tint(Color::Rgb(255, 100, 150))
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
                     "a",
                     Style::default()
-                        .fg(Color::Rgb(255, 120, 170))
+                        .tint(Color::Rgb(255, 120, 170))
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
                     "r",
                     Style::default()
-                        .fg(Color::Rgb(255, 140, 190))
+                        .tint(Color::Rgb(255, 140, 190))
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
                     "k",
                     Style::default()
-                        .fg(Color::Rgb(255, 160, 210))
+                        .tint(Color::Rgb(255, 160, 210))
                         .add_modifier(Modifier::BOLD),
                 ),
             ];
@@ -115,33 +152,39 @@ impl Header {
             let title = Paragraph::new(title_line).alignment(Alignment::Center);
 
             // File count info with search query if applicable
-            let count_line = if self.is_searching && !self.search_query.is_empty() {
+            let mut count_line = if self.is_searching && !self.search_query.is_empty() {
                 let mut spans = vec![
                     Span::styled(
                         format!("  {} elements | ", self.original_count),
-                        Style::default().fg(Color::Rgb(100, 100, 100)), // Greyed out original count
+                        Style::default().tint(Color::Rgb(100, 100, 100)), // Greyed out original count
                     ),
                     Span::styled(
                         format!("{} \"{}\"", self.file_count, self.search_query),
-                        Style::default().fg(Color::Rgb(150, 150, 150)), // Normal color for filtered count
+                        Style::default().tint(Color::Rgb(150, 150, 150)), // Normal color for filtered count
                     ),
                 ];
-                if self.is_loading {
-                    spans.push(Span::raw(" "));
-                    spans.push(self.spinner.render_inline());
-                }
+                spans.extend(self.loading_spans());
                 Line::from(spans)
             } else {
                 let mut spans = vec![Span::styled(
                     format!("  {} elements", self.file_count),
-                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                    Style::default().tint(Color::Rgb(150, 150, 150)),
                 )];
-                if self.is_loading {
-                    spans.push(Span::raw(" "));
-                    spans.push(self.spinner.render_inline());
-                }
+                spans.extend(self.loading_spans());
                 Line::from(spans)
             };
+            if self.bookmarks_only {
+                count_line.spans.push(Span::styled(
+                    "  ★ bookmarks",
+                    Style::default().tint(Color::Rgb(255, 200, 100)),
+                ));
+            }
+            if self.show_all {
+                count_line.spans.push(Span::styled(
+                    "  ◉ all files",
+                    Style::default().tint(Color::Rgb(100, 200, 255)),
+                ));
+            }
             let count = Paragraph::new(count_line).alignment(Alignment::Left);
 
             frame.render_widget(title, chunks[0]);
@@ -1,3 +1,4 @@
+use crate::ui::style::StyleExt;
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Style},
@@ -15,15 +16,15 @@ impl Help {
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
         let help_spans = vec![
-            Span::styled("j/k", Style::default().fg(Color::Rgb(120, 120, 120))),
+            Span::styled("j/k", Style::default().tint(Color::Rgb(120, 120, 120))),
             Span::styled(
                 ": Navigate  ",
-                Style::default().fg(Color::Rgb(120, 120, 120)),
+                Style::default().tint(Color::Rgb(120, 120, 120)),
             ),
-            Span::styled("q", Style::default().fg(Color::Rgb(120, 120, 120))),
-            Span::styled(": Quit  ", Style::default().fg(Color::Rgb(120, 120, 120))),
-            Span::styled("?", Style::default().fg(Color::Rgb(120, 120, 120))),
-            Span::styled(": Help", Style::default().fg(Color::Rgb(120, 120, 120))),
+            Span::styled("q", Style::default().tint(Color::Rgb(120, 120, 120))),
+            Span::styled(": Quit  ", Style::default().tint(Color::Rgb(120, 120, 120))),
+            Span::styled("?", Style::default().tint(Color::Rgb(120, 120, 120))),
+            Span::styled(": Help", Style::default().tint(Color::Rgb(120, 120, 120))),
         ];
 
         let help_line = Line::from(help_spans);
@@ -33,3 +34,9 @@ impl Help {
         frame.render_widget(help, area);
     }
 }
+
+impl Default for Help {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ui::style::StyleExt;
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
@@ -36,166 +37,246 @@ impl HelpPopup {
             return;
         }
 
-        // Calculate popup size (centered, 60% of screen width, auto height)
-        let popup_width = (area.width * 60) / 100;
-        let popup_height = 24;
-        let x = (area.width - popup_width) / 2;
-        let y = (area.height - popup_height) / 2;
-
-        let popup_area = Rect {
-            x,
-            y,
-            width: popup_width,
-            height: popup_height,
-        };
-
-        // Clear the area behind the popup
-        frame.render_widget(Clear, popup_area);
-
         // Create help content
         let help_lines = vec![
             Line::from(vec![Span::styled(
                 "Navigation:",
                 Style::default()
-                    .fg(Color::Rgb(255, 200, 100))
+                    .tint(Color::Rgb(255, 200, 100))
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![
-                Span::styled("  j / ↓", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  j / ↓", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
                     "        Move down",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  k / ↑", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  k / ↑", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
                     "        Move up",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  h / ←", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  h / ←", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
-                    "        Previous page",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    "        Previous page or column",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  l / →", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  l / →", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
-                    "        Next page",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    "        Next page or column",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  gg", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  gg", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
                     "          Go to top",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  G", Style::default().fg(Color::Rgb(100, 200, 255))),
+                Span::styled("  G", Style::default().tint(Color::Rgb(100, 200, 255))),
                 Span::styled(
                     "           Go to bottom",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled(
+                    "  5j / 10G",
+                    Style::default().tint(Color::Rgb(100, 200, 255)),
+                ),
+                Span::styled(
+                    "     Repeat or jump by count",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![]),
             Line::from(vec![Span::styled(
                 "Search:",
                 Style::default()
-                    .fg(Color::Rgb(255, 200, 100))
+                    .tint(Color::Rgb(255, 200, 100))
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![
-                Span::styled("  /", Style::default().fg(Color::Rgb(255, 200, 100))),
+                Span::styled("  /", Style::default().tint(Color::Rgb(255, 200, 100))),
                 Span::styled(
                     "           Start search/filter",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  Enter", Style::default().fg(Color::Rgb(255, 200, 100))),
+                Span::styled("  Enter", Style::default().tint(Color::Rgb(255, 200, 100))),
                 Span::styled(
                     "       Apply search filter",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  Esc", Style::default().fg(Color::Rgb(255, 200, 100))),
+                Span::styled("  Esc", Style::default().tint(Color::Rgb(255, 200, 100))),
                 Span::styled(
                     "         Exit search/show all",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![]),
             Line::from(vec![Span::styled(
                 "Actions:",
                 Style::default()
-                    .fg(Color::Rgb(255, 200, 100))
+                    .tint(Color::Rgb(255, 200, 100))
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![
-                Span::styled("  Enter", Style::default().fg(Color::Rgb(100, 255, 100))),
+                Span::styled("  Enter", Style::default().tint(Color::Rgb(100, 255, 100))),
                 Span::styled(
                     "       Open selected file",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  t", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Group files by directory",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  Space", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "       Mark file, or collapse/expand directory",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  x/X", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "         Export marked files as HTML/text",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  f", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Search file names only",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  q", Style::default().fg(Color::Rgb(255, 100, 100))),
+                Span::styled("  s", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Show document stats",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  o", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Open with default app",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  b", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Toggle bookmark",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  B", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Show only bookmarks",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  .", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Show hidden and ignored files",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  R", Style::default().tint(Color::Rgb(100, 255, 100))),
+                Span::styled(
+                    "           Reload config",
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  q", Style::default().tint(Color::Rgb(255, 100, 100))),
                 Span::styled(
                     "           Quit application",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![]),
             Line::from(vec![Span::styled(
                 "Help:",
                 Style::default()
-                    .fg(Color::Rgb(255, 200, 100))
+                    .tint(Color::Rgb(255, 200, 100))
                     .add_modifier(Modifier::BOLD),
             )]),
             Line::from(vec![
-                Span::styled("  ?", Style::default().fg(Color::Rgb(255, 200, 100))),
+                Span::styled("  ?", Style::default().tint(Color::Rgb(255, 200, 100))),
                 Span::styled(
                     "           Show/hide this help",
-                    Style::default().fg(Color::Rgb(200, 200, 200)),
+                    Style::default().tint(Color::Rgb(200, 200, 200)),
                 ),
             ]),
             Line::from(vec![]),
             Line::from(vec![
-                Span::styled("Press ", Style::default().fg(Color::Rgb(150, 150, 150))),
+                Span::styled("Press ", Style::default().tint(Color::Rgb(150, 150, 150))),
                 Span::styled(
                     "?",
                     Style::default()
-                        .fg(Color::Rgb(255, 200, 100))
+                        .tint(Color::Rgb(255, 200, 100))
                         .add_modifier(Modifier::BOLD),
                 ),
-                Span::styled(" or ", Style::default().fg(Color::Rgb(150, 150, 150))),
+                Span::styled(" or ", Style::default().tint(Color::Rgb(150, 150, 150))),
                 Span::styled(
                     "Esc",
                     Style::default()
-                        .fg(Color::Rgb(255, 200, 100))
+                        .tint(Color::Rgb(255, 200, 100))
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
                     " to close this help",
-                    Style::default().fg(Color::Rgb(150, 150, 150)),
+                    Style::default().tint(Color::Rgb(150, 150, 150)),
                 ),
             ]),
         ];
 
+        // Calculate popup size (centered, 60% of screen width, auto height)
+        let popup_width = (area.width * 60) / 100;
+        let popup_height = (help_lines.len() as u16 + 2).min(area.height);
+        let x = (area.width - popup_width) / 2;
+        let y = (area.height - popup_height) / 2;
+
+        let popup_area = Rect {
+            x,
+            y,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        // Clear the area behind the popup
+        frame.render_widget(Clear, popup_area);
+
         let help_paragraph = Paragraph::new(help_lines)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title(" Help ")
                     .title_alignment(Alignment::Center)
-                    .border_style(Style::default().fg(Color::Rgb(100, 200, 255)))
-                    .style(Style::default().bg(Color::Rgb(20, 20, 30))),
+                    .border_style(Style::default().tint(Color::Rgb(100, 200, 255)))
+                    .style(Style::default().tint_bg(Color::Rgb(20, 20, 30))),
             )
             .alignment(Alignment::Left)
             .wrap(ratatui::widgets::Wrap { trim: true });
@@ -203,3 +284,9 @@ impl HelpPopup {
         frame.render_widget(help_paragraph, popup_area);
     }
 }
+
+impl Default for HelpPopup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,14 +2,14 @@ pub mod file_list;
 pub mod header;
 pub mod help;
 pub mod help_popup;
-pub mod pagination;
 pub mod search;
 pub mod spinner;
+pub mod status_line;
 
 pub use file_list::FileList;
 pub use header::Header;
 pub use help::Help;
 pub use help_popup::HelpPopup;
-pub use pagination::Pagination;
 pub use search::SearchBar;
 pub use spinner::Spinner;
+pub use status_line::StatusLine;
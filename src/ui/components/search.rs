@@ -1,3 +1,4 @@
+use crate::ui::style::StyleExt;
 use ratatui::{
     layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
@@ -8,6 +9,8 @@ use ratatui::{
 use std::time::Instant;
 
 pub struct SearchBar {
+    /// Text shown before the query
+    label: &'static str,
     active: bool,
     query: String,
     cursor_position: usize,
@@ -17,6 +20,7 @@ pub struct SearchBar {
 impl SearchBar {
     pub fn new() -> Self {
         Self {
+            label: "Filter: ",
             active: false,
             query: String::new(),
             cursor_position: 0,
@@ -24,6 +28,10 @@ impl SearchBar {
         }
     }
 
+    pub fn set_label(&mut self, label: &'static str) {
+        self.label = label;
+    }
+
     pub fn activate(&mut self) {
         self.active = true;
         self.query.clear();
@@ -90,8 +98,8 @@ impl SearchBar {
         };
 
         let mut spans = vec![Span::styled(
-            "Filter: ",
-            Style::default().fg(Color::Rgb(100, 200, 255)),
+            self.label,
+            Style::default().tint(Color::Rgb(100, 200, 255)),
         )];
 
         // Split query at cursor position
@@ -107,7 +115,7 @@ impl SearchBar {
         if !before_cursor.is_empty() {
             spans.push(Span::styled(
                 before_cursor.to_string(),
-                Style::default().fg(Color::Rgb(100, 200, 255)),
+                Style::default().tint(Color::Rgb(100, 200, 255)),
             ));
         }
 
@@ -121,15 +129,15 @@ impl SearchBar {
             spans.push(Span::styled(
                 cursor_char.to_string(),
                 Style::default()
-                    .bg(Color::Rgb(100, 200, 255))
-                    .fg(Color::Rgb(20, 20, 30))
+                    .tint_bg(Color::Rgb(100, 200, 255))
+                    .tint(Color::Rgb(20, 20, 30))
                     .add_modifier(Modifier::BOLD),
             ));
         } else if self.cursor_position < self.query.len() {
             // Show character without cursor highlighting when not visible
             spans.push(Span::styled(
                 at_cursor.to_string(),
-                Style::default().fg(Color::Rgb(100, 200, 255)),
+                Style::default().tint(Color::Rgb(100, 200, 255)),
             ));
         }
 
@@ -137,7 +145,7 @@ impl SearchBar {
         if !after_cursor.is_empty() {
             spans.push(Span::styled(
                 after_cursor.to_string(),
-                Style::default().fg(Color::Rgb(100, 200, 255)),
+                Style::default().tint(Color::Rgb(100, 200, 255)),
             ));
         }
 
@@ -147,3 +155,9 @@ impl SearchBar {
         frame.render_widget(search_paragraph, area);
     }
 }
+
+impl Default for SearchBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
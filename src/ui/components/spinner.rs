@@ -1,3 +1,4 @@
+use crate::ui::style::StyleExt;
 use ratatui::{
     layout::Rect,
     style::{Color, Style},
@@ -35,10 +36,10 @@ impl Spinner {
         self.frames[self.current_frame]
     }
 
-    pub fn render_inline(&self) -> Span {
+    pub fn render_inline(&self) -> Span<'_> {
         Span::styled(
             self.get_current_frame(),
-            Style::default().fg(Color::Rgb(100, 150, 255)),
+            Style::default().tint(Color::Rgb(100, 150, 255)),
         )
     }
 
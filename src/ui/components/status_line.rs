@@ -0,0 +1,154 @@
+use crate::ui::style::StyleExt;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use std::path::Path;
+
+/// A single line with a path on the left and position details on the right
+pub struct StatusLine {
+    left: String,
+    right: String,
+}
+
+impl StatusLine {
+    pub fn new(left: impl Into<String>, right: impl Into<String>) -> Self {
+        Self {
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    /// Status for the file browser: selected file, page and file counts
+    pub fn browser(
+        selected: Option<&Path>,
+        current_page: usize,
+        total_pages: usize,
+        filtered: usize,
+        total: usize,
+    ) -> Self {
+        let left = selected
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        Self::new(
+            left,
+            browser_counts(current_page, total_pages, filtered, total),
+        )
+    }
+
+    /// Status for the viewer: document path and scroll position
+    pub fn viewer(path: &Path, scroll: usize, viewport_height: usize, line_count: usize) -> Self {
+        Self::new(
+            path.display().to_string(),
+            viewer_position(scroll, viewport_height, line_count),
+        )
+    }
+
+    /// Status for a viewer that has only laid out the first `laid_out` lines so far
+    pub fn partial_viewer(path: &Path, scroll: usize, laid_out: usize) -> Self {
+        Self::new(
+            path.display().to_string(),
+            partial_viewer_position(scroll, laid_out),
+        )
+    }
+
+    pub fn left(&self) -> &str {
+        &self.left
+    }
+
+    pub fn right(&self) -> &str {
+        &self.right
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let style = Style::default().tint(Color::Rgb(120, 120, 120));
+        let right_width = self.right.chars().count() as u16 + 1;
+
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(1), Constraint::Length(right_width)])
+            .split(area);
+
+        let left = Paragraph::new(Line::from(Span::styled(format!(" {}", self.left), style)));
+        let right = Paragraph::new(Line::from(Span::styled(self.right.clone(), style)))
+            .alignment(Alignment::Right);
+
+        frame.render_widget(left, chunks[0]);
+        frame.render_widget(right, chunks[1]);
+    }
+}
+
+/// Format the page indicator and file counts, e.g. `Page 2/5  12/40 files`
+pub fn browser_counts(
+    current_page: usize,
+    total_pages: usize,
+    filtered: usize,
+    total: usize,
+) -> String {
+    let files = if filtered == total {
+        format!("{} files", total)
+    } else {
+        format!("{}/{} files", filtered, total)
+    };
+    format!(
+        "Page {}/{}  {}",
+        current_page.max(1),
+        total_pages.max(1),
+        files
+    )
+}
+
+/// Format the first visible line and how far through the document it is, e.g. `Ln 11/200  10%`
+pub fn viewer_position(scroll: usize, viewport_height: usize, line_count: usize) -> String {
+    if line_count == 0 {
+        return "Ln 0/0  100%".to_string();
+    }
+
+    let line = scroll.min(line_count - 1) + 1;
+    let bottom = (scroll + viewport_height).min(line_count);
+    let percent = bottom * 100 / line_count;
+    format!("Ln {}/{}  {}%", line, line_count, percent)
+}
+
+/// Format the top line against the lines laid out so far, e.g. "Ln 1/74+"
+pub fn partial_viewer_position(scroll: usize, laid_out: usize) -> String {
+    format!("Ln {}/{}+", scroll + 1, laid_out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser_counts() {
+        assert_eq!(browser_counts(2, 5, 40, 40), "Page 2/5  40 files");
+        assert_eq!(browser_counts(1, 1, 12, 40), "Page 1/1  12/40 files");
+        assert_eq!(browser_counts(0, 0, 0, 0), "Page 1/1  0 files");
+    }
+
+    #[test]
+    fn test_browser_status_shows_selected_path() {
+        let status = StatusLine::browser(Some(Path::new("docs/guide.md")), 1, 3, 5, 9);
+        assert_eq!(status.left(), "docs/guide.md");
+        assert_eq!(status.right(), "Page 1/3  5/9 files");
+
+        let empty = StatusLine::browser(None, 1, 1, 0, 9);
+        assert_eq!(empty.left(), "");
+    }
+
+    #[test]
+    fn test_viewer_position() {
+        assert_eq!(viewer_position(0, 20, 200), "Ln 1/200  10%");
+        assert_eq!(viewer_position(180, 20, 200), "Ln 181/200  100%");
+        assert_eq!(viewer_position(0, 50, 10), "Ln 1/10  100%");
+        assert_eq!(viewer_position(0, 20, 0), "Ln 0/0  100%");
+    }
+
+    #[test]
+    fn test_partial_viewer_position() {
+        assert_eq!(partial_viewer_position(0, 74), "Ln 1/74+");
+    }
+}
@@ -1,11 +1,46 @@
+use crate::bookmarks::Bookmarks;
+use crate::cli::export::{export_files, ExportFormat};
 use crate::error::Result;
-use crate::search::{background::BackgroundSearcher, MarkdownFile};
-use crate::ui::components::{FileList, Header, Help, HelpPopup, Pagination, SearchBar};
+use crate::markdown_parser::{parse_markdown_or_default_with, stats::DocumentStats};
+use crate::opener::{Opener, SystemOpener};
+use crate::search::{
+    background::BackgroundSearcher,
+    markdown::{read_lossy, PathDisplay},
+    MarkdownFile,
+};
+use crate::ui::components::{FileList, Header, Help, HelpPopup, SearchBar, StatusLine};
+use crate::ui::renderer::Theme;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     Frame,
 };
+use std::path::{Path, PathBuf};
+
+/// Options used to (re)start the background search
+struct SearchOptions {
+    directories: Vec<PathBuf>,
+    ignored_dirs: Vec<String>,
+    ignored_files: Vec<String>,
+    show_hidden: bool,
+    show_all: bool,
+    follow_symlinks: bool,
+    max_file_size: Option<u64>,
+}
+
+impl SearchOptions {
+    fn start(&self) -> Result<BackgroundSearcher> {
+        BackgroundSearcher::with_directories(
+            self.directories.clone(),
+            self.ignored_dirs.clone(),
+            self.ignored_files.clone(),
+            self.show_hidden,
+            self.show_all,
+            self.follow_symlinks,
+            self.max_file_size,
+        )
+    }
+}
 
 pub struct FileBrowser {
     file_list: FileList,
@@ -13,9 +48,21 @@ pub struct FileBrowser {
     help: Help,
     help_popup: HelpPopup,
     search_bar: SearchBar,
+    /// Prompt for the directory to export the marked files to
+    export_bar: SearchBar,
+    export_format: ExportFormat,
     should_quit: bool,
     last_key_was_g: bool,
+    /// Numeric prefix typed before a motion, as in vim's `5j`
+    count: Option<usize>,
     background_searcher: Option<BackgroundSearcher>,
+    search_options: Option<SearchOptions>,
+    bookmarks_path: Option<PathBuf>,
+    reload_requested: bool,
+    message: Option<String>,
+    opener: Box<dyn Opener>,
+    /// Whether unclosed emphasis fails the parse, from `[settings] strict_emphasis`
+    strict_emphasis: bool,
 }
 
 impl FileBrowser {
@@ -33,27 +80,51 @@ impl FileBrowser {
             help,
             help_popup,
             search_bar,
+            export_bar: SearchBar::new(),
+            export_format: ExportFormat::Html,
             should_quit: false,
             last_key_was_g: false,
+            count: None,
             background_searcher: None,
+            search_options: None,
+            bookmarks_path: None,
+            reload_requested: false,
+            message: None,
+            opener: Box::new(SystemOpener),
+            strict_emphasis: false,
         }
     }
 
     pub fn new_with_background_search(
-        directory: &str,
+        directories: Vec<PathBuf>,
         ignored_dirs: Vec<String>,
+        ignored_files: Vec<String>,
         show_hidden: bool,
         show_all: bool,
+        follow_symlinks: bool,
+        max_file_size: Option<u64>,
     ) -> Result<Self> {
-        let file_list = FileList::new(Vec::new());
+        let mut file_list = FileList::new(Vec::new());
+        file_list.set_search_roots(directories.clone());
+        file_list.set_loading(true);
+        file_list.set_show_all(show_all);
         let mut header = Header::new(0);
         header.set_loading(true);
+        header.set_show_all(show_all);
         let help = Help::new();
         let help_popup = HelpPopup::new();
         let search_bar = SearchBar::new();
 
-        let background_searcher =
-            BackgroundSearcher::new(directory, ignored_dirs, show_hidden, show_all)?;
+        let search_options = SearchOptions {
+            directories,
+            ignored_dirs,
+            ignored_files,
+            show_hidden,
+            show_all,
+            follow_symlinks,
+            max_file_size,
+        };
+        let background_searcher = search_options.start()?;
 
         Ok(Self {
             file_list,
@@ -61,12 +132,275 @@ impl FileBrowser {
             help,
             help_popup,
             search_bar,
+            export_bar: SearchBar::new(),
+            export_format: ExportFormat::Html,
             should_quit: false,
             last_key_was_g: false,
+            count: None,
             background_searcher: Some(background_searcher),
+            search_options: Some(search_options),
+            bookmarks_path: None,
+            reload_requested: false,
+            message: None,
+            opener: Box::new(SystemOpener),
+            strict_emphasis: false,
         })
     }
 
+    /// Use `bookmarks`, saving changes back to `path`
+    pub fn with_bookmarks(mut self, bookmarks: Bookmarks, path: PathBuf) -> Self {
+        self.file_list.set_bookmarks(bookmarks);
+        self.bookmarks_path = Some(path);
+        self
+    }
+
+    /// List each file on a single line, without its date
+    pub fn with_compact_list(mut self, compact: bool) -> Self {
+        self.set_compact_list(compact);
+        self
+    }
+
+    pub fn set_compact_list(&mut self, compact: bool) {
+        self.file_list.set_compact(compact);
+    }
+
+    /// Wrap from the last file to the first, and back, when moving the selection
+    pub fn with_wrap_navigation(mut self, wrap: bool) -> Self {
+        self.set_wrap_navigation(wrap);
+        self
+    }
+
+    pub fn set_wrap_navigation(&mut self, wrap: bool) {
+        self.file_list.set_wrap_navigation(wrap);
+    }
+
+    /// Split the file list into columns when the terminal is wide enough
+    pub fn with_multi_column_list(mut self, multi_column: bool) -> Self {
+        self.set_multi_column_list(multi_column);
+        self
+    }
+
+    pub fn set_multi_column_list(&mut self, multi_column: bool) {
+        self.file_list.set_multi_column(multi_column);
+    }
+
+    /// Show paths relative to the searched directories, in full, or as file names only
+    pub fn with_path_display(mut self, display: PathDisplay) -> Self {
+        self.set_path_display(display);
+        self
+    }
+
+    pub fn set_path_display(&mut self, display: PathDisplay) {
+        self.file_list.set_path_display(display);
+    }
+
+    /// Take the search highlight colors from `theme`
+    pub fn with_theme(mut self, theme: &Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.file_list
+            .set_search_colors(theme.search_match, theme.search_dim);
+    }
+
+    /// Search file names only instead of whole paths
+    pub fn with_match_filename_only(mut self, filename_only: bool) -> Self {
+        self.set_match_filename_only(filename_only);
+        self
+    }
+
+    pub fn set_match_filename_only(&mut self, filename_only: bool) {
+        self.file_list.set_match_filename_only(filename_only);
+        self.update_header();
+    }
+
+    fn toggle_match_filename_only(&mut self) {
+        let filename_only = !self.file_list.is_match_filename_only();
+        self.set_match_filename_only(filename_only);
+        self.message = Some(if filename_only {
+            "Searching file names only".to_string()
+        } else {
+            "Searching full paths".to_string()
+        });
+    }
+
+    /// Show file dates using the strftime `format`
+    pub fn with_date_format(mut self, format: &str) -> Self {
+        self.set_date_format(format);
+        self
+    }
+
+    pub fn set_date_format(&mut self, format: &str) {
+        self.file_list.set_date_format(format);
+    }
+
+    /// Show file dates relative to now, e.g. "2 days ago"
+    pub fn with_relative_dates(mut self, relative: bool) -> Self {
+        self.set_relative_dates(relative);
+        self
+    }
+
+    pub fn set_relative_dates(&mut self, relative: bool) {
+        self.file_list.set_relative_dates(relative);
+    }
+
+    /// Count files with unclosed emphasis as empty documents, as `[settings] strict_emphasis` asks
+    pub fn with_strict_emphasis(mut self, strict_emphasis: bool) -> Self {
+        self.strict_emphasis = strict_emphasis;
+        self
+    }
+
+    /// Open files with `opener` instead of the platform's default handler
+    pub fn with_opener(mut self, opener: Box<dyn Opener>) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// Open the selected file with the default application, reporting the outcome
+    pub fn open_selected_externally(&mut self) {
+        let Some(path) = self
+            .file_list
+            .get_current_file()
+            .map(|file| file.path.clone())
+        else {
+            return;
+        };
+
+        self.message = Some(match self.opener.open(&path) {
+            Ok(()) => format!("Opened {}", path.display()),
+            Err(e) => format!("Could not open {}: {}", path.display(), e),
+        });
+    }
+
+    /// Show word and element counts for the selected file in the status line
+    pub fn show_selected_stats(&mut self) {
+        let Some(path) = self
+            .file_list
+            .get_current_file()
+            .map(|file| file.path.clone())
+        else {
+            return;
+        };
+
+        self.message = Some(match read_lossy(&path) {
+            Ok((content, _)) => DocumentStats::from_ast(&parse_markdown_or_default_with(
+                &content,
+                self.strict_emphasis,
+            ))
+            .summary(),
+            Err(e) => format!("Could not read {}: {}", path.display(), e),
+        });
+    }
+
+    /// Mark or unmark the selected file, showing how many are marked
+    pub fn toggle_mark(&mut self) {
+        if self.file_list.toggle_current_mark().is_none() {
+            return;
+        }
+        self.message = Some(match self.file_list.marked_paths().len() {
+            1 => "1 file marked".to_string(),
+            count => format!("{} files marked", count),
+        });
+    }
+
+    /// Ask for the directory to export the marked files to as `format`
+    fn start_export(&mut self, format: ExportFormat) {
+        if self.file_list.marked_paths().is_empty() {
+            self.message = Some("Mark files to export with Space first".to_string());
+            return;
+        }
+        self.export_format = format;
+        self.export_bar.set_label(match format {
+            ExportFormat::Html => "Export HTML to: ",
+            ExportFormat::Text => "Export text to: ",
+        });
+        self.export_bar.activate();
+    }
+
+    /// Export the listed marked files to `directory`, one file each, and unmark them
+    ///
+    /// Files already in `directory` are kept, and the status message says how
+    /// many exports were numbered to avoid them.
+    pub fn export_marked(&mut self, directory: &Path) {
+        let paths = self.file_list.marked_paths();
+        self.message = Some(match export_files(&paths, self.export_format, directory) {
+            Ok(exported) => {
+                self.file_list.unmark(&paths);
+                let mut message = format!(
+                    "Exported {} file(s) to {}",
+                    exported.written.len(),
+                    directory.display()
+                );
+                if exported.renamed > 0 {
+                    message.push_str(&format!(
+                        ", numbering {} to keep existing files",
+                        exported.renamed
+                    ));
+                }
+                message
+            }
+            Err(e) => format!("Could not export to {}: {}", directory.display(), e),
+        });
+    }
+
+    /// Toggle the bookmark on the selected file and persist the change
+    ///
+    /// A failed save is shown in the status line; the bookmark still applies
+    /// for the rest of the session.
+    pub fn toggle_bookmark(&mut self) {
+        if self.file_list.toggle_current_bookmark().is_none() {
+            return;
+        }
+
+        if let Some(path) = &self.bookmarks_path {
+            if let Err(e) = self.file_list.bookmarks().save(path) {
+                self.message = Some(format!("Could not save bookmarks: {}", e));
+            }
+        }
+        self.update_header();
+    }
+
+    /// Switch between listing every file and hiding hidden/ignored ones, searching again
+    pub fn toggle_show_all(&mut self) -> Result<()> {
+        let Some(options) = &mut self.search_options else {
+            return Ok(());
+        };
+
+        options.show_all = !options.show_all;
+        let searcher = match options.start() {
+            Ok(searcher) => searcher,
+            Err(e) => {
+                options.show_all = !options.show_all;
+                return Err(e);
+            }
+        };
+
+        self.header.set_show_all(options.show_all);
+        self.file_list.set_show_all(options.show_all);
+        self.set_loading(true);
+        if let Some(mut previous) = self.background_searcher.replace(searcher) {
+            previous.cancel();
+        }
+        self.file_list.clear();
+        self.update_header();
+        Ok(())
+    }
+
+    /// Show the search as running or finished, in the header and the list
+    fn set_loading(&mut self, loading: bool) {
+        self.header.set_loading(loading);
+        self.file_list.set_loading(loading);
+    }
+
+    /// Whether hidden and ignored files are listed
+    pub fn is_showing_all(&self) -> bool {
+        self.search_options
+            .as_ref()
+            .is_some_and(|options| options.show_all)
+    }
+
     pub fn update_background_search(&mut self) {
         if let Some(ref mut searcher) = self.background_searcher {
             let messages = searcher.try_recv();
@@ -78,13 +412,16 @@ impl FileBrowser {
                         self.file_list.add_file(file);
                         files_added += 1;
                     }
+                    crate::search::background::SearchMessage::Progress { scanned } => {
+                        self.header.set_scanned_dirs(scanned);
+                    }
                     crate::search::background::SearchMessage::Finished => {
                         self.header.set_loading(false);
+                        self.file_list.set_loading(false);
                         break;
                     }
                     crate::search::background::SearchMessage::Error(_) => {
-                        self.header.set_loading(false);
-                        break;
+                        // One unreadable directory shouldn't hide results from the others
                     }
                 }
             }
@@ -101,15 +438,49 @@ impl FileBrowser {
         }
     }
 
+    /// Check whether the background search has finished
+    pub fn is_search_complete(&self) -> bool {
+        self.background_searcher
+            .as_ref()
+            .map(|searcher| searcher.is_complete)
+            .unwrap_or(true)
+    }
+
+    /// Number of files in the listing, ignoring any search filter
+    pub fn file_count(&self) -> usize {
+        self.file_list.get_original_count()
+    }
+
+    /// Quit the browser, stopping any background search still running
+    fn quit(&mut self) {
+        if let Some(ref mut searcher) = self.background_searcher {
+            searcher.cancel();
+        }
+        self.set_loading(false);
+        self.should_quit = true;
+    }
+
     pub fn should_quit(&self) -> bool {
         self.should_quit
     }
 
+    /// Check and reset whether the user asked to reload the config
+    pub fn take_reload_request(&mut self) -> bool {
+        std::mem::take(&mut self.reload_requested)
+    }
+
+    /// Show a message in the status line until the next key press
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
     pub fn get_selected_file(&self) -> Option<&MarkdownFile> {
         self.file_list.get_current_file()
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<&MarkdownFile>> {
+        self.message = None;
+
         // If help popup is visible, handle help-specific keys
         if self.help_popup.is_visible() {
             match key.code {
@@ -119,6 +490,23 @@ impl FileBrowser {
                 }
                 _ => Ok(None),
             }
+        } else if self.export_bar.is_active() {
+            match key.code {
+                KeyCode::Char(c) => self.export_bar.add_char(c),
+                KeyCode::Backspace => self.export_bar.remove_char(),
+                KeyCode::Left => self.export_bar.move_cursor_left(),
+                KeyCode::Right => self.export_bar.move_cursor_right(),
+                KeyCode::Enter => {
+                    let directory = PathBuf::from(self.export_bar.get_query());
+                    self.export_bar.deactivate();
+                    if !directory.as_os_str().is_empty() {
+                        self.export_marked(&directory);
+                    }
+                }
+                KeyCode::Esc => self.export_bar.deactivate(),
+                _ => {}
+            }
+            Ok(None)
         } else if self.search_bar.is_active() {
             // Search mode
             match key.code {
@@ -145,18 +533,20 @@ impl FileBrowser {
                     Ok(None)
                 }
                 KeyCode::Enter => {
+                    self.last_key_was_g = false;
                     // Apply search and exit search mode
-                    if !self.search_bar.get_query().is_empty() {
-                        self.search_bar.deactivate();
-                        self.file_list.exit_search_input_mode();
-                        // Select first filtered file when applying search
-                        if self.file_list.get_file_count() > 0 {
-                            self.file_list.select_first();
-                        }
-                        self.update_header();
+                    if self.search_bar.get_query().is_empty() {
+                        return Ok(None);
                     }
-                    self.last_key_was_g = false;
-                    Ok(None)
+                    self.search_bar.deactivate();
+                    self.file_list.exit_search_input_mode();
+                    // Select first filtered file when applying search
+                    if self.file_list.get_file_count() > 0 {
+                        self.file_list.select_first();
+                    }
+                    self.update_header();
+                    // Open the file straight away when it's the only match
+                    Ok(self.file_list.only_file())
                 }
                 KeyCode::Esc => {
                     // Cancel search and show all files
@@ -172,14 +562,25 @@ impl FileBrowser {
                 }
             }
         } else {
-            // Normal navigation
+            // Normal navigation; any key other than a digit consumes the count
+            let count = self.count.take();
+            // Moving more times than there are files can't get any further
+            let repeat = count
+                .unwrap_or(1)
+                .min(self.file_list.get_file_count().max(1));
             match key.code {
+                KeyCode::Char(digit @ '0'..='9') if count.is_some() || digit != '0' => {
+                    let digit = digit.to_digit(10).unwrap_or(0) as usize;
+                    self.count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
                 KeyCode::Char('q') => {
-                    self.should_quit = true;
+                    self.quit();
                     Ok(None)
                 }
                 KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    self.should_quit = true;
+                    self.quit();
                     Ok(None)
                 }
                 KeyCode::Esc => {
@@ -198,6 +599,68 @@ impl FileBrowser {
                     self.last_key_was_g = false;
                     Ok(None)
                 }
+                KeyCode::Char('b') => {
+                    self.toggle_bookmark();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('B') => {
+                    self.file_list.toggle_bookmarks_only();
+                    self.update_header();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('.') => {
+                    self.toggle_show_all()?;
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('f') => {
+                    self.toggle_match_filename_only();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('t') => {
+                    self.file_list.toggle_tree_view();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char(' ') => {
+                    // Directory headers fold; files are marked
+                    if self.file_list.is_tree_view() && self.file_list.get_current_file().is_none()
+                    {
+                        self.file_list.toggle_collapse();
+                    } else {
+                        self.toggle_mark();
+                    }
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('x') => {
+                    self.start_export(ExportFormat::Html);
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('X') => {
+                    self.start_export(ExportFormat::Text);
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('s') => {
+                    self.show_selected_stats();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('o') => {
+                    self.open_selected_externally();
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
+                KeyCode::Char('R') => {
+                    self.reload_requested = true;
+                    self.last_key_was_g = false;
+                    Ok(None)
+                }
                 KeyCode::Char('/') => {
                     self.search_bar.activate();
                     self.file_list.start_search();
@@ -207,43 +670,74 @@ impl FileBrowser {
                 }
                 KeyCode::Char('g') => {
                     if self.last_key_was_g {
-                        // gg - go to top
-                        self.file_list.go_to_top();
+                        // gg - go to top, or to the counted file
+                        match count {
+                            Some(n) => self.file_list.go_to(n.saturating_sub(1)),
+                            None => self.file_list.go_to_top(),
+                        }
                         self.last_key_was_g = false;
                     } else {
+                        // Keep the count for the second `g`
+                        self.count = count;
                         self.last_key_was_g = true;
                     }
                     Ok(None)
                 }
                 KeyCode::Char('G') => {
-                    // G - go to bottom
-                    self.file_list.go_to_bottom();
+                    // G - go to bottom, or to the counted file
+                    match count {
+                        Some(n) => self.file_list.go_to(n.saturating_sub(1)),
+                        None => self.file_list.go_to_bottom(),
+                    }
                     self.last_key_was_g = false;
                     Ok(None)
                 }
                 KeyCode::Down | KeyCode::Char('j') => {
-                    self.file_list.next();
+                    for _ in 0..repeat {
+                        self.file_list.next();
+                    }
                     self.last_key_was_g = false;
                     Ok(None)
                 }
                 KeyCode::Up | KeyCode::Char('k') => {
-                    self.file_list.previous();
+                    for _ in 0..repeat {
+                        self.file_list.previous();
+                    }
                     self.last_key_was_g = false;
                     Ok(None)
                 }
                 KeyCode::Left | KeyCode::Char('h') => {
-                    self.file_list.previous_page();
+                    // With several columns left and right move between them instead
+                    for _ in 0..repeat {
+                        if self.file_list.columns() > 1 {
+                            self.file_list.previous_column();
+                        } else {
+                            self.file_list.previous_page();
+                        }
+                    }
                     self.last_key_was_g = false;
                     Ok(None)
                 }
                 KeyCode::Right | KeyCode::Char('l') => {
-                    self.file_list.next_page();
+                    for _ in 0..repeat {
+                        if self.file_list.columns() > 1 {
+                            self.file_list.next_column();
+                        } else {
+                            self.file_list.next_page();
+                        }
+                    }
                     self.last_key_was_g = false;
                     Ok(None)
                 }
                 KeyCode::Enter => {
-                    // Return the selected file to open it
                     self.last_key_was_g = false;
+                    // Enter on a directory header folds it like Space
+                    if self.file_list.is_tree_view() && self.file_list.get_current_file().is_none()
+                    {
+                        self.file_list.toggle_collapse();
+                        return Ok(None);
+                    }
+                    // Return the selected file to open it
                     Ok(self.file_list.get_current_file())
                 }
                 _ => {
@@ -261,6 +755,8 @@ impl FileBrowser {
         let original_count = self.file_list.get_original_count();
         self.header
             .set_search_mode(is_searching, query, filtered_count, original_count);
+        self.header
+            .set_bookmarks_only(self.file_list.is_bookmarks_only());
     }
 
     pub fn render(&mut self, frame: &mut Frame) {
@@ -271,6 +767,7 @@ impl FileBrowser {
 
         // Update items per page based on screen size
         self.file_list.update_items_per_page(size.height as usize);
+        self.file_list.update_columns(size.width as usize);
 
         // Create layout
         let chunks = Layout::default()
@@ -278,23 +775,34 @@ impl FileBrowser {
             .constraints([
                 Constraint::Length(4), // Header
                 Constraint::Min(1),    // File list (flexible)
-                Constraint::Length(1), // Pagination
+                Constraint::Length(1), // Status line
                 Constraint::Length(1), // Help
             ])
             .split(size);
 
         // Render components
-        if self.search_bar.is_active() {
+        if self.export_bar.is_active() {
+            self.export_bar.render(frame, chunks[0]);
+        } else if self.search_bar.is_active() {
             self.search_bar.render(frame, chunks[0]);
         } else {
             self.header.render(frame, chunks[0]);
         }
         self.file_list.render(frame, chunks[1]);
 
-        // Render pagination
-        let pagination =
-            Pagination::new(self.file_list.current_page(), self.file_list.total_pages());
-        pagination.render(frame, chunks[2]);
+        let mut status = StatusLine::browser(
+            self.file_list
+                .get_current_file()
+                .map(|file| file.path.as_path()),
+            self.file_list.current_page(),
+            self.file_list.total_pages(),
+            self.file_list.get_file_count(),
+            self.file_list.get_original_count(),
+        );
+        if let Some(message) = &self.message {
+            status = StatusLine::new(message.clone(), status.right());
+        }
+        status.render(frame, chunks[2]);
 
         self.help.render(frame, chunks[3]);
 
@@ -302,3 +810,315 @@ impl FileBrowser {
         self.help_popup.render(frame, size);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Recording;
+    use std::cell::RefCell;
+    use std::fs::File;
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_merges_files_from_multiple_directories() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        File::create(first.path().join("a.md")).unwrap();
+        File::create(first.path().join("b.md")).unwrap();
+        File::create(second.path().join("c.md")).unwrap();
+
+        let directories = vec![first.path().to_path_buf(), second.path().to_path_buf()];
+        let mut browser = FileBrowser::new_with_background_search(
+            directories,
+            vec![],
+            vec![],
+            false,
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+
+        for _ in 0..200 {
+            browser.update_background_search();
+            if browser.is_search_complete() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(browser.is_search_complete());
+        assert_eq!(browser.file_count(), 3);
+    }
+
+    fn press(browser: &mut FileBrowser, keys: &str) {
+        for c in keys.chars() {
+            browser
+                .handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        }
+    }
+
+    fn selected_name(browser: &FileBrowser) -> String {
+        browser
+            .get_selected_file()
+            .map(|file| file.path.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_single_search_result_opens_on_enter() {
+        let files = ["guide.md", "notes.md", "docs/notes-old.md"]
+            .iter()
+            .map(|path| MarkdownFile::new(PathBuf::from(path)))
+            .collect();
+        let mut browser = FileBrowser::new(files);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+
+        press(&mut browser, "/notes");
+        let opened = browser.handle_key_event(enter).unwrap();
+        assert!(opened.is_none());
+
+        press(&mut browser, "/guide");
+        let opened = browser.handle_key_event(enter).unwrap();
+        assert_eq!(opened.unwrap().path, PathBuf::from("guide.md"));
+    }
+
+    #[test]
+    fn test_count_prefix_repeats_motion() {
+        let files = (0..20)
+            .map(|i| MarkdownFile::new(PathBuf::from(format!("{i:02}.md"))))
+            .collect();
+        let mut browser = FileBrowser::new(files);
+        browser.file_list.update_items_per_page(100);
+
+        press(&mut browser, "3j");
+        assert_eq!(selected_name(&browser), "03.md");
+
+        // The count is reset after the motion
+        press(&mut browser, "j");
+        assert_eq!(selected_name(&browser), "04.md");
+
+        press(&mut browser, "2k");
+        assert_eq!(selected_name(&browser), "02.md");
+
+        // Huge counts stop at the last file instead of looping for ages
+        press(&mut browser, "99999999999999999999j");
+        assert_eq!(selected_name(&browser), "19.md");
+    }
+
+    #[test]
+    fn test_count_prefix_with_go_to() {
+        let files = (0..20)
+            .map(|i| MarkdownFile::new(PathBuf::from(format!("{i:02}.md"))))
+            .collect();
+        let mut browser = FileBrowser::new(files);
+        // Four files per page
+        browser.file_list.update_items_per_page(18);
+
+        press(&mut browser, "10G");
+        assert_eq!(selected_name(&browser), "09.md");
+
+        // Counts past the end stop at the last file, on its page
+        press(&mut browser, "99G");
+        assert_eq!(selected_name(&browser), "19.md");
+        assert_eq!(browser.file_list.current_page(), 5);
+
+        press(&mut browser, "5gg");
+        assert_eq!(selected_name(&browser), "04.md");
+
+        // A non-digit key drops a pending count
+        press(&mut browser, "4?");
+        browser
+            .handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        press(&mut browser, "j");
+        assert_eq!(selected_name(&browser), "05.md");
+    }
+
+    #[test]
+    fn test_open_key_opens_selected_file() {
+        let files = ["a.md", "docs/b.md"]
+            .iter()
+            .map(|path| MarkdownFile::new(PathBuf::from(path)))
+            .collect();
+        let opened = Rc::new(RefCell::new(Vec::new()));
+        let mut browser = FileBrowser::new(files).with_opener(Box::new(Recording {
+            calls: opened.clone(),
+            fail: false,
+        }));
+
+        press(&mut browser, "jo");
+        assert_eq!(*opened.borrow(), vec![PathBuf::from("docs/b.md")]);
+        assert_eq!(browser.message.as_deref(), Some("Opened docs/b.md"));
+    }
+
+    #[test]
+    fn test_open_failure_shows_message() {
+        let files = vec![MarkdownFile::new(PathBuf::from("a.md"))];
+        let opened = Rc::new(RefCell::new(Vec::new()));
+        let mut browser = FileBrowser::new(files).with_opener(Box::new(Recording {
+            calls: opened.clone(),
+            fail: true,
+        }));
+
+        press(&mut browser, "o");
+        assert_eq!(opened.borrow().len(), 1);
+        assert_eq!(
+            browser.message.as_deref(),
+            Some("Could not open a.md: no handler")
+        );
+
+        // The message is transient
+        press(&mut browser, "j");
+        assert_eq!(browser.message, None);
+    }
+
+    #[test]
+    fn test_bookmark_save_failure_shows_message() {
+        let dir = TempDir::new().unwrap();
+        let blocker = dir.path().join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+        let files = vec![MarkdownFile::new(PathBuf::from("a.md"))];
+        let mut browser = FileBrowser::new(files)
+            .with_bookmarks(Bookmarks::default(), blocker.join("bookmarks.toml"));
+
+        press(&mut browser, "b");
+        assert!(browser
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Could not save bookmarks"));
+        assert!(browser.file_list.bookmarks().contains(Path::new("a.md")));
+    }
+
+    #[test]
+    fn test_export_marked_files() {
+        let dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["a.md", "b.md", "c.md"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        for (path, text) in paths.iter().zip(["First", "Second", "Third"]) {
+            std::fs::write(path, text).unwrap();
+        }
+        let mut browser = FileBrowser::new(paths.iter().cloned().map(MarkdownFile::new).collect());
+
+        press(&mut browser, "x");
+        assert!(!browser.export_bar.is_active());
+        assert!(browser.message.as_deref().unwrap().contains("Space"));
+
+        press(&mut browser, " jj ");
+        assert_eq!(browser.message.as_deref(), Some("2 files marked"));
+        assert_eq!(
+            browser.file_list.marked_paths(),
+            vec![paths[0].clone(), paths[2].clone()]
+        );
+
+        let out = dir.path().join("out");
+        press(&mut browser, "X");
+        assert!(browser.export_bar.is_active());
+        press(&mut browser, &out.to_string_lossy());
+        browser
+            .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+
+        assert!(!browser.export_bar.is_active());
+        assert_eq!(
+            std::fs::read_to_string(out.join("a.txt")).unwrap(),
+            "First\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(out.join("c.txt")).unwrap(),
+            "Third\n"
+        );
+        assert!(!out.join("b.txt").exists());
+        assert!(browser.file_list.marked_paths().is_empty());
+        assert!(browser
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Exported 2 file(s)"));
+
+        // Exporting again keeps the earlier files and says so
+        press(&mut browser, " X");
+        press(&mut browser, &out.to_string_lossy());
+        browser
+            .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(out.join("c.txt")).unwrap(),
+            "Third\n"
+        );
+        assert!(out.join("c-2.txt").exists());
+        assert!(browser
+            .message
+            .as_deref()
+            .unwrap()
+            .ends_with("numbering 1 to keep existing files"));
+    }
+
+    #[test]
+    fn test_stats_key_shows_counts() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.md");
+        std::fs::write(&path, "# Title\n\nTwo words").unwrap();
+        let mut browser = FileBrowser::new(vec![MarkdownFile::new(path)]);
+
+        press(&mut browser, "s");
+        assert_eq!(
+            browser.message.as_deref(),
+            Some("3 words, 14 characters, 1 headings, 0 links, 0 code blocks")
+        );
+    }
+
+    /// Drain the background search until it finishes
+    fn wait_for_search(browser: &mut FileBrowser) {
+        for _ in 0..200 {
+            browser.update_background_search();
+            if browser.is_search_complete() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(browser.is_search_complete());
+    }
+
+    #[test]
+    fn test_toggle_show_all_includes_hidden_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".hidden")).unwrap();
+        File::create(dir.path().join("a.md")).unwrap();
+        File::create(dir.path().join(".hidden").join("b.md")).unwrap();
+
+        let directories = vec![dir.path().to_path_buf()];
+        let mut browser = FileBrowser::new_with_background_search(
+            directories,
+            vec![],
+            vec![],
+            false,
+            false,
+            false,
+            None,
+        )
+        .unwrap();
+        wait_for_search(&mut browser);
+        assert_eq!(browser.file_count(), 1);
+        assert!(!browser.is_showing_all());
+
+        browser
+            .handle_key_event(KeyEvent::new(KeyCode::Char('.'), KeyModifiers::NONE))
+            .unwrap();
+        wait_for_search(&mut browser);
+        assert_eq!(browser.file_count(), 2);
+        assert!(browser.is_showing_all());
+
+        browser.toggle_show_all().unwrap();
+        wait_for_search(&mut browser);
+        assert_eq!(browser.file_count(), 1);
+    }
+}
@@ -1,11 +1,17 @@
 pub mod app;
+pub mod background;
 pub mod components;
 pub mod events;
 pub mod file_browser;
+pub mod renderer;
+pub mod style;
+pub mod viewer;
+pub mod watcher;
 
 pub use app::App;
 pub use events::{Event, EventHandler};
 pub use file_browser::FileBrowser;
+pub use viewer::MarkdownViewer;
 
 use crate::error::Result;
 use crossterm::{
@@ -15,11 +21,16 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io::{self, Stdout};
+use std::sync::Once;
 
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
 /// Initialize the terminal
+///
+/// Also installs a panic hook that restores the terminal before the panic
+/// message is printed, so a crash doesn't leave the shell in raw mode.
 pub fn init() -> Result<Tui> {
+    install_panic_hook();
     execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
     enable_raw_mode()?;
 
@@ -34,3 +45,74 @@ pub fn restore() -> Result<()> {
     disable_raw_mode()?;
     Ok(())
 }
+
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = restore();
+            previous(info);
+        }));
+    });
+}
+
+/// Runs a restore function when dropped, unless it was already run with [`RestoreGuard::restore`]
+pub struct RestoreGuard<F: FnMut() -> Result<()>> {
+    restore: Option<F>,
+}
+
+impl<F: FnMut() -> Result<()>> RestoreGuard<F> {
+    pub fn new(restore: F) -> Self {
+        Self {
+            restore: Some(restore),
+        }
+    }
+
+    /// Run the restore function now, returning its error
+    pub fn restore(mut self) -> Result<()> {
+        match self.restore.take() {
+            Some(mut restore) => restore(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<F: FnMut() -> Result<()>> Drop for RestoreGuard<F> {
+    fn drop(&mut self) {
+        if let Some(mut restore) = self.restore.take() {
+            // Nothing can be reported from here, e.g. while unwinding
+            let _ = restore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_restore_guard_restores_on_drop() {
+        let calls = Cell::new(0);
+        {
+            let _guard = RestoreGuard::new(|| {
+                calls.set(calls.get() + 1);
+                Ok(())
+            });
+        }
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_restore_guard_restores_once() {
+        let calls = Cell::new(0);
+        let guard = RestoreGuard::new(|| {
+            calls.set(calls.get() + 1);
+            Ok(())
+        });
+
+        guard.restore().unwrap();
+        assert_eq!(calls.get(), 1);
+    }
+}
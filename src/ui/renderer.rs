@@ -0,0 +1,1679 @@
+use crate::config::{colors::hex_to_rgb, MarkConfig};
+use crate::images::fallback_text;
+use crate::markdown_parser::parser::ast::is_break_tag;
+use crate::markdown_parser::AstNode;
+use crate::ui::style::StyleExt;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+use std::collections::HashSet;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Colors used when rendering a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub text: Color,
+    pub code_block: Color,
+    pub headings: [Color; 6],
+    pub link: Color,
+    pub passive: Color,
+    /// Matched characters of a file list search
+    pub search_match: Color,
+    /// The rest of a file name during a file list search
+    pub search_dim: Color,
+    /// Background of the viewer's current line
+    pub current_line: Color,
+}
+
+impl Theme {
+    /// Build a theme from the active color scheme in the config
+    pub fn from_config(config: &MarkConfig) -> Self {
+        let colors = if config.settings.is_light_theme() {
+            config.color.light.all_colors()
+        } else {
+            config.color.dark.all_colors()
+        };
+
+        let color = |name: &str| {
+            colors
+                .iter()
+                .find(|(field, _)| *field == name)
+                .and_then(|(_, hex)| hex_to_rgb(hex).ok())
+                .map(|(r, g, b)| Color::Rgb(r, g, b))
+                .unwrap_or(Color::Reset)
+        };
+
+        Self {
+            text: color("text"),
+            code_block: color("code_block"),
+            headings: [
+                color("h1"),
+                color("h2"),
+                color("h3"),
+                color("h4"),
+                color("h5"),
+                color("h6"),
+            ],
+            link: color("link"),
+            passive: color("passive"),
+            search_match: color("search_match"),
+            search_dim: color("search_dim"),
+            current_line: color("current_line"),
+        }
+    }
+
+    /// Get the color for a heading level (1-6)
+    pub fn heading(&self, level: u8) -> Color {
+        let index = (level.clamp(1, 6) - 1) as usize;
+        self.headings[index]
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: Color::Rgb(220, 220, 220),
+            code_block: Color::Rgb(40, 40, 40),
+            headings: [
+                Color::Rgb(255, 100, 150),
+                Color::Rgb(255, 140, 100),
+                Color::Rgb(255, 200, 100),
+                Color::Rgb(100, 255, 150),
+                Color::Rgb(100, 200, 255),
+                Color::Rgb(180, 140, 255),
+            ],
+            link: Color::Rgb(100, 200, 255),
+            passive: Color::Rgb(120, 120, 120),
+            search_match: Color::Rgb(200, 200, 200),
+            search_dim: Color::Rgb(100, 100, 100),
+            current_line: Color::Rgb(42, 42, 42),
+        }
+    }
+}
+
+/// Glyphs put before headings so their level shows without relying on color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeadingPrefix {
+    #[default]
+    None,
+    /// One `#` per level, as in the markdown source
+    Hash,
+    /// One `▎` bar per level
+    Bar,
+}
+
+impl HeadingPrefix {
+    /// Read the `heading_prefix` setting, showing no prefix for unknown names
+    pub fn from_setting(name: &str) -> Self {
+        match name {
+            "hash" => HeadingPrefix::Hash,
+            "bar" => HeadingPrefix::Bar,
+            _ => HeadingPrefix::None,
+        }
+    }
+
+    /// Prefix for a heading of `level`, including the space after it
+    pub fn text(self, level: u8) -> String {
+        let glyph = match self {
+            HeadingPrefix::None => return String::new(),
+            HeadingPrefix::Hash => "#",
+            HeadingPrefix::Bar => "▎",
+        };
+        format!("{} ", glyph.repeat(level.clamp(1, 6) as usize))
+    }
+}
+
+/// How inline code is set apart from the text around it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InlineCodeStyle {
+    /// On the code block background color
+    #[default]
+    Background,
+    /// Between backticks, as in the markdown source
+    Backticks,
+}
+
+impl InlineCodeStyle {
+    /// Read the `inline_code_style` setting, using a background for unknown names
+    pub fn from_setting(name: &str) -> Self {
+        match name {
+            "backticks" => InlineCodeStyle::Backticks,
+            _ => InlineCodeStyle::Background,
+        }
+    }
+}
+
+/// Frame drawn around code blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodeBlockBorder {
+    #[default]
+    None,
+    /// A `▎` bar down the left side
+    Bar,
+    /// A rounded box, with the language in its top edge
+    Box,
+}
+
+impl CodeBlockBorder {
+    /// Read the `code_block_border` setting, drawing no border for unknown names
+    pub fn from_setting(name: &str) -> Self {
+        match name {
+            "bar" => CodeBlockBorder::Bar,
+            "box" => CodeBlockBorder::Box,
+            _ => CodeBlockBorder::None,
+        }
+    }
+}
+
+/// An image on a line of its own, with rows reserved below it for drawing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImagePlacement {
+    /// First rendered line of the reserved area
+    pub line: usize,
+    pub rows: usize,
+    pub alt: String,
+    pub url: String,
+}
+
+/// Renders a markdown AST into styled, wrapped terminal lines
+pub struct Renderer<'a> {
+    theme: &'a Theme,
+    width: usize,
+    image_rows: usize,
+    /// Images that couldn't be loaded, shown as text without reserved rows
+    failed_images: Option<&'a HashSet<String>>,
+    show_urls: bool,
+    indent_by_heading: bool,
+    text_modifiers: bool,
+    wrap_code: bool,
+    show_frontmatter: bool,
+    heading_prefix: HeadingPrefix,
+    conceal: bool,
+    inline_code_style: InlineCodeStyle,
+    code_block_border: CodeBlockBorder,
+    collapse_blank_lines: bool,
+    show_image_urls: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(theme: &'a Theme, width: usize) -> Self {
+        Self {
+            theme,
+            width: width.max(1),
+            image_rows: 0,
+            failed_images: None,
+            show_urls: false,
+            indent_by_heading: false,
+            text_modifiers: true,
+            wrap_code: false,
+            show_frontmatter: false,
+            heading_prefix: HeadingPrefix::None,
+            conceal: false,
+            inline_code_style: InlineCodeStyle::Background,
+            code_block_border: CodeBlockBorder::None,
+            collapse_blank_lines: false,
+            show_image_urls: true,
+        }
+    }
+
+    /// Reserve `rows` lines for each standalone image so it can be drawn inline
+    pub fn with_image_rows(mut self, rows: usize) -> Self {
+        self.image_rows = rows;
+        self
+    }
+
+    /// Show the images whose URLs are in `failed` as text instead of reserving rows
+    pub fn with_failed_images(mut self, failed: &'a HashSet<String>) -> Self {
+        self.failed_images = Some(failed);
+        self
+    }
+
+    /// Append each link's URL after its text, for terminals without clickable links
+    pub fn with_show_urls(mut self, show_urls: bool) -> Self {
+        self.show_urls = show_urls;
+        self
+    }
+
+    /// Indent each section by its heading level, two spaces per level below H1
+    pub fn with_indent_by_heading(mut self, indent_by_heading: bool) -> Self {
+        self.indent_by_heading = indent_by_heading;
+        self
+    }
+
+    /// Show bold and italic text with terminal modifiers, or with colors only when false
+    pub fn with_text_modifiers(mut self, text_modifiers: bool) -> Self {
+        self.text_modifiers = text_modifiers;
+        self
+    }
+
+    /// Wrap long code lines at the width instead of leaving them to scroll sideways
+    pub fn with_wrap_code(mut self, wrap_code: bool) -> Self {
+        self.wrap_code = wrap_code;
+        self
+    }
+
+    /// Show a document's frontmatter as a code block instead of hiding it
+    pub fn with_show_frontmatter(mut self, show_frontmatter: bool) -> Self {
+        self.show_frontmatter = show_frontmatter;
+        self
+    }
+
+    /// Put `prefix` before headings to mark their level
+    pub fn with_heading_prefix(mut self, prefix: HeadingPrefix) -> Self {
+        self.heading_prefix = prefix;
+        self
+    }
+
+    /// Show links as their text only and images as a placeholder with their alt text
+    ///
+    /// Takes precedence over `show_urls`, and standalone images aren't drawn.
+    pub fn with_conceal(mut self, conceal: bool) -> Self {
+        self.conceal = conceal;
+        self
+    }
+
+    /// Set inline code apart with a background or with backticks
+    pub fn with_inline_code_style(mut self, style: InlineCodeStyle) -> Self {
+        self.inline_code_style = style;
+        self
+    }
+
+    /// Frame code blocks with `border`
+    pub fn with_code_block_border(mut self, border: CodeBlockBorder) -> Self {
+        self.code_block_border = border;
+        self
+    }
+
+    /// Leave at most one blank line between blocks, dropping blocks that render as blank
+    pub fn with_collapse_blank_lines(mut self, collapse_blank_lines: bool) -> Self {
+        self.collapse_blank_lines = collapse_blank_lines;
+        self
+    }
+
+    /// Show each image's URL after its alt text and title
+    pub fn with_show_image_urls(mut self, show_image_urls: bool) -> Self {
+        self.show_image_urls = show_image_urls;
+        self
+    }
+
+    /// Render a node (usually a document) into lines
+    pub fn render(&self, node: &AstNode) -> Vec<Line<'static>> {
+        self.render_with_images(node).0
+    }
+
+    /// Render a node into lines, along with where standalone images were placed
+    pub fn render_with_images(&self, node: &AstNode) -> (Vec<Line<'static>>, Vec<ImagePlacement>) {
+        let mut lines = Vec::new();
+        let mut images = Vec::new();
+
+        match node {
+            AstNode::Document { children } => {
+                let mut heading_level = 0;
+                for child in children {
+                    heading_level = section_level(child, heading_level);
+                    let (block_lines, block_images) =
+                        self.render_section_block(child, heading_level);
+                    append_block(&mut lines, &mut images, block_lines, block_images);
+                }
+            }
+            other => self.render_block(other, &mut lines),
+        }
+
+        (lines, images)
+    }
+
+    /// Render one top-level block of a document, along with its standalone image
+    pub fn render_top_level(&self, node: &AstNode) -> (Vec<Line<'static>>, Vec<ImagePlacement>) {
+        let mut lines = Vec::new();
+        let mut images = Vec::new();
+        let failed = |url: &str| {
+            self.failed_images
+                .is_some_and(|failed| failed.contains(url))
+        };
+        match standalone_image(node)
+            .filter(|(_, url, _)| self.image_rows > 0 && !self.conceal && !failed(url))
+        {
+            Some((alt, url, title)) => {
+                let alt = alt.iter().map(|node| node.to_plain_text()).collect();
+                self.reserve_image(alt, url, title, &mut lines, &mut images);
+            }
+            None => self.render_block(node, &mut lines),
+        }
+        (lines, images)
+    }
+
+    /// Render a top-level block that belongs under a heading of `heading_level`
+    ///
+    /// With `indent_by_heading` the block is wrapped to the narrower width and
+    /// indented; otherwise this is the same as [`Renderer::render_top_level`].
+    /// With `collapse_blank_lines` the block's own blank lines are collapsed too.
+    pub fn render_section_block(
+        &self,
+        node: &AstNode,
+        heading_level: u8,
+    ) -> (Vec<Line<'static>>, Vec<ImagePlacement>) {
+        let (lines, images) = self.render_indented_block(node, heading_level);
+        if !self.collapse_blank_lines || !images.is_empty() {
+            return (lines, images);
+        }
+        (collapse_blank_lines(lines), images)
+    }
+
+    fn render_indented_block(
+        &self,
+        node: &AstNode,
+        heading_level: u8,
+    ) -> (Vec<Line<'static>>, Vec<ImagePlacement>) {
+        let indent = if self.indent_by_heading {
+            heading_indent(node, heading_level).min(self.width.saturating_sub(1))
+        } else {
+            0
+        };
+        if indent == 0 {
+            return self.render_top_level(node);
+        }
+
+        let narrower = Renderer {
+            width: self.width - indent,
+            ..*self
+        };
+        let (lines, images) = narrower.render_top_level(node);
+        let lines = lines
+            .into_iter()
+            .map(|mut line| {
+                line.spans.insert(0, Span::raw(" ".repeat(indent)));
+                line
+            })
+            .collect();
+        (lines, images)
+    }
+
+    /// Add the fallback text for an image followed by blank rows to draw it over
+    fn reserve_image(
+        &self,
+        alt: String,
+        url: &str,
+        title: Option<&str>,
+        lines: &mut Vec<Line<'static>>,
+        images: &mut Vec<ImagePlacement>,
+    ) {
+        let mut segments = vec![Vec::new()];
+        self.push_image_label(&alt, title, url, Style::default(), &mut segments);
+        images.push(ImagePlacement {
+            line: lines.len(),
+            rows: self.image_rows,
+            alt,
+            url: url.to_string(),
+        });
+        lines.push(Line::from(segments.concat()));
+        lines.extend((1..self.image_rows).map(|_| Line::default()));
+    }
+
+    fn render_block(&self, node: &AstNode, lines: &mut Vec<Line<'static>>) {
+        let text_style = Style::default().tint(self.theme.text);
+
+        match node {
+            AstNode::Heading { level, content } => {
+                let style = Style::default()
+                    .tint(self.theme.heading(*level))
+                    .add_modifier(Modifier::BOLD);
+                let prefix = self.heading_prefix.text(*level);
+                // Wrapped lines line up with the text after the prefix
+                let hang = " ".repeat(text_width(&prefix));
+                for (index, segment) in self.inline_segments(content, style).into_iter().enumerate()
+                {
+                    let first = if index == 0 { &prefix } else { &hang };
+                    let first = vec![Span::styled(first.clone(), style)];
+                    let rest = vec![Span::raw(hang.clone())];
+                    lines.extend(wrap_spans(segment, self.width, first, rest));
+                }
+            }
+            AstNode::Paragraph { content } => {
+                let segments = self.inline_segments(content, text_style);
+                // Skip paragraphs that only hold line breaks
+                if segments.iter().all(|segment| segment.is_empty()) {
+                    return;
+                }
+                for segment in segments {
+                    lines.extend(wrap_spans(segment, self.width, Vec::new(), Vec::new()));
+                }
+            }
+            AstNode::List {
+                ordered,
+                start,
+                items,
+            } => self.render_list(*ordered, *start, items, text_style, lines),
+            AstNode::ListItem { content } => {
+                for segment in self.inline_segments(content, text_style) {
+                    lines.extend(wrap_spans(segment, self.width, Vec::new(), Vec::new()));
+                }
+            }
+            AstNode::BlockQuote { content } => self.render_quote(content, 1, lines),
+            AstNode::DefinitionList { items } => {
+                let term_style = text_style.add_modifier(Modifier::BOLD);
+                let indent = || vec![Span::raw("    ")];
+                for (term, definitions) in items {
+                    for segment in self.inline_segments(term, term_style) {
+                        lines.extend(wrap_spans(segment, self.width, Vec::new(), Vec::new()));
+                    }
+                    for definition in definitions {
+                        let content = match definition {
+                            AstNode::Paragraph { content } => content.as_slice(),
+                            other => std::slice::from_ref(other),
+                        };
+                        for segment in self.inline_segments(content, text_style) {
+                            lines.extend(wrap_spans(segment, self.width, indent(), indent()));
+                        }
+                    }
+                }
+            }
+            AstNode::CodeBlock { language, code, .. } => {
+                self.render_code_block(language.as_deref(), code, lines)
+            }
+            AstNode::BlockMath(math) => {
+                let style = Style::default()
+                    .tint(self.theme.link)
+                    .add_modifier(Modifier::ITALIC);
+                for math_line in math.lines() {
+                    lines.push(Line::from(Span::styled(format!("  {}", math_line), style)));
+                }
+            }
+            AstNode::HorizontalRule => {
+                lines.push(Line::from(Span::styled(
+                    "─".repeat(self.width),
+                    Style::default().tint(self.theme.passive),
+                )));
+            }
+            AstNode::Table { headers, rows, .. } => self.render_table(headers, rows, lines),
+            AstNode::FrontMatter { format, raw } => {
+                if self.show_frontmatter {
+                    let code = AstNode::CodeBlock {
+                        language: Some(format.name().to_string()),
+                        info: None,
+                        code: raw.clone(),
+                    };
+                    self.render_block(&code, lines);
+                }
+            }
+            AstNode::Document { .. } => lines.extend(self.render(node)),
+            inline => {
+                let segments = self.inline_segments(std::slice::from_ref(inline), text_style);
+                for segment in segments {
+                    lines.extend(wrap_spans(segment, self.width, Vec::new(), Vec::new()));
+                }
+            }
+        }
+    }
+
+    fn render_code_block(
+        &self,
+        language: Option<&str>,
+        code: &str,
+        lines: &mut Vec<Line<'static>>,
+    ) {
+        let border_style = Style::default().tint(self.theme.passive);
+        let style = Style::default()
+            .tint(self.theme.text)
+            .tint_bg(self.theme.code_block);
+        let boxed = self.code_block_border == CodeBlockBorder::Box;
+        // Columns taken by the border or margin on each side of the code
+        let (left, right) = if boxed { (2, 2) } else { (2, 0) };
+        let inner = self.width.saturating_sub(left + right);
+
+        if boxed {
+            let label = language.map(|l| format!(" {} ", l)).unwrap_or_default();
+            let rule = "─".repeat(self.width.saturating_sub(text_width(&label) + 3));
+            lines.push(Line::from(Span::styled(
+                format!("╭─{}{}╮", label, rule),
+                border_style,
+            )));
+        } else if let Some(language) = language {
+            lines.push(Line::from(Span::styled(
+                language.to_string(),
+                border_style.add_modifier(Modifier::ITALIC),
+            )));
+        }
+
+        for code_line in code.trim_end_matches('\n').split('\n') {
+            let pieces = if self.wrap_code {
+                split_at_width(code_line, inner.max(1))
+            } else {
+                vec![code_line.to_string()]
+            };
+            for piece in pieces {
+                let padding = inner.saturating_sub(text_width(&piece));
+                let text = format!("{}{}", piece, " ".repeat(padding));
+                lines.push(Line::from(match self.code_block_border {
+                    CodeBlockBorder::None => vec![Span::styled(format!("  {}", text), style)],
+                    CodeBlockBorder::Bar => vec![
+                        Span::styled("▎", border_style),
+                        Span::styled(format!(" {}", text), style),
+                    ],
+                    CodeBlockBorder::Box => vec![
+                        Span::styled("│", border_style),
+                        Span::styled(format!(" {} ", text), style),
+                        Span::styled("│", border_style),
+                    ],
+                }));
+            }
+        }
+
+        if boxed {
+            lines.push(Line::from(Span::styled(
+                format!("╰{}╯", "─".repeat(self.width.saturating_sub(2))),
+                border_style,
+            )));
+        }
+    }
+
+    fn render_table(
+        &self,
+        headers: &[AstNode],
+        rows: &[Vec<AstNode>],
+        lines: &mut Vec<Line<'static>>,
+    ) {
+        let columns = rows
+            .iter()
+            .map(|row| row.len())
+            .chain(std::iter::once(headers.len()))
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                std::iter::once(headers)
+                    .chain(rows.iter().map(|row| row.as_slice()))
+                    .map(|cells| self.inline_width(cell_content(cells, column)))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let border_style = Style::default().tint(self.theme.passive);
+        let row_line = |cells: &[AstNode], style: Style| {
+            let mut spans = Vec::new();
+            for (column, width) in widths.iter().enumerate() {
+                if column > 0 {
+                    spans.push(Span::styled(" │ ", border_style));
+                }
+                let content = cell_content(cells, column);
+                for segment in self.inline_segments(content, style) {
+                    spans.extend(segment);
+                }
+                let padding = width.saturating_sub(self.inline_width(content));
+                if padding > 0 {
+                    spans.push(Span::styled(" ".repeat(padding), style));
+                }
+            }
+            Line::from(spans)
+        };
+
+        let header_style = Style::default()
+            .tint(self.theme.text)
+            .add_modifier(Modifier::BOLD);
+        lines.push(row_line(headers, header_style));
+
+        let separator = widths
+            .iter()
+            .map(|width| "─".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("─┼─");
+        lines.push(Line::from(Span::styled(separator, border_style)));
+
+        let text_style = Style::default().tint(self.theme.text);
+        for row in rows {
+            lines.push(row_line(row, text_style));
+        }
+    }
+
+    /// Render a quote with one bar per nesting `level`, recursing into nested quotes
+    fn render_quote(&self, content: &[AstNode], level: usize, lines: &mut Vec<Line<'static>>) {
+        let bars = || {
+            vec![Span::styled(
+                "│ ".repeat(level),
+                Style::default().tint(self.theme.passive),
+            )]
+        };
+        let style = Style::default()
+            .tint(self.theme.passive)
+            .add_modifier(Modifier::ITALIC);
+
+        for group in content.split_inclusive(|node| matches!(node, AstNode::BlockQuote { .. })) {
+            let (inline, nested) = match group.split_last() {
+                Some((AstNode::BlockQuote { content }, inline)) => (inline, Some(content)),
+                _ => (group, None),
+            };
+            if !inline.is_empty() {
+                for segment in self.inline_segments(inline, style) {
+                    lines.extend(wrap_spans(segment, self.width, bars(), bars()));
+                }
+            }
+            if let Some(nested) = nested {
+                self.render_quote(nested, level + 1, lines);
+            }
+        }
+    }
+
+    /// Render list items, numbering ordered ones from `start` with right-aligned markers
+    fn render_list(
+        &self,
+        ordered: bool,
+        start: u32,
+        items: &[AstNode],
+        text_style: Style,
+        lines: &mut Vec<Line<'static>>,
+    ) {
+        let numbered = items
+            .iter()
+            .filter(|item| !matches!(item, AstNode::List { .. }))
+            .count();
+        let last = start as usize + numbered.saturating_sub(1);
+        let number_width = last.to_string().len();
+        let marker_width = if ordered { number_width + 2 } else { 2 };
+        let indent = " ".repeat(marker_width);
+        let marker_style = Style::default().tint(self.theme.link);
+
+        let mut number = start as usize;
+        for item in items {
+            // Nested lists sit under the item text and keep their own numbering
+            if let AstNode::List {
+                ordered,
+                start,
+                items,
+            } = item
+            {
+                let nested = Renderer {
+                    width: self.width.saturating_sub(marker_width).max(1),
+                    ..*self
+                };
+                let mut nested_lines = Vec::new();
+                nested.render_list(*ordered, *start, items, text_style, &mut nested_lines);
+                lines.extend(nested_lines.into_iter().map(|mut line| {
+                    line.spans.insert(0, Span::raw(indent.clone()));
+                    line
+                }));
+                continue;
+            }
+
+            let marker = if ordered {
+                format!("{:>width$}. ", number, width = number_width)
+            } else {
+                "• ".to_string()
+            };
+            number += 1;
+
+            let (content, checkbox) = match item {
+                AstNode::ListItem { content } => (content.as_slice(), ""),
+                AstNode::TaskListItem { checked, content } => {
+                    (content.as_slice(), checkbox(*checked))
+                }
+                other => (std::slice::from_ref(other), ""),
+            };
+            // Task text lines up after the checkbox
+            let hang = format!("{}{}", indent, " ".repeat(text_width(checkbox)));
+            for (j, segment) in self
+                .inline_segments(content, text_style)
+                .into_iter()
+                .enumerate()
+            {
+                let first = if j == 0 {
+                    let mut first = vec![Span::styled(marker.clone(), marker_style)];
+                    if !checkbox.is_empty() {
+                        first.push(Span::styled(checkbox, marker_style));
+                    }
+                    first
+                } else {
+                    vec![Span::raw(hang.clone())]
+                };
+                lines.extend(wrap_spans(
+                    segment,
+                    self.width,
+                    first,
+                    vec![Span::raw(hang.clone())],
+                ));
+            }
+        }
+    }
+
+    /// Convert inline nodes to spans, split into segments at line breaks
+    fn inline_segments(&self, nodes: &[AstNode], style: Style) -> Vec<Vec<Span<'static>>> {
+        let mut segments = vec![Vec::new()];
+        self.collect_inline(nodes, style, &mut segments);
+        segments
+    }
+
+    /// Width of inline nodes as this renderer draws them, including shown URLs
+    fn inline_width(&self, nodes: &[AstNode]) -> usize {
+        if !self.show_urls && !self.conceal && self.inline_code_style == InlineCodeStyle::Background
+        {
+            return display_width(nodes, self.show_image_urls);
+        }
+        self.inline_segments(nodes, Style::default())
+            .iter()
+            .flatten()
+            .map(|span| text_width(&span.content))
+            .sum()
+    }
+
+    /// Add the fallback label for an image, followed by its URL unless those are hidden
+    fn push_image_label(
+        &self,
+        alt: &str,
+        title: Option<&str>,
+        url: &str,
+        style: Style,
+        segments: &mut [Vec<Span<'static>>],
+    ) {
+        push_span(
+            segments,
+            Span::styled(
+                fallback_text(alt, title, url),
+                style
+                    .tint(self.theme.passive)
+                    .add_modifier(Modifier::ITALIC),
+            ),
+        );
+        if self.show_image_urls {
+            push_span(
+                segments,
+                Span::styled(image_url_suffix(url), style.tint(self.theme.link)),
+            );
+        }
+    }
+
+    /// Style for bold or italic text, using a color instead of `modifier` if modifiers are off
+    fn emphasis(&self, style: Style, modifier: Modifier) -> Style {
+        if self.text_modifiers {
+            style.add_modifier(modifier)
+        } else if modifier == Modifier::BOLD {
+            style.tint(self.theme.headings[0])
+        } else {
+            style.tint(self.theme.passive)
+        }
+    }
+
+    /// Collect the text of a link, then layer the link color and `link_style` over it
+    ///
+    /// Nested formatting such as bold keeps its modifiers and inline code its
+    /// background, but the link color wins over any color set inside the link.
+    fn collect_link_text(
+        &self,
+        text: &[AstNode],
+        style: Style,
+        link_style: Style,
+        segments: &mut Vec<Vec<Span<'static>>>,
+    ) {
+        let link_style = link_style.tint(self.theme.link);
+        let (first_segment, first_span) = (segments.len() - 1, segments.last().map_or(0, Vec::len));
+        self.collect_inline(text, style.patch(link_style), segments);
+        for (index, segment) in segments.iter_mut().enumerate().skip(first_segment) {
+            let start = if index == first_segment {
+                first_span
+            } else {
+                0
+            };
+            for span in segment.iter_mut().skip(start) {
+                span.style = span.style.patch(link_style);
+            }
+        }
+    }
+
+    fn collect_inline(
+        &self,
+        nodes: &[AstNode],
+        style: Style,
+        segments: &mut Vec<Vec<Span<'static>>>,
+    ) {
+        for node in nodes {
+            match node {
+                AstNode::LineBreak => segments.push(Vec::new()),
+                // Raw HTML isn't rendered, apart from `<br>` breaking the line
+                AstNode::Html(tag) if is_break_tag(tag) => segments.push(Vec::new()),
+                AstNode::Html(_) => {}
+                AstNode::Bold(children) => {
+                    self.collect_inline(children, self.emphasis(style, Modifier::BOLD), segments)
+                }
+                AstNode::Italic(children) => {
+                    self.collect_inline(children, self.emphasis(style, Modifier::ITALIC), segments)
+                }
+                AstNode::Strikethrough(children) => self.collect_inline(
+                    children,
+                    style.add_modifier(Modifier::CROSSED_OUT),
+                    segments,
+                ),
+                AstNode::Highlight(children) => {
+                    self.collect_inline(children, style.add_modifier(Modifier::REVERSED), segments)
+                }
+                AstNode::Link { text, .. } if self.conceal => {
+                    self.collect_link_text(text, style, Style::default(), segments)
+                }
+                AstNode::Link { text, url } => {
+                    let underline = Style::default().add_modifier(Modifier::UNDERLINED);
+                    self.collect_link_text(text, style, underline, segments);
+                    // Autolinks already show their URL as the text
+                    let label: String = text.iter().map(|node| node.to_plain_text()).collect();
+                    if self.show_urls && label != *url {
+                        push_span(
+                            segments,
+                            Span::styled(
+                                format!(" ({})", url),
+                                Style::default().tint(self.theme.passive),
+                            ),
+                        );
+                    }
+                }
+                AstNode::Image { alt, .. } if self.conceal => {
+                    push_span(
+                        segments,
+                        Span::styled(concealed_image_label(alt), style.tint(self.theme.passive)),
+                    );
+                }
+                AstNode::Image { alt, url, title } => {
+                    let alt: String = alt.iter().map(|node| node.to_plain_text()).collect();
+                    self.push_image_label(&alt, title.as_deref(), url, style, segments);
+                }
+                AstNode::InlineCode(code) => match self.inline_code_style {
+                    InlineCodeStyle::Background => push_span(
+                        segments,
+                        Span::styled(code.clone(), style.tint_bg(self.theme.code_block)),
+                    ),
+                    InlineCodeStyle::Backticks => {
+                        let tick = style.tint(self.theme.passive);
+                        push_span(segments, Span::styled("`", tick));
+                        push_span(segments, Span::styled(code.clone(), style));
+                        push_span(segments, Span::styled("`", tick));
+                    }
+                },
+                AstNode::InlineMath(math) => push_span(
+                    segments,
+                    Span::styled(
+                        math.clone(),
+                        style.tint(self.theme.link).add_modifier(Modifier::ITALIC),
+                    ),
+                ),
+                other => push_span(segments, Span::styled(other.to_plain_text(), style)),
+            }
+        }
+    }
+}
+
+/// The heading level in effect after `node`, given the level before it
+pub fn section_level(node: &AstNode, heading_level: u8) -> u8 {
+    match node {
+        AstNode::Heading { level, .. } => *level,
+        _ => heading_level,
+    }
+}
+
+/// Columns to indent a block by under a heading of `heading_level`
+///
+/// Headings line up with the content of their parent section.
+fn heading_indent(node: &AstNode, heading_level: u8) -> usize {
+    let depth = match node {
+        AstNode::Heading { level, .. } => level.saturating_sub(2),
+        _ => heading_level.saturating_sub(1),
+    };
+    depth as usize * 2
+}
+
+/// Render a markdown AST into lines at the given width
+pub fn render_markdown(ast: &AstNode, theme: &Theme, width: usize) -> Vec<Line<'static>> {
+    Renderer::new(theme, width).render(ast)
+}
+
+/// Split `text` into pieces at most `width` columns wide, keeping all whitespace
+fn split_at_width(text: &str, width: usize) -> Vec<String> {
+    let mut pieces = vec![String::new()];
+    let mut current_width = 0;
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if current_width + ch_width > width && current_width > 0 {
+            pieces.push(String::new());
+            current_width = 0;
+        }
+        pieces.last_mut().unwrap().push(ch);
+        current_width += ch_width;
+    }
+    pieces
+}
+
+/// Get the plain text of a rendered line
+pub fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// Measure how many terminal columns a string occupies
+pub fn text_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Measure how many terminal columns inline nodes occupy once rendered
+///
+/// This counts the text that is actually drawn, so markup such as backticks,
+/// emphasis markers and link targets doesn't contribute to the width. Image
+/// URLs only count when `show_image_urls` is set, as only then are they drawn.
+pub fn display_width(nodes: &[AstNode], show_image_urls: bool) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            AstNode::LineBreak | AstNode::Html(_) => 0,
+            AstNode::Bold(children)
+            | AstNode::Italic(children)
+            | AstNode::Strikethrough(children)
+            | AstNode::Highlight(children)
+            | AstNode::TableCell { content: children } => display_width(children, show_image_urls),
+            AstNode::Link { text, .. } => display_width(text, show_image_urls),
+            AstNode::Image { alt, url, title } => {
+                let alt: String = alt.iter().map(|node| node.to_plain_text()).collect();
+                let label = text_width(&fallback_text(&alt, title.as_deref(), url));
+                if show_image_urls {
+                    label + text_width(&image_url_suffix(url))
+                } else {
+                    label
+                }
+            }
+            AstNode::InlineCode(code) | AstNode::InlineMath(code) => text_width(code),
+            other => text_width(&other.to_plain_text()),
+        })
+        .sum()
+}
+
+/// Get the inline content of a table cell, empty if the row is short
+fn cell_content(cells: &[AstNode], column: usize) -> &[AstNode] {
+    match cells.get(column) {
+        Some(AstNode::TableCell { content }) => content,
+        Some(other) => std::slice::from_ref(other),
+        None => &[],
+    }
+}
+
+/// The URL shown after an image's label, if it has one
+fn image_url_suffix(url: &str) -> String {
+    if url.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", url)
+    }
+}
+
+/// The box drawn before a task list item's text
+pub fn checkbox(checked: bool) -> &'static str {
+    if checked {
+        "☑ "
+    } else {
+        "☐ "
+    }
+}
+
+/// Get the placeholder shown for an image in conceal mode
+fn concealed_image_label(alt: &[AstNode]) -> String {
+    let alt: String = alt.iter().map(|node| node.to_plain_text()).collect();
+    if alt.is_empty() {
+        "🖼".to_string()
+    } else {
+        format!("🖼 {}", alt)
+    }
+}
+
+/// Get the alt text, URL and title of a paragraph holding nothing but an image
+fn standalone_image(node: &AstNode) -> Option<(&[AstNode], &str, Option<&str>)> {
+    let AstNode::Paragraph { content } = node else {
+        return None;
+    };
+
+    let mut nodes = content
+        .iter()
+        .filter(|node| !matches!(node, AstNode::LineBreak));
+    match (nodes.next(), nodes.next()) {
+        (Some(AstNode::Image { alt, url, title }), None) => Some((alt, url, title.as_deref())),
+        _ => None,
+    }
+}
+
+/// Trim blank lines off both ends of `lines` and shorten runs of them to one
+///
+/// Lines with a background, like empty lines in code blocks, aren't blank.
+fn collapse_blank_lines(lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    let is_blank = |line: &Line| {
+        line.style.bg.is_none()
+            && line
+                .spans
+                .iter()
+                .all(|span| span.style.bg.is_none() && span.content.trim().is_empty())
+    };
+
+    let mut collapsed: Vec<Line<'static>> = Vec::with_capacity(lines.len());
+    for line in lines {
+        if is_blank(&line) && collapsed.last().map_or(true, is_blank) {
+            continue;
+        }
+        collapsed.push(line);
+    }
+    if collapsed.last().is_some_and(is_blank) {
+        collapsed.pop();
+    }
+    collapsed
+}
+
+/// Append a rendered top-level block, separating it from earlier output by a blank line
+pub fn append_block(
+    lines: &mut Vec<Line<'static>>,
+    images: &mut Vec<ImagePlacement>,
+    block_lines: Vec<Line<'static>>,
+    block_images: Vec<ImagePlacement>,
+) {
+    if block_lines.is_empty() {
+        return;
+    }
+    if !lines.is_empty() {
+        lines.push(Line::default());
+    }
+
+    let offset = lines.len();
+    images.extend(block_images.into_iter().map(|image| ImagePlacement {
+        line: image.line + offset,
+        ..image
+    }));
+    lines.extend(block_lines);
+}
+
+fn push_span(segments: &mut [Vec<Span<'static>>], span: Span<'static>) {
+    if span.content.is_empty() {
+        return;
+    }
+    if let Some(segment) = segments.last_mut() {
+        segment.push(span);
+    }
+}
+
+/// Word-wrap spans to `width`, prefixing the first and following lines
+fn wrap_spans(
+    spans: Vec<Span<'static>>,
+    width: usize,
+    first_prefix: Vec<Span<'static>>,
+    rest_prefix: Vec<Span<'static>>,
+) -> Vec<Line<'static>> {
+    let prefix_width =
+        |prefix: &[Span]| -> usize { prefix.iter().map(|span| text_width(&span.content)).sum() };
+
+    let mut lines = Vec::new();
+    let mut current = first_prefix.clone();
+    let mut current_width = prefix_width(&first_prefix);
+    let mut line_has_content = false;
+    let rest_width = prefix_width(&rest_prefix);
+
+    // Break spans into words and whitespace runs that keep their style
+    let mut pieces: Vec<(String, Style, bool)> = Vec::new();
+    for span in &spans {
+        let mut piece = String::new();
+        let mut piece_is_space = false;
+        for ch in span.content.chars() {
+            let is_space = ch.is_whitespace();
+            if !piece.is_empty() && is_space != piece_is_space {
+                pieces.push((std::mem::take(&mut piece), span.style, piece_is_space));
+            }
+            piece_is_space = is_space;
+            piece.push(if is_space { ' ' } else { ch });
+        }
+        if !piece.is_empty() {
+            pieces.push((piece, span.style, piece_is_space));
+        }
+    }
+
+    for (text, style, is_space) in pieces {
+        let piece_width = text_width(&text);
+
+        if is_space {
+            // Whitespace never starts a wrapped line
+            if line_has_content && current_width + piece_width <= width {
+                current.push(Span::styled(text, style));
+                current_width += piece_width;
+            }
+            continue;
+        }
+
+        if line_has_content && current_width + piece_width > width {
+            // Trailing whitespace isn't worth keeping at a wrap point
+            if current
+                .last()
+                .is_some_and(|span| span.content.trim().is_empty())
+            {
+                current.pop();
+            }
+            lines.push(Line::from(std::mem::replace(
+                &mut current,
+                rest_prefix.clone(),
+            )));
+            current_width = rest_width;
+            line_has_content = false;
+        }
+
+        // Hard-split words longer than the whole line
+        let mut remaining: Vec<char> = text.chars().collect();
+        while current_width + chars_width(&remaining) > width && current_width < width {
+            let mut take = 0;
+            let mut taken_width = 0;
+            while let Some(ch) = remaining.get(take) {
+                let ch_width = ch.width().unwrap_or(0);
+                if current_width + taken_width + ch_width > width {
+                    break;
+                }
+                taken_width += ch_width;
+                take += 1;
+            }
+            if take == 0 && !line_has_content {
+                // A single character wider than the line still has to go somewhere
+                take = 1;
+            }
+            let head: String = remaining.drain(..take).collect();
+            current.push(Span::styled(head, style));
+            lines.push(Line::from(std::mem::replace(
+                &mut current,
+                rest_prefix.clone(),
+            )));
+            current_width = rest_width;
+            line_has_content = false;
+        }
+        if !remaining.is_empty() {
+            current_width += chars_width(&remaining);
+            current.push(Span::styled(
+                remaining.into_iter().collect::<String>(),
+                style,
+            ));
+            line_has_content = true;
+        }
+    }
+
+    lines.push(Line::from(current));
+    lines
+}
+
+fn chars_width(chars: &[char]) -> usize {
+    chars.iter().map(|ch| ch.width().unwrap_or(0)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+
+    fn render_text(markdown: &str, width: usize) -> Vec<String> {
+        let ast = parse_markdown(markdown).unwrap();
+        render_markdown(&ast, &Theme::default(), width)
+            .iter()
+            .map(line_text)
+            .collect()
+    }
+
+    #[test]
+    fn test_render_blocks() {
+        let lines = render_text("# Title\n\nSome **bold** text\n\n- one\n- two", 40);
+        assert_eq!(
+            lines,
+            vec!["Title", "", "Some bold text", "", "• one", "• two"]
+        );
+    }
+
+    #[test]
+    fn test_inline_html_is_hidden_except_line_breaks() {
+        let lines = render_text("one<br>two <kbd>q</kbd>", 40);
+        assert_eq!(lines, vec!["one", "two q"]);
+    }
+
+    #[test]
+    fn test_text_modifiers_toggle() {
+        let ast = parse_markdown("**bold** and *italic*").unwrap();
+        let span_style = |lines: &[Line], text: &str| {
+            lines[0]
+                .spans
+                .iter()
+                .find(|span| span.content == text)
+                .unwrap()
+                .style
+        };
+        let theme = Theme::default();
+
+        let lines = Renderer::new(&theme, 40).render(&ast);
+        assert!(span_style(&lines, "bold")
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span_style(&lines, "italic")
+            .add_modifier
+            .contains(Modifier::ITALIC));
+
+        let lines = Renderer::new(&theme, 40)
+            .with_text_modifiers(false)
+            .render(&ast);
+        assert!(span_style(&lines, "bold").add_modifier.is_empty());
+        assert!(span_style(&lines, "italic").add_modifier.is_empty());
+    }
+
+    #[test]
+    fn test_heading_prefix() {
+        let ast = parse_markdown("### Usage").unwrap();
+        let theme = Theme::default();
+        let first_line = |prefix: HeadingPrefix| {
+            line_text(
+                &Renderer::new(&theme, 40)
+                    .with_heading_prefix(prefix)
+                    .render(&ast)[0],
+            )
+        };
+
+        assert_eq!(first_line(HeadingPrefix::None), "Usage");
+        assert_eq!(first_line(HeadingPrefix::Hash), "### Usage");
+        assert_eq!(first_line(HeadingPrefix::Bar), "▎▎▎ Usage");
+        assert_eq!(HeadingPrefix::from_setting("hash"), HeadingPrefix::Hash);
+        assert_eq!(HeadingPrefix::from_setting("none"), HeadingPrefix::None);
+    }
+
+    #[test]
+    fn test_inline_code_style() {
+        let ast = parse_markdown("Run `make` now").unwrap();
+        let theme = Theme::default();
+        let render = |style: InlineCodeStyle| {
+            Renderer::new(&theme, 40)
+                .with_inline_code_style(style)
+                .render(&ast)
+        };
+
+        assert_eq!(
+            line_text(&render(InlineCodeStyle::Background)[0]),
+            "Run make now"
+        );
+        let lines = render(InlineCodeStyle::Backticks);
+        assert_eq!(line_text(&lines[0]), "Run `make` now");
+        let code = lines[0].spans.iter().find(|s| s.content == "make").unwrap();
+        assert_eq!(code.style.bg, None);
+        assert_eq!(
+            InlineCodeStyle::from_setting("backticks"),
+            InlineCodeStyle::Backticks
+        );
+    }
+
+    #[test]
+    fn test_code_block_border() {
+        let ast = parse_markdown("```rust\nfn main() {}\n```").unwrap();
+        let theme = Theme::default();
+        let text = |border: CodeBlockBorder| -> Vec<String> {
+            Renderer::new(&theme, 20)
+                .with_code_block_border(border)
+                .render(&ast)
+                .iter()
+                .map(line_text)
+                .collect()
+        };
+
+        assert_eq!(
+            text(CodeBlockBorder::Box),
+            vec![
+                "╭─ rust ───────────╮",
+                "│ fn main() {}     │",
+                "╰──────────────────╯",
+            ]
+        );
+        assert_eq!(
+            text(CodeBlockBorder::Bar),
+            vec!["rust", "▎ fn main() {}      "]
+        );
+        assert_eq!(
+            text(CodeBlockBorder::None),
+            vec!["rust", "  fn main() {}      "]
+        );
+        assert_eq!(CodeBlockBorder::from_setting("box"), CodeBlockBorder::Box);
+    }
+
+    #[test]
+    fn test_collapse_blank_lines() {
+        let source = "First\n\n\n\nSecond\n<br>\n<br>\n<br>\nThird\n\n```\na\n\nb\n```";
+        let ast = parse_markdown(source).unwrap();
+        let theme = Theme::default();
+        let text = |collapse: bool| -> Vec<String> {
+            Renderer::new(&theme, 20)
+                .with_collapse_blank_lines(collapse)
+                .render(&ast)
+                .iter()
+                .map(|line| line_text(line).trim_end().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            text(true),
+            vec!["First", "", "Second", "", "Third", "", "  a", "", "  b"]
+        );
+        assert!(text(false).len() > text(true).len());
+    }
+
+    #[test]
+    fn test_wrap_code_splits_long_lines() {
+        let ast = parse_markdown("```\nabcdefghij\n```").unwrap();
+        let theme = Theme::default();
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines
+                .iter()
+                .map(|line| line_text(line).trim_end().to_string())
+                .collect()
+        };
+
+        let lines = Renderer::new(&theme, 6).render(&ast);
+        assert_eq!(text(lines), vec!["  abcdefghij"]);
+
+        let lines = Renderer::new(&theme, 6).with_wrap_code(true).render(&ast);
+        assert_eq!(text(lines), vec!["  abcd", "  efgh", "  ij"]);
+    }
+
+    #[test]
+    fn test_wraps_paragraphs() {
+        let lines = render_text("one two three four five", 10);
+        assert_eq!(lines, vec!["one two", "three four", "five"]);
+    }
+
+    #[test]
+    fn test_wraps_list_items_with_indent() {
+        let lines = render_text("- alpha beta gamma", 12);
+        assert_eq!(lines, vec!["• alpha beta", "  gamma"]);
+    }
+
+    #[test]
+    fn test_splits_long_words() {
+        let lines = render_text("abcdefghij", 4);
+        assert_eq!(lines, vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_render_table() {
+        let lines = render_text("| a | bb |\n|---|---|\n| ccc | d |", 40);
+        assert_eq!(lines, vec!["a   │ bb", "────┼───", "ccc │ d "]);
+    }
+
+    #[test]
+    fn test_display_width_ignores_markup() {
+        let code = vec![AstNode::InlineCode("abcd".to_string())];
+        let plain = vec![AstNode::Text("abcd".to_string())];
+        assert_eq!(display_width(&code, true), display_width(&plain, true));
+        assert_eq!(display_width(&code, true), 4);
+
+        let link = vec![AstNode::Link {
+            text: vec![AstNode::Bold(vec![AstNode::Text("docs".to_string())])],
+            url: "https://example.com".to_string(),
+        }];
+        assert_eq!(display_width(&link, true), 4);
+        assert_eq!(display_width(&[AstNode::Text("日本".to_string())], true), 4);
+    }
+
+    #[test]
+    fn test_display_width_counts_image_urls_only_when_shown() {
+        let image = vec![AstNode::Image {
+            alt: vec![AstNode::Text("logo".to_string())],
+            url: "a.png".to_string(),
+            title: None,
+        }];
+        let label = text_width(&fallback_text("logo", None, "a.png"));
+        assert_eq!(display_width(&image, false), label);
+        assert_eq!(display_width(&image, true), label + " (a.png)".len());
+    }
+
+    #[test]
+    fn test_table_aligns_inline_formatting() {
+        let lines = render_text("| `code` | **bold** |\n|---|---|\n| text | x |", 40);
+        assert_eq!(lines, vec!["code │ bold", "─────┼─────", "text │ x   "]);
+    }
+
+    #[test]
+    fn test_conceal_hides_urls_and_images() {
+        let ast = parse_markdown("See [docs](https://example.com) and ![logo](logo.png)").unwrap();
+        let theme = Theme::default();
+
+        let lines = Renderer::new(&theme, 80)
+            .with_show_urls(true)
+            .with_conceal(true)
+            .render(&ast);
+        assert_eq!(line_text(&lines[0]), "See docs and 🖼 logo");
+
+        let spans = &lines[0].spans;
+        let link: Vec<_> = spans
+            .iter()
+            .filter(|span| span.style.fg == Some(theme.link))
+            .collect();
+        assert_eq!(link.len(), 1);
+        assert_eq!(link[0].content, "docs");
+        let image: String = spans
+            .iter()
+            .filter(|span| span.style.fg == Some(theme.passive))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(image, "🖼 logo");
+    }
+
+    #[test]
+    fn test_nested_link_formatting_combines_styles() {
+        let theme = Theme::default();
+        let ast = parse_markdown("**[bold link](https://example.com)** and [`code`](x)").unwrap();
+        let lines = Renderer::new(&theme, 80).render(&ast);
+        assert_eq!(line_text(&lines[0]), "bold link and code");
+
+        let spans = &lines[0].spans;
+        let bold = spans.iter().find(|span| span.content == "bold").unwrap();
+        assert_eq!(bold.style.fg, Some(theme.link));
+        assert!(bold
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+
+        let code = spans.iter().find(|span| span.content == "code").unwrap();
+        assert_eq!(code.style.fg, Some(theme.link));
+        assert_eq!(code.style.bg, Some(theme.code_block));
+        assert!(code.style.add_modifier.contains(Modifier::UNDERLINED));
+
+        // Without modifiers bold is a color, which the link color overrides
+        let ast = parse_markdown("[**bold link**](https://example.com)").unwrap();
+        let lines = Renderer::new(&theme, 80)
+            .with_text_modifiers(false)
+            .render(&ast);
+        assert_eq!(lines[0].spans[0].style.fg, Some(theme.link));
+    }
+
+    #[test]
+    fn test_show_urls_appends_passive_url() {
+        let ast = parse_markdown("See [docs](https://example.com) now").unwrap();
+        let theme = Theme::default();
+
+        let lines = Renderer::new(&theme, 80).render(&ast);
+        assert_eq!(line_text(&lines[0]), "See docs now");
+
+        let lines = Renderer::new(&theme, 80).with_show_urls(true).render(&ast);
+        assert_eq!(line_text(&lines[0]), "See docs (https://example.com) now");
+
+        let spans = &lines[0].spans;
+        let text = spans.iter().find(|span| span.content == "docs").unwrap();
+        assert_eq!(text.style.fg, Some(theme.link));
+        assert!(text.style.add_modifier.contains(Modifier::UNDERLINED));
+        let url = spans
+            .iter()
+            .find(|span| span.content.contains("https://example.com"))
+            .unwrap();
+        assert_eq!(url.style.fg, Some(theme.passive));
+    }
+
+    #[test]
+    fn test_indent_by_heading() {
+        let ast = parse_markdown(
+            "# One\n\nunder one\n\n## Two\n\nunder two and wrapped\n\n# Three\n\nback",
+        )
+        .unwrap();
+        let theme = Theme::default();
+        let lines: Vec<String> = Renderer::new(&theme, 12)
+            .with_indent_by_heading(true)
+            .render(&ast)
+            .iter()
+            .map(line_text)
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "One",
+                "",
+                "under one",
+                "",
+                "Two",
+                "",
+                "  under two",
+                "  and",
+                "  wrapped",
+                "",
+                "Three",
+                "",
+                "back",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_images_fall_back_to_text() {
+        let lines = render_text("See ![logo](img/logo.png) here", 40);
+        assert_eq!(lines, vec!["See 🖼 logo (img/logo.png) here"]);
+    }
+
+    #[test]
+    fn test_image_title_and_hidden_url() {
+        let ast = parse_markdown("![logo](img/logo.png \"The logo\") and ![](img/a.png)").unwrap();
+        let theme = Theme::default();
+        let text = |show_image_urls: bool| -> Vec<String> {
+            Renderer::new(&theme, 80)
+                .with_show_image_urls(show_image_urls)
+                .render(&ast)
+                .iter()
+                .map(line_text)
+                .collect()
+        };
+
+        assert_eq!(
+            text(true),
+            vec!["🖼 logo — The logo (img/logo.png) and 🖼 a.png (img/a.png)"]
+        );
+        assert_eq!(text(false), vec!["🖼 logo — The logo and 🖼 a.png"]);
+    }
+
+    #[test]
+    fn test_reserves_rows_for_standalone_images() {
+        let ast = parse_markdown("# Intro\n![logo](img/logo.png)\n# Outro").unwrap();
+        let theme = Theme::default();
+        let (lines, images) = Renderer::new(&theme, 40)
+            .with_image_rows(3)
+            .render_with_images(&ast);
+
+        let text: Vec<String> = lines.iter().map(line_text).collect();
+        assert_eq!(
+            text,
+            vec!["Intro", "", "🖼 logo (img/logo.png)", "", "", "", "Outro"]
+        );
+        assert_eq!(
+            images,
+            vec![ImagePlacement {
+                line: 2,
+                rows: 3,
+                alt: "logo".to_string(),
+                url: "img/logo.png".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_wraps_wide_characters_by_width() {
+        let lines = render_text("日本語テキスト", 6);
+        assert_eq!(lines, vec!["日本語", "テキス", "ト"]);
+    }
+
+    #[test]
+    fn test_ordered_list_numbers_from_start() {
+        use ratatui::{backend::TestBackend, widgets::Paragraph, Terminal};
+
+        let ast = parse_markdown("9. nine\n10. ten and more").unwrap();
+        let lines = render_markdown(&ast, &Theme::default(), 12);
+
+        let mut terminal = Terminal::new(TestBackend::new(12, 3)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(Paragraph::new(lines), frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+
+        assert_eq!(row(0), " 9. nine");
+        assert_eq!(row(1), "10. ten and");
+        assert_eq!(row(2), "    more");
+    }
+
+    #[test]
+    fn test_nested_list_restarts_numbering() {
+        let item = |text: &str| AstNode::ListItem {
+            content: vec![AstNode::Text(text.to_string())],
+        };
+        let list = AstNode::List {
+            ordered: true,
+            start: 1,
+            items: vec![
+                item("a"),
+                AstNode::List {
+                    ordered: true,
+                    start: 1,
+                    items: vec![item("x"), item("y")],
+                },
+                item("b"),
+            ],
+        };
+
+        let lines: Vec<String> = render_markdown(&list, &Theme::default(), 20)
+            .iter()
+            .map(line_text)
+            .collect();
+        assert_eq!(lines, vec!["1. a", "   1. x", "   2. y", "2. b"]);
+    }
+
+    #[test]
+    fn test_nested_blockquote_bars() {
+        use ratatui::{backend::TestBackend, widgets::Paragraph, Terminal};
+
+        let ast = parse_markdown("> outer **bold**\n> > inner\n> back").unwrap();
+        let lines = render_markdown(&ast, &Theme::default(), 30);
+
+        let mut terminal = Terminal::new(TestBackend::new(30, 3)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(Paragraph::new(lines.clone()), frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        };
+
+        assert_eq!(row(0), "│ outer bold");
+        assert_eq!(row(1), "│ │ inner");
+        assert_eq!(row(2), "│ back");
+
+        // Inline formatting still applies inside the quote
+        assert!(
+            lines[0]
+                .spans
+                .iter()
+                .any(|span| span.content == "bold"
+                    && span.style.add_modifier.contains(Modifier::BOLD))
+        );
+    }
+}
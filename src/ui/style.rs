@@ -0,0 +1,70 @@
+use ratatui::style::{Color, Style};
+use std::cell::Cell;
+
+thread_local! {
+    // Rendering happens on the UI thread, so a thread-local keeps tests independent
+    static NO_COLOR: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Enable or disable all colored output
+pub fn set_no_color(no_color: bool) {
+    NO_COLOR.with(|flag| flag.set(no_color));
+}
+
+/// Check whether colored output is disabled
+pub fn no_color() -> bool {
+    NO_COLOR.with(|flag| flag.get())
+}
+
+/// Combine the `--no-color` flag with the `NO_COLOR` environment convention
+pub fn no_color_requested(flag: bool, env_value: Option<std::ffi::OsString>) -> bool {
+    flag || env_value.is_some_and(|value| !value.is_empty())
+}
+
+/// Color setters that become no-ops when colored output is disabled
+pub trait StyleExt {
+    fn tint(self, color: Color) -> Self;
+    fn tint_bg(self, color: Color) -> Self;
+}
+
+impl StyleExt for Style {
+    fn tint(self, color: Color) -> Self {
+        if no_color() {
+            self
+        } else {
+            self.fg(color)
+        }
+    }
+
+    fn tint_bg(self, color: Color) -> Self {
+        if no_color() {
+            self
+        } else {
+            self.bg(color)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tint_respects_no_color() {
+        set_no_color(false);
+        assert_eq!(Style::default().tint(Color::Red).fg, Some(Color::Red));
+
+        set_no_color(true);
+        assert_eq!(Style::default().tint(Color::Red), Style::default());
+        assert_eq!(Style::default().tint_bg(Color::Red), Style::default());
+        set_no_color(false);
+    }
+
+    #[test]
+    fn test_no_color_requested() {
+        assert!(no_color_requested(true, None));
+        assert!(no_color_requested(false, Some("1".into())));
+        assert!(!no_color_requested(false, Some("".into())));
+        assert!(!no_color_requested(false, None));
+    }
+}
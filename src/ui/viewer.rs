@@ -0,0 +1,1793 @@
+use crate::clipboard::{Clipboard, SystemClipboard};
+use crate::error::Result;
+use crate::images::{self, ImageProtocol};
+use crate::markdown_parser::tasks::{find_task_markers, toggle_task};
+use crate::markdown_parser::{
+    generate_toc, parse_markdown_or_default, parse_markdown_or_default_with, AstNode, TocEntry,
+};
+use crate::ui::components::StatusLine;
+use crate::ui::renderer::{
+    append_block, checkbox, line_text, section_level, CodeBlockBorder, HeadingPrefix,
+    ImagePlacement, InlineCodeStyle, Renderer, Theme,
+};
+use crate::ui::style::{self, StyleExt};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use unicode_width::UnicodeWidthChar;
+
+/// Number of terminal rows reserved for each inline image
+const IMAGE_ROWS: usize = 12;
+
+/// Lines laid out below the visible window so short scrolls need no new layout
+const LAYOUT_BUFFER: usize = 50;
+
+/// Columns moved by each horizontal scroll key press
+const HSCROLL_STEP: usize = 4;
+
+/// Widest the outline sidebar gets, including its border
+const OUTLINE_WIDTH: u16 = 32;
+
+/// An image drawn on screen at a terminal position
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DrawnImage {
+    x: u16,
+    y: u16,
+    url: String,
+}
+
+/// An image being loaded in the background, or its outcome
+enum ImageLoad {
+    Loading,
+    /// The escape sequence that draws the image
+    Ready(String),
+    Failed,
+}
+
+/// A search match inside the rendered lines, as char offsets within a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The document's headings listed beside it, with one selected
+struct Outline {
+    entries: Vec<TocEntry>,
+    selected: usize,
+}
+
+/// Scrollable view of a rendered markdown document
+pub struct MarkdownViewer {
+    path: PathBuf,
+    ast: AstNode,
+    /// Markdown the document was parsed from, when it didn't come from `path`
+    source: Option<String>,
+    /// Whether `path` is a file on disk that toggling a task may rewrite
+    editable: bool,
+    /// Whether re-parsing after a task toggle fails on unclosed emphasis
+    strict_emphasis: bool,
+    theme: Theme,
+    max_width: usize,
+    show_urls: bool,
+    indent_by_heading: bool,
+    text_modifiers: bool,
+    wrap_code: bool,
+    show_frontmatter: bool,
+    heading_prefix: HeadingPrefix,
+    /// Show links as their text only and images as placeholders
+    conceal: bool,
+    inline_code_style: InlineCodeStyle,
+    code_block_border: CodeBlockBorder,
+    collapse_blank_lines: bool,
+    show_image_urls: bool,
+    /// Lines moved by each `j`/`k` or arrow key press
+    scroll_step: usize,
+    rendered_width: usize,
+    /// Number of top-level blocks rendered into `lines` so far
+    laid_out_blocks: usize,
+    /// Level of the last heading laid out, 0 before the first one
+    heading_level: u8,
+    /// First rendered line of each top-level block laid out so far
+    block_starts: Vec<usize>,
+    outline: Option<Outline>,
+    lines: Vec<Line<'static>>,
+    plain_lines: Vec<String>,
+    scroll: usize,
+    /// The line the reader is on, moved by scrolling and jumps
+    current_line: usize,
+    highlight_current_line: bool,
+    /// Columns scrolled to the right in lines too wide for the viewport
+    hscroll: usize,
+    viewport_height: usize,
+    query: String,
+    search_input: Option<String>,
+    matches: Vec<SearchMatch>,
+    current_match: Option<usize>,
+    /// Index of the selected task and where its checkbox is drawn
+    selected_task: Option<(usize, SearchMatch)>,
+    last_key_was_g: bool,
+    should_quit: bool,
+    should_close: bool,
+    reload_requested: bool,
+    message: Option<String>,
+    image_protocol: Option<ImageProtocol>,
+    images: Vec<ImagePlacement>,
+    image_cache: HashMap<String, ImageLoad>,
+    /// URLs of images that couldn't be shown, laid out as their fallback text
+    failed_images: HashSet<String>,
+    /// Encoded escape sequences, or `None`, sent back by image loading threads
+    image_sender: Sender<(String, Option<String>)>,
+    image_receiver: Receiver<(String, Option<String>)>,
+    drawn_images: Vec<DrawnImage>,
+    content_area: Rect,
+    clipboard: Box<dyn Clipboard>,
+}
+
+impl MarkdownViewer {
+    pub fn new(path: &Path, content: &str, theme: Theme, max_width: usize) -> Self {
+        let mut viewer = Self::with_ast(path, parse_markdown_or_default(content), theme, max_width);
+        viewer.source = Some(content.to_string());
+        viewer
+    }
+
+    /// Show an already built document under the name `path`
+    pub fn with_ast(path: &Path, ast: AstNode, theme: Theme, max_width: usize) -> Self {
+        let (image_sender, image_receiver) = mpsc::channel();
+        let mut viewer = Self {
+            path: path.to_path_buf(),
+            ast,
+            source: None,
+            editable: false,
+            strict_emphasis: false,
+            theme,
+            max_width,
+            show_urls: false,
+            indent_by_heading: false,
+            text_modifiers: true,
+            wrap_code: false,
+            show_frontmatter: false,
+            heading_prefix: HeadingPrefix::None,
+            conceal: false,
+            inline_code_style: InlineCodeStyle::Background,
+            code_block_border: CodeBlockBorder::None,
+            collapse_blank_lines: false,
+            show_image_urls: true,
+            scroll_step: 1,
+            rendered_width: 0,
+            laid_out_blocks: 0,
+            heading_level: 0,
+            block_starts: Vec::new(),
+            outline: None,
+            lines: Vec::new(),
+            plain_lines: Vec::new(),
+            scroll: 0,
+            current_line: 0,
+            highlight_current_line: false,
+            hscroll: 0,
+            viewport_height: 1,
+            query: String::new(),
+            search_input: None,
+            matches: Vec::new(),
+            current_match: None,
+            selected_task: None,
+            last_key_was_g: false,
+            should_quit: false,
+            should_close: false,
+            reload_requested: false,
+            message: None,
+            image_protocol: None,
+            images: Vec::new(),
+            image_cache: HashMap::new(),
+            failed_images: HashSet::new(),
+            image_sender,
+            image_receiver,
+            drawn_images: Vec::new(),
+            content_area: Rect::default(),
+            clipboard: Box::new(SystemClipboard),
+        };
+        viewer.rerender(max_width);
+        viewer
+    }
+
+    /// Read and render a markdown file
+    pub fn open(path: &Path, theme: Theme, max_width: usize) -> Result<Self> {
+        let (content, _) = crate::search::markdown::read_lossy(path)?;
+        Ok(Self::new(path, &content, theme, max_width).with_editable(true))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// Whether the user asked to leave the viewer
+    pub fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll
+    }
+
+    /// Number of lines laid out so far; call `ensure_layout` first to count the whole document
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Draw standalone images inline using `protocol`, if the terminal supports one
+    pub fn with_image_protocol(mut self, protocol: Option<ImageProtocol>) -> Self {
+        self.image_protocol = protocol;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Show each link's URL after its text
+    pub fn with_show_urls(mut self, show_urls: bool) -> Self {
+        self.show_urls = show_urls;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Indent each section by its heading level
+    pub fn with_indent_by_heading(mut self, indent_by_heading: bool) -> Self {
+        self.indent_by_heading = indent_by_heading;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Show bold and italic text with terminal modifiers rather than colors only
+    pub fn with_text_modifiers(mut self, text_modifiers: bool) -> Self {
+        self.text_modifiers = text_modifiers;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Wrap long code lines instead of scrolling them sideways
+    pub fn with_wrap_code(mut self, wrap_code: bool) -> Self {
+        self.wrap_code = wrap_code;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Show frontmatter as a code block at the top instead of hiding it
+    pub fn with_show_frontmatter(mut self, show_frontmatter: bool) -> Self {
+        self.show_frontmatter = show_frontmatter;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Mark heading levels with `prefix` as well as color
+    pub fn with_heading_prefix(mut self, prefix: HeadingPrefix) -> Self {
+        self.heading_prefix = prefix;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Start with links and images concealed behind their labels
+    pub fn with_conceal(mut self, conceal: bool) -> Self {
+        self.conceal = conceal;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Set inline code apart with a background or with backticks
+    pub fn with_inline_code_style(mut self, style: InlineCodeStyle) -> Self {
+        self.inline_code_style = style;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Frame code blocks with `border`
+    pub fn with_code_block_border(mut self, border: CodeBlockBorder) -> Self {
+        self.code_block_border = border;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Leave at most one blank line between blocks
+    pub fn with_collapse_blank_lines(mut self, collapse_blank_lines: bool) -> Self {
+        self.collapse_blank_lines = collapse_blank_lines;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Show each image's URL after its alt text when it isn't drawn
+    pub fn with_show_image_urls(mut self, show_image_urls: bool) -> Self {
+        self.show_image_urls = show_image_urls;
+        self.rerender(self.rendered_width.max(1));
+        self
+    }
+
+    /// Draw the current line on the theme's `current_line` background
+    pub fn with_highlight_current_line(mut self, highlight: bool) -> Self {
+        self.highlight_current_line = highlight;
+        self
+    }
+
+    /// Move `step` lines at a time with `j`/`k` and the arrow keys
+    pub fn with_scroll_step(mut self, step: usize) -> Self {
+        self.scroll_step = step.max(1);
+        self
+    }
+
+    /// Switch between concealed and full links and images
+    pub fn toggle_conceal(&mut self) {
+        self.conceal = !self.conceal;
+        self.rerender(self.rendered_width.max(1));
+        self.scroll = self.scroll.min(self.max_scroll());
+        self.message = Some(if self.conceal {
+            "Concealing link URLs and images".to_string()
+        } else {
+            "Showing link URLs and images".to_string()
+        });
+    }
+
+    /// Allow toggling tasks to write back to the file at `path`
+    pub fn with_editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+
+    /// Re-parse toggled files with unclosed emphasis as an error, as `[settings] strict_emphasis` asks
+    pub fn with_strict_emphasis(mut self, strict_emphasis: bool) -> Self {
+        self.strict_emphasis = strict_emphasis;
+        self
+    }
+
+    /// Copy text with `clipboard` instead of the system clipboard
+    pub fn with_clipboard(mut self, clipboard: Box<dyn Clipboard>) -> Self {
+        self.clipboard = clipboard;
+        self
+    }
+
+    /// Check and reset whether the user asked to reload the config
+    pub fn take_reload_request(&mut self) -> bool {
+        std::mem::take(&mut self.reload_requested)
+    }
+
+    /// Switch to a new theme, re-rendering the document
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.rerender(self.rendered_width.max(1));
+    }
+
+    /// Show a message in the status line until the next key press
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
+    /// Replace the document, e.g. after the file changed, keeping the scroll position
+    pub fn set_ast(&mut self, ast: AstNode) {
+        self.ast = ast;
+        self.rerender(self.rendered_width.max(1));
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    /// Change the maximum render width, re-wrapping the document
+    pub fn set_max_width(&mut self, width: usize) {
+        self.max_width = width;
+        self.rerender(width);
+    }
+
+    /// Set the number of visible lines, e.g. before handling input in tests
+    pub fn set_viewport_height(&mut self, height: usize) {
+        self.viewport_height = height.max(1);
+        self.ensure_lines(self.scroll + self.viewport_height + LAYOUT_BUFFER);
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    /// Drop the laid out lines and lay out the visible part of the document again
+    fn rerender(&mut self, width: usize) {
+        self.lines.clear();
+        self.plain_lines.clear();
+        self.images.clear();
+        self.laid_out_blocks = 0;
+        self.heading_level = 0;
+        self.block_starts.clear();
+        self.rendered_width = width;
+        self.ensure_lines(self.scroll + self.viewport_height + LAYOUT_BUFFER);
+
+        // Match positions depend on wrapping, so recompute them
+        if !self.query.is_empty() {
+            self.ensure_layout();
+            let current = self.current_match;
+            self.matches = find_matches(&self.plain_lines, &self.query);
+            self.current_match = current
+                .filter(|_| !self.matches.is_empty())
+                .map(|index| index.min(self.matches.len() - 1));
+        }
+        if let Some((index, _)) = self.selected_task {
+            self.selected_task = self.task_checkbox(index).map(|m| (index, m));
+        }
+    }
+
+    /// Whether every top-level block has been laid out
+    fn is_fully_laid_out(&self) -> bool {
+        self.laid_out_blocks >= top_level_blocks(&self.ast).len()
+    }
+
+    /// Lay out blocks until there are at least `count` lines or the document ends
+    fn ensure_lines(&mut self, count: usize) {
+        while self.lines.len() < count && !self.is_fully_laid_out() {
+            self.layout_next_block();
+        }
+    }
+
+    /// Lay out the rest of the document
+    pub fn ensure_layout(&mut self) {
+        self.ensure_lines(usize::MAX);
+    }
+
+    fn layout_next_block(&mut self) {
+        let image_rows = if self.image_protocol.is_some() {
+            IMAGE_ROWS
+        } else {
+            0
+        };
+        let Some(block) = top_level_blocks(&self.ast).get(self.laid_out_blocks) else {
+            return;
+        };
+        self.heading_level = section_level(block, self.heading_level);
+        let (block_lines, block_images) = Renderer::new(&self.theme, self.rendered_width)
+            .with_image_rows(image_rows)
+            .with_failed_images(&self.failed_images)
+            .with_show_urls(self.show_urls)
+            .with_indent_by_heading(self.indent_by_heading)
+            .with_text_modifiers(self.text_modifiers)
+            .with_wrap_code(self.wrap_code)
+            .with_show_frontmatter(self.show_frontmatter)
+            .with_heading_prefix(self.heading_prefix)
+            .with_conceal(self.conceal)
+            .with_inline_code_style(self.inline_code_style)
+            .with_code_block_border(self.code_block_border)
+            .with_collapse_blank_lines(self.collapse_blank_lines)
+            .with_show_image_urls(self.show_image_urls)
+            .render_section_block(block, self.heading_level);
+
+        let start = self.lines.len();
+        append_block(&mut self.lines, &mut self.images, block_lines, block_images);
+        self.plain_lines
+            .extend(self.lines[start..].iter().map(line_text));
+        // Skip the blank line put between blocks
+        let separated = start > 0 && self.lines.len() > start;
+        self.block_starts.push(start + usize::from(separated));
+        self.laid_out_blocks += 1;
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.viewport_height)
+    }
+
+    pub fn scroll_down(&mut self, amount: usize) {
+        self.ensure_lines(self.scroll + amount + self.viewport_height + LAYOUT_BUFFER);
+        self.scroll = (self.scroll + amount).min(self.max_scroll());
+        self.move_current_line_to(self.current_line + amount);
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.scroll = self.scroll.saturating_sub(amount);
+        self.move_current_line_to(self.current_line.saturating_sub(amount));
+    }
+
+    /// Index of the line the reader is on
+    pub fn current_line(&self) -> usize {
+        self.current_line
+    }
+
+    /// Make `line` the current line, kept within the document and the visible window
+    ///
+    /// Scrolling moves the current line along with the view, so it only moves
+    /// within the window once the view can't scroll any further.
+    fn move_current_line_to(&mut self, line: usize) {
+        let last_visible = (self.scroll + self.viewport_height)
+            .min(self.lines.len())
+            .saturating_sub(1);
+        self.current_line = line.clamp(self.scroll, last_visible.max(self.scroll));
+    }
+
+    /// Columns scrolled sideways in lines wider than the viewport
+    pub fn hscroll_offset(&self) -> usize {
+        self.hscroll
+    }
+
+    /// Scroll wide lines on screen to the right, stopping once the widest one ends
+    pub fn scroll_right(&mut self, amount: usize) {
+        let limit = max_hscroll(self.widest_visible_line(), self.rendered_width);
+        self.hscroll = (self.hscroll + amount).min(limit);
+    }
+
+    pub fn scroll_left(&mut self, amount: usize) {
+        self.hscroll = self.hscroll.saturating_sub(amount);
+    }
+
+    /// Width of the widest line in the visible window
+    fn widest_visible_line(&self) -> usize {
+        let end = (self.scroll + self.viewport_height).min(self.lines.len());
+        self.lines[self.scroll.min(end)..end]
+            .iter()
+            .map(Line::width)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Show or hide the outline, selecting the section at the top of the view
+    pub fn toggle_outline(&mut self) {
+        if self.outline.take().is_some() {
+            return;
+        }
+        let entries = generate_toc(&self.ast);
+        if entries.is_empty() {
+            self.message = Some("No headings in this document".to_string());
+            return;
+        }
+        let selected = entries
+            .iter()
+            .rposition(|entry| {
+                self.block_starts
+                    .get(entry.block)
+                    .is_some_and(|&line| line <= self.scroll)
+            })
+            .unwrap_or(0);
+        self.outline = Some(Outline { entries, selected });
+    }
+
+    pub fn is_outline_open(&self) -> bool {
+        self.outline.is_some()
+    }
+
+    /// Scroll so the heading of top-level block `block` is the first visible line
+    pub fn jump_to_block(&mut self, block: usize) {
+        let Some(line) = self.block_start(block) else {
+            return;
+        };
+        self.ensure_lines(line + self.viewport_height + LAYOUT_BUFFER);
+        self.scroll = line.min(self.max_scroll());
+        self.move_current_line_to(line);
+    }
+
+    /// First rendered line of top-level block `block`, laying out the document up to it
+    fn block_start(&mut self, block: usize) -> Option<usize> {
+        while self.laid_out_blocks <= block && !self.is_fully_laid_out() {
+            self.layout_next_block();
+        }
+        self.block_starts.get(block).copied()
+    }
+
+    /// Scroll to the first heading below the top of the view, staying put after the last one
+    pub fn next_heading(&mut self) {
+        for entry in generate_toc(&self.ast) {
+            if self
+                .block_start(entry.block)
+                .is_some_and(|line| line > self.scroll)
+            {
+                self.jump_to_block(entry.block);
+                return;
+            }
+        }
+    }
+
+    /// Scroll to the last heading above the top of the view, staying put before the first one
+    pub fn previous_heading(&mut self) {
+        let target = generate_toc(&self.ast).into_iter().rev().find(|entry| {
+            self.block_starts
+                .get(entry.block)
+                .is_some_and(|&line| line < self.scroll)
+        });
+        if let Some(entry) = target {
+            self.jump_to_block(entry.block);
+        }
+    }
+
+    /// Handle a key while the outline is open, returning false for keys it leaves alone
+    fn handle_outline_key(&mut self, key: KeyEvent) -> bool {
+        let Some(outline) = self.outline.as_mut() else {
+            return false;
+        };
+        let last = outline.entries.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Down | KeyCode::Char('j') => {
+                outline.selected = (outline.selected + 1).min(last)
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                outline.selected = outline.selected.saturating_sub(1)
+            }
+            KeyCode::Char('g') => {
+                if self.last_key_was_g {
+                    outline.selected = 0;
+                    self.last_key_was_g = false;
+                } else {
+                    self.last_key_was_g = true;
+                }
+                return true;
+            }
+            KeyCode::Char('G') => outline.selected = last,
+            KeyCode::Enter => {
+                let block = outline
+                    .entries
+                    .get(outline.selected)
+                    .map(|entry| entry.block);
+                self.outline = None;
+                if let Some(block) = block {
+                    self.jump_to_block(block);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('o') => self.outline = None,
+            _ => return false,
+        }
+        self.last_key_was_g = false;
+        true
+    }
+
+    /// Search the rendered text, jumping to the first match at or below the top line
+    pub fn search(&mut self, query: &str) {
+        self.ensure_layout();
+        self.query = query.to_string();
+        self.matches = find_matches(&self.plain_lines, query);
+
+        let first = self.matches.iter().position(|m| m.line >= self.scroll).or(
+            if self.matches.is_empty() {
+                None
+            } else {
+                Some(0)
+            },
+        );
+        self.current_match = first;
+        self.scroll_to_current_match();
+    }
+
+    /// Clear the search query and highlights
+    pub fn clear_search(&mut self) {
+        self.query.clear();
+        self.matches.clear();
+        self.current_match = None;
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self) -> &[SearchMatch] {
+        &self.matches
+    }
+
+    pub fn current_match(&self) -> Option<SearchMatch> {
+        self.current_match.map(|index| self.matches[index])
+    }
+
+    /// Jump to the next match, wrapping around at the end
+    pub fn next_match(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let next = self.current_match.map(|index| index + 1).unwrap_or(0);
+        self.current_match = Some(next % self.matches.len());
+        self.scroll_to_current_match();
+    }
+
+    /// Jump to the previous match, wrapping around at the start
+    pub fn previous_match(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let count = self.matches.len();
+        let previous = self
+            .current_match
+            .map(|index| (index + count - 1) % count)
+            .unwrap_or(count - 1);
+        self.current_match = Some(previous);
+        self.scroll_to_current_match();
+    }
+
+    fn scroll_to_current_match(&mut self) {
+        let Some(current) = self.current_match() else {
+            return;
+        };
+
+        let visible = self.scroll..self.scroll + self.viewport_height;
+        if !visible.contains(&current.line) {
+            // Keep some context above the match
+            self.scroll = current
+                .line
+                .saturating_sub(self.viewport_height / 3)
+                .min(self.max_scroll());
+        }
+        self.move_current_line_to(current.line);
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
+        self.message = None;
+
+        if let Some(input) = self.search_input.as_mut() {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    if input.pop().is_none() {
+                        self.search_input = None;
+                    }
+                }
+                KeyCode::Enter => {
+                    let query = self.search_input.take().unwrap_or_default();
+                    if query.is_empty() {
+                        self.clear_search();
+                    } else {
+                        self.search(&query);
+                    }
+                }
+                KeyCode::Esc => self.search_input = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if self.handle_outline_key(key) {
+            return Ok(());
+        }
+
+        let page = self.viewport_height.saturating_sub(1).max(1);
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true
+            }
+            KeyCode::Esc => {
+                if self.selected_task.is_some() {
+                    self.selected_task = None;
+                } else if self.query.is_empty() {
+                    self.should_close = true;
+                } else {
+                    self.clear_search();
+                }
+            }
+            KeyCode::Char('/') => self.search_input = Some(String::new()),
+            KeyCode::Char('n') => self.next_match(),
+            KeyCode::Char('N') => self.previous_match(),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(self.scroll_step),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(self.scroll_step),
+            KeyCode::Left | KeyCode::Char('h') => self.scroll_left(HSCROLL_STEP),
+            KeyCode::Right | KeyCode::Char('l') => self.scroll_right(HSCROLL_STEP),
+            KeyCode::Char(' ') if self.selected_task.is_some() => self.toggle_selected_task(),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_down(page),
+            KeyCode::Tab => self.select_task(true),
+            KeyCode::BackTab => self.select_task(false),
+            KeyCode::PageUp => self.scroll_up(page),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_down(page / 2)
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_up(page / 2)
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_down(page)
+            }
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_up(page)
+            }
+            KeyCode::Char('g') => {
+                if self.last_key_was_g {
+                    self.scroll = 0;
+                    self.move_current_line_to(0);
+                    self.last_key_was_g = false;
+                } else {
+                    self.last_key_was_g = true;
+                }
+                return Ok(());
+            }
+            KeyCode::Char('G') => {
+                self.ensure_layout();
+                self.scroll = self.max_scroll();
+                self.move_current_line_to(self.lines.len());
+            }
+            KeyCode::Char('R') => self.reload_requested = true,
+            KeyCode::Char('o') => self.toggle_outline(),
+            KeyCode::Char(']') => self.next_heading(),
+            KeyCode::Char('[') => self.previous_heading(),
+            KeyCode::Char('c') => self.toggle_conceal(),
+            KeyCode::Char('Y') => self.copy_document(false),
+            KeyCode::Char('M') => self.copy_document(true),
+            _ => {}
+        }
+
+        self.last_key_was_g = false;
+        Ok(())
+    }
+
+    /// Where the checkbox of the `index`th task list item is drawn
+    ///
+    /// Tasks are counted in the AST, so only the blocks up to the task's own
+    /// are laid out, and checkbox characters in ordinary text or code are
+    /// never taken for a task.
+    fn task_checkbox(&mut self, index: usize) -> Option<SearchMatch> {
+        let mut remaining = index;
+        let block = top_level_blocks(&self.ast).iter().position(|block| {
+            let count = task_count(block);
+            if remaining < count {
+                return true;
+            }
+            remaining -= count;
+            false
+        })?;
+
+        while self.laid_out_blocks <= block && !self.is_fully_laid_out() {
+            self.layout_next_block();
+        }
+        let start = *self.block_starts.get(block)?;
+        let end = self
+            .block_starts
+            .get(block + 1)
+            .copied()
+            .unwrap_or(self.lines.len());
+        self.lines[start..end]
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, line)| {
+                let column = checkbox_column(line)?;
+                Some(SearchMatch {
+                    line: start + offset,
+                    start: column,
+                    end: column + 1,
+                })
+            })
+            .nth(remaining)
+    }
+
+    /// Select the next or previous task list item, wrapping around
+    pub fn select_task(&mut self, forward: bool) {
+        let count = task_count(&self.ast);
+        if count == 0 {
+            self.message = Some("No tasks in this document".to_string());
+            return;
+        }
+        let last = count - 1;
+        let index = match (self.selected_task, forward) {
+            (None, true) => 0,
+            (None, false) => last,
+            (Some((index, _)), true) if index < last => index + 1,
+            (Some(_), true) => 0,
+            (Some((index, _)), false) => index.checked_sub(1).unwrap_or(last),
+        };
+        let Some(checkbox) = self.task_checkbox(index) else {
+            return;
+        };
+        self.selected_task = Some((index, checkbox));
+        if checkbox.line < self.scroll || checkbox.line >= self.scroll + self.viewport_height {
+            self.scroll = checkbox
+                .line
+                .saturating_sub(self.viewport_height / 2)
+                .min(self.max_scroll());
+        }
+        self.move_current_line_to(checkbox.line);
+        self.message = Some(format!("Task {}/{}: Space to toggle", index + 1, count));
+    }
+
+    /// Check or uncheck the selected task in the file on disk
+    fn toggle_selected_task(&mut self) {
+        let Some((index, _)) = self.selected_task else {
+            return;
+        };
+        if !self.editable {
+            self.message = Some("Only files on disk can be edited".to_string());
+            return;
+        }
+        let content = match crate::search::markdown::read_lossy(&self.path) {
+            Ok((_, true)) => {
+                self.message = Some(format!("{} is not valid UTF-8", self.path.display()));
+                return;
+            }
+            Ok((content, false)) => content,
+            Err(e) => {
+                self.message = Some(format!("Could not read {}: {}", self.path.display(), e));
+                return;
+            }
+        };
+        // The file may have changed since it was rendered
+        let toggled = (find_task_markers(&content).len() == task_count(&self.ast))
+            .then(|| toggle_task(&content, index))
+            .flatten();
+        let Some(toggled) = toggled else {
+            self.message = Some("The file changed since it was shown".to_string());
+            return;
+        };
+        if let Err(e) = std::fs::write(&self.path, &toggled) {
+            self.message = Some(format!("Could not write {}: {}", self.path.display(), e));
+            return;
+        }
+        self.set_ast(parse_markdown_or_default_with(
+            &toggled,
+            self.strict_emphasis,
+        ));
+    }
+
+    /// Copy the document as plain text, or as its markdown source when `raw`
+    fn copy_document(&mut self, raw: bool) {
+        let (text, kind) = if raw {
+            let source = match &self.source {
+                Some(source) => Ok(source.clone()),
+                None => crate::search::markdown::read_lossy(&self.path).map(|(source, _)| source),
+            };
+            match source {
+                Ok(source) => (source, "markdown"),
+                Err(e) => {
+                    self.message = Some(format!("Could not read {}: {}", self.path.display(), e));
+                    return;
+                }
+            }
+        } else {
+            (self.ast.to_plain_text(), "plain text")
+        };
+
+        self.message = Some(match self.clipboard.copy(&text) {
+            Ok(()) => format!("Copied {} ({} characters)", kind, text.chars().count()),
+            Err(e) => format!("Could not copy to the clipboard: {}", e),
+        });
+    }
+
+    pub fn render(&mut self, frame: &mut Frame) {
+        let size = frame.area();
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(1),    // Document
+                Constraint::Length(1), // Search/status line
+            ])
+            .split(size);
+
+        let document_area = match &self.outline {
+            Some(outline) => {
+                let sidebar_width = (chunks[0].width / 3).min(OUTLINE_WIDTH);
+                let panes =
+                    Layout::horizontal([Constraint::Length(sidebar_width), Constraint::Min(1)])
+                        .split(chunks[0]);
+                self.render_outline(outline, frame, panes[0]);
+                panes[1]
+            }
+            None => chunks[0],
+        };
+        let content_area = Rect {
+            x: document_area.x + 1,
+            width: document_area.width.saturating_sub(2),
+            ..document_area
+        };
+
+        let width = self.max_width.min(content_area.width as usize).max(1);
+        if width != self.rendered_width {
+            self.rerender(width);
+        }
+        self.set_viewport_height(content_area.height as usize);
+        self.move_current_line_to(self.current_line);
+
+        self.hscroll = self
+            .hscroll
+            .min(max_hscroll(self.widest_visible_line(), width));
+        let marker_style = Style::default().tint(self.theme.passive);
+        let end = (self.scroll + self.viewport_height).min(self.lines.len());
+        let visible: Vec<Line> = (self.scroll..end)
+            .map(|index| {
+                let line = scroll_line(
+                    self.highlighted_line(index),
+                    self.hscroll,
+                    width,
+                    marker_style,
+                );
+                if self.highlight_current_line && index == self.current_line {
+                    fill_line(
+                        line,
+                        width,
+                        Style::default().tint_bg(self.theme.current_line),
+                    )
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(visible), content_area);
+        self.content_area = content_area;
+        match self.search_status() {
+            Some(line) => frame.render_widget(Paragraph::new(line), chunks[1]),
+            None if self.is_fully_laid_out() => StatusLine::viewer(
+                &self.path,
+                self.scroll,
+                self.viewport_height,
+                self.lines.len(),
+            )
+            .render(frame, chunks[1]),
+            None => StatusLine::partial_viewer(&self.path, self.scroll, self.lines.len())
+                .render(frame, chunks[1]),
+        }
+    }
+
+    /// Draw the outline's headings, indented by level, with the selected one highlighted
+    fn render_outline(&self, outline: &Outline, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = outline
+            .entries
+            .iter()
+            .map(|entry| {
+                let indent = "  ".repeat(usize::from(entry.level.saturating_sub(1)));
+                ListItem::new(Line::from(Span::styled(
+                    format!("{}{}", indent, entry.title),
+                    Style::default().tint(self.theme.text),
+                )))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::RIGHT)
+                    .border_style(Style::default().tint(self.theme.passive)),
+            )
+            .highlight_style(
+                Style::default()
+                    .tint(self.theme.headings[0])
+                    .add_modifier(Modifier::BOLD),
+            );
+        let mut state = ListState::default();
+        state.select(Some(outline.selected));
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    /// Images that fit entirely in the visible area, with their screen positions
+    fn visible_images(&self) -> Vec<DrawnImage> {
+        let area = self.content_area;
+        self.images
+            .iter()
+            .filter(|image| {
+                image.line >= self.scroll
+                    && image.line + image.rows <= self.scroll + self.viewport_height
+            })
+            .map(|image| DrawnImage {
+                x: area.x,
+                y: area.y + (image.line - self.scroll) as u16,
+                url: image.url.clone(),
+            })
+            .collect()
+    }
+
+    /// Whether the images on screen changed since they were last drawn
+    pub fn images_changed(&self) -> bool {
+        self.image_protocol.is_some() && self.visible_images() != self.drawn_images
+    }
+
+    /// Take the images that finished loading in the background
+    ///
+    /// Returns whether any arrived, so the screen can be drawn again. Images
+    /// that failed to load give up their reserved rows and keep their
+    /// fallback text.
+    pub fn poll_images(&mut self) -> bool {
+        let mut arrived = false;
+        let mut failed = false;
+        while let Ok((url, sequence)) = self.image_receiver.try_recv() {
+            arrived = true;
+            match sequence {
+                Some(sequence) => {
+                    self.image_cache.insert(url, ImageLoad::Ready(sequence));
+                }
+                None => {
+                    self.image_cache.insert(url.clone(), ImageLoad::Failed);
+                    failed |= self.failed_images.insert(url);
+                }
+            }
+        }
+
+        if failed {
+            self.rerender(self.rendered_width);
+            self.scroll = self.scroll.min(self.max_scroll());
+        }
+        if arrived {
+            self.drawn_images.clear();
+        }
+        arrived
+    }
+
+    /// Draw visible images over their reserved rows, after the frame has been rendered
+    ///
+    /// Images not loaded yet are loaded on a background thread, so slow files
+    /// or servers don't hold up drawing; `poll_images` picks them up.
+    pub fn draw_images<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        let Some(protocol) = self.image_protocol else {
+            return Ok(());
+        };
+
+        let visible = self.visible_images();
+        if visible == self.drawn_images {
+            return Ok(());
+        }
+
+        let document_dir = self
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        write!(out, "{}", images::clear_sequence(protocol))?;
+        for image in &visible {
+            match self.image_cache.get(&image.url) {
+                Some(ImageLoad::Ready(sequence)) => {
+                    crossterm::queue!(out, crossterm::cursor::MoveTo(image.x, image.y))?;
+                    write!(out, "{}", sequence)?;
+                }
+                Some(ImageLoad::Loading | ImageLoad::Failed) => {}
+                None => {
+                    self.image_cache
+                        .insert(image.url.clone(), ImageLoad::Loading);
+                    let sender = self.image_sender.clone();
+                    let url = image.url.clone();
+                    let document_dir = document_dir.clone();
+                    thread::spawn(move || {
+                        let sequence =
+                            images::encode_image(protocol, &url, &document_dir, IMAGE_ROWS);
+                        // The viewer may have been closed in the meantime
+                        let _ = sender.send((url, sequence));
+                    });
+                }
+            }
+        }
+        out.flush()?;
+
+        self.drawn_images = visible;
+        Ok(())
+    }
+
+    /// Remove any drawn images, e.g. before leaving the viewer
+    pub fn clear_images<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        if let Some(protocol) = self.image_protocol {
+            write!(out, "{}", images::clear_sequence(protocol))?;
+            out.flush()?;
+        }
+        self.drawn_images.clear();
+        Ok(())
+    }
+
+    /// Get a rendered line with any search matches highlighted
+    fn highlighted_line(&self, index: usize) -> Line<'static> {
+        let line = &self.lines[index];
+        let current = self.current_match();
+        let ranges: Vec<(usize, usize, Style)> = self
+            .matches
+            .iter()
+            .filter(|m| m.line == index)
+            .map(|m| (m.start, m.end, match_style(Some(*m) == current)))
+            .chain(
+                self.selected_task
+                    .filter(|(_, checkbox)| checkbox.line == index)
+                    .map(|(_, checkbox)| (checkbox.start, checkbox.end, match_style(true))),
+            )
+            .collect();
+
+        if ranges.is_empty() {
+            return line.clone();
+        }
+        highlight_ranges(line, &ranges)
+    }
+
+    /// Get the search prompt or match summary, if a search is in progress
+    fn search_status(&self) -> Option<Line<'static>> {
+        let passive = Style::default().tint(Color::Rgb(120, 120, 120));
+
+        if let Some(input) = &self.search_input {
+            return Some(Line::from(vec![
+                Span::styled("/", Style::default().tint(Color::Rgb(255, 200, 100))),
+                Span::raw(input.clone()),
+            ]));
+        }
+
+        if let Some(message) = &self.message {
+            return Some(Line::from(Span::styled(format!(" {}", message), passive)));
+        }
+
+        if self.query.is_empty() {
+            return None;
+        }
+
+        let text = match self.current_match {
+            Some(index) => format!(" [{}/{}] {}", index + 1, self.matches.len(), self.query),
+            None => format!(" Pattern not found: {}", self.query),
+        };
+        Some(Line::from(Span::styled(text, passive)))
+    }
+}
+
+/// Largest useful horizontal offset: enough to bring the end of the widest line into view
+pub fn max_hscroll(widest: usize, width: usize) -> usize {
+    widest.saturating_sub(width)
+}
+
+/// Show `width` columns of `line` starting at column `offset`
+///
+/// Lines that fit aren't scrolled. A `‹` or `›` in the first or last column
+/// marks content cut off to the left or right.
+fn scroll_line(
+    line: Line<'static>,
+    offset: usize,
+    width: usize,
+    marker_style: Style,
+) -> Line<'static> {
+    let line_width = line.width();
+    if line_width <= width {
+        return line;
+    }
+    let offset = offset.min(line_width - width);
+
+    let mut cells: Vec<(char, Style)> = Vec::new();
+    let mut column = 0;
+    for span in &line.spans {
+        let style = line.style.patch(span.style);
+        for ch in span.content.chars() {
+            let ch_width = ch.width().unwrap_or(0);
+            if column >= offset && column + ch_width <= offset + width {
+                cells.push((ch, style));
+            }
+            column += ch_width;
+        }
+    }
+    if offset > 0 {
+        if let Some(first) = cells.first_mut() {
+            *first = ('‹', marker_style);
+        }
+    }
+    if offset + width < line_width {
+        if let Some(last) = cells.last_mut() {
+            *last = ('›', marker_style);
+        }
+    }
+
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (ch, style) in cells {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(ch),
+            _ => spans.push(Span::styled(ch.to_string(), style)),
+        }
+    }
+    Line::from(spans)
+}
+
+/// Pad `line` to `width` columns and give it `style`, so the whole row shows it
+fn fill_line(mut line: Line<'static>, width: usize, style: Style) -> Line<'static> {
+    let padding = width.saturating_sub(line.width());
+    if padding > 0 {
+        line.spans.push(Span::raw(" ".repeat(padding)));
+    }
+    line.style = line.style.patch(style);
+    line
+}
+
+/// Number of task list items in `node` and its children
+fn task_count(node: &AstNode) -> usize {
+    let mut count = 0;
+    node.walk(&mut |node| count += usize::from(matches!(node, AstNode::TaskListItem { .. })));
+    count
+}
+
+/// Column of a task's checkbox in `line`, if the line starts a task list item
+///
+/// The renderer draws the checkbox as its own span right after the list marker.
+fn checkbox_column(line: &Line) -> Option<usize> {
+    let mut column = 0;
+    for pair in line.spans.windows(2) {
+        column += pair[0].content.chars().count();
+        let is_marker = pair[0].content.ends_with("• ") || pair[0].content.ends_with(". ");
+        if is_marker && [checkbox(false), checkbox(true)].contains(&pair[1].content.as_ref()) {
+            return Some(column);
+        }
+    }
+    None
+}
+
+/// The blocks laid out one at a time: a document's children, or the node itself
+fn top_level_blocks(ast: &AstNode) -> &[AstNode] {
+    match ast {
+        AstNode::Document { children } => children,
+        other => std::slice::from_ref(other),
+    }
+}
+
+/// Find case-insensitive, non-overlapping matches of `query` in each line
+pub fn find_matches(lines: &[String], query: &str) -> Vec<SearchMatch> {
+    let needle: Vec<char> = query.chars().map(fold_case).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for (line_index, line) in lines.iter().enumerate() {
+        let haystack: Vec<char> = line.chars().map(fold_case).collect();
+        let mut start = 0;
+        while start + needle.len() <= haystack.len() {
+            if haystack[start..start + needle.len()] == needle[..] {
+                matches.push(SearchMatch {
+                    line: line_index,
+                    start,
+                    end: start + needle.len(),
+                });
+                start += needle.len();
+            } else {
+                start += 1;
+            }
+        }
+    }
+    matches
+}
+
+/// Lowercase a char while keeping a one-to-one char mapping
+fn fold_case(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+fn match_style(is_current: bool) -> Style {
+    if style::no_color() {
+        let style = Style::default().add_modifier(Modifier::UNDERLINED);
+        return if is_current {
+            style.add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            style
+        };
+    }
+
+    if is_current {
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Rgb(255, 150, 80))
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Rgb(200, 170, 60))
+    }
+}
+
+/// Split a line's spans so the given char ranges get an extra style
+fn highlight_ranges(line: &Line<'static>, ranges: &[(usize, usize, Style)]) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut position = 0;
+
+    for span in &line.spans {
+        let mut chunk = String::new();
+        let mut chunk_style = None;
+
+        for ch in span.content.chars() {
+            let style = ranges
+                .iter()
+                .find(|(start, end, _)| (*start..*end).contains(&position))
+                .map(|(_, _, highlight)| span.style.patch(*highlight))
+                .unwrap_or(span.style);
+
+            if chunk_style.is_some_and(|current| current != style) {
+                spans.push(Span::styled(
+                    std::mem::take(&mut chunk),
+                    chunk_style.unwrap(),
+                ));
+            }
+            chunk_style = Some(style);
+            chunk.push(ch);
+            position += 1;
+        }
+
+        if let Some(style) = chunk_style {
+            spans.push(Span::styled(chunk, style));
+        }
+    }
+
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::Recording;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn viewer(content: &str) -> MarkdownViewer {
+        let mut viewer = MarkdownViewer::new(Path::new("test.md"), content, Theme::default(), 80);
+        viewer.set_viewport_height(3);
+        viewer
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_copy_plain_text_and_markdown() {
+        let content =
+            "# Title\n\nSome **bold** text and a [link](https://example.com).\n\n- one\n- two";
+        let copied = Rc::new(RefCell::new(Vec::new()));
+        let mut viewer = viewer(content).with_clipboard(Box::new(Recording {
+            calls: Rc::clone(&copied),
+            fail: false,
+        }));
+
+        viewer.handle_key_event(key(KeyCode::Char('Y'))).unwrap();
+        viewer.handle_key_event(key(KeyCode::Char('M'))).unwrap();
+
+        let expected = parse_markdown_or_default(content).to_plain_text();
+        assert_eq!(*copied.borrow(), vec![expected, content.to_string()]);
+    }
+
+    #[test]
+    fn test_find_matches() {
+        let lines = vec![
+            "Rust is fast".to_string(),
+            "no hits here".to_string(),
+            "rust and RUST".to_string(),
+        ];
+        let matches = find_matches(&lines, "rust");
+
+        assert_eq!(
+            matches,
+            vec![
+                SearchMatch {
+                    line: 0,
+                    start: 0,
+                    end: 4
+                },
+                SearchMatch {
+                    line: 2,
+                    start: 0,
+                    end: 4
+                },
+                SearchMatch {
+                    line: 2,
+                    start: 9,
+                    end: 13
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_jumps_to_match() {
+        let content = "first\n\nsecond\n\nthird\n\nfourth\n\nneedle here";
+        let mut viewer = viewer(content);
+
+        for c in "/needle".chars() {
+            viewer.handle_key_event(key(KeyCode::Char(c))).unwrap();
+        }
+        viewer.handle_key_event(key(KeyCode::Enter)).unwrap();
+
+        let current = viewer.current_match().unwrap();
+        assert_eq!(viewer.matches().len(), 1);
+        assert!(viewer.scroll_offset() <= current.line);
+        assert!(current.line < viewer.scroll_offset() + 3);
+    }
+
+    #[test]
+    fn test_cycling_matches() {
+        let mut viewer = viewer("a match\n\nb match\n\nc match");
+        viewer.search("match");
+        assert_eq!(viewer.matches().len(), 3);
+
+        let lines: Vec<usize> = (0..4)
+            .map(|_| {
+                let line = viewer.current_match().unwrap().line;
+                viewer.handle_key_event(key(KeyCode::Char('n'))).unwrap();
+                line
+            })
+            .collect();
+        assert_eq!(lines, vec![0, 2, 4, 0]);
+
+        // `N` walks backwards and wraps to the last match
+        viewer.handle_key_event(key(KeyCode::Char('N'))).unwrap();
+        viewer.handle_key_event(key(KeyCode::Char('N'))).unwrap();
+        assert_eq!(viewer.current_match().unwrap().line, 4);
+    }
+
+    #[test]
+    fn test_no_match() {
+        let mut viewer = viewer("nothing to see");
+        viewer.search("missing");
+
+        assert!(viewer.matches().is_empty());
+        assert_eq!(viewer.current_match(), None);
+        assert_eq!(viewer.scroll_offset(), 0);
+
+        viewer.next_match();
+        viewer.previous_match();
+        assert_eq!(viewer.current_match(), None);
+        assert!(line_text(&viewer.search_status().unwrap()).contains("Pattern not found"));
+    }
+
+    #[test]
+    fn test_highlight_ranges_splits_spans() {
+        let line = Line::from(vec![Span::raw("hello "), Span::raw("world")]);
+        let highlight = Style::default().add_modifier(Modifier::BOLD);
+        let highlighted = highlight_ranges(&line, &[(4, 8, highlight)]);
+
+        let parts: Vec<(&str, bool)> = highlighted
+            .spans
+            .iter()
+            .map(|span| {
+                (
+                    span.content.as_ref(),
+                    span.style.add_modifier.contains(Modifier::BOLD),
+                )
+            })
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("hell", false), ("o ", true), ("wo", true), ("rld", false)]
+        );
+    }
+
+    #[test]
+    fn test_outline_jumps_to_heading() {
+        let filler = "paragraph\n\n".repeat(10);
+        let content = format!("# Title\n\n{filler}## Section\n\n{filler}## Later\n\n{filler}");
+        let mut viewer = viewer(&content);
+
+        viewer.handle_key_event(key(KeyCode::Char('o'))).unwrap();
+        assert!(viewer.is_outline_open());
+        viewer.handle_key_event(key(KeyCode::Char('j'))).unwrap();
+        viewer.handle_key_event(key(KeyCode::Enter)).unwrap();
+
+        let section = viewer
+            .plain_lines
+            .iter()
+            .position(|line| line.contains("Section"))
+            .unwrap();
+        assert_eq!(viewer.scroll_offset(), section);
+        assert!(!viewer.is_outline_open());
+
+        // Reopening selects the section at the top of the view
+        viewer.handle_key_event(key(KeyCode::Char('o'))).unwrap();
+        assert_eq!(viewer.outline.as_ref().unwrap().selected, 1);
+        viewer.handle_key_event(key(KeyCode::Esc)).unwrap();
+        assert!(!viewer.is_outline_open());
+        assert!(!viewer.should_close());
+    }
+
+    #[test]
+    fn test_heading_jumps_stop_at_the_ends() {
+        let filler = "paragraph\n\n".repeat(10);
+        let content = format!("# Title\n\n{filler}## Section\n\n{filler}## Later\n\n{filler}");
+        let mut viewer = viewer(&content);
+        let heading_line = |viewer: &MarkdownViewer, title: &str| {
+            viewer
+                .plain_lines
+                .iter()
+                .position(|line| line.contains(title))
+                .unwrap()
+        };
+
+        viewer.handle_key_event(key(KeyCode::Char(']'))).unwrap();
+        assert_eq!(viewer.scroll_offset(), heading_line(&viewer, "Section"));
+        viewer.handle_key_event(key(KeyCode::Char(']'))).unwrap();
+        let later = heading_line(&viewer, "Later");
+        assert_eq!(viewer.scroll_offset(), later);
+
+        // There is no heading after the last one, so the view stays put
+        viewer.handle_key_event(key(KeyCode::Char(']'))).unwrap();
+        assert_eq!(viewer.scroll_offset(), later);
+
+        viewer.handle_key_event(key(KeyCode::Char('['))).unwrap();
+        assert_eq!(viewer.scroll_offset(), heading_line(&viewer, "Section"));
+        viewer.handle_key_event(key(KeyCode::Char('['))).unwrap();
+        assert_eq!(viewer.scroll_offset(), 0);
+        viewer.handle_key_event(key(KeyCode::Char('['))).unwrap();
+        assert_eq!(viewer.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_space_toggles_selected_task_in_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("todo.md");
+        std::fs::write(&path, "# Todo\n\n- [x] write\n- [ ] test\n").unwrap();
+        let mut file_viewer = MarkdownViewer::open(&path, Theme::default(), 80).unwrap();
+        file_viewer.set_viewport_height(10);
+
+        file_viewer.handle_key_event(key(KeyCode::Tab)).unwrap();
+        file_viewer.handle_key_event(key(KeyCode::Tab)).unwrap();
+        file_viewer
+            .handle_key_event(key(KeyCode::Char(' ')))
+            .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "# Todo\n\n- [x] write\n- [x] test\n");
+        assert!(file_viewer
+            .plain_lines
+            .iter()
+            .all(|line| !line.contains('☐')));
+
+        // Pasted documents aren't written anywhere
+        let mut pasted = viewer("- [ ] task");
+        pasted.handle_key_event(key(KeyCode::Tab)).unwrap();
+        pasted.handle_key_event(key(KeyCode::Char(' '))).unwrap();
+        assert!(pasted.message.unwrap().contains("Only files on disk"));
+    }
+
+    #[test]
+    fn test_literal_checkboxes_are_not_tasks() {
+        let mut viewer =
+            viewer("Draw ☐ for a todo\n\n```\n- ☐ in code\n```\n\n- ☐ plain item\n- [ ] real\n");
+        viewer.set_viewport_height(20);
+
+        viewer.handle_key_event(key(KeyCode::Tab)).unwrap();
+        let (index, checkbox) = viewer.selected_task.unwrap();
+        assert_eq!(index, 0);
+        assert!(viewer.plain_lines[checkbox.line].ends_with("☐ real"));
+        assert_eq!(viewer.message.as_deref(), Some("Task 1/1: Space to toggle"));
+
+        // There's only the one task to cycle through
+        viewer.handle_key_event(key(KeyCode::Tab)).unwrap();
+        assert_eq!(viewer.selected_task.unwrap(), (0, checkbox));
+    }
+
+    #[test]
+    fn test_page_scrolling_clamps_at_the_ends() {
+        let content: String = (1..=40).map(|i| format!("Line {}\n\n", i)).collect();
+        let mut viewer = viewer(&content).with_scroll_step(3);
+        viewer.set_viewport_height(11);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        viewer.handle_key_event(ctrl('d')).unwrap();
+        assert_eq!(viewer.scroll_offset(), 5);
+        viewer.handle_key_event(ctrl('f')).unwrap();
+        assert_eq!(viewer.scroll_offset(), 15);
+        viewer.handle_key_event(ctrl('b')).unwrap();
+        viewer.handle_key_event(ctrl('u')).unwrap();
+        assert_eq!(viewer.scroll_offset(), 0);
+        viewer.handle_key_event(ctrl('u')).unwrap();
+        assert_eq!(viewer.scroll_offset(), 0);
+
+        viewer.handle_key_event(key(KeyCode::Char('j'))).unwrap();
+        assert_eq!(viewer.scroll_offset(), 3);
+
+        viewer.ensure_layout();
+        let last = viewer.line_count() - 11;
+        for _ in 0..10 {
+            viewer.handle_key_event(ctrl('f')).unwrap();
+        }
+        assert_eq!(viewer.scroll_offset(), last);
+    }
+
+    #[test]
+    fn test_hscroll_offset_math() {
+        assert_eq!(max_hscroll(100, 40), 60);
+        assert_eq!(max_hscroll(30, 40), 0);
+
+        let code_line = || Line::from("0123456789".repeat(10));
+        let text = |offset| line_text(&scroll_line(code_line(), offset, 40, Style::default()));
+
+        let start = text(0);
+        assert_eq!(start.chars().count(), 40);
+        assert!(start.starts_with("0123") && start.ends_with('›'));
+
+        let middle = text(30);
+        assert!(middle.starts_with("‹1234") && middle.ends_with('›'));
+
+        // Offsets past the end stop at the last column of the line
+        let end = text(70);
+        assert_eq!(end, text(60));
+        assert!(end.starts_with('‹') && end.ends_with("6789"));
+
+        let short = Line::from("fits");
+        assert_eq!(
+            line_text(&scroll_line(short, 10, 40, Style::default())),
+            "fits"
+        );
+    }
+
+    #[test]
+    fn test_horizontal_scroll_keys() {
+        let content = format!("```\n{}\n```", "x".repeat(100));
+        let mut viewer = MarkdownViewer::new(Path::new("test.md"), &content, Theme::default(), 40);
+        viewer.set_viewport_height(3);
+
+        viewer.handle_key_event(key(KeyCode::Char('l'))).unwrap();
+        viewer.handle_key_event(key(KeyCode::Right)).unwrap();
+        assert_eq!(viewer.hscroll_offset(), 2 * HSCROLL_STEP);
+
+        // The code line is 102 columns with its indent, so 62 is as far as it goes
+        viewer.scroll_right(1_000);
+        assert_eq!(viewer.hscroll_offset(), 62);
+
+        viewer.handle_key_event(key(KeyCode::Char('h'))).unwrap();
+        assert_eq!(viewer.hscroll_offset(), 62 - HSCROLL_STEP);
+        viewer.scroll_left(1_000);
+        assert_eq!(viewer.hscroll_offset(), 0);
+    }
+
+    #[test]
+    fn test_lays_out_only_visible_blocks() {
+        let content: String = (0..5_000).map(|i| format!("Paragraph {}\n\n", i)).collect();
+        let mut viewer = MarkdownViewer::new(Path::new("big.md"), &content, Theme::default(), 80);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| viewer.render(frame)).unwrap();
+
+        let laid_out = viewer.line_count();
+        assert!(laid_out >= 23);
+        assert!(
+            laid_out <= 23 + LAYOUT_BUFFER + 1,
+            "laid out {} lines",
+            laid_out
+        );
+        assert_eq!(viewer.scroll_offset(), 0);
+
+        // Scrolling lays out more, and jumping to the end lays out everything
+        viewer.scroll_down(100);
+        assert!(viewer.line_count() >= 100 + 23);
+        viewer.handle_key_event(key(KeyCode::Char('G'))).unwrap();
+        assert_eq!(viewer.line_count(), 9_999);
+    }
+
+    #[test]
+    fn test_current_line_is_highlighted_as_it_moves() {
+        let content: String = (1..=20).map(|i| format!("Line {}\n\n", i)).collect();
+        let theme = Theme::default();
+        let mut viewer = viewer(&content).with_highlight_current_line(true);
+        let mut terminal = Terminal::new(TestBackend::new(20, 6)).unwrap();
+        let mut highlighted_row = |viewer: &mut MarkdownViewer| {
+            terminal.draw(|frame| viewer.render(frame)).unwrap();
+            let buffer = terminal.backend().buffer();
+            let rows: Vec<u16> = (0..5)
+                .filter(|&row| buffer[(1, row)].bg == theme.current_line)
+                .collect();
+            assert_eq!(rows.len(), 1, "one highlighted row, got {:?}", rows);
+            rows[0]
+        };
+
+        assert_eq!(highlighted_row(&mut viewer), 0);
+        // Scrolling moves the current line along with the view
+        viewer.handle_key_event(key(KeyCode::Char('j'))).unwrap();
+        assert_eq!((viewer.scroll_offset(), viewer.current_line()), (1, 1));
+        assert_eq!(highlighted_row(&mut viewer), 0);
+
+        // At the end it moves down the window instead
+        viewer.handle_key_event(key(KeyCode::Char('G'))).unwrap();
+        assert_eq!(viewer.current_line(), viewer.line_count() - 1);
+        assert_eq!(highlighted_row(&mut viewer), 4);
+        viewer.handle_key_event(key(KeyCode::Char('k'))).unwrap();
+        assert_eq!(viewer.current_line(), viewer.line_count() - 2);
+        assert_eq!(highlighted_row(&mut viewer), 4);
+
+        viewer.search("Line 3");
+        assert_eq!(viewer.current_line(), 4);
+        viewer.handle_key_event(key(KeyCode::Char('g'))).unwrap();
+        viewer.handle_key_event(key(KeyCode::Char('g'))).unwrap();
+        assert_eq!(viewer.current_line(), 0);
+        assert_eq!(highlighted_row(&mut viewer), 0);
+    }
+
+    #[test]
+    fn test_failed_image_collapses_to_its_text() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut viewer = MarkdownViewer::new(
+            &dir.path().join("doc.md"),
+            "# Intro\n\n![logo](missing.png)\n\nAfter",
+            Theme::default(),
+            80,
+        )
+        .with_image_protocol(Some(ImageProtocol::Kitty));
+        viewer.set_viewport_height(20);
+        assert_eq!(viewer.line_count(), 3 + IMAGE_ROWS + 1);
+
+        // Loading happens in the background, drawing doesn't wait for it
+        let mut out = Vec::new();
+        viewer.draw_images(&mut out).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !viewer.poll_images() {
+            assert!(std::time::Instant::now() < deadline, "image never loaded");
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        assert_eq!(viewer.line_count(), 5);
+        assert!(viewer.plain_lines[2].contains("logo"));
+        assert!(!viewer.images_changed());
+    }
+}
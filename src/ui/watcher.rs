@@ -0,0 +1,160 @@
+use crate::error::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// How long a file must stay unchanged before it is reloaded
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Tracks changes to one file and decides when they have settled
+#[derive(Debug, Clone)]
+pub struct ChangeTracker {
+    path: PathBuf,
+    delay: Duration,
+    last_change: Option<Instant>,
+}
+
+impl ChangeTracker {
+    pub fn new(path: &Path, delay: Duration) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            delay,
+            last_change: None,
+        }
+    }
+
+    /// Record `event` if it touches the tracked file, restarting the quiet period
+    pub fn handle(&mut self, event: &Event, now: Instant) {
+        // Reading the file ourselves shows up as access events
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        let name = self.path.file_name();
+        if event.paths.iter().any(|path| path.file_name() == name) {
+            self.last_change = Some(now);
+        }
+    }
+
+    /// Whether the file changed and has been quiet for the debounce delay
+    ///
+    /// Editors that save atomically remove or rename the file before the new
+    /// one appears, so a missing file waits for the next change instead.
+    pub fn reload_due(&mut self, now: Instant) -> bool {
+        match self.last_change {
+            Some(changed) if now.duration_since(changed) >= self.delay => {
+                self.last_change = None;
+                self.path.is_file()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Watches a file on disk so the viewer can re-render it when it changes
+pub struct FileWatcher {
+    // Dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    tracker: ChangeTracker,
+}
+
+impl FileWatcher {
+    /// Start watching `path`
+    ///
+    /// The parent directory is watched rather than the file itself, so the
+    /// watch survives the file being replaced.
+    pub fn new(path: &Path) -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        watcher.watch(directory, RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            tracker: ChangeTracker::new(path, DEBOUNCE),
+        })
+    }
+
+    /// Check for changes, returning true once the file should be reloaded
+    pub fn poll(&mut self) -> bool {
+        let now = Instant::now();
+        while let Ok(event) = self.events.try_recv() {
+            if let Ok(event) = event {
+                self.tracker.handle(&event, now);
+            }
+        }
+        self.tracker.reload_due(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind, RemoveKind};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn event(kind: EventKind, path: &Path) -> Event {
+        Event::new(kind).add_path(path.to_path_buf())
+    }
+
+    #[test]
+    fn test_rapid_changes_reload_once_after_quiet_period() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "# Notes").unwrap();
+        let modify = event(EventKind::Modify(ModifyKind::Any), &path);
+
+        let start = Instant::now();
+        let mut tracker = ChangeTracker::new(&path, Duration::from_millis(200));
+        assert!(!tracker.reload_due(start));
+
+        tracker.handle(&modify, start);
+        tracker.handle(&modify, start + Duration::from_millis(150));
+        assert!(!tracker.reload_due(start + Duration::from_millis(300)));
+        assert!(tracker.reload_due(start + Duration::from_millis(350)));
+        assert!(!tracker.reload_due(start + Duration::from_millis(600)));
+    }
+
+    #[test]
+    fn test_ignores_other_files_and_access() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "# Notes").unwrap();
+
+        let start = Instant::now();
+        let mut tracker = ChangeTracker::new(&path, Duration::from_millis(200));
+        tracker.handle(
+            &event(
+                EventKind::Modify(ModifyKind::Any),
+                &dir.path().join("other.md"),
+            ),
+            start,
+        );
+        tracker.handle(&event(EventKind::Access(AccessKind::Any), &path), start);
+        assert!(!tracker.reload_due(start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_atomic_save_waits_for_replacement() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "# Notes").unwrap();
+
+        let start = Instant::now();
+        let mut tracker = ChangeTracker::new(&path, Duration::from_millis(200));
+        fs::remove_file(&path).unwrap();
+        tracker.handle(&event(EventKind::Remove(RemoveKind::File), &path), start);
+        assert!(!tracker.reload_due(start + Duration::from_millis(250)));
+
+        fs::write(&path, "# Notes, edited").unwrap();
+        let created = start + Duration::from_millis(300);
+        tracker.handle(&event(EventKind::Create(CreateKind::File), &path), created);
+        assert!(tracker.reload_due(created + Duration::from_millis(200)));
+    }
+}